@@ -0,0 +1,136 @@
+//! EIP-191 facilitator attestations.
+//!
+//! Signs settlement receipts with an EVM key as an EIP-191 personal message.
+//! The advertised public key is the signer's checksummed address, and the
+//! signature is the 65-byte `r || s || v` encoding in `0x`-prefixed hex.
+
+#[cfg(feature = "facilitator")]
+use alloy_primitives::eip191_hash_message;
+use alloy_primitives::{Address, Signature, hex};
+#[cfg(feature = "facilitator")]
+use alloy_signer::SignerSync;
+#[cfg(feature = "facilitator")]
+use r402::attestation::AttestationSigner;
+use r402::attestation::AttestationVerifier;
+#[cfg(feature = "facilitator")]
+use r402::facilitator::{BoxFuture, FacilitatorError};
+
+/// Signs facilitator attestations with an EVM key.
+#[cfg(feature = "facilitator")]
+#[derive(Debug, Clone)]
+pub struct Eip191AttestationSigner<S> {
+    signer: S,
+    address: Address,
+}
+
+#[cfg(feature = "facilitator")]
+impl<S: alloy_signer::Signer> Eip191AttestationSigner<S> {
+    /// Creates an attestation signer backed by `signer`.
+    pub fn new(signer: S) -> Self {
+        let address = signer.address();
+        Self { signer, address }
+    }
+}
+
+#[cfg(feature = "facilitator")]
+impl<S> AttestationSigner for Eip191AttestationSigner<S>
+where
+    S: SignerSync + Send + Sync,
+{
+    fn public_key(&self) -> String {
+        self.address.to_checksum(None)
+    }
+
+    fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<String, FacilitatorError>> {
+        let result = self
+            .signer
+            .sign_hash_sync(&eip191_hash_message(message))
+            .map(|signature| hex::encode_prefixed(signature.as_bytes()))
+            .map_err(|e| FacilitatorError::Other(Box::new(e)));
+        Box::pin(async move { result })
+    }
+}
+
+/// Verifies EIP-191 facilitator attestations.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Eip191AttestationVerifier;
+
+impl AttestationVerifier for Eip191AttestationVerifier {
+    fn verify(&self, public_key: &str, message: &[u8], signature: &str) -> bool {
+        let Ok(expected) = public_key.parse::<Address>() else {
+            return false;
+        };
+        let Ok(bytes) = hex::decode(signature) else {
+            return false;
+        };
+        Signature::from_raw(&bytes)
+            .and_then(|signature| signature.recover_address_from_msg(message))
+            .is_ok_and(|recovered| recovered == expected)
+    }
+}
+
+#[cfg(all(test, feature = "facilitator"))]
+mod tests {
+    use alloy_signer_local::PrivateKeySigner;
+    use r402::attestation::{AttestingFacilitator, FacilitatorAttestation, attestation_message};
+    use r402::facilitator::Facilitator;
+    use r402::proto;
+
+    use super::*;
+
+    struct SettlingFacilitator;
+
+    impl Facilitator for SettlingFacilitator {
+        fn verify(
+            &self,
+            _request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::VerifyResponse::valid("0xpayer".into())) })
+        }
+
+        fn settle(
+            &self,
+            _request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            Box::pin(async {
+                Ok(proto::SettleResponse::Success {
+                    payer: "0xpayer".into(),
+                    transaction: "0xtx".into(),
+                    network: "eip155:8453".into(),
+                    extensions: None,
+                })
+            })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::SupportedResponse::default()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_attestation_verifies_against_advertised_key() {
+        let facilitator = AttestingFacilitator::new(
+            SettlingFacilitator,
+            Eip191AttestationSigner::new(PrivateKeySigner::random()),
+        );
+
+        let key = facilitator
+            .supported()
+            .await
+            .unwrap()
+            .attestation_key
+            .unwrap();
+        let response = facilitator
+            .settle(proto::SettleRequest::from(serde_json::json!({})))
+            .await
+            .unwrap();
+        let attestation = FacilitatorAttestation::from_response(&response).unwrap();
+        let message = attestation_message(&response).unwrap();
+
+        let verifier = Eip191AttestationVerifier;
+        assert!(verifier.verify(&key, &message, &attestation.signature));
+        assert!(!verifier.verify(&key, b"tampered", &attestation.signature));
+        let other = PrivateKeySigner::random().address().to_string();
+        assert!(!verifier.verify(&other, &message, &attestation.signature));
+    }
+}
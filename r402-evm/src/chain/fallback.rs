@@ -0,0 +1,343 @@
+//! JSON-RPC transport failing over between ordered endpoints.
+//!
+//! Alloy's `FallbackLayer` races each request over its best-scored endpoints.
+//! [`AdaptiveFallback`] instead sends every request to a single active
+//! endpoint, preferring them in configuration order: after
+//! [`FailoverConfig::threshold`] consecutive transport failures it switches
+//! to the next endpoint for all subsequent calls, and while a fallback is
+//! active it retries the primary every [`FailoverConfig::probe_interval`],
+//! returning to it as soon as it answers.
+//!
+//! JSON-RPC error responses (reverts, invalid params, ...) come from a
+//! healthy endpoint and never count as failures.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use alloy_json_rpc::{RequestPacket, ResponsePacket, RpcError};
+use alloy_transport::{TransportError, TransportFut, TransportResult};
+use r402::chain::ChainId;
+use r402::metrics::{Metrics, NoopMetrics};
+use tower::{Service, ServiceExt};
+
+/// Default number of consecutive failures before switching endpoints.
+pub const DEFAULT_FAILOVER_THRESHOLD: u32 = 3;
+
+/// Default interval between probes of the primary endpoint.
+pub const DEFAULT_PRIMARY_PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Options of an [`AdaptiveFallback`] transport.
+#[derive(Clone)]
+pub struct FailoverConfig {
+    /// Consecutive transport failures of the active endpoint after which the
+    /// next endpoint takes over.
+    ///
+    /// Default: [`DEFAULT_FAILOVER_THRESHOLD`].
+    pub threshold: u32,
+    /// How often the primary endpoint is retried while a fallback is active.
+    ///
+    /// Default: [`DEFAULT_PRIMARY_PROBE_INTERVAL`].
+    pub probe_interval: Duration,
+    /// Sink counting failovers, see [`Metrics::record_rpc_failover`].
+    ///
+    /// Default: none.
+    pub metrics: Option<Arc<dyn Metrics>>,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_FAILOVER_THRESHOLD,
+            probe_interval: DEFAULT_PRIMARY_PROBE_INTERVAL,
+            metrics: None,
+        }
+    }
+}
+
+impl fmt::Debug for FailoverConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FailoverConfig")
+            .field("threshold", &self.threshold)
+            .field("probe_interval", &self.probe_interval)
+            .field("metrics", &self.metrics.is_some())
+            .finish()
+    }
+}
+
+/// Failover state shared by every clone of an [`AdaptiveFallback`].
+struct FailoverState {
+    network: String,
+    threshold: u32,
+    probe_interval: Duration,
+    metrics: Arc<dyn Metrics>,
+    /// Index of the endpoint requests are sent to.
+    active: AtomicUsize,
+    /// Consecutive failures of the active endpoint.
+    failures: AtomicU32,
+    /// Number of switches away from a failing endpoint.
+    failovers: AtomicU64,
+    /// Earliest time the primary may be probed again.
+    next_probe: Mutex<Instant>,
+}
+
+impl FailoverState {
+    /// Returns `true` if the primary is due for a probe, and schedules the next.
+    fn probe_due(&self) -> bool {
+        let mut next_probe = self
+            .next_probe
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        if now < *next_probe {
+            return false;
+        }
+        *next_probe = now + self.probe_interval;
+        true
+    }
+
+    /// Switches back to the primary after it answered a probe.
+    fn restore_primary(&self, active: usize) {
+        if self
+            .active
+            .compare_exchange(active, 0, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            self.failures.store(0, Ordering::Release);
+            #[cfg(feature = "telemetry")]
+            tracing::info!(network = %self.network, from = active, "Returned to the primary RPC endpoint");
+        }
+    }
+
+    /// Records a failure of endpoint `failed` out of `len`, returning the
+    /// endpoint to retry on if the failure triggered a failover.
+    fn record_failure(&self, failed: usize, len: usize) -> Option<usize> {
+        let failures = self.failures.fetch_add(1, Ordering::AcqRel) + 1;
+        if len < 2 || failures < self.threshold {
+            return None;
+        }
+        let next = (failed + 1) % len;
+        if let Err(current) =
+            self.active
+                .compare_exchange(failed, next, Ordering::AcqRel, Ordering::Acquire)
+        {
+            // Another request already switched away from the failed endpoint.
+            return Some(current);
+        }
+        self.failures.store(0, Ordering::Release);
+        self.failovers.fetch_add(1, Ordering::Relaxed);
+        self.metrics.record_rpc_failover(&self.network);
+        if failed == 0 {
+            *self
+                .next_probe
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Instant::now() + self.probe_interval;
+        }
+        #[cfg(feature = "telemetry")]
+        tracing::warn!(network = %self.network, from = failed, to = next, failures, "RPC endpoint failing, switching to the next one");
+        Some(next)
+    }
+}
+
+/// JSON-RPC transport sending requests to one active endpoint out of an
+/// ordered list, failing over when it keeps failing.
+///
+/// See the [module documentation](self) for the failover policy. Clones
+/// share their state, so keep a clone to read [`Self::failovers`] after
+/// handing the transport to an `RpcClient`.
+pub struct AdaptiveFallback<S> {
+    endpoints: Arc<[S]>,
+    state: Arc<FailoverState>,
+}
+
+impl<S> AdaptiveFallback<S> {
+    /// Creates a transport over `endpoints` of `chain_id`, in order of
+    /// preference.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `endpoints` is empty.
+    #[must_use]
+    pub fn new(chain_id: &ChainId, endpoints: Vec<S>, config: FailoverConfig) -> Self {
+        assert!(
+            !endpoints.is_empty(),
+            "at least one RPC endpoint is required"
+        );
+        let state = FailoverState {
+            network: chain_id.to_string(),
+            threshold: config.threshold.max(1),
+            probe_interval: config.probe_interval,
+            metrics: config.metrics.unwrap_or_else(|| Arc::new(NoopMetrics)),
+            active: AtomicUsize::new(0),
+            failures: AtomicU32::new(0),
+            failovers: AtomicU64::new(0),
+            next_probe: Mutex::new(Instant::now()),
+        };
+        Self {
+            endpoints: endpoints.into(),
+            state: Arc::new(state),
+        }
+    }
+
+    /// Returns the index of the endpoint requests are currently sent to.
+    #[must_use]
+    pub fn active_endpoint(&self) -> usize {
+        self.state.active.load(Ordering::Acquire)
+    }
+
+    /// Returns how many times the transport switched away from a failing
+    /// endpoint.
+    #[must_use]
+    pub fn failovers(&self) -> u64 {
+        self.state.failovers.load(Ordering::Relaxed)
+    }
+}
+
+impl<S> Clone for AdaptiveFallback<S> {
+    fn clone(&self) -> Self {
+        Self {
+            endpoints: Arc::clone(&self.endpoints),
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl<S> fmt::Debug for AdaptiveFallback<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdaptiveFallback")
+            .field("network", &self.state.network)
+            .field("endpoints", &self.endpoints.len())
+            .field("active", &self.active_endpoint())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> AdaptiveFallback<S>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    async fn send(self, request: RequestPacket) -> TransportResult<ResponsePacket> {
+        let state = &self.state;
+        let active = state.active.load(Ordering::Acquire);
+        if active != 0
+            && state.probe_due()
+            && let Ok(response) = self.endpoints[0].clone().oneshot(request.clone()).await
+        {
+            state.restore_primary(active);
+            return Ok(response);
+        }
+
+        match self.endpoints[active]
+            .clone()
+            .oneshot(request.clone())
+            .await
+        {
+            Err(error) if is_endpoint_failure(&error) => {
+                match state.record_failure(active, self.endpoints.len()) {
+                    Some(next) => self.endpoints[next].clone().oneshot(request).await,
+                    None => Err(error),
+                }
+            }
+            result => {
+                state.failures.store(0, Ordering::Release);
+                result
+            }
+        }
+    }
+}
+
+impl<S> Service<RequestPacket> for AdaptiveFallback<S>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().send(request))
+    }
+}
+
+/// Returns `true` if `error` means the endpoint itself is unhealthy, rather
+/// than that it rejected the request.
+const fn is_endpoint_failure(error: &TransportError) -> bool {
+    !matches!(error, RpcError::ErrorResp(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::U64;
+    use alloy_rpc_client::RpcClient;
+    use alloy_transport::{BoxTransport, Transport};
+    use alloy_transport_http::Http;
+    use alloy_transport_http::reqwest::Client as HttpClient;
+    use url::Url;
+    use wiremock::MockServer;
+
+    use super::*;
+    use crate::mock::mock_rpc;
+
+    #[derive(Default)]
+    struct CountingMetrics(AtomicU64);
+
+    impl Metrics for CountingMetrics {
+        fn record_rpc_failover(&self, network: &str) {
+            assert_eq!(network, "eip155:8453");
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn transport(server: &MockServer) -> BoxTransport {
+        let url = Url::parse(&server.uri()).unwrap();
+        Transport::boxed(Http::with_client(HttpClient::new(), url))
+    }
+
+    #[tokio::test]
+    async fn test_failing_endpoint_fails_over_to_healthy_fallback() {
+        // No mock is mounted on the primary, so it answers every call with 404.
+        let primary = MockServer::start().await;
+        let fallback = mock_rpc([]).await;
+        let metrics = Arc::new(CountingMetrics::default());
+        let recorder: Arc<dyn Metrics> = Arc::<CountingMetrics>::clone(&metrics);
+        let transport = AdaptiveFallback::new(
+            &ChainId::new("eip155", "8453"),
+            vec![transport(&primary), transport(&fallback)],
+            FailoverConfig {
+                threshold: 2,
+                probe_interval: Duration::from_hours(1),
+                metrics: Some(recorder),
+            },
+        );
+        let client = RpcClient::new(transport.clone(), false);
+
+        let first = client.request_noparams::<U64>("eth_chainId").await;
+        assert!(first.is_err());
+        assert_eq!(transport.active_endpoint(), 0);
+
+        for _ in 0..3 {
+            let chain_id: U64 = client.request_noparams("eth_chainId").await.unwrap();
+            assert_eq!(chain_id, U64::from(8453));
+        }
+        assert_eq!(transport.active_endpoint(), 1);
+        assert_eq!(transport.failovers(), 1);
+        assert_eq!(metrics.0.load(Ordering::Relaxed), 1);
+        assert_eq!(primary.received_requests().await.unwrap().len(), 2);
+    }
+}
@@ -15,23 +15,41 @@
 //!
 //! - [`types`] - Wire format types like [`ChecksummedAddress`] and [`TokenAmount`]
 //! - [`nonce`] - Nonce management for concurrent transaction submission
+//! - [`signer`] - Pluggable signing backends (local keys, KMS, HSM)
+//! - [`attestation`] - EIP-191 signatures over settlement receipts
+//! - [`transport`] - HTTP JSON-RPC transport with a response size limit
+//! - [`fallback`] - JSON-RPC transport failing over between ordered endpoints
 //!
 //! # ERC-3009 Support
 //!
 //! The x402 protocol uses ERC-3009 `transferWithAuthorization` for payments. This allows
 //! users to sign payment authorizations off-chain, which the facilitator then submits
 //! on-chain. The facilitator pays the gas fees and is reimbursed through the payment.
+pub mod attestation;
 pub mod types;
 
+#[cfg(feature = "facilitator")]
+pub mod fallback;
 /// Pending nonce management for EVM transactions.
 #[cfg(feature = "facilitator")]
 pub mod nonce;
 /// EVM chain provider implementation.
 #[cfg(feature = "facilitator")]
 pub mod provider;
+#[cfg(feature = "facilitator")]
+pub mod signer;
+#[cfg(feature = "facilitator")]
+pub mod transport;
 
+pub use attestation::*;
+#[cfg(feature = "facilitator")]
+pub use fallback::*;
 #[cfg(feature = "facilitator")]
 pub use nonce::*;
 #[cfg(feature = "facilitator")]
 pub use provider::*;
+#[cfg(feature = "facilitator")]
+pub use signer::*;
+#[cfg(feature = "facilitator")]
+pub use transport::*;
 pub use types::*;
@@ -77,6 +77,22 @@ async fn get_next_nonce<P, N>(&self, provider: &P, address: Address) -> Transpor
 }
 
 impl PendingNonceManager {
+    /// Returns the last nonce allocated locally for `address`.
+    ///
+    /// Returns `None` if the address has never been used or its nonce was reset,
+    /// meaning the next allocation will query the RPC provider.
+    pub async fn tracked_nonce(&self, address: Address) -> Option<u64> {
+        let nonce_lock = Arc::clone(self.nonces.get(&address)?.value());
+        let nonce = *nonce_lock.lock().await;
+        (nonce != u64::MAX).then_some(nonce)
+    }
+
+    /// Returns the addresses currently tracked by this manager.
+    #[must_use]
+    pub fn tracked_addresses(&self) -> Vec<Address> {
+        self.nonces.iter().map(|entry| *entry.key()).collect()
+    }
+
     /// Resets the cached nonce for a given address, forcing a fresh query on next use.
     ///
     /// This should be called when a transaction fails, as we cannot be certain of the
@@ -92,3 +108,79 @@ pub async fn reset_nonce(&self, address: Address) {
         }
     }
 }
+
+/// Snapshot of the nonce state of a single signer, for diagnosing settlement stalls.
+///
+/// Combines the on-chain view (mined and pending transaction counts) with the
+/// nonce tracked locally by [`PendingNonceManager`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonceState {
+    /// The signer address.
+    pub signer: Address,
+    /// Transaction count at the latest block (next nonce to be mined).
+    pub onchain_latest: u64,
+    /// Transaction count including the mempool (next nonce the node would accept).
+    pub onchain_pending: u64,
+    /// Last nonce allocated locally, or `None` if untracked.
+    pub tracked: Option<u64>,
+}
+
+impl NonceState {
+    /// Number of transactions sitting in the mempool without being mined.
+    ///
+    /// A persistently non-zero value usually means transactions are stuck
+    /// (e.g. underpriced).
+    #[must_use]
+    pub const fn stuck_transactions(&self) -> u64 {
+        self.onchain_pending.saturating_sub(self.onchain_latest)
+    }
+
+    /// Number of nonces allocated locally that the node does not know about.
+    ///
+    /// A non-zero value means locally submitted transactions were dropped, and
+    /// every later transaction will wait on the gap until the nonce is reset.
+    #[must_use]
+    pub fn pending_gap(&self) -> u64 {
+        self.tracked
+            .map_or(0, |nonce| (nonce + 1).saturating_sub(self.onchain_pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tracked_nonce() {
+        let manager = PendingNonceManager::default();
+        let address = Address::repeat_byte(0x01);
+        assert_eq!(manager.tracked_nonce(address).await, None);
+
+        manager.nonces.insert(address, Arc::new(Mutex::new(7)));
+        assert_eq!(manager.tracked_nonce(address).await, Some(7));
+        assert_eq!(manager.tracked_addresses(), vec![address]);
+
+        manager.reset_nonce(address).await;
+        assert_eq!(manager.tracked_nonce(address).await, None);
+    }
+
+    #[test]
+    fn test_nonce_state_reports_pending_gap() {
+        // Nonces 10..=11 are in the mempool, but 12..=14 were allocated locally
+        // and never reached the node.
+        let state = NonceState {
+            signer: Address::repeat_byte(0x01),
+            onchain_latest: 10,
+            onchain_pending: 12,
+            tracked: Some(14),
+        };
+        assert_eq!(state.stuck_transactions(), 2);
+        assert_eq!(state.pending_gap(), 3);
+
+        let untracked = NonceState {
+            tracked: None,
+            ..state
+        };
+        assert_eq!(untracked.pending_gap(), 0);
+    }
+}
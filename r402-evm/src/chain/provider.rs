@@ -1,8 +1,13 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::BuildHasher;
 use std::num::NonZeroUsize;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
 
-use alloy_network::{Ethereum as AlloyEthereum, EthereumWallet, NetworkWallet, TransactionBuilder};
+use alloy_network::{
+    Ethereum as AlloyEthereum, EthereumWallet, Network, NetworkWallet, TransactionBuilder,
+};
 use alloy_primitives::{Address, Bytes};
 use alloy_provider::fillers::{
     BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller, WalletFiller,
@@ -12,16 +17,21 @@
 };
 use alloy_rpc_client::RpcClient;
 use alloy_rpc_types_eth::{BlockId, TransactionReceipt, TransactionRequest};
-use alloy_transport::TransportError;
 use alloy_transport::layers::{FallbackLayer, ThrottleLayer};
+use alloy_transport::{Transport, TransportError};
 use alloy_transport_http::Http;
+use alloy_transport_http::reqwest::Client as HttpClient;
+use alloy_transport_http::reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use r402::chain::{ChainId, ChainProvider};
+use r402::preflight::PreflightError;
 use tower::ServiceBuilder;
 #[cfg(feature = "telemetry")]
 use tracing::Instrument;
 use url::Url;
 
-use crate::chain::nonce::PendingNonceManager;
+use crate::chain::fallback::{AdaptiveFallback, FailoverConfig};
+use crate::chain::nonce::{NonceState, PendingNonceManager};
+use crate::chain::transport::LimitedHttp;
 use crate::chain::types::Eip155ChainReference;
 
 /// Combined filler type for gas, blob gas, nonce, and chain ID.
@@ -35,10 +45,82 @@
 /// Combines multiple filler layers for gas, nonce, chain ID, blob gas, and wallet signing,
 /// and wraps a [`RootProvider`] for actual JSON-RPC communication.
 pub type InnerProvider = FillProvider<
-    JoinFill<JoinFill<Identity, InnerFiller>, WalletFiller<EthereumWallet>>,
+    JoinFill<JoinFill<Identity, InnerFiller>, WalletFiller<RotatingWallet>>,
     RootProvider,
 >;
 
+/// Signers currently used to send transactions.
+#[derive(Debug)]
+struct SignerPool {
+    wallet: EthereumWallet,
+    addresses: Arc<Vec<Address>>,
+}
+
+/// Wallet whose signers can be replaced at runtime.
+///
+/// Shared between [`Eip155ChainProvider`] and its wallet filler; see
+/// [`Eip155ChainProvider::update_signers`].
+#[derive(Clone)]
+pub struct RotatingWallet(Arc<RwLock<SignerPool>>);
+
+impl RotatingWallet {
+    fn new(wallet: EthereumWallet, addresses: Vec<Address>) -> Self {
+        Self(Arc::new(RwLock::new(SignerPool {
+            wallet,
+            addresses: Arc::new(addresses),
+        })))
+    }
+
+    /// Returns the addresses of the current signers.
+    #[must_use]
+    pub fn addresses(&self) -> Arc<Vec<Address>> {
+        let pool = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(&pool.addresses)
+    }
+
+    fn replace(&self, wallet: EthereumWallet, addresses: Vec<Address>) {
+        let mut pool = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        *pool = SignerPool {
+            wallet,
+            addresses: Arc::new(addresses),
+        };
+    }
+}
+
+impl fmt::Debug for RotatingWallet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RotatingWallet")
+            .field(&self.addresses())
+            .finish()
+    }
+}
+
+impl NetworkWallet<AlloyEthereum> for RotatingWallet {
+    fn default_signer_address(&self) -> Address {
+        self.addresses()[0]
+    }
+
+    fn has_signer_for(&self, address: &Address) -> bool {
+        self.addresses().contains(address)
+    }
+
+    fn signer_addresses(&self) -> impl Iterator<Item = Address> {
+        self.addresses().to_vec().into_iter()
+    }
+
+    async fn sign_transaction_from(
+        &self,
+        sender: Address,
+        tx: <AlloyEthereum as Network>::UnsignedTx,
+    ) -> alloy_signer::Result<<AlloyEthereum as Network>::TxEnvelope> {
+        let wallet = {
+            let pool = self.0.read().unwrap_or_else(PoisonError::into_inner);
+            pool.wallet.clone()
+        };
+        NetworkWallet::<AlloyEthereum>::sign_transaction_from(&wallet, sender, tx).await
+    }
+}
+
 /// Provider for interacting with EVM-compatible blockchains.
 ///
 /// This provider handles:
@@ -58,6 +140,11 @@
 /// Uses [`PendingNonceManager`] to track nonces locally and query pending
 /// transactions on initialization. If a transaction fails, the nonce is
 /// automatically reset to force a fresh query on the next transaction.
+///
+/// # Signer Rotation
+///
+/// [`Self::update_signers`] replaces the signers at runtime, e.g. to retire a
+/// compromised or drained key without restarting the facilitator.
 #[derive(Debug)]
 pub struct Eip155ChainProvider {
     chain: Eip155ChainReference,
@@ -65,8 +152,11 @@ pub struct Eip155ChainProvider {
     flashblocks: bool,
     receipt_timeout_secs: u64,
     inner: InnerProvider,
-    /// Available signer addresses for round-robin selection.
-    signer_addresses: Arc<Vec<Address>>,
+    /// Available signers for round-robin selection.
+    wallet: RotatingWallet,
+    /// Held shared by every transaction in flight and exclusively while the
+    /// signers are replaced.
+    rotation: tokio::sync::RwLock<()>,
     /// Current position in round-robin signer rotation.
     signer_cursor: Arc<AtomicUsize>,
     /// Nonce manager for resetting nonces on transaction failures.
@@ -82,9 +172,81 @@ impl Eip155ChainProvider {
     /// # Panics
     ///
     /// Panics if no valid HTTP transports remain after filtering.
-    #[allow(unused_variables)] // chain_id is needed for tracing only
     #[must_use]
     pub fn rpc_client(chain_id: &ChainId, endpoints: &[(Url, Option<u32>)]) -> RpcClient {
+        Self::build_rpc_client(chain_id, endpoints, &HttpClient::new(), None, None)
+    }
+
+    /// Creates an RPC client whose requests all carry `headers`.
+    ///
+    /// The headers are sent to every endpoint, including fallbacks, which lets
+    /// operators use authenticated RPC providers without putting the API key in
+    /// the URL. See [`rpc_headers`] for building `headers` from configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying HTTP client cannot be built.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no valid HTTP transports remain after filtering.
+    pub fn rpc_client_with_headers(
+        chain_id: &ChainId,
+        endpoints: &[(Url, Option<u32>)],
+        headers: HeaderMap,
+    ) -> Result<RpcClient, Box<dyn std::error::Error>> {
+        let config = RpcClientConfig {
+            headers,
+            ..RpcClientConfig::default()
+        };
+        Self::rpc_client_with_config(chain_id, endpoints, config)
+    }
+
+    /// Creates an RPC client with the headers, limits and connection pool
+    /// settings of `config`.
+    ///
+    /// A single HTTP client, and hence a single connection pool, serves all
+    /// `endpoints`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying HTTP client cannot be built.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no valid HTTP transports remain after filtering.
+    pub fn rpc_client_with_config(
+        chain_id: &ChainId,
+        endpoints: &[(Url, Option<u32>)],
+        config: RpcClientConfig,
+    ) -> Result<RpcClient, Box<dyn std::error::Error>> {
+        let mut http = HttpClient::builder().default_headers(config.headers);
+        if let Some(max_idle) = config.pool_max_idle_per_host {
+            http = http.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(secs) = config.pool_idle_timeout_secs {
+            http = http.pool_idle_timeout(std::time::Duration::from_secs(secs));
+        }
+        if let Some(secs) = config.tcp_keepalive_secs {
+            http = http.tcp_keepalive(std::time::Duration::from_secs(secs));
+        }
+        let http = http.build()?;
+        Ok(Self::build_rpc_client(
+            chain_id,
+            endpoints,
+            &http,
+            config.max_response_bytes,
+            config.failover,
+        ))
+    }
+
+    fn build_rpc_client(
+        chain_id: &ChainId,
+        endpoints: &[(Url, Option<u32>)],
+        http: &HttpClient,
+        max_response_bytes: Option<usize>,
+        failover: Option<FailoverConfig>,
+    ) -> RpcClient {
         let transports = endpoints
             .iter()
             .filter_map(|(url, rate_limit)| {
@@ -96,12 +258,19 @@ pub fn rpc_client(chain_id: &ChainId, endpoints: &[(Url, Option<u32>)]) -> RpcCl
                 #[cfg(feature = "telemetry")]
                 tracing::info!(chain=%chain_id, rpc_url=%url, rate_limit=?rate_limit, "Using HTTP transport");
                 let limit = rate_limit.unwrap_or(u32::MAX);
+                let transport = max_response_bytes.map_or_else(
+                    || Http::with_client(http.clone(), url.clone()).boxed(),
+                    |max| LimitedHttp::new(http.clone(), url.clone(), max).boxed(),
+                );
                 let service = ServiceBuilder::new()
                     .layer(ThrottleLayer::new(limit))
-                    .service(Http::new(url.clone()));
+                    .service(transport);
                 Some(service)
             })
             .collect::<Vec<_>>();
+        if let Some(failover) = failover {
+            return RpcClient::new(AdaptiveFallback::new(chain_id, transports, failover), false);
+        }
         let fallback = ServiceBuilder::new()
             .layer(
                 FallbackLayer::default().with_active_transport_count(
@@ -118,7 +287,8 @@ pub fn rpc_client(chain_id: &ChainId, endpoints: &[(Url, Option<u32>)]) -> RpcCl
     /// # Parameters
     ///
     /// - `chain`: The numeric chain reference (e.g., 8453 for Base)
-    /// - `wallet`: A pre-built Ethereum wallet containing one or more signers
+    /// - `wallet`: A pre-built Ethereum wallet containing one or more signers;
+    ///   see [`facilitator_wallet`](super::facilitator_wallet) for KMS or HSM keys
     /// - `rpc_endpoints`: HTTP RPC endpoints as `(url, optional_rate_limit)` pairs
     /// - `eip1559`: Whether the chain supports EIP-1559 gas pricing
     /// - `flashblocks`: Whether the chain supports flashblocks
@@ -134,18 +304,45 @@ pub fn new(
         eip1559: bool,
         flashblocks: bool,
         receipt_timeout_secs: u64,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let client = Self::rpc_client(&chain.into(), rpc_endpoints);
+        Self::from_rpc_client(
+            chain,
+            wallet,
+            client,
+            eip1559,
+            flashblocks,
+            receipt_timeout_secs,
+        )
+    }
+
+    /// Creates a new EVM chain provider over an existing RPC client.
+    ///
+    /// Use together with [`Self::rpc_client_with_headers`] to reach
+    /// endpoints that require authentication headers. Parameters are otherwise
+    /// the same as for [`Self::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the wallet has no signers.
+    pub fn from_rpc_client(
+        chain: Eip155ChainReference,
+        wallet: EthereumWallet,
+        client: RpcClient,
+        eip1559: bool,
+        flashblocks: bool,
+        receipt_timeout_secs: u64,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let signer_addresses =
             NetworkWallet::<AlloyEthereum>::signer_addresses(&wallet).collect::<Vec<_>>();
         if signer_addresses.is_empty() {
             return Err("at least one signer must be provided".into());
         }
-        let signer_addresses = Arc::new(signer_addresses);
+        #[cfg(feature = "telemetry")]
+        tracing::info!(chain=%ChainId::from(chain), signers=?signer_addresses, "Using EVM provider");
+        let wallet = RotatingWallet::new(wallet, signer_addresses);
         let signer_cursor = Arc::new(AtomicUsize::new(0));
 
-        let chain_id: ChainId = chain.into();
-        let client = Self::rpc_client(&chain_id, rpc_endpoints);
-
         let nonce_manager = PendingNonceManager::default();
         let filler = JoinFill::new(
             GasFiller,
@@ -159,37 +356,191 @@ pub fn new(
         );
         let inner: InnerProvider = ProviderBuilder::default()
             .filler(filler)
-            .wallet(wallet)
+            .wallet(wallet.clone())
             .connect_client(client);
 
-        #[cfg(feature = "telemetry")]
-        tracing::info!(chain=%chain_id, signers=?signer_addresses, "Using EVM provider");
-
         Ok(Self {
             chain,
             eip1559,
             flashblocks,
             receipt_timeout_secs,
             inner,
-            signer_addresses,
+            wallet,
+            rotation: tokio::sync::RwLock::new(()),
             signer_cursor,
             nonce_manager,
         })
     }
 
+    /// Replaces the signers used to send transactions.
+    ///
+    /// Waits for transactions in flight to complete with the current signers
+    /// and holds new ones back until the swap is done, so no transaction is
+    /// sent by a signer being retired once this returns. Nonces of the new
+    /// signers are queried afresh, and [`ChainProvider::signer_addresses`],
+    /// which backs `/supported`, reports them from then on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the wallet has no signers.
+    pub async fn update_signers(
+        &self,
+        wallet: EthereumWallet,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let signer_addresses =
+            NetworkWallet::<AlloyEthereum>::signer_addresses(&wallet).collect::<Vec<_>>();
+        if signer_addresses.is_empty() {
+            return Err("at least one signer must be provided".into());
+        }
+        let _rotation = self.rotation.write().await;
+        for &signer in &signer_addresses {
+            self.nonce_manager.reset_nonce(signer).await;
+        }
+        #[cfg(feature = "telemetry")]
+        tracing::info!(chain=%self.chain_id(), signers=?signer_addresses, "Rotated EVM signers");
+        self.wallet.replace(wallet, signer_addresses);
+        self.signer_cursor.store(0, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Returns the nonce manager shared with the transaction filler.
+    #[must_use]
+    pub const fn nonce_manager(&self) -> &PendingNonceManager {
+        &self.nonce_manager
+    }
+
+    /// Collects the nonce state of every configured signer.
+    ///
+    /// Queries the latest and pending transaction counts for each signer and
+    /// pairs them with the locally tracked nonce.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransportError`] if an RPC call fails.
+    pub async fn nonce_states(&self) -> Result<Vec<NonceState>, TransportError> {
+        let signer_addresses = self.wallet.addresses();
+        let mut states = Vec::with_capacity(signer_addresses.len());
+        for &signer in &*signer_addresses {
+            let (onchain_latest, onchain_pending) = tokio::try_join!(
+                self.inner
+                    .get_transaction_count(signer)
+                    .latest()
+                    .into_future(),
+                self.inner
+                    .get_transaction_count(signer)
+                    .pending()
+                    .into_future(),
+            )?;
+            states.push(NonceState {
+                signer,
+                onchain_latest,
+                onchain_pending,
+                tracked: self.nonce_manager.tracked_nonce(signer).await,
+            });
+        }
+        Ok(states)
+    }
+
+    /// Checks that the RPC endpoint is reachable and serves the configured
+    /// chain, returning the signer addresses on success.
+    ///
+    /// Lets an operator validate a configuration at startup without serving,
+    /// see [`r402::preflight`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PreflightError::Unreachable`] if the chain ID cannot be
+    /// queried, and [`PreflightError::ChainIdMismatch`] if the endpoint
+    /// serves another chain.
+    pub async fn preflight(&self) -> Result<Vec<String>, PreflightError> {
+        let actual = self
+            .inner
+            .get_chain_id()
+            .await
+            .map_err(|e| PreflightError::Unreachable(e.to_string()))?;
+        if actual != self.chain.inner() {
+            return Err(PreflightError::ChainIdMismatch {
+                expected: self.chain.inner().to_string(),
+                actual: actual.to_string(),
+            });
+        }
+        Ok(self.signer_addresses())
+    }
+
     /// Round-robin selection of next signer from wallet.
     fn next_signer_address(&self) -> Address {
-        debug_assert!(!self.signer_addresses.is_empty());
-        if self.signer_addresses.len() == 1 {
-            self.signer_addresses[0]
+        let signer_addresses = self.wallet.addresses();
+        debug_assert!(!signer_addresses.is_empty());
+        if signer_addresses.len() == 1 {
+            signer_addresses[0]
         } else {
-            let next =
-                self.signer_cursor.fetch_add(1, Ordering::Relaxed) % self.signer_addresses.len();
-            self.signer_addresses[next]
+            let next = self.signer_cursor.fetch_add(1, Ordering::Relaxed) % signer_addresses.len();
+            signer_addresses[next]
         }
     }
 }
 
+/// Options of the RPC client built by [`Eip155ChainProvider::rpc_client_with_config`].
+#[derive(Debug, Clone, Default)]
+pub struct RpcClientConfig {
+    /// Headers sent with every request, e.g. to authenticate with the RPC
+    /// provider. See [`rpc_headers`].
+    ///
+    /// Default: none.
+    pub headers: HeaderMap,
+    /// Largest response body accepted from an endpoint, in bytes. Larger
+    /// responses fail with a transport error instead of being buffered.
+    ///
+    /// Default: unlimited.
+    pub max_response_bytes: Option<usize>,
+    /// Most idle connections kept open per RPC host for reuse. `Some(0)`
+    /// disables connection reuse.
+    ///
+    /// Default: unbounded.
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Seconds an idle connection is kept open before being closed.
+    ///
+    /// Default: 90.
+    pub pool_idle_timeout_secs: Option<u64>,
+    /// Interval of the TCP keep-alive probes sent on idle connections, in
+    /// seconds, so that pooled connections are not silently dropped by
+    /// load balancers in front of the RPC provider.
+    ///
+    /// Default: disabled.
+    pub tcp_keepalive_secs: Option<u64>,
+    /// Sends requests to one endpoint at a time, in the given order, failing
+    /// over to the next when it keeps failing. See [`AdaptiveFallback`].
+    ///
+    /// Default: none, requests race over all endpoints.
+    pub failover: Option<FailoverConfig>,
+}
+
+/// Builds the HTTP headers attached to every JSON-RPC request.
+///
+/// Values of the form `$NAME` are read from the environment variable `NAME`,
+/// so secrets such as API keys can be kept out of configuration files.
+///
+/// # Errors
+///
+/// Returns an error if a referenced environment variable is not set, or if a
+/// header name or value is not valid HTTP.
+pub fn rpc_headers<S: BuildHasher>(
+    headers: &HashMap<String, String, S>,
+) -> Result<HeaderMap, Box<dyn std::error::Error>> {
+    let mut map = HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers {
+        let value = match value.strip_prefix('$') {
+            Some(var) => std::env::var(var)
+                .map_err(|e| format!("RPC header {name}: environment variable {var}: {e}"))?,
+            None => value.clone(),
+        };
+        let mut value = HeaderValue::from_str(&value)?;
+        value.set_sensitive(true);
+        map.insert(HeaderName::from_bytes(name.as_bytes())?, value);
+    }
+    Ok(map)
+}
+
 /// Errors that can occur when sending a meta-transaction.
 #[derive(Debug, thiserror::Error)]
 pub enum MetaTransactionSendError {
@@ -320,6 +671,8 @@ async fn send_transaction(
         &self,
         tx: MetaTransaction,
     ) -> Result<TransactionReceipt, Self::Error> {
+        // Keeps the signers from being rotated until this transaction is done.
+        let _rotation = self.rotation.read().await;
         let from_address = self.next_signer_address();
         let mut txr = TransactionRequest::default()
             .with_to(tx.to)
@@ -377,3 +730,261 @@ async fn send_transaction(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use alloy_signer_local::PrivateKeySigner;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    use super::*;
+    use crate::mock::{mock_rpc, provider, rpc_response};
+
+    #[tokio::test]
+    async fn test_rpc_headers_are_sent_to_endpoint() {
+        let server = mock_rpc([]).await;
+
+        let headers = rpc_headers(&HashMap::from([(
+            "x-api-key".to_owned(),
+            "secret".to_owned(),
+        )]))
+        .unwrap();
+        let url = Url::parse(&server.uri()).unwrap();
+        let client = Eip155ChainProvider::rpc_client_with_headers(
+            &ChainId::new("eip155", "8453"),
+            &[(url, None)],
+            headers,
+        )
+        .unwrap();
+
+        let chain_id: alloy_primitives::U64 = client.request_noparams("eth_chainId").await.unwrap();
+        assert_eq!(chain_id, alloy_primitives::U64::from(8453));
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].headers["x-api-key"], "secret");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_rpc_response_is_rejected() {
+        let padding = "0".repeat(64 * 1024);
+        let server = mock_rpc([("eth_call", serde_json::json!(format!("0x{padding}")))]).await;
+
+        let url = Url::parse(&server.uri()).unwrap();
+        let client = |max_response_bytes| {
+            let config = RpcClientConfig {
+                max_response_bytes: Some(max_response_bytes),
+                ..RpcClientConfig::default()
+            };
+            Eip155ChainProvider::rpc_client_with_config(
+                &ChainId::new("eip155", "8453"),
+                &[(url.clone(), None)],
+                config,
+            )
+            .unwrap()
+        };
+
+        let err = client(1024)
+            .request_noparams::<String>("eth_call")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeds the limit of 1024 bytes"));
+
+        let result: String = client(1024 * 1024)
+            .request_noparams("eth_call")
+            .await
+            .unwrap();
+        assert_eq!(result.len(), padding.len() + 2);
+    }
+
+    /// Serves `eth_chainId` over plain HTTP/1.1 keep-alive connections,
+    /// counting the connections accepted.
+    fn counting_rpc_server() -> (Url, Arc<AtomicUsize>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = Arc::clone(&connections);
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().map_while(Result::ok) {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || {
+                    let mut buf = [0u8; 4096];
+                    // Requests are small enough to arrive in a single read.
+                    while let Ok(n @ 1..) = stream.read(&mut buf) {
+                        let request = String::from_utf8_lossy(&buf[..n]);
+                        let (_, body) = request.split_once("\r\n\r\n").unwrap();
+                        let body: serde_json::Value = serde_json::from_str(body).unwrap();
+                        let body = rpc_response(&body, &"0x2105".into()).to_string();
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+                            body.len()
+                        );
+                        if stream.write_all(response.as_bytes()).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        (url, connections)
+    }
+
+    #[tokio::test]
+    async fn test_pool_settings_apply_to_rpc_connections() {
+        let connections = |pool_max_idle_per_host| async move {
+            let (url, connections) = counting_rpc_server();
+            let config = RpcClientConfig {
+                pool_max_idle_per_host,
+                pool_idle_timeout_secs: Some(30),
+                tcp_keepalive_secs: Some(15),
+                ..RpcClientConfig::default()
+            };
+            let client = Eip155ChainProvider::rpc_client_with_config(
+                &ChainId::new("eip155", "8453"),
+                &[(url, None)],
+                config,
+            )
+            .unwrap();
+            for _ in 0..3 {
+                let _: alloy_primitives::U64 =
+                    client.request_noparams("eth_chainId").await.unwrap();
+            }
+            connections.load(Ordering::SeqCst)
+        };
+
+        assert_eq!(connections(None).await, 1);
+        assert_eq!(connections(Some(0)).await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_preflight_checks_rpc_chain_id() {
+        let server = mock_rpc([]).await;
+        let signer = PrivateKeySigner::random();
+        let provider = |chain_id| {
+            Eip155ChainProvider::new(
+                Eip155ChainReference::new(chain_id),
+                EthereumWallet::new(signer.clone()),
+                &[(Url::parse(&server.uri()).unwrap(), None)],
+                true,
+                false,
+                30,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            provider(8453).preflight().await.unwrap(),
+            vec![signer.address().to_string()]
+        );
+        assert_eq!(
+            provider(1).preflight().await.unwrap_err(),
+            PreflightError::ChainIdMismatch {
+                expected: "1".into(),
+                actual: "8453".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_unresolved_signer_key_fails_preflight() {
+        let missing = std::env::temp_dir().join("r402-evm-missing-signer-key");
+        let key = format!("file:{}", missing.display());
+        assert!(matches!(
+            crate::chain::signer::local_signer(&key),
+            Err(PreflightError::Signer(_))
+        ));
+        assert!(matches!(
+            crate::chain::signer::local_signer("0xnot-a-key"),
+            Err(PreflightError::Signer(_))
+        ));
+        let key = PrivateKeySigner::random();
+        let encoded = alloy_primitives::hex::encode(key.to_bytes());
+        assert_eq!(
+            crate::chain::signer::local_signer(&encoded)
+                .unwrap()
+                .address(),
+            key.address()
+        );
+    }
+
+    #[test]
+    fn test_rpc_header_values_resolve_from_env() {
+        let headers = HashMap::from([(
+            "authorization".to_owned(),
+            "$R402_TEST_UNSET_RPC_TOKEN".to_owned(),
+        )]);
+        assert!(rpc_headers(&headers).is_err());
+
+        let headers = HashMap::from([("authorization".to_owned(), "$PATH".to_owned())]);
+        let map = rpc_headers(&headers).unwrap();
+        assert_eq!(
+            map["authorization"].to_str().unwrap(),
+            std::env::var("PATH").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_signers_drains_in_flight_transactions() {
+        // Gas estimation is the first RPC call of a transaction: record its
+        // sender and fail it after a delay so it stays in flight meanwhile.
+        let server = MockServer::start().await;
+        let senders = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&senders);
+        Mock::given(method("POST"))
+            .respond_with(move |req: &Request| {
+                let body: serde_json::Value = req.body_json().unwrap();
+                let from: Address = body["params"][0]["from"].as_str().unwrap().parse().unwrap();
+                recorded.lock().unwrap().push(from);
+                ResponseTemplate::new(200)
+                    .set_delay(Duration::from_millis(200))
+                    .set_body_json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": body["id"],
+                        "error": { "code": -32000, "message": "execution reverted" }
+                    }))
+            })
+            .mount(&server)
+            .await;
+
+        let provider = Arc::new(provider(&server));
+        let old_signer: Address = provider.signer_addresses()[0].parse().unwrap();
+        let new_signer = PrivateKeySigner::random();
+        let tx = || MetaTransaction {
+            to: Address::ZERO,
+            calldata: Bytes::new(),
+            confirmations: 1,
+        };
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let in_flight = {
+            let provider = Arc::clone(&provider);
+            let events = Arc::clone(&events);
+            tokio::spawn(async move {
+                let result = provider.send_transaction(tx()).await;
+                events.lock().unwrap().push("settled");
+                result
+            })
+        };
+        // Let the transaction start and pick its signer before rotating.
+        tokio::task::yield_now().await;
+        provider
+            .update_signers(EthereumWallet::new(new_signer.clone()))
+            .await
+            .unwrap();
+        events.lock().unwrap().push("rotated");
+
+        assert!(in_flight.await.unwrap().is_err());
+        assert_eq!(*events.lock().unwrap(), ["settled", "rotated"]);
+        assert_eq!(
+            provider.signer_addresses(),
+            [new_signer.address().to_string()]
+        );
+
+        assert!(provider.send_transaction(tx()).await.is_err());
+        assert_eq!(*senders.lock().unwrap(), [old_signer, new_signer.address()]);
+    }
+}
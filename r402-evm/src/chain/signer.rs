@@ -0,0 +1,174 @@
+//! Pluggable signing backends for facilitator transactions.
+//!
+//! The facilitator signs settlement transactions with local keys by default.
+//! [`FacilitatorSigner`] abstracts over the key so that operators can sign
+//! with a remote backend such as AWS KMS or a cloud HSM instead. Any
+//! [`alloy_signer::Signer`] (including alloy's KMS signers) is a
+//! `FacilitatorSigner`; other backends implement the trait directly.
+//!
+//! Use [`facilitator_wallet`] to build the wallet passed to
+//! [`Eip155ChainProvider::new`](super::Eip155ChainProvider::new).
+
+use std::sync::Arc;
+
+use alloy_consensus::SignableTransaction;
+use alloy_network::{EthereumWallet, TxSigner};
+use alloy_primitives::{Address, B256, Signature};
+use alloy_signer_local::PrivateKeySigner;
+use async_trait::async_trait;
+use r402::preflight::{PreflightError, resolve_signer_key};
+
+/// A key that signs facilitator transactions.
+#[async_trait]
+pub trait FacilitatorSigner: Send + Sync {
+    /// Returns the address transactions are sent from.
+    fn address(&self) -> Address;
+
+    /// Signs a transaction signature hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend fails to produce a signature.
+    async fn sign_hash(&self, hash: &B256) -> alloy_signer::Result<Signature>;
+}
+
+#[async_trait]
+impl<S> FacilitatorSigner for S
+where
+    S: alloy_signer::Signer + Send + Sync,
+{
+    fn address(&self) -> Address {
+        alloy_signer::Signer::address(self)
+    }
+
+    async fn sign_hash(&self, hash: &B256) -> alloy_signer::Result<Signature> {
+        alloy_signer::Signer::sign_hash(self, hash).await
+    }
+}
+
+/// Adapts a [`FacilitatorSigner`] to alloy's transaction signer interface.
+#[derive(Clone)]
+pub struct FacilitatorTxSigner(Arc<dyn FacilitatorSigner>);
+
+impl FacilitatorTxSigner {
+    /// Wraps `signer`.
+    pub fn new(signer: impl FacilitatorSigner + 'static) -> Self {
+        Self(Arc::new(signer))
+    }
+}
+
+impl From<Arc<dyn FacilitatorSigner>> for FacilitatorTxSigner {
+    fn from(signer: Arc<dyn FacilitatorSigner>) -> Self {
+        Self(signer)
+    }
+}
+
+impl std::fmt::Debug for FacilitatorTxSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("FacilitatorTxSigner")
+            .field(&self.0.address())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl TxSigner<Signature> for FacilitatorTxSigner {
+    fn address(&self) -> Address {
+        self.0.address()
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<Signature>,
+    ) -> alloy_signer::Result<Signature> {
+        self.0.sign_hash(&tx.signature_hash()).await
+    }
+}
+
+/// Resolves and parses a configured local signer key.
+///
+/// The key is hex-encoded and may reference a secret file, see
+/// [`r402::secret::resolve_secret`].
+///
+/// # Errors
+///
+/// Returns [`PreflightError::Signer`] if the key cannot be resolved or is not
+/// a valid private key.
+pub fn local_signer(value: &str) -> Result<PrivateKeySigner, PreflightError> {
+    resolve_signer_key(value)?
+        .parse()
+        .map_err(|e| PreflightError::Signer(format!("invalid private key: {e}")))
+}
+
+/// Builds a wallet signing with `signers`.
+///
+/// The first signer is the wallet's default. Returns `None` if `signers` is
+/// empty.
+pub fn facilitator_wallet(
+    signers: impl IntoIterator<Item = Arc<dyn FacilitatorSigner>>,
+) -> Option<EthereumWallet> {
+    let mut signers = signers.into_iter().map(FacilitatorTxSigner::from);
+    let mut wallet = EthereumWallet::new(signers.next()?);
+    for signer in signers {
+        wallet.register_signer(signer);
+    }
+    Some(wallet)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_consensus::transaction::SignerRecoverable;
+    use alloy_network::{Ethereum, NetworkWallet, TransactionBuilder};
+    use alloy_primitives::U256;
+    use alloy_rpc_types_eth::TransactionRequest;
+
+    use super::*;
+
+    /// Stands in for a remote KMS key: only exposes an address and signs hashes.
+    struct MockKms {
+        key: PrivateKeySigner,
+    }
+
+    #[async_trait]
+    impl FacilitatorSigner for MockKms {
+        fn address(&self) -> Address {
+            self.key.address()
+        }
+
+        async fn sign_hash(&self, hash: &B256) -> alloy_signer::Result<Signature> {
+            alloy_signer::SignerSync::sign_hash_sync(&self.key, hash)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kms_signer_produces_valid_transaction_signature() {
+        let kms: Arc<dyn FacilitatorSigner> = Arc::new(MockKms {
+            key: PrivateKeySigner::random(),
+        });
+        let address = kms.address();
+        let wallet = facilitator_wallet([kms]).unwrap();
+        assert_eq!(
+            NetworkWallet::<Ethereum>::default_signer_address(&wallet),
+            address
+        );
+
+        let request = TransactionRequest::default()
+            .with_from(address)
+            .with_to(Address::repeat_byte(0x22))
+            .with_value(U256::ZERO)
+            .with_nonce(0)
+            .with_chain_id(8453)
+            .with_gas_limit(100_000)
+            .with_max_fee_per_gas(1_000_000_000)
+            .with_max_priority_fee_per_gas(1_000_000);
+        let envelope = NetworkWallet::<Ethereum>::sign_request(&wallet, request)
+            .await
+            .unwrap();
+        assert_eq!(envelope.recover_signer().unwrap(), address);
+    }
+
+    #[test]
+    fn test_empty_signers_build_no_wallet() {
+        assert!(facilitator_wallet(Vec::new()).is_none());
+    }
+}
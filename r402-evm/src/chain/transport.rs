@@ -0,0 +1,90 @@
+//! HTTP JSON-RPC transport with a response size limit.
+//!
+//! Alloy's HTTP transport buffers every response body whole, so a malicious
+//! or misbehaving RPC endpoint can make the facilitator allocate arbitrarily
+//! large buffers. [`LimitedHttp`] reads the body incrementally and fails with
+//! a transport error as soon as it grows past the configured limit.
+
+use std::task::{Context, Poll};
+
+use alloy_json_rpc::{RequestPacket, ResponsePacket};
+use alloy_transport::{TransportError, TransportErrorKind, TransportFut, TransportResult};
+use alloy_transport_http::reqwest::Client as HttpClient;
+use alloy_transport_http::reqwest::header::{CONTENT_TYPE, HeaderValue};
+use tower::Service;
+use url::Url;
+
+/// HTTP JSON-RPC transport rejecting responses larger than a byte limit.
+#[derive(Debug, Clone)]
+pub struct LimitedHttp {
+    client: HttpClient,
+    url: Url,
+    max_response_bytes: usize,
+}
+
+impl LimitedHttp {
+    /// Creates a transport posting to `url` with `client`, accepting response
+    /// bodies of at most `max_response_bytes`.
+    #[must_use]
+    pub const fn new(client: HttpClient, url: Url, max_response_bytes: usize) -> Self {
+        Self {
+            client,
+            url,
+            max_response_bytes,
+        }
+    }
+
+    async fn send(self, request: RequestPacket) -> TransportResult<ResponsePacket> {
+        let body = serde_json::to_vec(&request).map_err(TransportError::ser_err)?;
+        let mut response = self
+            .client
+            .post(self.url)
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .body(body)
+            .send()
+            .await
+            .map_err(TransportErrorKind::custom)?;
+        let status = response.status();
+
+        let too_large = || {
+            TransportErrorKind::custom_str(&format!(
+                "RPC response exceeds the limit of {} bytes",
+                self.max_response_bytes
+            ))
+        };
+        if response
+            .content_length()
+            .is_some_and(|len| len > self.max_response_bytes as u64)
+        {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(TransportErrorKind::custom)? {
+            if body.len() + chunk.len() > self.max_response_bytes {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        if !status.is_success() {
+            let body = String::from_utf8_lossy(&body).into_owned();
+            return Err(TransportErrorKind::http_error(status.as_u16(), body));
+        }
+        serde_json::from_slice(&body)
+            .map_err(|err| TransportError::deser_err(err, String::from_utf8_lossy(&body)))
+    }
+}
+
+impl Service<RequestPacket> for LimitedHttp {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().send(request))
+    }
+}
@@ -7,7 +7,7 @@
 use std::ops::Mul;
 use std::str::FromStr;
 
-use alloy_primitives::{Address, U256, hex};
+use alloy_primitives::{Address, FixedBytes, U256, hex};
 use r402::amount::{MoneyAmount, MoneyAmountParseError};
 use r402::chain::{ChainId, DeployedTokenAmount};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -239,9 +239,26 @@ pub struct Eip155TokenDeployment {
     pub decimals: u8,
     /// Optional EIP-712 domain parameters for signature verification.
     pub eip712: Option<TokenDeploymentEip712>,
+    /// Function selector of the token's `transferWithAuthorization`, if it
+    /// deviates from ERC-3009.
+    ///
+    /// An escape hatch for non-conforming tokens, such as USDC forks exposing
+    /// the function under a custom selector: register it with the facilitator
+    /// to settle with it. Only the selector is replaced; the arguments and the
+    /// EIP-712 type hash stay standard. `None` for conforming tokens.
+    pub transfer_with_auth_selector: Option<FixedBytes<4>>,
 }
 
 impl Eip155TokenDeployment {
+    /// Sets the non-standard `transferWithAuthorization` selector of the token.
+    ///
+    /// See [`Self::transfer_with_auth_selector`].
+    #[must_use]
+    pub const fn with_transfer_with_auth_selector(mut self, selector: FixedBytes<4>) -> Self {
+        self.transfer_with_auth_selector = Some(selector);
+        self
+    }
+
     /// Creates a token amount from a raw value.
     ///
     /// The value should already be in the token's smallest unit (e.g., wei).
@@ -311,6 +328,7 @@ fn create_test_deployment(decimals: u8) -> Eip155TokenDeployment {
             address: Address::ZERO,
             decimals,
             eip712: None,
+            transfer_with_auth_selector: None,
         }
     }
 
@@ -24,15 +24,26 @@
 //! When an approver is set, the client checks allowances before each Permit2
 //! payment and sends an `approve` transaction if needed, making the experience
 //! as seamless as EIP-3009.
+//!
+//! # Chain Time
+//!
+//! Authorization windows are derived from the system clock unless a
+//! [`PaymentClock`] is set, e.g. one following the latest block timestamp via
+//! [`Eip155ExactClientBuilder::chain_clock`] (`client-provider` feature).
+//! They start [`DEFAULT_VALID_AFTER_BACKDATE`] in the past to tolerate clock
+//! skew; [`Eip155ExactClientBuilder::valid_after_backdate`] tunes this.
 
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
-use alloy_primitives::{Address, Bytes, FixedBytes, Signature, U256};
+use alloy_primitives::{Address, B256, Bytes, FixedBytes, Signature, U256, keccak256};
 use alloy_signer_local::PrivateKeySigner;
 use alloy_sol_types::{SolCall, SolStruct, eip712_domain, sol};
 use r402::proto::Base64Bytes;
+use r402::proto::Extensions;
 use r402::proto::PaymentRequired;
 use r402::proto::UnixTimestamp;
 use r402::proto::v2::{self, ResourceInfo};
@@ -87,6 +98,69 @@ async fn sign_hash(&self, hash: &FixedBytes<32>) -> Result<Signature, alloy_sign
     }
 }
 
+/// A [`SignerLike`] that delegates signing of EIP-712 digests to a callback.
+///
+/// For integrators with their own key management (KMS, HSM, remote signers)
+/// that do not expose an alloy signer: the client assembles the EIP-712
+/// domain, the authorization and the payload, and only hands the digest to
+/// `sign`. The callback must return the 65-byte ECDSA signature of `address`
+/// over the digest, or an EIP-1271/EIP-6492 compatible one for smart wallets.
+pub struct CallbackSigner<F> {
+    address: Address,
+    sign: Arc<F>,
+}
+
+impl<F> CallbackSigner<F> {
+    /// Creates a signer for `address` that signs digests with `sign`.
+    pub fn new(address: Address, sign: F) -> Self {
+        Self {
+            address,
+            sign: Arc::new(sign),
+        }
+    }
+}
+
+impl<F> Clone for CallbackSigner<F> {
+    fn clone(&self) -> Self {
+        Self {
+            address: self.address,
+            sign: Arc::clone(&self.sign),
+        }
+    }
+}
+
+impl<F> std::fmt::Debug for CallbackSigner<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackSigner")
+            .field("address", &self.address)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F, Fut, E> SignerLike for CallbackSigner<F>
+where
+    F: Fn(B256) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Signature, E>> + Send,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_hash(&self, hash: &FixedBytes<32>) -> Result<Signature, alloy_signer::Error> {
+        (self.sign)(*hash).await.map_err(alloy_signer::Error::other)
+    }
+}
+
+/// An [`Eip155ExactClient`] signing through a [`CallbackSigner`].
+///
+/// ```ignore
+/// let client = CallbackSchemeClient::from_callback(payer, |digest| async move {
+///     my_kms.sign_digest(digest).await
+/// });
+/// ```
+pub type CallbackSchemeClient<F> = Eip155ExactClient<CallbackSigner<F>>;
+
 /// Abstraction for on-chain interactions needed by the Permit2 auto-approve flow.
 ///
 /// Implement this trait to enable automatic Permit2 allowance management
@@ -164,6 +238,174 @@ fn approve_permit2(
     ) -> Pin<Box<dyn Future<Output = Result<(), ClientError>> + Send + '_>>;
 }
 
+/// Source of the current time that authorization windows are derived from.
+///
+/// By default the client uses the system clock. On chains whose block
+/// timestamps drift from it, authorizations may be rejected as early or
+/// expired; a clock following chain time avoids this. With the
+/// **`client-provider`** feature, [`Eip155ExactClientBuilder::chain_clock`]
+/// installs one reading the latest block timestamp.
+pub trait PaymentClock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Pin<Box<dyn Future<Output = Result<UnixTimestamp, ClientError>> + Send + '_>>;
+}
+
+/// Source of the 32-byte nonces that make each signed authorization unique.
+///
+/// Defaults to [`RandomNonces`]. [`SeededNonces`] derives a reproducible
+/// sequence from a seed, for end-to-end tests and audit replay.
+pub trait NonceSource: Send + Sync {
+    /// Returns a nonce this source has never returned before.
+    fn next_nonce(&self) -> [u8; 32];
+}
+
+/// [`NonceSource`] drawing nonces from the thread-local RNG.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomNonces;
+
+impl NonceSource for RandomNonces {
+    fn next_nonce(&self) -> [u8; 32] {
+        rng().random()
+    }
+}
+
+/// Deterministic [`NonceSource`] hashing a seed with a monotonic counter.
+///
+/// The `n`-th nonce is `keccak256(seed || n)` with `n` as big-endian `u64`,
+/// so two sources with the same seed yield the same sequence while nonces of
+/// one source never repeat. Keep the seed secret and never share it between
+/// signers running concurrently: nonces already used on-chain are rejected.
+#[derive(Debug)]
+pub struct SeededNonces {
+    seed: [u8; 32],
+    counter: AtomicU64,
+}
+
+impl SeededNonces {
+    /// Creates a source starting at the first nonce of `seed`'s sequence.
+    #[must_use]
+    pub const fn new(seed: [u8; 32]) -> Self {
+        Self::starting_at(seed, 0)
+    }
+
+    /// Creates a source resuming `seed`'s sequence at index `counter`, e.g.
+    /// after a restart.
+    #[must_use]
+    pub const fn starting_at(seed: [u8; 32], counter: u64) -> Self {
+        Self {
+            seed,
+            counter: AtomicU64::new(counter),
+        }
+    }
+}
+
+impl NonceSource for SeededNonces {
+    fn next_nonce(&self) -> [u8; 32] {
+        let counter = self.counter.fetch_add(1, Ordering::Relaxed);
+        let mut preimage = [0u8; 40];
+        preimage[..32].copy_from_slice(&self.seed);
+        preimage[32..].copy_from_slice(&counter.to_be_bytes());
+        keccak256(preimage).0
+    }
+}
+
+/// How far in the past the default authorization window starts, so that the
+/// payment is valid immediately despite clock drift.
+pub const DEFAULT_VALID_AFTER_BACKDATE: Duration = Duration::from_mins(10);
+
+/// Validity window of a signed ERC-3009 authorization.
+///
+/// The window is guaranteed to be non-empty (`valid_after < valid_before`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthorizationWindow {
+    valid_after: UnixTimestamp,
+    valid_before: UnixTimestamp,
+}
+
+impl AuthorizationWindow {
+    /// Creates a window from explicit bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::SigningError`] if `valid_after` is not strictly
+    /// before `valid_before`.
+    pub fn new(
+        valid_after: UnixTimestamp,
+        valid_before: UnixTimestamp,
+    ) -> Result<Self, ClientError> {
+        if valid_after >= valid_before {
+            return Err(ClientError::SigningError(format!(
+                "empty authorization window: validAfter {valid_after} is not before validBefore {valid_before}"
+            )));
+        }
+        Ok(Self {
+            valid_after,
+            valid_before,
+        })
+    }
+
+    /// Returns the default window for immediate submission.
+    ///
+    /// Starts [`DEFAULT_VALID_AFTER_BACKDATE`] in the past to tolerate clock
+    /// drift and ends `max_timeout_seconds` from now.
+    #[must_use]
+    pub fn immediate(max_timeout_seconds: u64) -> Self {
+        Self::immediate_at(UnixTimestamp::now(), max_timeout_seconds)
+    }
+
+    /// Returns the window for immediate submission relative to `now`.
+    ///
+    /// Same as [`Self::immediate`], but with the current time supplied by the
+    /// caller, e.g. from a [`PaymentClock`].
+    #[must_use]
+    pub fn immediate_at(now: UnixTimestamp, max_timeout_seconds: u64) -> Self {
+        Self::backdated(now, DEFAULT_VALID_AFTER_BACKDATE, max_timeout_seconds)
+    }
+
+    /// Returns the window for immediate submission relative to `now`,
+    /// starting `backdate` in the past.
+    ///
+    /// A longer backdate tolerates more clock skew between the client and the
+    /// chain; a shorter one narrows the window in which the signed payment
+    /// can be executed.
+    #[must_use]
+    pub fn backdated(now: UnixTimestamp, backdate: Duration, max_timeout_seconds: u64) -> Self {
+        Self {
+            valid_after: UnixTimestamp::from_secs(now.as_secs().saturating_sub(backdate.as_secs())),
+            valid_before: now + max_timeout_seconds,
+        }
+    }
+
+    /// Returns a window opening `delay` from now and lasting `lifetime`.
+    ///
+    /// Use this to pre-sign payments that are submitted later, e.g. by a
+    /// scheduler or in a batch.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::SigningError`] if `lifetime` is shorter than one
+    /// second.
+    pub fn for_delayed_submission(
+        delay: Duration,
+        lifetime: Duration,
+    ) -> Result<Self, ClientError> {
+        let valid_after = UnixTimestamp::now() + delay.as_secs();
+        Self::new(valid_after, valid_after + lifetime.as_secs())
+    }
+
+    /// Returns the earliest time the authorization can be executed.
+    #[must_use]
+    pub const fn valid_after(&self) -> UnixTimestamp {
+        self.valid_after
+    }
+
+    /// Returns the time from which the authorization is no longer valid.
+    #[must_use]
+    pub const fn valid_before(&self) -> UnixTimestamp {
+        self.valid_before
+    }
+}
+
 /// Shared EIP-712 signing parameters for ERC-3009 authorization.
 #[derive(Debug, Clone)]
 pub struct Eip3009SigningParams {
@@ -177,14 +419,22 @@ pub struct Eip3009SigningParams {
     pub amount: U256,
     /// Maximum timeout in seconds for the authorization validity window
     pub max_timeout_seconds: u64,
-    /// Optional EIP-712 domain name and version override
+    /// Optional EIP-712 domain name, version and salt override
     pub extra: Option<PaymentRequirementsExtra>,
+    /// Explicit validity window, overriding the one derived from
+    /// `max_timeout_seconds`
+    pub window: Option<AuthorizationWindow>,
+    /// Explicit nonce; a random one if `None`
+    pub nonce: Option<[u8; 32]>,
 }
 
 /// Signs an ERC-3009 `TransferWithAuthorization` using EIP-712.
 /// It constructs the EIP-712 domain, builds the authorization struct with appropriate
 /// timing parameters, and signs the resulting hash.
 ///
+/// The authorization is valid over `params.window` if set, otherwise over
+/// [`AuthorizationWindow::immediate`].
+///
 /// # Errors
 ///
 /// Returns [`ClientError`] if EIP-712 signing fails.
@@ -192,32 +442,54 @@ pub async fn sign_erc3009_authorization<S: SignerLike + Sync>(
     signer: &S,
     params: &Eip3009SigningParams,
 ) -> Result<Eip3009Payload, ClientError> {
+    let (eip712_hash, authorization) = compute_erc3009_digest(signer.address(), params);
+    let signature = signer
+        .sign_hash(&eip712_hash)
+        .await
+        .map_err(|e| ClientError::SigningError(format!("{e:?}")))?;
+    Ok(assemble_eip3009_payload(
+        authorization,
+        signature.as_bytes().into(),
+    ))
+}
+
+/// Computes the EIP-712 digest of an ERC-3009 authorization from `from`,
+/// without signing it.
+///
+/// For hardware wallets and air-gapped signers: sign the returned digest
+/// externally, then pass the signature and the returned authorization to
+/// [`assemble_eip3009_payload`]. The digest is exactly the one
+/// [`sign_erc3009_authorization`] signs. If `params.nonce` is `None`, a random
+/// nonce is drawn and recorded in the returned authorization.
+#[must_use]
+pub fn compute_erc3009_digest(
+    from: Address,
+    params: &Eip3009SigningParams,
+) -> (B256, Eip3009Authorization) {
     let (name, version) = params.extra.as_ref().map_or_else(
         || (String::new(), String::new()),
         |extra| (extra.name.clone(), extra.version.clone()),
     );
 
-    let domain = eip712_domain! {
+    let mut domain = eip712_domain! {
         name: name,
         version: version,
         chain_id: params.chain_id,
         verifying_contract: params.asset_address,
     };
+    domain.salt = params.extra.as_ref().and_then(|extra| extra.salt);
 
-    let now = UnixTimestamp::now();
-    // valid_after should be in the past (10 minutes ago) to ensure the payment is immediately valid
-    let valid_after_secs = now.as_secs().saturating_sub(10 * 60);
-    let valid_after = UnixTimestamp::from_secs(valid_after_secs);
-    let valid_before = now + params.max_timeout_seconds;
-    let nonce: [u8; 32] = rng().random();
-    let nonce = FixedBytes(nonce);
+    let window = params
+        .window
+        .unwrap_or_else(|| AuthorizationWindow::immediate(params.max_timeout_seconds));
+    let nonce = FixedBytes(params.nonce.unwrap_or_else(|| RandomNonces.next_nonce()));
 
     let authorization = Eip3009Authorization {
-        from: signer.address(),
+        from,
         to: params.pay_to,
         value: params.amount.into(),
-        valid_after,
-        valid_before,
+        valid_after: window.valid_after,
+        valid_before: window.valid_before,
         nonce,
     };
 
@@ -233,16 +505,26 @@ pub async fn sign_erc3009_authorization<S: SignerLike + Sync>(
         nonce: authorization.nonce,
     };
 
-    let eip712_hash = transfer_with_authorization.eip712_signing_hash(&domain);
-    let signature = signer
-        .sign_hash(&eip712_hash)
-        .await
-        .map_err(|e| ClientError::SigningError(format!("{e:?}")))?;
+    (
+        transfer_with_authorization.eip712_signing_hash(&domain),
+        authorization,
+    )
+}
 
-    Ok(Eip3009Payload {
-        signature: signature.as_bytes().into(),
+/// Builds the payload of an authorization from [`compute_erc3009_digest`]
+/// and the signature of its digest, produced outside this crate.
+///
+/// `signature` is the 65-byte `r || s || v` ECDSA signature of an EOA, or
+/// the EIP-1271/EIP-6492 signature of a smart wallet.
+#[must_use]
+pub const fn assemble_eip3009_payload(
+    authorization: Eip3009Authorization,
+    signature: Bytes,
+) -> Eip3009Payload {
+    Eip3009Payload {
+        signature,
         authorization,
-    })
+    }
 }
 
 /// Shared signing parameters for Permit2 authorization.
@@ -258,6 +540,13 @@ pub struct Permit2SigningParams {
     pub amount: U256,
     /// Maximum timeout in seconds for the authorization validity window
     pub max_timeout_seconds: u64,
+    /// Time the validity window is derived from; the system clock if `None`
+    pub now: Option<UnixTimestamp>,
+    /// How far before `now` the witness `validAfter` lies;
+    /// [`DEFAULT_VALID_AFTER_BACKDATE`] if `None`
+    pub valid_after_backdate: Option<Duration>,
+    /// Explicit nonce; a random one if `None`
+    pub nonce: Option<[u8; 32]>,
 }
 
 /// Signs a Permit2 `PermitWitnessTransferFrom` using EIP-712.
@@ -279,12 +568,15 @@ pub async fn sign_permit2_authorization<S: SignerLike + Sync>(
         verifying_contract: PERMIT2_ADDRESS,
     };
 
-    let now = UnixTimestamp::now();
-    let valid_after_secs = now.as_secs().saturating_sub(10 * 60);
+    let now = params.now.unwrap_or_else(UnixTimestamp::now);
+    let backdate = params
+        .valid_after_backdate
+        .unwrap_or(DEFAULT_VALID_AFTER_BACKDATE);
+    let valid_after_secs = now.as_secs().saturating_sub(backdate.as_secs());
     let deadline_secs = now.as_secs() + params.max_timeout_seconds;
 
-    // Permit2 uses uint256 nonce (random 32 bytes interpreted as uint256)
-    let nonce_bytes: [u8; 32] = rng().random();
+    // Permit2 uses uint256 nonce (32 bytes interpreted as uint256)
+    let nonce_bytes = params.nonce.unwrap_or_else(|| RandomNonces.next_nonce());
     let nonce = U256::from_be_bytes(nonce_bytes);
 
     let permit_witness = PermitWitnessTransferFrom {
@@ -370,6 +662,9 @@ pub struct Eip155ExactClient<S> {
     signer: S,
     approver: Option<Arc<dyn Permit2Approver>>,
     auto_approve: bool,
+    clock: Option<Arc<dyn PaymentClock>>,
+    valid_after_backdate: Duration,
+    nonces: Option<Arc<dyn NonceSource>>,
 }
 
 impl<S: std::fmt::Debug> std::fmt::Debug for Eip155ExactClient<S> {
@@ -378,6 +673,9 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             .field("signer", &self.signer)
             .field("has_approver", &self.approver.is_some())
             .field("auto_approve", &self.auto_approve)
+            .field("has_clock", &self.clock.is_some())
+            .field("valid_after_backdate", &self.valid_after_backdate)
+            .field("has_nonce_source", &self.nonces.is_some())
             .finish()
     }
 }
@@ -395,6 +693,9 @@ pub const fn new(signer: S) -> Self {
             signer,
             approver: None,
             auto_approve: false,
+            clock: None,
+            valid_after_backdate: DEFAULT_VALID_AFTER_BACKDATE,
+            nonces: None,
         }
     }
 
@@ -413,10 +714,25 @@ pub fn builder(signer: S) -> Eip155ExactClientBuilder<S> {
             signer,
             approver: None,
             auto_approve: true,
+            clock: None,
+            valid_after_backdate: DEFAULT_VALID_AFTER_BACKDATE,
+            nonces: None,
         }
     }
 }
 
+impl<F> Eip155ExactClient<CallbackSigner<F>> {
+    /// Creates a client paying from `address` whose EIP-712 digests are
+    /// signed by `sign`.
+    ///
+    /// Shorthand for `Eip155ExactClient::new(CallbackSigner::new(address, sign))`;
+    /// use [`builder`](Self::builder) with a [`CallbackSigner`] for further
+    /// configuration.
+    pub fn from_callback(address: Address, sign: F) -> Self {
+        Self::new(CallbackSigner::new(address, sign))
+    }
+}
+
 /// Builder for constructing an [`Eip155ExactClient`] with optional Permit2
 /// auto-approve capabilities.
 ///
@@ -443,6 +759,9 @@ pub struct Eip155ExactClientBuilder<S> {
     signer: S,
     approver: Option<Arc<dyn Permit2Approver>>,
     auto_approve: bool,
+    clock: Option<Arc<dyn PaymentClock>>,
+    valid_after_backdate: Duration,
+    nonces: Option<Arc<dyn NonceSource>>,
 }
 
 impl<S: std::fmt::Debug> std::fmt::Debug for Eip155ExactClientBuilder<S> {
@@ -451,6 +770,9 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             .field("signer", &self.signer)
             .field("has_approver", &self.approver.is_some())
             .field("auto_approve", &self.auto_approve)
+            .field("has_clock", &self.clock.is_some())
+            .field("valid_after_backdate", &self.valid_after_backdate)
+            .field("has_nonce_source", &self.nonces.is_some())
             .finish()
     }
 }
@@ -515,16 +837,99 @@ pub fn provider<P: alloy_provider::Provider + Send + Sync + 'static>(
         self.approver(BuiltinPermit2Approver { provider })
     }
 
+    /// Sets the [`PaymentClock`] that authorization windows are derived from.
+    ///
+    /// Defaults to the system clock.
+    #[must_use]
+    pub fn clock<C: PaymentClock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Derives authorization windows from the latest block timestamp read
+    /// through an Alloy [`Provider`](alloy_provider::Provider), instead of
+    /// the system clock.
+    ///
+    /// Costs one RPC call per signed payment.
+    ///
+    /// # Feature
+    ///
+    /// Requires the **`client-provider`** feature flag.
+    #[cfg(feature = "client-provider")]
+    #[must_use]
+    pub fn chain_clock<P: alloy_provider::Provider + Send + Sync + 'static>(
+        self,
+        provider: P,
+    ) -> Self {
+        self.clock(BuiltinChainClock { provider })
+    }
+
+    /// Sets how far in the past authorization windows start.
+    ///
+    /// A longer backdate accepts more clock skew between the client and the
+    /// chain, a shorter one limits how long before signing the payment could
+    /// be executed. Applies to both EIP-3009 and Permit2 payments.
+    ///
+    /// Defaults to [`DEFAULT_VALID_AFTER_BACKDATE`] (10 minutes).
+    #[must_use]
+    pub const fn valid_after_backdate(mut self, backdate: Duration) -> Self {
+        self.valid_after_backdate = backdate;
+        self
+    }
+
+    /// Sets the [`NonceSource`] of signed authorizations.
+    ///
+    /// Defaults to [`RandomNonces`]. Use [`SeededNonces`] for reproducible
+    /// payments.
+    #[must_use]
+    pub fn nonce_source<N: NonceSource + 'static>(mut self, nonces: N) -> Self {
+        self.nonces = Some(Arc::new(nonces));
+        self
+    }
+
     /// Builds the configured [`Eip155ExactClient`].
     pub fn build(self) -> Eip155ExactClient<S> {
         Eip155ExactClient {
             signer: self.signer,
             approver: self.approver,
             auto_approve: self.auto_approve,
+            clock: self.clock,
+            valid_after_backdate: self.valid_after_backdate,
+            nonces: self.nonces,
         }
     }
 }
 
+/// Built-in [`PaymentClock`] reading the latest block timestamp.
+///
+/// Created automatically when calling [`Eip155ExactClientBuilder::chain_clock`].
+#[cfg(feature = "client-provider")]
+struct BuiltinChainClock<P> {
+    provider: P,
+}
+
+#[cfg(feature = "client-provider")]
+impl<P> PaymentClock for BuiltinChainClock<P>
+where
+    P: alloy_provider::Provider + Send + Sync,
+{
+    fn now(&self) -> Pin<Box<dyn Future<Output = Result<UnixTimestamp, ClientError>> + Send + '_>> {
+        Box::pin(async move {
+            let block = self
+                .provider
+                .get_block_by_number(alloy_rpc_types_eth::BlockNumberOrTag::Latest)
+                .await
+                .map_err(|e| {
+                    ClientError::PreConditionFailed(format!("Chain time lookup failed: {e}"))
+                })?
+                .ok_or_else(|| {
+                    ClientError::PreConditionFailed("Chain time lookup found no block".into())
+                })?;
+            Ok(UnixTimestamp::from_secs(block.header.timestamp))
+        })
+    }
+}
+
 /// Built-in [`Permit2Approver`] backed by an Alloy provider.
 ///
 /// Created automatically when calling
@@ -616,11 +1021,16 @@ fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
                     pay_to: requirements.pay_to.to_string(),
                     signer: Box::new(V2PayloadSigner {
                         resource_info: Some(payment_required.resource.clone()),
+                        extensions: requirements
+                            .select_required_extensions(payment_required.extensions.as_ref()),
                         signer: self.signer.clone(),
                         chain_reference,
                         requirements,
                         approver: self.approver.clone(),
                         auto_approve: self.auto_approve,
+                        clock: self.clock.clone(),
+                        valid_after_backdate: self.valid_after_backdate,
+                        nonces: self.nonces.clone(),
                     }),
                 };
                 Some(candidate)
@@ -632,10 +1042,21 @@ fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
 struct V2PayloadSigner<S> {
     signer: S,
     resource_info: Option<ResourceInfo>,
+    extensions: Option<Extensions>,
     chain_reference: Eip155ChainReference,
     requirements: types::v2::PaymentRequirements,
     approver: Option<Arc<dyn Permit2Approver>>,
     auto_approve: bool,
+    clock: Option<Arc<dyn PaymentClock>>,
+    valid_after_backdate: Duration,
+    nonces: Option<Arc<dyn NonceSource>>,
+}
+
+impl<S> V2PayloadSigner<S> {
+    /// Draws a nonce from the configured source, if any.
+    fn next_nonce(&self) -> Option<[u8; 32]> {
+        self.nonces.as_ref().map(|nonces| nonces.next_nonce())
+    }
 }
 
 impl<S> PaymentCandidateSigner for V2PayloadSigner<S>
@@ -650,6 +1071,10 @@ fn sign_payment(&self) -> r402::facilitator::BoxFuture<'_, Result<String, Client
                 .as_ref()
                 .and_then(|e| e.asset_transfer_method)
                 == Some(AssetTransferMethod::Permit2);
+            let now = match &self.clock {
+                Some(clock) => clock.now().await?,
+                None => UnixTimestamp::now(),
+            };
 
             let exact_payload = if use_permit2 {
                 // Auto-approve: ensure Permit2 has sufficient ERC-20 allowance
@@ -681,6 +1106,9 @@ fn sign_payment(&self) -> r402::facilitator::BoxFuture<'_, Result<String, Client
                     pay_to: self.requirements.pay_to.into(),
                     amount: self.requirements.amount.into(),
                     max_timeout_seconds: self.requirements.max_timeout_seconds,
+                    now: Some(now),
+                    valid_after_backdate: Some(self.valid_after_backdate),
+                    nonce: self.next_nonce(),
                 };
                 let permit2_payload = sign_permit2_authorization(&self.signer, &params).await?;
                 ExactPayload::Permit2(permit2_payload)
@@ -692,6 +1120,12 @@ fn sign_payment(&self) -> r402::facilitator::BoxFuture<'_, Result<String, Client
                     amount: self.requirements.amount.into(),
                     max_timeout_seconds: self.requirements.max_timeout_seconds,
                     extra: self.requirements.extra.clone(),
+                    window: Some(AuthorizationWindow::backdated(
+                        now,
+                        self.valid_after_backdate,
+                        self.requirements.max_timeout_seconds,
+                    )),
+                    nonce: self.next_nonce(),
                 };
                 let eip3009_payload = sign_erc3009_authorization(&self.signer, &params).await?;
                 ExactPayload::Eip3009(eip3009_payload)
@@ -702,7 +1136,7 @@ fn sign_payment(&self) -> r402::facilitator::BoxFuture<'_, Result<String, Client
                 accepted: self.requirements.clone(),
                 resource: self.resource_info.clone(),
                 payload: exact_payload,
-                extensions: None,
+                extensions: self.extensions.clone(),
             };
             let json = serde_json::to_vec(&payload)?;
             let b64 = Base64Bytes::encode(&json);
@@ -764,3 +1198,367 @@ pub fn permit2_approval_calldata(token: Address) -> (Address, Bytes) {
     };
     (token, call.abi_encode().into())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(window: Option<AuthorizationWindow>) -> Eip3009SigningParams {
+        Eip3009SigningParams {
+            chain_id: 8453,
+            asset_address: Address::repeat_byte(0x11),
+            pay_to: Address::repeat_byte(0x22),
+            amount: U256::from(1_000u64),
+            max_timeout_seconds: 60,
+            extra: None,
+            window,
+            nonce: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_window_is_immediately_valid() {
+        let now = UnixTimestamp::now();
+        let payload = sign_erc3009_authorization(&PrivateKeySigner::random(), &params(None))
+            .await
+            .unwrap();
+        let authorization = payload.authorization;
+        assert!(authorization.valid_after < now);
+        assert!(authorization.valid_before >= now + 60);
+        assert!(authorization.valid_before <= UnixTimestamp::now() + 60);
+    }
+
+    #[tokio::test]
+    async fn test_window_override_is_signed() {
+        let window = AuthorizationWindow::new(
+            UnixTimestamp::from_secs(1_000),
+            UnixTimestamp::from_secs(2_000),
+        )
+        .unwrap();
+        let payload =
+            sign_erc3009_authorization(&PrivateKeySigner::random(), &params(Some(window)))
+                .await
+                .unwrap();
+        assert_eq!(payload.authorization.valid_after.as_secs(), 1_000);
+        assert_eq!(payload.authorization.valid_before.as_secs(), 2_000);
+    }
+
+    #[tokio::test]
+    async fn test_extra_salt_is_signed() {
+        let signer = PrivateKeySigner::random();
+        let salt = FixedBytes::repeat_byte(0x44);
+        let mut params = params(None);
+        params.extra = Some(PaymentRequirementsExtra {
+            name: "Salted".into(),
+            version: "1".into(),
+            salt: Some(salt),
+            asset_transfer_method: None,
+        });
+        let payload = sign_erc3009_authorization(&signer, &params).await.unwrap();
+
+        let authorization = payload.authorization;
+        let hash = TransferWithAuthorization {
+            from: authorization.from,
+            to: authorization.to,
+            value: authorization.value.into(),
+            validAfter: U256::from(authorization.valid_after.as_secs()),
+            validBefore: U256::from(authorization.valid_before.as_secs()),
+            nonce: authorization.nonce,
+        }
+        .eip712_signing_hash(&eip712_domain! {
+            name: "Salted",
+            version: "1",
+            chain_id: params.chain_id,
+            verifying_contract: params.asset_address,
+            salt: salt,
+        });
+        let signature = Signature::from_raw(&payload.signature).unwrap();
+        assert_eq!(
+            signature.recover_address_from_prehash(&hash).unwrap(),
+            signer.address()
+        );
+    }
+
+    #[test]
+    fn test_delayed_submission_window() {
+        let now = UnixTimestamp::now();
+        let window = AuthorizationWindow::for_delayed_submission(
+            Duration::from_hours(1),
+            Duration::from_mins(5),
+        )
+        .unwrap();
+        assert!(window.valid_after() >= now + 3_600);
+        assert_eq!(
+            window.valid_before().as_secs() - window.valid_after().as_secs(),
+            300
+        );
+
+        assert!(
+            AuthorizationWindow::for_delayed_submission(Duration::from_mins(1), Duration::ZERO)
+                .is_err()
+        );
+        let at = UnixTimestamp::from_secs(1_000);
+        assert!(AuthorizationWindow::new(at, at).is_err());
+    }
+
+    /// Clock stopped at a fixed time.
+    struct FixedClock(UnixTimestamp);
+
+    impl PaymentClock for FixedClock {
+        fn now(
+            &self,
+        ) -> Pin<Box<dyn Future<Output = Result<UnixTimestamp, ClientError>> + Send + '_>> {
+            Box::pin(async move { Ok(self.0) })
+        }
+    }
+
+    /// Signs a payment for a single offer with the given transfer method,
+    /// returning the decoded payload.
+    async fn sign_payload<S: SignerLike + Clone + 'static>(
+        client: &Eip155ExactClient<S>,
+        method: &str,
+    ) -> serde_json::Value {
+        let payment_required: PaymentRequired = serde_json::from_value(serde_json::json!({
+            "x402Version": 2,
+            "resource": {
+                "description": "Weather report",
+                "mimeType": "application/json",
+                "url": "https://api.example.com/weather",
+            },
+            "accepts": [{
+                "scheme": "exact",
+                "network": "eip155:8453",
+                "amount": "1000",
+                "payTo": Address::repeat_byte(0x22),
+                "maxTimeoutSeconds": 60,
+                "asset": Address::repeat_byte(0x11),
+                "extra": {
+                    "name": "USD Coin",
+                    "version": "2",
+                    "assetTransferMethod": method,
+                },
+            }],
+        }))
+        .unwrap();
+        let candidates = client.accept(&payment_required);
+        let header = candidates[0].sign().await.unwrap();
+        let payload = Base64Bytes::from(header.as_bytes()).decode().unwrap();
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_zero_amount_is_not_signed() {
+        let client = Eip155ExactClient::builder(PrivateKeySigner::random()).build();
+        let payment_required: PaymentRequired = serde_json::from_value(serde_json::json!({
+            "x402Version": 2,
+            "resource": {
+                "description": "Weather report",
+                "mimeType": "application/json",
+                "url": "https://api.example.com/weather",
+            },
+            "accepts": [{
+                "scheme": "exact",
+                "network": "eip155:8453",
+                "amount": "0",
+                "payTo": Address::repeat_byte(0x22),
+                "maxTimeoutSeconds": 60,
+                "asset": Address::repeat_byte(0x11),
+                "extra": { "name": "USD Coin", "version": "2" },
+            }],
+        }))
+        .unwrap();
+        let candidates = client.accept(&payment_required);
+        assert!(candidates[0].is_zero_amount());
+        assert!(matches!(
+            candidates[0].sign().await,
+            Err(ClientError::ZeroAmount)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_seeded_nonces_are_reproducible() {
+        let signer = PrivateKeySigner::random();
+        let client = || {
+            Eip155ExactClient::builder(signer.clone())
+                .nonce_source(SeededNonces::new([7; 32]))
+                .build()
+        };
+        let nonces = |client: Eip155ExactClient<PrivateKeySigner>| async move {
+            let eip3009 = sign_payload(&client, "eip3009").await;
+            let permit2 = sign_payload(&client, "permit2").await;
+            let eip3009 = eip3009["payload"]["authorization"]["nonce"].clone();
+            let permit2 = permit2["payload"]["permit2Authorization"]["nonce"].clone();
+            (eip3009, permit2)
+        };
+
+        let first = nonces(client()).await;
+        assert_eq!(first, nonces(client()).await);
+        // Consecutive payments still get distinct nonces.
+        let eip3009: FixedBytes<32> = serde_json::from_value(first.0.clone()).unwrap();
+        let permit2 = U256::from_str_radix(first.1.as_str().unwrap(), 10).unwrap();
+        assert_ne!(U256::from_be_bytes(eip3009.0), permit2);
+
+        let other = Eip155ExactClient::builder(signer)
+            .nonce_source(SeededNonces::new([8; 32]))
+            .build();
+        assert_ne!(first, nonces(other).await);
+    }
+
+    #[tokio::test]
+    async fn test_configured_backdate_is_applied() {
+        let now = UnixTimestamp::from_secs(1_700_000_000);
+        let client = Eip155ExactClient::builder(PrivateKeySigner::random())
+            .clock(FixedClock(now))
+            .valid_after_backdate(Duration::from_secs(30))
+            .build();
+        let valid_after = (now.as_secs() - 30).to_string();
+
+        let payload = sign_payload(&client, "eip3009").await;
+        let authorization = &payload["payload"]["authorization"];
+        assert_eq!(authorization["validAfter"], valid_after.as_str());
+
+        let payload = sign_payload(&client, "permit2").await;
+        let witness = &payload["payload"]["permit2Authorization"]["witness"];
+        assert_eq!(witness["validAfter"], valid_after.as_str());
+    }
+
+    #[cfg(feature = "client-provider")]
+    #[tokio::test]
+    async fn test_chain_clock_window_tracks_chain_time() {
+        use serde_json::json;
+
+        use crate::mock::mock_rpc;
+
+        // The chain runs an hour behind the system clock.
+        let chain_time = UnixTimestamp::now().as_secs() - 3_600;
+        let block = json!({
+            "hash": FixedBytes::<32>::repeat_byte(0x01),
+            "parentHash": FixedBytes::<32>::ZERO,
+            "sha3Uncles": FixedBytes::<32>::ZERO,
+            "miner": Address::ZERO,
+            "stateRoot": FixedBytes::<32>::ZERO,
+            "transactionsRoot": FixedBytes::<32>::ZERO,
+            "receiptsRoot": FixedBytes::<32>::ZERO,
+            "logsBloom": alloy_primitives::Bloom::default(),
+            "difficulty": "0x0",
+            "number": "0x1",
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0x0",
+            "timestamp": format!("{chain_time:#x}"),
+            "extraData": "0x",
+            "mixHash": FixedBytes::<32>::ZERO,
+            "nonce": "0x0000000000000000",
+            "uncles": [],
+            "transactions": [],
+        });
+        let server = mock_rpc([("eth_getBlockByNumber", block)]).await;
+        let provider: alloy_provider::RootProvider =
+            alloy_provider::RootProvider::new_http(server.uri().parse().unwrap());
+
+        let client = Eip155ExactClient::builder(PrivateKeySigner::random())
+            .chain_clock(provider)
+            .build();
+        let requirements: types::v2::PaymentRequirements = serde_json::from_value(json!({
+            "scheme": "exact",
+            "network": "eip155:8453",
+            "amount": "1000",
+            "payTo": Address::repeat_byte(0x22),
+            "maxTimeoutSeconds": 60,
+            "asset": Address::repeat_byte(0x11),
+            "extra": { "name": "USD Coin", "version": "2" },
+        }))
+        .unwrap();
+        let signer = V2PayloadSigner {
+            signer: client.signer.clone(),
+            resource_info: None,
+            extensions: None,
+            chain_reference: Eip155ChainReference::new(8453),
+            requirements,
+            approver: None,
+            auto_approve: false,
+            clock: client.clock.clone(),
+            valid_after_backdate: client.valid_after_backdate,
+            nonces: None,
+        };
+
+        let header = signer.sign_payment().await.unwrap();
+        let payload = Base64Bytes::from(header.as_bytes()).decode().unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        let authorization = &payload["payload"]["authorization"];
+        let valid_before = (chain_time + 60).to_string();
+        assert_eq!(authorization["validBefore"], valid_before.as_str());
+        let valid_after = (chain_time - DEFAULT_VALID_AFTER_BACKDATE.as_secs()).to_string();
+        assert_eq!(authorization["validAfter"], valid_after.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_externally_signed_digest_matches_signer_flow() {
+        let signer = PrivateKeySigner::random();
+        let window = AuthorizationWindow::new(
+            UnixTimestamp::from_secs(1_000),
+            UnixTimestamp::from_secs(2_000),
+        )
+        .unwrap();
+        let params = Eip3009SigningParams {
+            nonce: Some([7; 32]),
+            ..params(Some(window))
+        };
+
+        let (digest, authorization) = compute_erc3009_digest(signer.address(), &params);
+        // Signed "on another device", with only the digest.
+        let signature = alloy_signer::SignerSync::sign_hash_sync(&signer, &digest).unwrap();
+        let external = assemble_eip3009_payload(authorization, signature.as_bytes().into());
+
+        // The facilitator accepts an EOA signature that recovers the payer
+        // from the digest it reconstructs from the authorization.
+        let recovered = Signature::try_from(external.signature.as_ref())
+            .unwrap()
+            .recover_address_from_prehash(&digest)
+            .unwrap();
+        assert_eq!(recovered, external.authorization.from);
+
+        let signed = sign_erc3009_authorization(&signer, &params).await.unwrap();
+        assert_eq!(external.signature, signed.signature);
+        assert_eq!(external.authorization.nonce, signed.authorization.nonce);
+    }
+
+    #[tokio::test]
+    async fn test_callback_client_signs_verifiable_payload() {
+        let key = Arc::new(PrivateKeySigner::random());
+        let payer = key.address();
+        let client = CallbackSchemeClient::from_callback(payer, move |digest: B256| {
+            let key = Arc::clone(&key);
+            async move { alloy_signer::SignerSync::sign_hash_sync(&*key, &digest) }
+        });
+
+        let payload = sign_payload(&client, "eip3009").await;
+        let payload: types::v2::PaymentPayload = serde_json::from_value(payload).unwrap();
+        let ExactPayload::Eip3009(eip3009) = payload.payload else {
+            panic!("expected an EIP-3009 payload");
+        };
+        let authorization = eip3009.authorization;
+        assert_eq!(authorization.from, payer);
+
+        // The digest the facilitator reconstructs from the payload and the
+        // advertised domain recovers the payer.
+        let hash = TransferWithAuthorization {
+            from: authorization.from,
+            to: authorization.to,
+            value: authorization.value.into(),
+            validAfter: U256::from(authorization.valid_after.as_secs()),
+            validBefore: U256::from(authorization.valid_before.as_secs()),
+            nonce: authorization.nonce,
+        }
+        .eip712_signing_hash(&eip712_domain! {
+            name: "USD Coin",
+            version: "2",
+            chain_id: 8453,
+            verifying_contract: payload.accepted.asset.0,
+        });
+        let signature = Signature::try_from(eip3009.signature.as_ref()).unwrap();
+        assert_eq!(
+            signature.recover_address_from_prehash(&hash).unwrap(),
+            payer
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Configuration for the EIP-155 exact scheme facilitator.
+//!
+//! Parsed from the optional scheme `config` JSON passed to
+//! [`SchemeBuilder::build`](r402::scheme::SchemeBuilder::build).
+
+use alloy_primitives::{Address, FixedBytes};
+use alloy_provider::MULTICALL3_ADDRESS;
+use r402::proto::ParseMode;
+use serde::{Deserialize, Serialize};
+
+use crate::chain::TokenAmount;
+
+/// Configuration for [`Eip155ExactFacilitator`](super::Eip155ExactFacilitator).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip155ExactFacilitatorConfig {
+    /// Grace period (in seconds) applied to time-window checks to tolerate
+    /// clock drift between the facilitator and the blockchain network.
+    /// Default: 30
+    #[serde(default = "default_clock_skew_tolerance")]
+    pub clock_skew_tolerance: u64,
+
+    /// Seconds past an authorization's `validBefore` (the Permit2
+    /// `deadline`) during which verification and settlement still accept it.
+    ///
+    /// A payment verified just before it expires would otherwise be rejected
+    /// at settle time, although the transaction could still be included in
+    /// time. The token contract enforces `validBefore` against the block
+    /// timestamp, so an expired authorization is never honored. It is not
+    /// free either: its settlement reverts, and the facilitator pays the gas
+    /// of the reverted transaction. Keep the grace to a few seconds.
+    /// Default: 0
+    #[serde(default)]
+    pub settle_grace_seconds: u64,
+
+    /// Retry signature verification against the token's on-chain EIP-712
+    /// domain (EIP-5267 `eip712Domain()`) when it does not match the domain
+    /// advertised in the requirements.
+    ///
+    /// Salvages payments where the server configured a slightly wrong domain,
+    /// at the cost of an extra RPC call for such payments. Only EOA signatures
+    /// can be checked this way.
+    /// Default: false
+    #[serde(default)]
+    pub resolve_domain_onchain: bool,
+
+    /// Token advertised as the network's default asset in `/supported`,
+    /// overriding the built-in registry choice (e.g. to prefer bridged USDC).
+    /// Default: none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_asset: Option<Address>,
+
+    /// Smallest authorized amount, in atomic token units, the facilitator
+    /// accepts for settlement.
+    ///
+    /// Rejects dust payments whose gas cost would exceed their value. This is
+    /// facilitator policy, independent of the price in the requirements;
+    /// clients paying less than this should batch payments off-band.
+    /// Default: none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_settle_amount: Option<TokenAmount>,
+
+    /// Multicall3 contract used to batch the nonce and balance reads of
+    /// EIP-3009 verification into a single call.
+    ///
+    /// Override it on chains with a non-standard deployment, or set it to
+    /// `null` to always issue individual calls. Verification falls back to
+    /// individual calls when no Multicall3 answers at the address.
+    /// Default: the canonical `0xcA11bde05977b3631167028862bE2a173976CA11`
+    #[serde(default = "default_multicall3_address")]
+    pub multicall3_address: Option<Address>,
+
+    /// Longest time, in seconds, an authorization may remain valid for.
+    ///
+    /// Caps how long a leaked payment signature stays usable. The lifetime
+    /// runs from the later of `validAfter` and now to `validBefore` (the
+    /// Permit2 `deadline`), so clients backdating `validAfter` to tolerate
+    /// clock skew are not penalized.
+    /// Default: 3600
+    #[serde(default = "default_max_authorization_lifetime")]
+    pub max_authorization_lifetime: u64,
+
+    /// Whether verify and settle requests with fields unknown to this
+    /// version are accepted (`tolerant`) or rejected (`strict`).
+    /// Default: tolerant
+    #[serde(default)]
+    pub parse_mode: ParseMode,
+
+    /// Probe the requirements' asset with an ERC-20 `decimals()` call before
+    /// verifying a payment, rejecting it with `AssetMismatch` if the address
+    /// is not a token contract.
+    ///
+    /// Surfaces a misconfigured `asset` (an EOA or another contract) as a
+    /// precise error instead of an opaque settlement failure, at the cost of
+    /// one RPC call per asset: a successful probe is cached for the lifetime
+    /// of the facilitator.
+    /// Default: false
+    #[serde(default)]
+    pub validate_asset_is_erc20: bool,
+
+    /// Tokens settled with a non-standard `transferWithAuthorization`
+    /// selector, see
+    /// [`Eip155TokenDeployment::transfer_with_auth_selector`](crate::chain::Eip155TokenDeployment::transfer_with_auth_selector).
+    /// Default: none
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transfer_with_auth_selectors: Vec<TransferSelectorOverride>,
+}
+
+/// A token whose `transferWithAuthorization` has a non-standard selector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferSelectorOverride {
+    /// The token contract address.
+    pub asset: Address,
+    /// The selector to call `transferWithAuthorization` with.
+    pub selector: FixedBytes<4>,
+}
+
+impl Eip155ExactFacilitatorConfig {
+    /// Returns the `transferWithAuthorization` selector overriding the
+    /// standard one for `asset`, if any.
+    #[must_use]
+    pub fn transfer_with_auth_selector(&self, asset: Address) -> Option<FixedBytes<4>> {
+        self.transfer_with_auth_selectors
+            .iter()
+            .find(|token| token.asset == asset)
+            .map(|token| token.selector)
+    }
+}
+
+const fn default_clock_skew_tolerance() -> u64 {
+    super::DEFAULT_CLOCK_SKEW_TOLERANCE
+}
+
+const fn default_max_authorization_lifetime() -> u64 {
+    super::DEFAULT_MAX_AUTHORIZATION_LIFETIME
+}
+
+#[allow(clippy::unnecessary_wraps)] // serde default of an optional field
+const fn default_multicall3_address() -> Option<Address> {
+    Some(MULTICALL3_ADDRESS)
+}
+
+impl Default for Eip155ExactFacilitatorConfig {
+    fn default() -> Self {
+        Self {
+            clock_skew_tolerance: default_clock_skew_tolerance(),
+            settle_grace_seconds: 0,
+            resolve_domain_onchain: false,
+            default_asset: None,
+            min_settle_amount: None,
+            multicall3_address: default_multicall3_address(),
+            max_authorization_lifetime: default_max_authorization_lifetime(),
+            parse_mode: ParseMode::Tolerant,
+            validate_asset_is_erc20: false,
+            transfer_with_auth_selectors: Vec::new(),
+        }
+    }
+}
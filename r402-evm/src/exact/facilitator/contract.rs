@@ -1,10 +1,11 @@
 //! Solidity interface definitions for on-chain interactions.
 //!
 //! Contains the minimal ABI surface needed by the facilitator:
-//! - [`IEIP3009`] — ERC-3009 + ERC-20 subset for USDC-style tokens
+//! - [`IEIP3009`] — ERC-3009 + ERC-20 subset for USDC-style tokens (plus EIP-5267)
 //! - [`IX402Permit2Proxy`] — x402 Permit2 proxy for settling Permit2 payments
 //! - [`IERC20`] — Minimal ERC-20 interface for allowance/balance checks
 //! - [`Validator6492`] — EIP-6492 universal signature validator
+//! - [`IERC1271`] — EIP-1271 contract wallet signature check
 //! - [`Sig6492`] — ABI-decodable prefix of an EIP-6492 wrapped signature
 
 use alloy_sol_types::sol;
@@ -27,6 +28,15 @@
         function version() external view returns (string);
         function balanceOf(address account) external view returns (uint256);
         function authorizationState(address authorizer, bytes32 nonce) external view returns (bool);
+        function eip712Domain() external view returns (
+            bytes1 fields,
+            string name,
+            string version,
+            uint256 chainId,
+            address verifyingContract,
+            bytes32 salt,
+            uint256[] extensions
+        );
         function transferWithAuthorization(
             address from,
             address to,
@@ -47,6 +57,8 @@
             bytes32 r,
             bytes32 s
         ) external;
+        event AuthorizationUsed(address indexed authorizer, bytes32 indexed nonce);
+        event Transfer(address indexed from, address indexed to, uint256 value);
     }
 }
 
@@ -65,6 +77,19 @@
     }
 }
 
+sol! {
+    /// EIP-1271 contract wallet signature check, used where the EIP-6492
+    /// validator is not deployed.
+    ///
+    /// Reference: <https://eips.ethereum.org/EIPS/eip-1271>
+    #[allow(missing_docs)]
+    #[derive(Debug)]
+    #[sol(rpc)]
+    interface IERC1271 {
+        function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4 magicValue);
+    }
+}
+
 sol! {
     /// Solidity-compatible struct for decoding the prefix of an EIP-6492 signature.
     #[derive(Debug)]
@@ -113,11 +138,12 @@ struct Witness {
 }
 
 sol! {
-    /// Minimal ERC-20 interface for allowance and balance checks.
+    /// Minimal ERC-20 interface for allowance, balance and metadata checks.
     #[allow(missing_docs)]
     #[derive(Debug)]
     #[sol(rpc)]
     interface IERC20 {
+        function decimals() external view returns (uint8);
         function balanceOf(address account) external view returns (uint256);
         function allowance(address owner, address spender) external view returns (uint256);
     }
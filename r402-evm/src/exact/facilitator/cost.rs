@@ -0,0 +1,56 @@
+//! Settlement cost estimation for the EIP-155 exact scheme.
+//!
+//! The facilitator pays the gas of every settlement. [`estimate_settlement_gas`]
+//! estimates it for an EIP-3009 payment, and a [`NativePriceFeed`] converts the
+//! resulting native-token cost into the payment asset, so that payments worth
+//! less than their settlement can be rejected.
+
+use alloy_primitives::{Address, Signature, U256};
+use alloy_provider::Provider;
+use r402::facilitator::BoxFuture;
+
+use super::Eip3009Payment;
+use super::contract::IEIP3009;
+use super::error::Eip155ExactError;
+use super::settle::{TransferWithAuthorization0Call, TransferWithAuthorization1Call};
+
+/// Converts native-token amounts into units of a payment asset.
+///
+/// Backed by whatever price source the operator trusts, e.g. an oracle or an
+/// exchange API.
+pub trait NativePriceFeed: Send + Sync {
+    /// Returns the value of `native` wei in atomic units of `asset`, or
+    /// `None` if the price is unknown.
+    fn native_to_asset(&self, asset: Address, native: U256) -> BoxFuture<'_, Option<U256>>;
+}
+
+/// Estimates the gas of settling `payment` with `eth_estimateGas`.
+///
+/// 65-byte signatures are estimated with the `(v, r, s)` overload EOA
+/// payments settle with, others with the bytes overload. Counterfactual
+/// (EIP-6492) wallets cannot be estimated this way, since they are deployed
+/// in the same transaction.
+///
+/// # Errors
+///
+/// Returns [`Eip155ExactError`] if the estimation fails, e.g. because the
+/// transfer would revert.
+pub async fn estimate_settlement_gas<P: Provider>(
+    contract: &IEIP3009::IEIP3009Instance<P>,
+    payment: &Eip3009Payment,
+) -> Result<u64, Eip155ExactError> {
+    let signature = if payment.signature.len() == 65 {
+        Signature::try_from(payment.signature.as_ref()).ok()
+    } else {
+        None
+    };
+    let gas = if let Some(signature) = signature {
+        let call = TransferWithAuthorization1Call::new(contract, payment, signature);
+        call.0.tx.estimate_gas().await?
+    } else {
+        let call =
+            TransferWithAuthorization0Call::new(contract, payment, payment.signature.clone());
+        call.0.tx.estimate_gas().await?
+    };
+    Ok(gas)
+}
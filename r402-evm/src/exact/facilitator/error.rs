@@ -1,6 +1,7 @@
 //! Error types for the EIP-155 exact scheme facilitator.
 
-use alloy_primitives::TxHash;
+use alloy_primitives::{Address, B256, TxHash};
+use alloy_sol_types::{Revert, SolError};
 use alloy_transport::TransportError;
 use r402::facilitator::FacilitatorError;
 use r402::proto::PaymentVerificationError;
@@ -26,6 +27,50 @@ pub enum Eip155ExactError {
     /// Payment verification failed.
     #[error(transparent)]
     PaymentVerification(#[from] PaymentVerificationError),
+    /// The ERC-3009 authorization was already consumed on-chain, and the
+    /// transaction that consumed it could not be located or did not make
+    /// the expected transfer.
+    #[error("Authorization {nonce} of {authorizer} already consumed")]
+    NonceAlreadyConsumed {
+        /// The payer that signed the authorization.
+        authorizer: Address,
+        /// The consumed authorization nonce.
+        nonce: B256,
+    },
+}
+
+/// Revert reason fragment emitted by ERC-3009 tokens (e.g. USDC
+/// `FiatTokenV2: authorization is used or canceled`) for a consumed nonce.
+const NONCE_CONSUMED_REVERT_REASON: &str = "authorization is used";
+
+impl Eip155ExactError {
+    /// Returns `true` if this error is an ERC-3009 revert caused by an
+    /// authorization nonce that has already been used.
+    ///
+    /// Decodes `Error(string)` revert data from the RPC error payload when
+    /// present, falling back to the error message otherwise.
+    #[must_use]
+    pub fn is_nonce_consumed_revert(&self) -> bool {
+        match self {
+            Self::Transport(e) => e.as_error_resp().is_some_and(|resp| {
+                resp.as_revert_data()
+                    .and_then(|data| Revert::abi_decode(&data).ok())
+                    .map_or_else(
+                        || is_nonce_consumed_reason(&resp.message),
+                        |revert| is_nonce_consumed_reason(&revert.reason),
+                    )
+            }),
+            Self::ContractCall(message) => is_nonce_consumed_reason(message),
+            _ => false,
+        }
+    }
+}
+
+/// Checks whether a revert reason signals a consumed ERC-3009 authorization.
+fn is_nonce_consumed_reason(reason: &str) -> bool {
+    reason
+        .to_ascii_lowercase()
+        .contains(NONCE_CONSUMED_REVERT_REASON)
 }
 
 impl From<Eip155ExactError> for FacilitatorError {
@@ -33,9 +78,12 @@ fn from(value: Eip155ExactError) -> Self {
         match value {
             Eip155ExactError::Transport(_)
             | Eip155ExactError::PendingTransaction(_)
-            | Eip155ExactError::TransactionReverted(_)
             | Eip155ExactError::ContractCall(_) => Self::OnchainFailure(value.to_string()),
+            Eip155ExactError::TransactionReverted(_) => Self::TransactionFailed(value.to_string()),
             Eip155ExactError::PaymentVerification(e) => Self::PaymentVerification(e),
+            Eip155ExactError::NonceAlreadyConsumed { .. } => {
+                Self::PaymentVerification(PaymentVerificationError::NonceAlreadyUsed)
+            }
         }
     }
 }
@@ -86,3 +134,24 @@ fn from(e: alloy_contract::Error) -> Self {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use r402::proto::{ErrorReason, SettleResponse};
+
+    use super::*;
+
+    #[test]
+    fn test_reverted_settlement_reports_transaction_failed() {
+        let error = FacilitatorError::from(Eip155ExactError::TransactionReverted(TxHash::ZERO));
+        let response = SettleResponse::from_facilitator_error(&error, "eip155:8453".into());
+
+        assert!(matches!(
+            response,
+            SettleResponse::Error {
+                code: Some(ErrorReason::TransactionFailed),
+                ..
+            }
+        ));
+    }
+}
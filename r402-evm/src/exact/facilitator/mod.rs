@@ -10,41 +10,58 @@
 //! - EIP-712 domain construction
 //! - On-chain settlement with gas management
 //! - Smart wallet deployment for counterfactual signatures
+//! - Settlement cost estimation ([`Eip155ExactFacilitator::estimate_settlement_cost`])
+//! - Third-party verification of settled payments ([`verify_payment_proof`])
 
+mod config;
 mod contract;
+mod cost;
 mod error;
+mod proof;
 mod settle;
 mod signature;
 mod verify;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
 
-use alloy_primitives::{Address, B256, Bytes, U256, address};
-use alloy_provider::Provider;
+use alloy_primitives::{Address, B256, Bytes, FixedBytes, U256, address};
+use alloy_provider::{MULTICALL3_ADDRESS, Provider};
+use alloy_sol_types::Eip712Domain;
+pub use config::{Eip155ExactFacilitatorConfig, TransferSelectorOverride};
 pub use contract::{IEIP3009, IX402Permit2Proxy, Validator6492};
+pub use cost::{NativePriceFeed, estimate_settlement_gas};
 pub use error::Eip155ExactError;
+pub use proof::{PaymentProof, ProofOutcome, verify_payment_proof};
 use r402::chain::ChainProvider;
 use r402::facilitator::{BoxFuture, Facilitator, FacilitatorError};
 use r402::proto;
 use r402::proto::UnixTimestamp;
 use r402::proto::v2;
 use r402::scheme::{SchemeBuilder, SchemeId};
+use r402::verification::VerificationTokens;
 pub use settle::{
-    TransferWithAuthorization0Call, TransferWithAuthorization1Call, TransferWithAuthorizationCall,
-    settle_payment, settle_permit2_payment,
+    Settlement, TransferWithAuthorization0Call, TransferWithAuthorization1Call,
+    TransferWithAuthorizationCall, find_authorization_settlement, settle_payment,
+    settle_permit2_payment,
 };
 pub use signature::StructuredSignatureFormatError;
 pub use verify::{
-    assert_domain, assert_enough_balance, assert_enough_value, assert_nonce_unused,
-    assert_requirements_match, assert_time, verify_payment, verify_permit2_payment,
+    SETTLEMENT_GAS_ESTIMATE, assert_asset_is_erc20, assert_authorization_lifetime, assert_domain,
+    assert_enough_balance, assert_enough_value, assert_nonce_unused, assert_requirements_match,
+    assert_signer_funded, assert_time, fetch_onchain_domain, remaining_validity,
+    resolve_signing_domain, verify_payment, verify_permit2_payment,
 };
 
-use crate::chain::Eip155MetaTransactionProvider;
+use crate::chain::{Eip155MetaTransactionProvider, TokenAmount};
 use crate::exact::types;
-use crate::exact::{Eip155Exact, ExactPayload, ExactScheme};
+use crate::exact::{Eip155Exact, ExactPayload, ExactScheme, SupportedPaymentKindExtra};
 
 /// Signature verifier for EIP-6492, EIP-1271, EOA, universally deployed on the supported EVM chains.
-/// If absent on a target chain, verification will fail; you should deploy the validator there.
+///
+/// If absent on a target chain, the facilitator falls back to EOA and EIP-1271 verification and
+/// rejects counterfactual wallets; deploy the validator there to accept them.
 pub const VALIDATOR_ADDRESS: Address = address!("0xdAcD51A54883eb67D95FAEb2BBfdC4a9a6BD2a3B");
 
 /// A fully specified ERC-3009 authorization payload for EVM settlement.
@@ -64,6 +81,9 @@ pub struct Eip3009Payment {
     pub nonce: B256,
     /// Raw signature bytes (EIP-1271 or EIP-6492-wrapped).
     pub signature: Bytes,
+    /// Selector replacing the standard `transferWithAuthorization` one, for
+    /// tokens that deviate from ERC-3009.
+    pub transfer_selector: Option<FixedBytes<4>>,
 }
 
 /// A fully specified Permit2 authorization payload for EVM settlement.
@@ -99,9 +119,15 @@ impl<P> SchemeBuilder<P> for Eip155Exact
     fn build(
         &self,
         provider: P,
-        _config: Option<serde_json::Value>,
+        config: Option<serde_json::Value>,
     ) -> Result<Box<dyn Facilitator>, Box<dyn std::error::Error>> {
-        Ok(Box::new(Eip155ExactFacilitator::new(provider)))
+        let config = config
+            .map(serde_json::from_value::<Eip155ExactFacilitatorConfig>)
+            .transpose()?
+            .unwrap_or_default();
+        Ok(Box::new(Eip155ExactFacilitator::with_config(
+            provider, config,
+        )))
     }
 }
 
@@ -112,20 +138,46 @@ fn build(
 /// facilitator host and the blockchain network.
 const DEFAULT_CLOCK_SKEW_TOLERANCE: u64 = 30;
 
+/// Default longest lifetime, in seconds, of an accepted authorization.
+const DEFAULT_MAX_AUTHORIZATION_LIFETIME: u64 = 60 * 60;
+
+/// How long a successful signer funds check is reused.
+///
+/// Keeps the balance queries off the hot path: a signer drained within the
+/// window is caught when its settlement fails, as it would be without the check.
+pub const SIGNER_FUNDS_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// How long an estimated settlement cost is reused for an asset.
+///
+/// Gas prices move slowly enough that a short window saves an estimation per
+/// request without letting the estimate drift far.
+pub const SETTLEMENT_COST_CACHE_TTL: Duration = Duration::from_secs(30);
+
 /// Facilitator for EIP-155 exact scheme payments.
 ///
 /// Supports both EIP-3009 and Permit2 transfer methods. The transfer method
 /// is determined by the [`ExactPayload`] variant in the payment payload.
 pub struct Eip155ExactFacilitator<P> {
     provider: P,
-    /// Grace period (in seconds) applied to time-window checks to tolerate
-    /// clock drift between the facilitator and the blockchain network.
-    clock_skew_tolerance: u64,
+    config: Eip155ExactFacilitatorConfig,
+    verification_tokens: Option<VerificationTokens>,
+    /// Assets that answered the ERC-20 probe, see
+    /// [`Eip155ExactFacilitatorConfig::validate_asset_is_erc20`].
+    erc20_assets: Mutex<BTreeSet<Address>>,
+    /// Price feed rejecting payments worth less than their settlement.
+    price_feed: Option<Arc<dyn NativePriceFeed>>,
+    /// Settlement cost, in wei, last estimated for each asset, and when.
+    settlement_costs: Mutex<BTreeMap<Address, (Instant, U256)>>,
+    /// Whether [`VALIDATOR_ADDRESS`] is deployed on the chain, once probed.
+    validator_deployed: OnceLock<bool>,
+    /// When a settlement signer was last found funded.
+    signer_funded_at: Mutex<Option<Instant>>,
 }
 
 impl<P> std::fmt::Debug for Eip155ExactFacilitator<P> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Eip155ExactFacilitator")
+            .field("config", &self.config)
             .finish_non_exhaustive()
     }
 }
@@ -135,9 +187,32 @@ impl<P> Eip155ExactFacilitator<P> {
     ///
     /// Uses `DEFAULT_CLOCK_SKEW_TOLERANCE` (30 s) for time-window validation.
     pub const fn new(provider: P) -> Self {
+        let config = Eip155ExactFacilitatorConfig {
+            clock_skew_tolerance: DEFAULT_CLOCK_SKEW_TOLERANCE,
+            settle_grace_seconds: 0,
+            resolve_domain_onchain: false,
+            default_asset: None,
+            min_settle_amount: None,
+            multicall3_address: Some(MULTICALL3_ADDRESS),
+            max_authorization_lifetime: DEFAULT_MAX_AUTHORIZATION_LIFETIME,
+            parse_mode: proto::ParseMode::Tolerant,
+            validate_asset_is_erc20: false,
+            transfer_with_auth_selectors: Vec::new(),
+        };
+        Self::with_config(provider, config)
+    }
+
+    /// Creates a new facilitator with an explicit configuration.
+    pub const fn with_config(provider: P, config: Eip155ExactFacilitatorConfig) -> Self {
         Self {
             provider,
-            clock_skew_tolerance: DEFAULT_CLOCK_SKEW_TOLERANCE,
+            config,
+            verification_tokens: None,
+            erc20_assets: Mutex::new(BTreeSet::new()),
+            price_feed: None,
+            settlement_costs: Mutex::new(BTreeMap::new()),
+            validator_deployed: OnceLock::new(),
+            signer_funded_at: Mutex::new(None),
         }
     }
 
@@ -147,120 +222,596 @@ pub const fn new(provider: P) -> Self {
     /// and the chain; a value of `0` enforces exact-time boundaries.
     #[must_use]
     pub const fn with_clock_skew_tolerance(mut self, seconds: u64) -> Self {
-        self.clock_skew_tolerance = seconds;
+        self.config.clock_skew_tolerance = seconds;
+        self
+    }
+
+    /// Sets the seconds past expiry during which payments are still accepted.
+    ///
+    /// See [`Eip155ExactFacilitatorConfig::settle_grace_seconds`].
+    #[must_use]
+    pub const fn with_settle_grace_seconds(mut self, seconds: u64) -> Self {
+        self.config.settle_grace_seconds = seconds;
+        self
+    }
+
+    /// Enables falling back to the token's on-chain EIP-712 domain (EIP-5267)
+    /// when a signature does not match the advertised domain.
+    ///
+    /// See [`Eip155ExactFacilitatorConfig::resolve_domain_onchain`].
+    #[must_use]
+    pub const fn with_onchain_domain_resolution(mut self, enabled: bool) -> Self {
+        self.config.resolve_domain_onchain = enabled;
+        self
+    }
+
+    /// Overrides the asset advertised as the network's default in `/supported`.
+    ///
+    /// See [`Eip155ExactFacilitatorConfig::default_asset`].
+    #[must_use]
+    pub const fn with_default_asset(mut self, asset: Address) -> Self {
+        self.config.default_asset = Some(asset);
+        self
+    }
+
+    /// Sets the smallest authorized amount accepted for settlement.
+    ///
+    /// See [`Eip155ExactFacilitatorConfig::min_settle_amount`].
+    #[must_use]
+    pub const fn with_min_settle_amount(mut self, amount: U256) -> Self {
+        self.config.min_settle_amount = Some(TokenAmount(amount));
+        self
+    }
+
+    /// Sets the Multicall3 contract used to batch verification reads, or
+    /// disables batching with `None`.
+    ///
+    /// See [`Eip155ExactFacilitatorConfig::multicall3_address`].
+    #[must_use]
+    pub const fn with_multicall3_address(mut self, address: Option<Address>) -> Self {
+        self.config.multicall3_address = address;
+        self
+    }
+
+    /// Sets the longest time, in seconds, an authorization may remain valid for.
+    ///
+    /// See [`Eip155ExactFacilitatorConfig::max_authorization_lifetime`].
+    #[must_use]
+    pub const fn with_max_authorization_lifetime(mut self, seconds: u64) -> Self {
+        self.config.max_authorization_lifetime = seconds;
+        self
+    }
+
+    /// Sets how fields unknown to this version are treated in requests.
+    ///
+    /// See [`Eip155ExactFacilitatorConfig::parse_mode`].
+    #[must_use]
+    pub const fn with_parse_mode(mut self, mode: proto::ParseMode) -> Self {
+        self.config.parse_mode = mode;
+        self
+    }
+
+    /// Enables probing the requirements' asset with an ERC-20 `decimals()`
+    /// call before verifying a payment.
+    ///
+    /// See [`Eip155ExactFacilitatorConfig::validate_asset_is_erc20`].
+    #[must_use]
+    pub const fn with_asset_erc20_validation(mut self, enabled: bool) -> Self {
+        self.config.validate_asset_is_erc20 = enabled;
+        self
+    }
+
+    /// Rejects EIP-3009 payments whose value does not cover the gas of their
+    /// settlement, converted into the payment asset by `feed`.
+    ///
+    /// Payments of assets the feed has no price for are accepted. See
+    /// [`estimate_settlement_cost`](Self::estimate_settlement_cost).
+    #[must_use]
+    pub fn with_settlement_cost_check(mut self, feed: Arc<dyn NativePriceFeed>) -> Self {
+        self.price_feed = Some(feed);
+        self
+    }
+
+    /// Calls `transferWithAuthorization` on `asset` with a non-standard
+    /// `selector`, replacing any selector set before for it.
+    ///
+    /// An escape hatch for tokens deviating from ERC-3009, see
+    /// [`Eip155TokenDeployment::transfer_with_auth_selector`](crate::chain::Eip155TokenDeployment::transfer_with_auth_selector).
+    #[must_use]
+    pub fn with_transfer_with_auth_selector(
+        mut self,
+        asset: Address,
+        selector: FixedBytes<4>,
+    ) -> Self {
+        let overrides = &mut self.config.transfer_with_auth_selectors;
+        overrides.retain(|token| token.asset != asset);
+        overrides.push(TransferSelectorOverride { asset, selector });
+        self
+    }
+
+    /// Issues a verification token with every valid verify response, and
+    /// accepts them in [`Facilitator::settle_verified`] to settle without
+    /// re-running the balance, nonce and signature checks.
+    ///
+    /// See [`r402::verification`].
+    #[must_use]
+    pub const fn with_verification_tokens(mut self, tokens: VerificationTokens) -> Self {
+        self.verification_tokens = Some(tokens);
         self
     }
 }
 
-impl<P> Facilitator for Eip155ExactFacilitator<P>
+impl<P> Eip155ExactFacilitator<P>
+where
+    P: Sync,
+{
+    /// Returns the EIP-712 domain to verify the payment signature against,
+    /// consulting the on-chain domain if enabled in the configuration.
+    async fn signing_domain<T: Provider>(
+        &self,
+        contract: &IEIP3009::IEIP3009Instance<T>,
+        payment: &Eip3009Payment,
+        advertised: Eip712Domain,
+    ) -> Eip712Domain {
+        if self.config.resolve_domain_onchain {
+            resolve_signing_domain(contract, payment, advertised).await
+        } else {
+            advertised
+        }
+    }
+}
+
+impl<P> Eip155ExactFacilitator<P>
+where
+    P: Eip155MetaTransactionProvider + Sync,
+    P::Inner: Provider,
+{
+    /// Returns whether the EIP-6492 validator is deployed on the chain.
+    ///
+    /// Probed with `eth_getCode` the first time it is needed, which is at
+    /// startup for servers fetching `/supported`, and remembered. Without
+    /// the validator, signatures are checked as EOA or EIP-1271 ones and
+    /// counterfactual wallets are rejected. A failed probe assumes the
+    /// validator is deployed and is retried next time.
+    pub async fn supports_eip6492(&self) -> bool {
+        if let Some(deployed) = self.validator_deployed.get() {
+            return *deployed;
+        }
+        let Ok(code) = self.provider.inner().get_code_at(VALIDATOR_ADDRESS).await else {
+            return true;
+        };
+        let deployed = !code.is_empty();
+        #[cfg(feature = "telemetry")]
+        if !deployed && self.validator_deployed.get().is_none() {
+            tracing::warn!(
+                network = %r402::chain::ChainId::from(self.provider.chain()),
+                validator = %VALIDATOR_ADDRESS,
+                "EIP-6492 validator not deployed, counterfactual wallet payments are rejected"
+            );
+        }
+        *self.validator_deployed.get_or_init(|| deployed)
+    }
+
+    /// Returns the EIP-6492 validator to verify signatures with, if deployed.
+    async fn signature_validator(&self) -> Option<Address> {
+        self.supports_eip6492().await.then_some(VALIDATOR_ADDRESS)
+    }
+
+    /// Estimates the native-token cost, in wei, of settling `payment` of
+    /// `asset`, from `eth_estimateGas` and the current gas price.
+    ///
+    /// The estimate is reused for every payment of `asset` for
+    /// [`SETTLEMENT_COST_CACHE_TTL`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Eip155ExactError`] if the gas estimation or the gas price
+    /// query fails.
+    pub async fn estimate_settlement_cost(
+        &self,
+        asset: Address,
+        payment: &Eip3009Payment,
+    ) -> Result<U256, Eip155ExactError> {
+        let costs = || {
+            self.settlement_costs
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+        };
+        if let Some(cost) = self.cached_settlement_cost(asset) {
+            return Ok(cost);
+        }
+
+        let contract = IEIP3009::new(asset, self.provider.inner());
+        let gas = estimate_settlement_gas(&contract, payment).await?;
+        let gas_price = self.provider.inner().get_gas_price().await?;
+        let cost = U256::from(gas) * U256::from(gas_price);
+        costs().insert(asset, (Instant::now(), cost));
+        Ok(cost)
+    }
+
+    /// Returns the settlement cost of `asset` estimated within the last
+    /// [`SETTLEMENT_COST_CACHE_TTL`], if any.
+    fn cached_settlement_cost(&self, asset: Address) -> Option<U256> {
+        let costs = self
+            .settlement_costs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        costs
+            .get(&asset)
+            .filter(|(estimated_at, _)| estimated_at.elapsed() < SETTLEMENT_COST_CACHE_TTL)
+            .map(|(_, cost)| *cost)
+    }
+
+    /// Checks that `payment` covers the cost of its settlement, if a price
+    /// feed is configured.
+    async fn assert_covers_settlement_cost(
+        &self,
+        asset: Address,
+        payment: &Eip3009Payment,
+    ) -> Result<(), Eip155ExactError> {
+        let Some(feed) = &self.price_feed else {
+            return Ok(());
+        };
+        let cost = self.estimate_settlement_cost(asset, payment).await?;
+        match feed.native_to_asset(asset, cost).await {
+            Some(cost) if cost > payment.value => {
+                Err(proto::PaymentVerificationError::BelowMinimumAmount(cost.to_string()).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks a payment claimed by a third party against this facilitator's chain.
+    ///
+    /// See [`verify_payment_proof`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PaymentVerificationError::ChainIdMismatch`](proto::PaymentVerificationError::ChainIdMismatch)
+    /// if the proof is for another network, or a transport error if the
+    /// receipt cannot be fetched.
+    pub async fn verify_payment_proof(
+        &self,
+        proof: &PaymentProof,
+    ) -> Result<ProofOutcome, Eip155ExactError> {
+        if proof.network != r402::chain::ChainId::from(self.provider.chain()) {
+            return Err(proto::PaymentVerificationError::ChainIdMismatch.into());
+        }
+        Ok(verify_payment_proof(self.provider.inner(), proof).await?)
+    }
+}
+
+/// Logs the audit record of a successful settlement.
+#[cfg(feature = "telemetry")]
+fn log_settlement(
+    network: &r402::chain::ChainId,
+    payer: Address,
+    pay_to: Address,
+    asset: Address,
+    amount: U256,
+    settlement: Settlement,
+) {
+    let signer = settlement.signer.map(|signer| signer.to_string());
+    r402::facilitator::SettlementRecord {
+        payer: &payer.to_string(),
+        pay_to: &pay_to.to_string(),
+        asset: &asset.to_string(),
+        amount: &amount.to_string(),
+        network: &network.to_string(),
+        transaction: &settlement.transaction.to_string(),
+        signer: signer.as_deref(),
+    }
+    .log();
+}
+
+impl<P> Eip155ExactFacilitator<P>
 where
     P: Eip155MetaTransactionProvider + ChainProvider + Send + Sync,
     P::Inner: Provider,
     Eip155ExactError: From<P::Error>,
 {
-    fn verify(
+    /// Checks that a configured signer can pay for the gas of settling a
+    /// payment of `asset`.
+    ///
+    /// The cost is the one [`estimate_settlement_cost`](Self::estimate_settlement_cost)
+    /// last estimated for `asset`, if still cached, and otherwise
+    /// [`SETTLEMENT_GAS_ESTIMATE`] at the current gas price.
+    ///
+    /// A successful check is reused for [`SIGNER_FUNDS_CACHE_TTL`]; a failed
+    /// one is repeated on the next request, so a topped-up signer is picked
+    /// up at once.
+    async fn assert_signer_funded(&self, asset: Address) -> Result<(), Eip155ExactError> {
+        let funded_at = || {
+            self.signer_funded_at
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+        };
+        if funded_at().is_some_and(|at| at.elapsed() < SIGNER_FUNDS_CACHE_TTL) {
+            return Ok(());
+        }
+        let signers: Vec<Address> = self
+            .provider
+            .signer_addresses()
+            .iter()
+            .filter_map(|signer| signer.parse().ok())
+            .collect();
+        let required = if let Some(cost) = self.cached_settlement_cost(asset) {
+            cost
+        } else {
+            let gas_price = self.provider.inner().get_gas_price().await?;
+            U256::from(gas_price) * U256::from(SETTLEMENT_GAS_ESTIMATE)
+        };
+        assert_signer_funded(self.provider.inner(), signers, required).await?;
+        *funded_at() = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Checks that `asset` is an ERC-20 token, if enabled in the configuration.
+    ///
+    /// Only a successful probe is cached, so a fixed misconfiguration is
+    /// picked up without a restart.
+    async fn assert_asset_is_erc20(&self, asset: Address) -> Result<(), Eip155ExactError> {
+        let probed = || {
+            self.erc20_assets
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+        };
+        if !self.config.validate_asset_is_erc20 || probed().contains(&asset) {
+            return Ok(());
+        }
+        assert_asset_is_erc20(self.provider.inner(), asset).await?;
+        probed().insert(asset);
+        Ok(())
+    }
+
+    /// Verifies a payment, without issuing a verification token.
+    async fn verify_request(
         &self,
         request: proto::VerifyRequest,
-    ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
-        Box::pin(async move {
-            let request = types::v2::VerifyRequest::from_proto(request)?;
-            let payload = &request.payment_payload;
-            let requirements = &request.payment_requirements;
-            match &payload.payload {
-                ExactPayload::Eip3009(eip3009) => {
-                    let (contract, payment, eip712_domain) = verify::assert_valid_payment(
+    ) -> Result<proto::VerifyResponse, FacilitatorError> {
+        let request = types::v2::VerifyRequest::from_proto_with(request, self.config.parse_mode)?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        self.assert_signer_funded(requirements.asset.into()).await?;
+        self.assert_asset_is_erc20(requirements.asset.into())
+            .await?;
+        match &payload.payload {
+            ExactPayload::Eip3009(eip3009) => {
+                let (contract, payment, eip712_domain) = verify::assert_valid_payment(
+                    self.provider.inner(),
+                    self.provider.chain(),
+                    eip3009,
+                    payload,
+                    requirements,
+                    &self.config,
+                    self.config.settle_grace_seconds,
+                )
+                .await?;
+                let eip712_domain = self
+                    .signing_domain(&contract, &payment, eip712_domain)
+                    .await;
+                let payer = verify_payment(
+                    self.provider.inner(),
+                    &contract,
+                    &payment,
+                    &eip712_domain,
+                    self.signature_validator().await,
+                )
+                .await?;
+                self.assert_covers_settlement_cost(*contract.address(), &payment)
+                    .await?;
+                let remaining =
+                    remaining_validity(payment.valid_before.as_secs(), UnixTimestamp::now());
+                Ok(v2::VerifyResponse::valid(payer.to_string()).with_remaining_validity(remaining))
+            }
+            ExactPayload::Permit2(permit2) => {
+                let (_erc20, payment, eip712_domain) = verify::assert_valid_permit2_payment(
+                    self.provider.inner(),
+                    self.provider.chain(),
+                    permit2,
+                    payload,
+                    requirements,
+                    &self.config,
+                    self.config.settle_grace_seconds,
+                )
+                .await?;
+                let payer = verify_permit2_payment(
+                    self.provider.inner(),
+                    &payment,
+                    &eip712_domain,
+                    self.signature_validator().await,
+                )
+                .await?;
+                let deadline = payment.deadline.try_into().unwrap_or(u64::MAX);
+                let remaining = remaining_validity(deadline, UnixTimestamp::now());
+                Ok(v2::VerifyResponse::valid(payer.to_string()).with_remaining_validity(remaining))
+            }
+        }
+    }
+
+    /// Settles a payment, skipping the checks that need chain access if it
+    /// was `verified` before with a verification token.
+    async fn settle_request(
+        &self,
+        request: proto::SettleRequest,
+        verified: bool,
+    ) -> Result<proto::SettleResponse, FacilitatorError> {
+        let request = types::v2::SettleRequest::from_settle_with(request, self.config.parse_mode)?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        self.assert_signer_funded(requirements.asset.into()).await?;
+        if !verified {
+            self.assert_asset_is_erc20(requirements.asset.into())
+                .await?;
+        }
+        match &payload.payload {
+            ExactPayload::Eip3009(eip3009) => {
+                let (contract, payment, eip712_domain) = if verified {
+                    verify::assert_verified_payment(
                         self.provider.inner(),
                         self.provider.chain(),
                         eip3009,
                         payload,
                         requirements,
-                        self.clock_skew_tolerance,
+                        &self.config,
+                        self.config.settle_grace_seconds,
                     )
-                    .await?;
-                    let payer =
-                        verify_payment(self.provider.inner(), &contract, &payment, &eip712_domain)
-                            .await?;
-                    Ok(v2::VerifyResponse::valid(payer.to_string()))
-                }
-                ExactPayload::Permit2(permit2) => {
-                    let (_erc20, payment, eip712_domain) = verify::assert_valid_permit2_payment(
+                    .await?
+                } else {
+                    verify::assert_valid_payment(
                         self.provider.inner(),
                         self.provider.chain(),
-                        permit2,
+                        eip3009,
                         payload,
                         requirements,
-                        self.clock_skew_tolerance,
+                        &self.config,
+                        self.config.settle_grace_seconds,
                     )
-                    .await?;
-                    let payer =
-                        verify_permit2_payment(self.provider.inner(), &payment, &eip712_domain)
-                            .await?;
-                    Ok(v2::VerifyResponse::valid(payer.to_string()))
-                }
+                    .await?
+                };
+                let eip712_domain = self
+                    .signing_domain(&contract, &payment, eip712_domain)
+                    .await;
+                let settlement =
+                    settle_payment(&self.provider, &contract, &payment, &eip712_domain).await?;
+                #[cfg(feature = "telemetry")]
+                log_settlement(
+                    &payload.accepted.network,
+                    payment.from,
+                    payment.to,
+                    *contract.address(),
+                    payment.value,
+                    settlement,
+                );
+                Ok(v2::SettleResponse::Success {
+                    payer: payment.from.to_string(),
+                    transaction: settlement.transaction.to_string(),
+                    network: payload.accepted.network.to_string(),
+                    extensions: None,
+                })
             }
-        })
-    }
-
-    fn settle(
-        &self,
-        request: proto::SettleRequest,
-    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
-        Box::pin(async move {
-            let request = types::v2::SettleRequest::from_settle(request)?;
-            let payload = &request.payment_payload;
-            let requirements = &request.payment_requirements;
-            match &payload.payload {
-                ExactPayload::Eip3009(eip3009) => {
-                    let (contract, payment, eip712_domain) = verify::assert_valid_payment(
-                        self.provider.inner(),
-                        self.provider.chain(),
-                        eip3009,
+            ExactPayload::Permit2(permit2) => {
+                let payment = if verified {
+                    verify::assert_verified_permit2_payment(
+                        *self.provider.chain(),
+                        permit2,
                         payload,
                         requirements,
-                        self.clock_skew_tolerance,
-                    )
-                    .await?;
-                    let tx_hash =
-                        settle_payment(&self.provider, &contract, &payment, &eip712_domain).await?;
-
-                    Ok(v2::SettleResponse::Success {
-                        payer: payment.from.to_string(),
-                        transaction: tx_hash.to_string(),
-                        network: payload.accepted.network.to_string(),
-                        extensions: None,
-                    })
-                }
-                ExactPayload::Permit2(permit2) => {
-                    let (_erc20, payment, _eip712_domain) = verify::assert_valid_permit2_payment(
+                        &self.config,
+                        self.config.settle_grace_seconds,
+                    )?
+                } else {
+                    verify::assert_valid_permit2_payment(
                         self.provider.inner(),
                         self.provider.chain(),
                         permit2,
                         payload,
                         requirements,
-                        self.clock_skew_tolerance,
+                        &self.config,
+                        self.config.settle_grace_seconds,
                     )
-                    .await?;
-                    let tx_hash = settle_permit2_payment(&self.provider, &payment).await?;
-                    Ok(v2::SettleResponse::Success {
-                        payer: payment.from.to_string(),
-                        transaction: tx_hash.to_string(),
-                        network: payload.accepted.network.to_string(),
-                        extensions: None,
-                    })
-                }
+                    .await?
+                    .1
+                };
+                let settlement = settle_permit2_payment(&self.provider, &payment).await?;
+                #[cfg(feature = "telemetry")]
+                log_settlement(
+                    &payload.accepted.network,
+                    payment.from,
+                    payment.to,
+                    payment.token,
+                    payment.amount,
+                    settlement,
+                );
+                Ok(v2::SettleResponse::Success {
+                    payer: payment.from.to_string(),
+                    transaction: settlement.transaction.to_string(),
+                    network: payload.accepted.network.to_string(),
+                    extensions: None,
+                })
             }
+        }
+    }
+}
+
+impl<P> Facilitator for Eip155ExactFacilitator<P>
+where
+    P: Eip155MetaTransactionProvider + ChainProvider + Send + Sync,
+    P::Inner: Provider,
+    Eip155ExactError: From<P::Error>,
+{
+    fn verify(
+        &self,
+        request: proto::VerifyRequest,
+    ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+        Box::pin(async move {
+            let Some(tokens) = &self.verification_tokens else {
+                return self.verify_request(request).await;
+            };
+            let response = self.verify_request(request.clone()).await?;
+            let token = match &response {
+                proto::VerifyResponse::Valid { payer, .. } => tokens.issue(&request, payer),
+                _ => return Ok(response),
+            };
+            Ok(response.with_verification_token(token))
         })
     }
 
+    fn settle(
+        &self,
+        request: proto::SettleRequest,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        Box::pin(self.settle_request(request, false))
+    }
+
+    fn settle_verified(
+        &self,
+        request: proto::SettleRequest,
+        token: String,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        Box::pin(async move {
+            let Some(tokens) = &self.verification_tokens else {
+                return self.settle_request(request, false).await;
+            };
+            tokens.check(&token, &request)?;
+            self.settle_request(request, true).await
+        })
+    }
+
+    fn pre_validate(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<(), proto::PaymentVerificationError> {
+        let request =
+            types::v2::VerifyRequest::from_proto_with(request.clone(), self.config.parse_mode)?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        let chain = self.provider.chain();
+        match &payload.payload {
+            ExactPayload::Eip3009(eip3009) => verify::assert_static_payment(
+                *chain,
+                eip3009,
+                payload,
+                requirements,
+                &self.config,
+                self.config.settle_grace_seconds,
+            ),
+            ExactPayload::Permit2(permit2) => verify::assert_static_permit2_payment(
+                *chain,
+                permit2,
+                payload,
+                requirements,
+                &self.config,
+                self.config.settle_grace_seconds,
+            ),
+        }
+    }
+
     fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
         Box::pin(async move {
             let chain_id = self.provider.chain_id();
-            let kinds = vec![proto::SupportedPaymentKind {
-                x402_version: v2::V2.into(),
-                scheme: ExactScheme.to_string(),
-                network: chain_id.into(),
-                extra: None,
-            }];
+            let eip6492 = self.supports_eip6492().await;
+            let kinds = vec![supported_kind(chain_id, &self.config, Some(eip6492))];
             let signers = {
                 let mut signers = HashMap::with_capacity(1);
                 signers.insert(Eip155Exact.caip_family(), self.provider.signer_addresses());
@@ -270,7 +821,248 @@ fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, Facilitato
                 kinds,
                 extensions: Vec::new(),
                 signers,
+                attestation_key: None,
             })
         })
     }
 }
+
+/// Builds the `/supported` entry for a chain, advertising the configured
+/// default asset and EIP-6492 support in `extra` when known.
+fn supported_kind(
+    chain_id: r402::chain::ChainId,
+    config: &Eip155ExactFacilitatorConfig,
+    eip6492: Option<bool>,
+) -> proto::SupportedPaymentKind {
+    let extra = SupportedPaymentKindExtra {
+        default_asset: config.default_asset,
+        eip6492,
+    };
+    let extra = (extra.default_asset.is_some() || extra.eip6492.is_some())
+        .then(|| serde_json::to_value(extra).ok())
+        .flatten();
+    proto::SupportedPaymentKind {
+        x402_version: v2::V2.into(),
+        scheme: ExactScheme.to_string(),
+        network: chain_id.into(),
+        extra,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r402::chain::ChainId;
+    use serde_json::json;
+
+    use super::*;
+    use crate::exact::{Eip3009Authorization, Eip3009Payload};
+    use crate::mock::{mock_rpc, provider, rpc_calls};
+
+    #[test]
+    fn test_supported_kind_advertises_default_asset_override() {
+        let bridged_usdc = address!("0xd9aAEc86B65D86f6A7B5B1b0c42FFA531710b6CA");
+        let config = Eip155ExactFacilitatorConfig {
+            default_asset: Some(bridged_usdc),
+            ..Eip155ExactFacilitatorConfig::default()
+        };
+
+        let kind = supported_kind(ChainId::new("eip155", "8453"), &config, None);
+        let extra: SupportedPaymentKindExtra = serde_json::from_value(kind.extra.unwrap()).unwrap();
+        assert_eq!(extra.default_asset, Some(bridged_usdc));
+    }
+
+    #[test]
+    fn test_supported_kind_without_override_has_no_extra() {
+        let kind = supported_kind(
+            ChainId::new("eip155", "8453"),
+            &Eip155ExactFacilitatorConfig::default(),
+            None,
+        );
+        assert!(kind.extra.is_none());
+    }
+
+    #[test]
+    fn test_config_parses_default_asset() {
+        let config: Eip155ExactFacilitatorConfig = serde_json::from_value(serde_json::json!({
+            "defaultAsset": "0xd9aAEc86B65D86f6A7B5B1b0c42FFA531710b6CA"
+        }))
+        .unwrap();
+        assert_eq!(
+            config.default_asset,
+            Some(address!("0xd9aAEc86B65D86f6A7B5B1b0c42FFA531710b6CA"))
+        );
+        assert_eq!(config.clock_skew_tolerance, DEFAULT_CLOCK_SKEW_TOLERANCE);
+    }
+
+    fn eip3009_request(network: ChainId, valid_before: UnixTimestamp) -> proto::VerifyRequest {
+        let pay_to = Address::repeat_byte(0x22);
+        let amount = TokenAmount(U256::from(1_000u64));
+        let requirements = types::v2::PaymentRequirements {
+            scheme: ExactScheme,
+            network,
+            amount,
+            pay_to: pay_to.into(),
+            max_timeout_seconds: 60,
+            asset: Address::repeat_byte(0x11).into(),
+            extra: None,
+            required_extensions: None,
+        };
+        let payment_payload = types::v2::PaymentPayload {
+            accepted: requirements.clone(),
+            payload: ExactPayload::Eip3009(Eip3009Payload {
+                signature: Bytes::from(vec![0; 65]),
+                authorization: Eip3009Authorization {
+                    from: Address::repeat_byte(0x33),
+                    to: pay_to,
+                    value: amount,
+                    valid_after: UnixTimestamp::from_secs(0),
+                    valid_before,
+                    nonce: B256::ZERO,
+                },
+            }),
+            resource: None,
+            x402_version: v2::V2,
+            extensions: None,
+        };
+        types::v2::VerifyRequest {
+            x402_version: v2::V2,
+            payment_payload,
+            payment_requirements: requirements,
+        }
+        .try_into()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_pre_validate_rejects_without_rpc() {
+        let server = mock_rpc([]).await;
+        let facilitator = Eip155ExactFacilitator::new(provider(&server));
+        let base = ChainId::new("eip155", "8453");
+        let later = UnixTimestamp::now() + 3_600;
+
+        let expired = eip3009_request(base.clone(), UnixTimestamp::from_secs(1));
+        assert!(matches!(
+            facilitator.pre_validate(&expired),
+            Err(proto::PaymentVerificationError::Expired)
+        ));
+        let wrong_network = eip3009_request(ChainId::new("eip155", "1"), later);
+        assert!(matches!(
+            facilitator.pre_validate(&wrong_network),
+            Err(proto::PaymentVerificationError::ChainIdMismatch)
+        ));
+        facilitator
+            .pre_validate(&eip3009_request(base, later))
+            .unwrap();
+
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pre_validate_enforces_authorization_lifetime() {
+        let server = mock_rpc([]).await;
+        let facilitator =
+            Eip155ExactFacilitator::new(provider(&server)).with_max_authorization_lifetime(600);
+        let base = ChainId::new("eip155", "8453");
+        let now = UnixTimestamp::now();
+
+        facilitator
+            .pre_validate(&eip3009_request(base.clone(), now + 600))
+            .unwrap();
+        assert!(matches!(
+            facilitator.pre_validate(&eip3009_request(base, now + 700)),
+            Err(proto::PaymentVerificationError::AuthorizationTooLong { max: 600, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unfunded_signer_is_rejected_early() {
+        let server = mock_rpc([
+            ("eth_gasPrice", json!("0x3b9aca00")),
+            ("eth_getBalance", json!("0x0")),
+        ])
+        .await;
+        let facilitator = Eip155ExactFacilitator::new(provider(&server));
+        let request = eip3009_request(ChainId::new("eip155", "8453"), UnixTimestamp::now() + 3_600);
+
+        let err = facilitator
+            .settle(request.clone().into())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            FacilitatorError::PaymentVerification(
+                proto::PaymentVerificationError::TransactionSimulation(ref reason)
+            ) if reason == "signer unfunded"
+        ));
+        assert!(facilitator.verify(request).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_funded_signer_check_is_cached() {
+        let server = mock_rpc([
+            ("eth_gasPrice", json!("0x3b9aca00")),
+            ("eth_getBalance", json!("0xde0b6b3a7640000")),
+        ])
+        .await;
+        let facilitator = Eip155ExactFacilitator::new(provider(&server));
+
+        let asset = Address::repeat_byte(0x11);
+        facilitator.assert_signer_funded(asset).await.unwrap();
+        facilitator.assert_signer_funded(asset).await.unwrap();
+        assert_eq!(rpc_calls(&server, "eth_getBalance").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_settlement_cost_is_estimated_once_per_asset() {
+        let server = mock_rpc([
+            ("eth_estimateGas", json!("0x186a0")),
+            ("eth_gasPrice", json!("0x3b9aca00")),
+            // Covers the estimate, but not `SETTLEMENT_GAS_ESTIMATE` gas.
+            ("eth_getBalance", json!("0x6d23ad5f8000")),
+        ])
+        .await;
+        let facilitator = Eip155ExactFacilitator::new(provider(&server));
+        let payment = Eip3009Payment {
+            from: Address::repeat_byte(0x33),
+            to: Address::repeat_byte(0x22),
+            value: U256::from(1_000u64),
+            valid_after: UnixTimestamp::from_secs(0),
+            valid_before: UnixTimestamp::now() + 3_600,
+            nonce: B256::ZERO,
+            signature: Bytes::from(vec![0; 65]),
+            transfer_selector: None,
+        };
+        let asset = Address::repeat_byte(0x11);
+
+        // 100_000 gas at 1 gwei
+        let expected = U256::from(100_000_000_000_000u64);
+        for _ in 0..2 {
+            let cost = facilitator
+                .estimate_settlement_cost(asset, &payment)
+                .await
+                .unwrap();
+            assert_eq!(cost, expected);
+        }
+        // The signer funds check reuses the cached estimate.
+        facilitator.assert_signer_funded(asset).await.unwrap();
+
+        for rpc_method in ["eth_estimateGas", "eth_gasPrice", "eth_getBalance"] {
+            assert_eq!(rpc_calls(&server, rpc_method).await, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_validator_disables_eip6492() {
+        let server = mock_rpc([("eth_getCode", json!("0x"))]).await;
+        let facilitator = Eip155ExactFacilitator::new(provider(&server));
+
+        assert!(!facilitator.supports_eip6492().await);
+        let supported = facilitator.supported().await.unwrap();
+        let extra: SupportedPaymentKindExtra =
+            serde_json::from_value(supported.kinds[0].extra.clone().unwrap()).unwrap();
+        assert_eq!(extra.eip6492, Some(false));
+        assert_eq!(facilitator.signature_validator().await, None);
+
+        assert_eq!(rpc_calls(&server, "eth_getCode").await, 1);
+    }
+}
@@ -0,0 +1,203 @@
+//! Verification of settled payments on behalf of third parties.
+//!
+//! Marketplaces and escrows that never saw the original payment payload can
+//! still confirm that a payment happened: given the transaction hash and the
+//! expected terms, the receipt is fetched and its ERC-20 `Transfer` logs are
+//! matched against them.
+
+use alloy_primitives::{Address, TxHash};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::Log;
+use alloy_sol_types::SolEvent;
+use alloy_transport::TransportError;
+use r402::chain::ChainId;
+use serde::{Deserialize, Serialize};
+
+use super::contract::IEIP3009;
+use crate::chain::TokenAmount;
+
+/// Awaits a future, optionally instrumenting it with a tracing span.
+macro_rules! traced {
+    ($fut:expr, $span:expr) => {{
+        #[cfg(feature = "telemetry")]
+        {
+            use tracing::Instrument;
+            $fut.instrument($span).await
+        }
+        #[cfg(not(feature = "telemetry"))]
+        {
+            $fut.await
+        }
+    }};
+}
+
+/// A claimed payment: the settlement transaction and the terms it must meet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentProof {
+    /// Network the transaction was sent on.
+    pub network: ChainId,
+    /// Hash of the settlement transaction.
+    pub transaction: TxHash,
+    /// Expected recipient of the transfer.
+    pub pay_to: Address,
+    /// Expected token contract.
+    pub asset: Address,
+    /// Expected transfer amount, in token units.
+    pub amount: TokenAmount,
+}
+
+/// Result of checking a [`PaymentProof`] against the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum ProofOutcome {
+    /// The transaction succeeded and transferred exactly the expected amount.
+    Confirmed {
+        /// Account the tokens were transferred from.
+        payer: Address,
+    },
+    /// No receipt exists for the transaction, e.g. it is unknown or pending.
+    NotFound,
+    /// The transaction was mined but reverted.
+    Reverted,
+    /// The transaction succeeded but contains no transfer matching the terms.
+    NoMatchingTransfer,
+}
+
+/// Checks on-chain that `proof.transaction` paid `proof.amount` of
+/// `proof.asset` to `proof.pay_to`.
+///
+/// The caller is responsible for picking a `provider` for `proof.network`.
+///
+/// # Errors
+///
+/// Returns [`TransportError`] if the receipt cannot be fetched.
+pub async fn verify_payment_proof<P: Provider>(
+    provider: &P,
+    proof: &PaymentProof,
+) -> Result<ProofOutcome, TransportError> {
+    let receipt_fut = provider.get_transaction_receipt(proof.transaction);
+    let receipt = traced!(
+        receipt_fut,
+        tracing::info_span!("verify_payment_proof",
+            transaction = %proof.transaction,
+            otel.kind = "client",
+        )
+    )?;
+    let Some(receipt) = receipt else {
+        return Ok(ProofOutcome::NotFound);
+    };
+    if !receipt.status() {
+        return Ok(ProofOutcome::Reverted);
+    }
+    Ok(matching_transfer(receipt.inner.logs(), proof)
+        .map_or(ProofOutcome::NoMatchingTransfer, |payer| {
+            ProofOutcome::Confirmed { payer }
+        }))
+}
+
+/// Returns the sender of the first `Transfer` log matching the proof terms.
+fn matching_transfer(logs: &[Log], proof: &PaymentProof) -> Option<Address> {
+    logs.iter()
+        .filter(|log| !log.removed && log.address() == proof.asset)
+        .filter_map(|log| IEIP3009::Transfer::decode_log(&log.inner).ok())
+        .find(|transfer| transfer.to == proof.pay_to && transfer.value == proof.amount.0)
+        .map(|transfer| transfer.from)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{B256, U256};
+    use alloy_provider::RootProvider;
+    use alloy_sol_types::SolValue;
+    use serde_json::json;
+    use url::Url;
+    use wiremock::MockServer;
+
+    use super::*;
+    use crate::mock::mock_rpc;
+
+    const ASSET: Address = Address::repeat_byte(0x11);
+    const PAYER: Address = Address::repeat_byte(0x22);
+    const PAY_TO: Address = Address::repeat_byte(0x33);
+    const TX: TxHash = TxHash::repeat_byte(0x44);
+
+    /// Serves a successful receipt for [`TX`] transferring `value` from
+    /// [`PAYER`] to [`PAY_TO`].
+    async fn receipt_server(value: u64) -> MockServer {
+        let block_hash = B256::repeat_byte(0x55);
+        let log = json!({
+            "address": ASSET,
+            "topics": [
+                IEIP3009::Transfer::SIGNATURE_HASH,
+                PAYER.into_word(),
+                PAY_TO.into_word(),
+            ],
+            "data": alloy_primitives::Bytes::from(U256::from(value).abi_encode()),
+            "blockHash": block_hash,
+            "blockNumber": "0x1",
+            "transactionHash": TX,
+            "transactionIndex": "0x0",
+            "logIndex": "0x0",
+            "removed": false,
+        });
+        let receipt = json!({
+            "type": "0x2",
+            "status": "0x1",
+            "cumulativeGasUsed": "0xea60",
+            "logsBloom": alloy_primitives::Bloom::default(),
+            "logs": [log],
+            "transactionHash": TX,
+            "transactionIndex": "0x0",
+            "blockHash": block_hash,
+            "blockNumber": "0x1",
+            "gasUsed": "0xea60",
+            "effectiveGasPrice": "0x1",
+            "from": Address::repeat_byte(0x66),
+            "to": ASSET,
+            "contractAddress": null,
+        });
+        mock_rpc([("eth_getTransactionReceipt", receipt)]).await
+    }
+
+    fn proof(amount: u64) -> PaymentProof {
+        PaymentProof {
+            network: ChainId::new("eip155", "8453"),
+            transaction: TX,
+            pay_to: PAY_TO,
+            asset: ASSET,
+            amount: TokenAmount(U256::from(amount)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_matching_transfer_is_confirmed() {
+        let server = receipt_server(1_000).await;
+        let provider = RootProvider::new_http(Url::parse(&server.uri()).unwrap());
+
+        let outcome = verify_payment_proof(&provider, &proof(1_000))
+            .await
+            .unwrap();
+        assert_eq!(outcome, ProofOutcome::Confirmed { payer: PAYER });
+    }
+
+    #[tokio::test]
+    async fn test_mismatching_transfer_is_rejected() {
+        let server = receipt_server(999).await;
+        let provider = RootProvider::new_http(Url::parse(&server.uri()).unwrap());
+
+        let outcome = verify_payment_proof(&provider, &proof(1_000))
+            .await
+            .unwrap();
+        assert_eq!(outcome, ProofOutcome::NoMatchingTransfer);
+
+        let other_recipient = PaymentProof {
+            pay_to: Address::repeat_byte(0x77),
+            ..proof(999)
+        };
+        let outcome = verify_payment_proof(&provider, &other_recipient)
+            .await
+            .unwrap();
+        assert_eq!(outcome, ProofOutcome::NoMatchingTransfer);
+    }
+}
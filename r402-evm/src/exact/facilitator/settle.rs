@@ -4,10 +4,12 @@
 //! `transferWithAuthorization` call wrapper types.
 
 use alloy_contract::SolCallBuilder;
-use alloy_primitives::{Address, B256, Bytes, Signature, TxHash, U256};
+use alloy_network::TransactionBuilder;
+use alloy_primitives::{Address, B256, Bytes, FixedBytes, Signature, TxHash, U256};
 use alloy_provider::bindings::IMulticall3;
 use alloy_provider::{MULTICALL3_ADDRESS, MulticallItem, Provider};
-use alloy_sol_types::{Eip712Domain, SolCall};
+use alloy_rpc_types_eth::{Filter, Log};
+use alloy_sol_types::{Eip712Domain, SolCall, SolEvent};
 use alloy_transport::TransportError;
 #[cfg(feature = "telemetry")]
 use tracing_core::Level;
@@ -69,6 +71,7 @@ pub fn new(
             nonce,
             signature.clone(),
         );
+        let tx = override_selector(tx, payment.transfer_selector);
         TransferWithAuthorization0Call(TransferWithAuthorizationCall {
             tx,
             from,
@@ -123,6 +126,7 @@ pub fn new(
             r,
             s,
         );
+        let tx = override_selector(tx, payment.transfer_selector);
         TransferWithAuthorization1Call(TransferWithAuthorizationCall {
             tx,
             from,
@@ -137,6 +141,31 @@ pub fn new(
     }
 }
 
+/// Returns `calldata` with its 4-byte function selector replaced by `selector`.
+///
+/// Calldata shorter than a selector is returned unchanged.
+#[must_use]
+pub fn replace_selector(calldata: &[u8], selector: FixedBytes<4>) -> Bytes {
+    let mut calldata = calldata.to_vec();
+    if let Some(head) = calldata.get_mut(..4) {
+        head.copy_from_slice(selector.as_slice());
+    }
+    calldata.into()
+}
+
+/// Swaps the selector of a `transferWithAuthorization` call for the token's
+/// non-standard one, if any. Arguments keep their standard ABI encoding.
+fn override_selector<P: Provider, TCall: SolCall>(
+    tx: SolCallBuilder<P, TCall>,
+    selector: Option<FixedBytes<4>>,
+) -> SolCallBuilder<P, TCall> {
+    let Some(selector) = selector else {
+        return tx;
+    };
+    let calldata = replace_selector(tx.calldata(), selector);
+    tx.map(|request| request.with_input(calldata))
+}
+
 /// A prepared call to `transferWithAuthorization` (ERC-3009) including all derived fields.
 #[allow(missing_debug_implementations)]
 pub struct TransferWithAuthorizationCall<P, TCall, TSignature> {
@@ -176,22 +205,187 @@ async fn is_contract_deployed<P: Provider>(
     Ok(!bytes.is_empty())
 }
 
+/// Number of blocks scanned backwards when looking for the transaction that
+/// already consumed an authorization. Payment windows are short-lived, so the
+/// competing settlement is expected to be recent.
+const RECONCILE_LOOKBACK_BLOCKS: u64 = 2_048;
+
+/// A settlement transaction and the facilitator signer that sent it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settlement {
+    /// Hash of the settlement transaction.
+    pub transaction: TxHash,
+    /// Facilitator signer that sent the transaction.
+    ///
+    /// `None` when the payment was reconciled with a transaction sent by
+    /// someone else.
+    pub signer: Option<Address>,
+}
+
 /// Settles a verified payment by sending the transfer transaction on-chain.
 ///
+/// If the transfer reverts because the authorization nonce was already
+/// consumed (e.g. another facilitator replica settled the same payment first),
+/// the chain is queried for the `AuthorizationUsed` event and the hash of the
+/// transaction that consumed it is returned instead, provided that
+/// transaction made the transfer of `payment`.
+///
 /// # Errors
 ///
-/// Returns [`Eip155ExactError`] if the on-chain settlement transaction fails.
+/// Returns [`Eip155ExactError`] if the on-chain settlement transaction fails,
+/// or [`Eip155ExactError::NonceAlreadyConsumed`] if the nonce was consumed by
+/// a transaction that could not be located or that did not pay `payment.to`
+/// the `payment.value`.
 ///
 /// # Panics
 ///
 /// Panics if the authorization deadline timestamp overflows `i64`.
-#[allow(clippy::cognitive_complexity)]
 pub async fn settle_payment<P, E>(
     provider: &P,
     contract: &IEIP3009::IEIP3009Instance<&P::Inner>,
     payment: &Eip3009Payment,
     eip712_domain: &Eip712Domain,
-) -> Result<TxHash, Eip155ExactError>
+) -> Result<Settlement, Eip155ExactError>
+where
+    P: Eip155MetaTransactionProvider<Error = E> + Sync,
+    Eip155ExactError: From<E>,
+{
+    let result = send_transfer_with_authorization(provider, contract, payment, eip712_domain).await;
+    reconcile_settlement(contract, payment, result).await
+}
+
+/// Replaces a failed settlement `result` with the transaction that already
+/// consumed the authorization, if that transaction paid `payment`.
+///
+/// A consumed nonce alone does not prove the payment: the payer may have
+/// signed another authorization with the same nonce, e.g. paying
+/// themselves, and front-run the settlement. The `Transfer` of the
+/// consuming transaction is therefore checked against `payment`.
+async fn reconcile_settlement<P: Provider>(
+    contract: &IEIP3009::IEIP3009Instance<&P>,
+    payment: &Eip3009Payment,
+    result: Result<Settlement, Eip155ExactError>,
+) -> Result<Settlement, Eip155ExactError> {
+    let consumed = match &result {
+        Err(Eip155ExactError::TransactionReverted(_)) => {
+            contract
+                .authorizationState(payment.from, payment.nonce)
+                .call()
+                .await?
+        }
+        Err(err) => err.is_nonce_consumed_revert(),
+        Ok(_) => false,
+    };
+    if !consumed {
+        return result;
+    }
+
+    #[cfg(feature = "telemetry")]
+    tracing::event!(Level::INFO,
+        from = %payment.from,
+        nonce = %payment.nonce,
+        "authorization already consumed, reconciling with existing settlement"
+    );
+    let provider = *contract.provider();
+    let token = *contract.address();
+    let consumed_by =
+        find_authorization_settlement(provider, token, payment.from, payment.nonce).await?;
+    if let Some(transaction) = consumed_by
+        && transaction_pays(provider, transaction, token, payment).await?
+    {
+        return Ok(Settlement {
+            transaction,
+            signer: None,
+        });
+    }
+    #[cfg(feature = "telemetry")]
+    tracing::event!(Level::WARN,
+        from = %payment.from,
+        nonce = %payment.nonce,
+        tx = ?consumed_by,
+        "authorization consumed without the expected transfer"
+    );
+    Err(Eip155ExactError::NonceAlreadyConsumed {
+        authorizer: payment.from,
+        nonce: payment.nonce,
+    })
+}
+
+/// Checks that `transaction` succeeded and transferred `payment.value` of
+/// `token` from `payment.from` to `payment.to`.
+async fn transaction_pays<P: Provider>(
+    provider: &P,
+    transaction: TxHash,
+    token: Address,
+    payment: &Eip3009Payment,
+) -> Result<bool, TransportError> {
+    let receipt = provider.get_transaction_receipt(transaction).await?;
+    Ok(receipt.is_some_and(|receipt| {
+        receipt.status() && has_transfer(receipt.inner.logs(), token, payment)
+    }))
+}
+
+/// Returns `true` if `logs` contain the `Transfer` of `token` made by `payment`.
+fn has_transfer(logs: &[Log], token: Address, payment: &Eip3009Payment) -> bool {
+    logs.iter()
+        .filter(|log| !log.removed && log.address() == token)
+        .filter_map(|log| IEIP3009::Transfer::decode_log(&log.inner).ok())
+        .any(|transfer| {
+            transfer.from == payment.from
+                && transfer.to == payment.to
+                && transfer.value == payment.value
+        })
+}
+
+/// Looks up the transaction that consumed an ERC-3009 authorization.
+///
+/// Scans the most recent blocks of `token` for the `AuthorizationUsed`
+/// event matching `authorizer` and `nonce`.
+///
+/// # Errors
+///
+/// Returns [`TransportError`] if the RPC calls fail.
+pub async fn find_authorization_settlement<P: Provider>(
+    provider: &P,
+    token: Address,
+    authorizer: Address,
+    nonce: B256,
+) -> Result<Option<TxHash>, TransportError> {
+    let latest = provider.get_block_number().await?;
+    let filter = Filter::new()
+        .address(token)
+        .event_signature(IEIP3009::AuthorizationUsed::SIGNATURE_HASH)
+        .topic1(authorizer.into_word())
+        .topic2(nonce)
+        .from_block(latest.saturating_sub(RECONCILE_LOOKBACK_BLOCKS));
+    let logs_fut = provider.get_logs(&filter).into_future();
+    let logs = traced!(
+        logs_fut,
+        tracing::info_span!("find_authorization_settlement",
+            token = %token,
+            authorizer = %authorizer,
+            nonce = %nonce,
+            otel.kind = "client",
+        )
+    )?;
+    Ok(settled_transaction(&logs))
+}
+
+/// Returns the transaction hash of the first non-removed log, if any.
+fn settled_transaction(logs: &[Log]) -> Option<TxHash> {
+    logs.iter()
+        .filter(|log| !log.removed)
+        .find_map(|log| log.transaction_hash)
+}
+
+/// Sends the `transferWithAuthorization` transaction matching the signature kind.
+#[allow(clippy::cognitive_complexity)]
+async fn send_transfer_with_authorization<P, E>(
+    provider: &P,
+    contract: &IEIP3009::IEIP3009Instance<&P::Inner>,
+    payment: &Eip3009Payment,
+    eip712_domain: &Eip712Domain,
+) -> Result<Settlement, Eip155ExactError>
 where
     P: Eip155MetaTransactionProvider<Error = E> + Sync,
     Eip155ExactError: From<E>,
@@ -335,7 +529,10 @@ pub async fn settle_payment<P, E>(
             tx = %receipt.transaction_hash,
             "transferWithAuthorization succeeded"
         );
-        Ok(receipt.transaction_hash)
+        Ok(Settlement {
+            transaction: receipt.transaction_hash,
+            signer: Some(receipt.from),
+        })
     } else {
         #[cfg(feature = "telemetry")]
         tracing::event!(
@@ -359,7 +556,7 @@ pub async fn settle_payment<P, E>(
 pub async fn settle_permit2_payment<P, E>(
     provider: &P,
     payment: &Permit2Payment,
-) -> Result<TxHash, Eip155ExactError>
+) -> Result<Settlement, Eip155ExactError>
 where
     P: Eip155MetaTransactionProvider<Error = E> + Sync,
     Eip155ExactError: From<E>,
@@ -411,7 +608,10 @@ pub async fn settle_permit2_payment<P, E>(
             tx = %receipt.transaction_hash,
             "Permit2 settle succeeded"
         );
-        Ok(receipt.transaction_hash)
+        Ok(Settlement {
+            transaction: receipt.transaction_hash,
+            signer: Some(receipt.from),
+        })
     } else {
         #[cfg(feature = "telemetry")]
         tracing::event!(
@@ -425,3 +625,194 @@ pub async fn settle_permit2_payment<P, E>(
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{address, b256, fixed_bytes};
+    use alloy_provider::RootProvider;
+    use alloy_sol_types::SolValue;
+    use r402::proto::UnixTimestamp;
+    use serde_json::json;
+    use url::Url;
+    use wiremock::MockServer;
+
+    use super::*;
+    use crate::mock::mock_rpc;
+
+    #[test]
+    fn test_nonce_consumed_revert_detected() {
+        let err = Eip155ExactError::ContractCall(
+            "execution reverted: FiatTokenV2: authorization is used or canceled".into(),
+        );
+        assert!(err.is_nonce_consumed_revert());
+
+        let err = Eip155ExactError::ContractCall("execution reverted: insufficient funds".into());
+        assert!(!err.is_nonce_consumed_revert());
+    }
+
+    #[test]
+    fn test_settled_transaction_from_logs() {
+        let tx_hash = b256!("0x1111111111111111111111111111111111111111111111111111111111111111");
+        let removed = Log {
+            transaction_hash: Some(B256::repeat_byte(0x22)),
+            removed: true,
+            ..Default::default()
+        };
+        let settled = Log {
+            transaction_hash: Some(tx_hash),
+            ..Default::default()
+        };
+        assert_eq!(settled_transaction(&[removed, settled]), Some(tx_hash));
+        assert_eq!(settled_transaction(&[]), None);
+    }
+
+    #[test]
+    fn test_transfer_call_uses_selector_override() {
+        let provider = RootProvider::new_http("http://localhost:8545".parse().unwrap());
+        let contract = IEIP3009::new(
+            address!("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"),
+            &provider,
+        );
+        let selector = fixed_bytes!("0xcafebabe");
+        let mut payment = Eip3009Payment {
+            from: Address::repeat_byte(0x11),
+            to: Address::repeat_byte(0x22),
+            value: U256::from(10_000),
+            valid_after: UnixTimestamp::from_secs(0),
+            valid_before: UnixTimestamp::from_secs(3_600),
+            nonce: B256::repeat_byte(0x33),
+            signature: Bytes::from_static(&[0xab; 65]),
+            transfer_selector: None,
+        };
+        let standard =
+            TransferWithAuthorization0Call::new(&contract, &payment, payment.signature.clone());
+        payment.transfer_selector = Some(selector);
+        let custom =
+            TransferWithAuthorization0Call::new(&contract, &payment, payment.signature.clone());
+
+        let standard = standard.0.tx.calldata().clone();
+        let custom = custom.0.tx.calldata().clone();
+        assert_eq!(
+            standard[..4],
+            IEIP3009::transferWithAuthorization_0Call::SELECTOR
+        );
+        assert_eq!(custom[..4], selector);
+        assert_eq!(custom[4..], standard[4..]);
+    }
+
+    const TOKEN: Address = Address::repeat_byte(0x44);
+    const CONSUMING_TX: TxHash = TxHash::repeat_byte(0x55);
+
+    fn payment() -> Eip3009Payment {
+        Eip3009Payment {
+            from: Address::repeat_byte(0x11),
+            to: Address::repeat_byte(0x22),
+            value: U256::from(10_000),
+            valid_after: UnixTimestamp::from_secs(0),
+            valid_before: UnixTimestamp::from_secs(3_600),
+            nonce: B256::repeat_byte(0x33),
+            signature: Bytes::from_static(&[0xab; 65]),
+            transfer_selector: None,
+        }
+    }
+
+    /// Serves a chain where the nonce of [`payment`] was consumed by
+    /// [`CONSUMING_TX`], which transferred `value` to `to`.
+    async fn consumed_nonce_server(to: Address, value: u64) -> MockServer {
+        let payment = payment();
+        let block_hash = B256::repeat_byte(0x66);
+        let log = |topics: Vec<B256>, data: Bytes, index: &str| {
+            json!({
+                "address": TOKEN,
+                "topics": topics,
+                "data": data,
+                "blockHash": block_hash,
+                "blockNumber": "0x1",
+                "transactionHash": CONSUMING_TX,
+                "transactionIndex": "0x0",
+                "logIndex": index,
+                "removed": false,
+            })
+        };
+        let authorization_used = log(
+            vec![
+                IEIP3009::AuthorizationUsed::SIGNATURE_HASH,
+                payment.from.into_word(),
+                payment.nonce,
+            ],
+            Bytes::new(),
+            "0x0",
+        );
+        let transfer = log(
+            vec![
+                IEIP3009::Transfer::SIGNATURE_HASH,
+                payment.from.into_word(),
+                to.into_word(),
+            ],
+            U256::from(value).abi_encode().into(),
+            "0x1",
+        );
+        let receipt = json!({
+            "type": "0x2",
+            "status": "0x1",
+            "cumulativeGasUsed": "0xea60",
+            "logsBloom": alloy_primitives::Bloom::default(),
+            "logs": [authorization_used, transfer],
+            "transactionHash": CONSUMING_TX,
+            "transactionIndex": "0x0",
+            "blockHash": block_hash,
+            "blockNumber": "0x1",
+            "gasUsed": "0xea60",
+            "effectiveGasPrice": "0x1",
+            "from": Address::repeat_byte(0x77),
+            "to": TOKEN,
+            "contractAddress": null,
+        });
+        mock_rpc([
+            // `authorizationState` is `true`.
+            ("eth_call", json!(Bytes::from(true.abi_encode()))),
+            ("eth_blockNumber", json!("0x10")),
+            ("eth_getLogs", json!([authorization_used])),
+            ("eth_getTransactionReceipt", receipt),
+        ])
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_consumed_nonce_is_reconciled_with_matching_transfer() {
+        let payment = payment();
+        let server = consumed_nonce_server(payment.to, 10_000).await;
+        let provider = RootProvider::new_http(Url::parse(&server.uri()).unwrap());
+        let contract = IEIP3009::new(TOKEN, &provider);
+
+        let reverted = TxHash::repeat_byte(0x99);
+        let reverted = Err(Eip155ExactError::TransactionReverted(reverted));
+        let settlement = reconcile_settlement(&contract, &payment, reverted)
+            .await
+            .unwrap();
+        assert_eq!(
+            settlement,
+            Settlement {
+                transaction: CONSUMING_TX,
+                signer: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_consumed_nonce_with_other_transfer_is_not_reconciled() {
+        let payment = payment();
+        for (to, value) in [(payment.from, 10_000), (payment.to, 1)] {
+            let server = consumed_nonce_server(to, value).await;
+            let provider = RootProvider::new_http(Url::parse(&server.uri()).unwrap());
+            let contract = IEIP3009::new(TOKEN, &provider);
+
+            let reverted = TxHash::repeat_byte(0x99);
+            let reverted = Err(Eip155ExactError::TransactionReverted(reverted));
+            let err = reconcile_settlement(&contract, &payment, reverted)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Eip155ExactError::NonceAlreadyConsumed { .. }));
+        }
+    }
+}
@@ -4,7 +4,7 @@
 //! composite [`verify_payment`] function that ties signature verification
 //! to an on-chain simulation.
 
-use alloy_primitives::{Address, B256, U256};
+use alloy_primitives::{Address, B256, FixedBytes, U256};
 use alloy_provider::Provider;
 use alloy_sol_types::SolStruct;
 use alloy_sol_types::{Eip712Domain, eip712_domain};
@@ -16,12 +16,12 @@
 
 use super::Eip3009Payment;
 use super::Permit2Payment;
-use super::VALIDATOR_ADDRESS;
-use super::contract::{IEIP3009, IERC20, Validator6492};
+use super::config::Eip155ExactFacilitatorConfig;
+use super::contract::{IEIP3009, IERC20, IERC1271, Validator6492};
 use super::error::Eip155ExactError;
 use super::settle::{TransferWithAuthorization0Call, TransferWithAuthorization1Call};
 use super::signature::{SignedMessage, StructuredSignature};
-use crate::chain::Eip155ChainReference;
+use crate::chain::{Eip155ChainReference, TokenAmount};
 use crate::exact::Eip3009Payload;
 use crate::exact::PaymentRequirementsExtra;
 use crate::exact::PermitWitnessTransferFrom;
@@ -53,23 +53,12 @@ pub(super) async fn assert_valid_payment<P: Provider>(
     eip3009: &Eip3009Payload,
     payload: &types::v2::PaymentPayload,
     requirements: &types::v2::PaymentRequirements,
-    clock_skew_tolerance: u64,
+    config: &Eip155ExactFacilitatorConfig,
+    expiry_grace: u64,
 ) -> Result<(IEIP3009::IEIP3009Instance<P>, Eip3009Payment, Eip712Domain), Eip155ExactError> {
+    assert_static_payment(*chain, eip3009, payload, requirements, config, expiry_grace)?;
     let accepted = &payload.accepted;
-    assert_requirements_match(accepted, requirements)?;
-
-    let chain_id: ChainId = chain.into();
-    let payload_chain_id = &accepted.network;
-    if payload_chain_id != &chain_id {
-        return Err(PaymentVerificationError::ChainIdMismatch.into());
-    }
     let authorization = &eip3009.authorization;
-    if authorization.to != accepted.pay_to {
-        return Err(PaymentVerificationError::RecipientMismatch.into());
-    }
-    let valid_after = authorization.valid_after;
-    let valid_before = authorization.valid_before;
-    assert_time(valid_after, valid_before, clock_skew_tolerance)?;
     let asset_address = accepted.asset;
     let contract = IEIP3009::new(asset_address.into(), provider);
 
@@ -77,14 +66,54 @@ pub(super) async fn assert_valid_payment<P: Provider>(
 
     // Run independent RPC checks in parallel to reduce latency from ~3 RTTs to ~1 RTT.
     let asset_addr: Address = asset_address.into();
-    let (domain, (), ()) = tokio::try_join!(
+    let (domain, ()) = tokio::try_join!(
         assert_domain(chain, &contract, &asset_addr, &accepted.extra),
-        assert_nonce_unused(&contract, &authorization.from, &authorization.nonce),
-        assert_enough_balance(&contract, &authorization.from, amount_required.into()),
+        assert_payment_state(
+            &contract,
+            &authorization.from,
+            &authorization.nonce,
+            amount_required.into(),
+            config.multicall3_address,
+        ),
     )?;
-    assert_enough_value(&authorization.value.into(), &amount_required.into())?;
 
-    let payment = Eip3009Payment {
+    let selector = config.transfer_with_auth_selector(asset_addr);
+    Ok((contract, eip3009_payment(eip3009, selector), domain))
+}
+
+/// Runs the EIP-3009 preconditions of a payment that was already verified
+/// and is settled with a verification token.
+///
+/// Skips the balance, nonce and signature checks of [`assert_valid_payment`],
+/// which the token vouches for. The EIP-712 domain is only read from the
+/// token contract if the requirements do not name it.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err))]
+pub(super) async fn assert_verified_payment<P: Provider>(
+    provider: P,
+    chain: &Eip155ChainReference,
+    eip3009: &Eip3009Payload,
+    payload: &types::v2::PaymentPayload,
+    requirements: &types::v2::PaymentRequirements,
+    config: &Eip155ExactFacilitatorConfig,
+    expiry_grace: u64,
+) -> Result<(IEIP3009::IEIP3009Instance<P>, Eip3009Payment, Eip712Domain), Eip155ExactError> {
+    assert_static_payment(*chain, eip3009, payload, requirements, config, expiry_grace)?;
+    let accepted = &payload.accepted;
+    let asset_addr: Address = accepted.asset.into();
+    let contract = IEIP3009::new(asset_addr, provider);
+    let domain = assert_domain(chain, &contract, &asset_addr, &accepted.extra).await?;
+    let selector = config.transfer_with_auth_selector(asset_addr);
+    Ok((contract, eip3009_payment(eip3009, selector), domain))
+}
+
+/// Builds the settlement parameters of an EIP-3009 payload, settled with the
+/// `transferWithAuthorization` selector override of the token, if any.
+fn eip3009_payment(
+    eip3009: &Eip3009Payload,
+    transfer_selector: Option<FixedBytes<4>>,
+) -> Eip3009Payment {
+    let authorization = &eip3009.authorization;
+    Eip3009Payment {
         from: authorization.from,
         to: authorization.to,
         value: authorization.value.into(),
@@ -92,9 +121,51 @@ pub(super) async fn assert_valid_payment<P: Provider>(
         valid_before: authorization.valid_before,
         nonce: authorization.nonce,
         signature: eip3009.signature.clone(),
-    };
+        transfer_selector,
+    }
+}
+
+/// Runs the EIP-3009 preconditions that need no chain access.
+///
+/// `expiry_grace` relaxes the expiration check, see [`assert_time`].
+///
+/// # Errors
+///
+/// Returns the [`PaymentVerificationError`] of the first failed check.
+pub(super) fn assert_static_payment(
+    chain: Eip155ChainReference,
+    eip3009: &Eip3009Payload,
+    payload: &types::v2::PaymentPayload,
+    requirements: &types::v2::PaymentRequirements,
+    config: &Eip155ExactFacilitatorConfig,
+    expiry_grace: u64,
+) -> Result<(), PaymentVerificationError> {
+    let accepted = &payload.accepted;
+    assert_requirements_match(accepted, requirements)?;
+    requirements.check_required_extensions(payload.extensions.as_ref())?;
 
-    Ok((contract, payment, domain))
+    let chain_id: ChainId = chain.into();
+    let payload_chain_id = &accepted.network;
+    if payload_chain_id != &chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch);
+    }
+    let authorization = &eip3009.authorization;
+    if authorization.to != accepted.pay_to {
+        return Err(PaymentVerificationError::RecipientMismatch);
+    }
+    assert_time(
+        authorization.valid_after,
+        authorization.valid_before,
+        config.clock_skew_tolerance,
+        expiry_grace,
+    )?;
+    assert_authorization_lifetime(
+        authorization.valid_after.as_secs(),
+        authorization.valid_before.as_secs(),
+        config.max_authorization_lifetime,
+    )?;
+    assert_min_settle_amount(&authorization.value.into(), config.min_settle_amount)?;
+    assert_enough_value(&authorization.value.into(), &accepted.amount.into())
 }
 
 /// Validates that the accepted requirements match the server-side requirements
@@ -152,10 +223,72 @@ pub async fn assert_nonce_unused<P: Provider>(
     Ok(())
 }
 
+/// Checks that the EIP-3009 nonce is unused and the payer can cover
+/// `max_amount_required`.
+///
+/// With a `multicall3` address, both reads are batched into a single
+/// `aggregate3` call. If that call cannot be decoded, e.g. because Multicall3
+/// is not deployed at the address, the reads are retried as individual calls.
+///
+/// # Errors
+///
+/// Returns [`Eip155ExactError`] if an RPC call fails, the nonce is already
+/// used, or funds are insufficient.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+    from = %authorizer,
+    nonce = %nonce
+)))]
+pub async fn assert_payment_state<P: Provider>(
+    contract: &IEIP3009::IEIP3009Instance<P>,
+    authorizer: &Address,
+    nonce: &B256,
+    max_amount_required: U256,
+    multicall3: Option<Address>,
+) -> Result<(), Eip155ExactError> {
+    if let Some(multicall3) = multicall3 {
+        let aggregate = contract
+            .provider()
+            .multicall()
+            .address(multicall3)
+            .add(contract.authorizationState(*authorizer, *nonce))
+            .add(contract.balanceOf(*authorizer));
+        let aggregate_fut = aggregate.aggregate();
+        match traced!(
+            aggregate_fut,
+            tracing::info_span!("prefetch_payment_state",
+                multicall3 = %multicall3,
+                otel.kind = "client"
+            )
+        ) {
+            Ok((used, balance)) => {
+                if used {
+                    return Err(PaymentVerificationError::NonceAlreadyUsed.into());
+                }
+                if balance < max_amount_required {
+                    return Err(PaymentVerificationError::InsufficientFunds.into());
+                }
+                return Ok(());
+            }
+            Err(alloy_provider::MulticallError::TransportError(e)) => {
+                return Err(Eip155ExactError::Transport(e));
+            }
+            Err(_) => {}
+        }
+    }
+    tokio::try_join!(
+        assert_nonce_unused(contract, authorizer, nonce),
+        assert_enough_balance(contract, authorizer, max_amount_required),
+    )?;
+    Ok(())
+}
+
 /// Validates that the current time is within the `validAfter` and `validBefore` bounds.
 ///
 /// Applies `clock_skew_tolerance` seconds of grace when checking both expiration
-/// and early-arrival to account for clock drift between nodes.
+/// and early-arrival to account for clock drift between nodes. Expiration is
+/// further relaxed by `expiry_grace` seconds, the facilitator's
+/// `settle_grace_seconds`, to accept payments that expire while they are
+/// being settled.
 ///
 /// # Errors
 ///
@@ -165,9 +298,11 @@ pub fn assert_time(
     valid_after: UnixTimestamp,
     valid_before: UnixTimestamp,
     clock_skew_tolerance: u64,
+    expiry_grace: u64,
 ) -> Result<(), PaymentVerificationError> {
     let now = UnixTimestamp::now();
-    if valid_before < now + clock_skew_tolerance {
+    let expires = valid_before.as_secs().saturating_add(expiry_grace);
+    if expires < (now + clock_skew_tolerance).as_secs() {
         return Err(PaymentVerificationError::Expired);
     }
     if valid_after > now + clock_skew_tolerance {
@@ -176,6 +311,39 @@ pub fn assert_time(
     Ok(())
 }
 
+/// Checks that an authorization valid from `valid_after` until `valid_before`
+/// (unix seconds) does not remain valid for more than `max_lifetime` seconds.
+///
+/// The lifetime starts at the later of `valid_after` and now: a backdated
+/// start does not lengthen the time the signature can still be used.
+///
+/// # Errors
+///
+/// Returns [`PaymentVerificationError::AuthorizationTooLong`] if the
+/// authorization outlives `max_lifetime`.
+pub fn assert_authorization_lifetime(
+    valid_after: u64,
+    valid_before: u64,
+    max_lifetime: u64,
+) -> Result<(), PaymentVerificationError> {
+    let start = valid_after.max(UnixTimestamp::now().as_secs());
+    let lifetime = valid_before.saturating_sub(start);
+    if lifetime > max_lifetime {
+        return Err(PaymentVerificationError::AuthorizationTooLong {
+            lifetime,
+            max: max_lifetime,
+        });
+    }
+    Ok(())
+}
+
+/// Returns the seconds an authorization valid until `valid_before` (unix
+/// seconds) remains valid for at `now`, or `0` once it has expired.
+#[must_use]
+pub const fn remaining_validity(valid_before: u64, now: UnixTimestamp) -> u64 {
+    valid_before.saturating_sub(now.as_secs())
+}
+
 /// Constructs the correct EIP-712 domain for signature verification.
 ///
 /// # Errors
@@ -213,13 +381,153 @@ pub async fn assert_domain<P: Provider>(
             tracing::info_span!("fetch_eip712_version", otel.kind = "client")
         )?
     };
-    let domain = eip712_domain! {
+    let salt = extra.as_ref().and_then(|extra| extra.salt);
+    Ok(build_eip712_domain(
+        *chain,
+        *asset_address,
+        name,
+        version,
+        salt,
+    ))
+}
+
+/// Builds the EIP-712 domain of an EIP-3009 token.
+///
+/// `salt` is only included in the domain when set, matching tokens whose
+/// `DOMAIN_SEPARATOR` omits it.
+#[must_use]
+pub fn build_eip712_domain(
+    chain: Eip155ChainReference,
+    asset_address: Address,
+    name: String,
+    version: String,
+    salt: Option<B256>,
+) -> Eip712Domain {
+    let mut domain = eip712_domain! {
         name: name,
         version: version,
         chain_id: chain.inner(),
-        verifying_contract: *asset_address,
+        verifying_contract: asset_address,
     };
-    Ok(domain)
+    domain.salt = salt;
+    domain
+}
+
+/// Fetches the token's EIP-712 domain from the chain via EIP-5267 `eip712Domain()`.
+///
+/// Only the fields flagged in the returned `fields` bitmap are set on the domain.
+///
+/// # Errors
+///
+/// Returns [`Eip155ExactError`] if the call fails, e.g. because the token does
+/// not implement EIP-5267.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
+    token_contract = %token_contract.address()
+)))]
+pub async fn fetch_onchain_domain<P: Provider>(
+    token_contract: &IEIP3009::IEIP3009Instance<P>,
+) -> Result<Eip712Domain, Eip155ExactError> {
+    let domain_b = token_contract.eip712Domain();
+    let domain_fut = domain_b.call().into_future();
+    let domain = traced!(
+        domain_fut,
+        tracing::info_span!("fetch_eip712_domain", otel.kind = "client")
+    )?;
+    let has = |bit: u8| domain.fields[0] & bit != 0;
+    Ok(Eip712Domain::new(
+        has(0x01).then(|| domain.name.into()),
+        has(0x02).then(|| domain.version.into()),
+        has(0x04).then_some(domain.chainId),
+        has(0x08).then_some(domain.verifyingContract),
+        has(0x10).then_some(domain.salt),
+    ))
+}
+
+/// Checks that `asset` behaves like an ERC-20 token by calling `decimals()`.
+///
+/// An EOA answers the call with no data and a non-token contract reverts,
+/// both of which reject the payment.
+///
+/// # Errors
+///
+/// Returns [`PaymentVerificationError::AssetMismatch`] if the call does not
+/// return a `uint8`, or [`Eip155ExactError::Transport`] if the RPC fails.
+#[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(asset = %asset)))]
+pub async fn assert_asset_is_erc20<P: Provider>(
+    provider: &P,
+    asset: Address,
+) -> Result<(), Eip155ExactError> {
+    let token = IERC20::new(asset, provider);
+    let decimals_b = token.decimals();
+    let decimals_fut = decimals_b.call().into_future();
+    let decimals = traced!(
+        decimals_fut,
+        tracing::info_span!("probe_erc20_decimals", otel.kind = "client")
+    );
+    match decimals {
+        Ok(_) => Ok(()),
+        Err(alloy_contract::Error::TransportError(e)) if e.as_error_resp().is_none() => {
+            Err(Eip155ExactError::Transport(e))
+        }
+        Err(error) => {
+            #[cfg(not(feature = "telemetry"))]
+            let _ = error;
+            #[cfg(feature = "telemetry")]
+            tracing::warn!(
+                %asset,
+                %error,
+                "configured asset does not answer ERC-20 decimals(), check the requirements' asset"
+            );
+            Err(PaymentVerificationError::AssetMismatch.into())
+        }
+    }
+}
+
+/// Returns the EIP-712 domain the payment signature was produced with.
+///
+/// If the signature is a plain ECDSA signature that does not recover to the
+/// payer under the `advertised` domain, the token's on-chain EIP-5267 domain is
+/// fetched and used instead when the signature recovers under it. In every
+/// other case (smart-wallet signatures, tokens without EIP-5267, or no match)
+/// the advertised domain is returned unchanged and verification proceeds as usual.
+pub async fn resolve_signing_domain<P: Provider>(
+    token_contract: &IEIP3009::IEIP3009Instance<P>,
+    payment: &Eip3009Payment,
+    advertised: Eip712Domain,
+) -> Eip712Domain {
+    let is_ecdsa = matches!(payment.signature.len(), 64 | 65);
+    if !is_ecdsa || recovers_under(payment, &advertised) {
+        return advertised;
+    }
+    let onchain = fetch_onchain_domain(token_contract).await.ok();
+    select_signing_domain(payment, advertised, onchain)
+}
+
+/// Picks the `onchain` domain if the payment signature recovers under it,
+/// falling back to `advertised` otherwise.
+fn select_signing_domain(
+    payment: &Eip3009Payment,
+    advertised: Eip712Domain,
+    onchain: Option<Eip712Domain>,
+) -> Eip712Domain {
+    match onchain {
+        Some(onchain) if recovers_under(payment, &onchain) => {
+            #[cfg(feature = "telemetry")]
+            tracing::info!(
+                advertised = ?advertised.name,
+                onchain = ?onchain.name,
+                "signature matches on-chain EIP-712 domain instead of advertised one"
+            );
+            onchain
+        }
+        _ => advertised,
+    }
+}
+
+/// Returns `true` if the payment's ECDSA signature recovers to the payer under `domain`.
+fn recovers_under(payment: &Eip3009Payment, domain: &Eip712Domain) -> bool {
+    SignedMessage::extract(payment, domain)
+        .is_ok_and(|message| matches!(message.signature, StructuredSignature::EOA(_)))
 }
 
 /// Checks if the payer has enough on-chain token balance to meet the `maxAmountRequired`.
@@ -256,11 +564,45 @@ pub async fn assert_enough_balance<P: Provider>(
     }
 }
 
+/// Gas assumed for a settlement transaction when checking signer funds and
+/// no estimate of the asset's settlement cost is cached.
+///
+/// Covers `transferWithAuthorization` and the Permit2 proxy settlement; smart
+/// wallet deployments cost more and are not accounted for.
+pub const SETTLEMENT_GAS_ESTIMATE: u64 = 150_000;
+
+/// Checks that at least one of the settlement `signers` holds `required`
+/// native balance, the cost of a settlement.
+///
+/// A signer without funds makes every settlement fail mid-flight, after the
+/// resource server may already have served the request, so the payment is
+/// rejected up front instead.
+///
+/// # Errors
+///
+/// Returns [`PaymentVerificationError::TransactionSimulation`] if no signer
+/// is funded, or a transport error if the balance queries fail.
+pub async fn assert_signer_funded<P: Provider>(
+    provider: &P,
+    signers: impl IntoIterator<Item = Address>,
+    required: U256,
+) -> Result<(), Eip155ExactError> {
+    for signer in signers {
+        if provider.get_balance(signer).await? >= required {
+            return Ok(());
+        }
+        #[cfg(feature = "telemetry")]
+        tracing::warn!(%signer, %required, "settlement signer cannot pay for gas, top it up");
+    }
+    Err(PaymentVerificationError::TransactionSimulation("signer unfunded".into()).into())
+}
+
 /// Verifies that the declared `value` in the payload is sufficient for the required amount.
 ///
 /// # Errors
 ///
-/// Returns [`PaymentVerificationError::InvalidPaymentAmount`] if value is too low.
+/// Returns [`PaymentVerificationError::InvalidPaymentAmount`] if value is too low,
+/// or if either amount is zero: a zero-amount payment settles nothing.
 #[cfg_attr(feature = "telemetry", instrument(skip_all, err, fields(
     sent = %sent,
     max_amount_required = %max_amount_required
@@ -269,15 +611,45 @@ pub fn assert_enough_value(
     sent: &U256,
     max_amount_required: &U256,
 ) -> Result<(), PaymentVerificationError> {
-    if sent < max_amount_required {
+    if sent.is_zero() || max_amount_required.is_zero() || sent < max_amount_required {
         Err(PaymentVerificationError::InvalidPaymentAmount)
     } else {
         Ok(())
     }
 }
 
+/// Verifies that the authorized amount meets the facilitator's settlement minimum.
+///
+/// # Errors
+///
+/// Returns [`PaymentVerificationError::BelowMinimumAmount`] if `sent` is below
+/// `min_settle_amount`.
+pub fn assert_min_settle_amount(
+    sent: &U256,
+    min_settle_amount: Option<TokenAmount>,
+) -> Result<(), PaymentVerificationError> {
+    match min_settle_amount {
+        Some(TokenAmount(minimum)) if *sent < minimum => Err(
+            PaymentVerificationError::BelowMinimumAmount(minimum.to_string()),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Rejection of EIP-6492 signatures on chains without the validator.
+fn eip6492_unsupported() -> Eip155ExactError {
+    PaymentVerificationError::InvalidSignature(
+        "EIP-6492 signatures are not supported on this chain".into(),
+    )
+    .into()
+}
+
 /// Verifies a payment by checking the signature and simulating the transfer call.
 ///
+/// Counterfactual wallet (EIP-6492) signatures are checked by the EIP-6492
+/// `validator`, and rejected if it is `None` because it is not deployed on
+/// the chain.
+///
 /// # Errors
 ///
 /// Returns [`Eip155ExactError`] if signature verification or simulation fails.
@@ -286,6 +658,7 @@ pub async fn verify_payment<P: Provider>(
     contract: &IEIP3009::IEIP3009Instance<&P>,
     payment: &Eip3009Payment,
     eip712_domain: &Eip712Domain,
+    validator: Option<Address>,
 ) -> Result<Address, Eip155ExactError> {
     let signed_message = SignedMessage::extract(payment, eip712_domain)?;
 
@@ -298,7 +671,8 @@ pub async fn verify_payment<P: Provider>(
             inner,
             original,
         } => {
-            let validator6492 = Validator6492::new(VALIDATOR_ADDRESS, &provider);
+            let validator = validator.ok_or_else(eip6492_unsupported)?;
+            let validator6492 = Validator6492::new(validator, &provider);
             let is_valid_signature_call =
                 validator6492.isValidSigWithSideEffects(payer, hash, original);
             let transfer_call = TransferWithAuthorization0Call::new(contract, payment, inner);
@@ -388,54 +762,13 @@ pub(super) async fn assert_valid_permit2_payment<P: Provider>(
     permit2: &crate::exact::Permit2Payload,
     payload: &types::v2::PaymentPayload,
     requirements: &types::v2::PaymentRequirements,
-    clock_skew_tolerance: u64,
+    config: &Eip155ExactFacilitatorConfig,
+    expiry_grace: u64,
 ) -> Result<(IERC20::IERC20Instance<P>, Permit2Payment, Eip712Domain), Eip155ExactError> {
+    assert_static_permit2_payment(*chain, permit2, payload, requirements, config, expiry_grace)?;
     let accepted = &payload.accepted;
-    assert_requirements_match(accepted, requirements)?;
-
-    let chain_id: ChainId = chain.into();
-    if accepted.network != chain_id {
-        return Err(PaymentVerificationError::ChainIdMismatch.into());
-    }
-
     let auth = &permit2.permit2_authorization;
-
-    // Verify spender is x402ExactPermit2Proxy
-    if auth.spender != X402_EXACT_PERMIT2_PROXY {
-        return Err(PaymentVerificationError::InvalidSignature(
-            "invalid Permit2 spender: must be x402ExactPermit2Proxy".into(),
-        )
-        .into());
-    }
-
-    // Verify witness.to matches payTo
-    if auth.witness.to != Address::from(accepted.pay_to) {
-        return Err(PaymentVerificationError::RecipientMismatch.into());
-    }
-
-    // Parse and verify deadline not expired (with clock skew tolerance)
-    let now = UnixTimestamp::now();
-    let deadline_u64: u64 = auth.deadline.0.try_into().unwrap_or(u64::MAX);
-    let deadline_threshold = now.as_secs() + clock_skew_tolerance;
-    if deadline_u64 < deadline_threshold {
-        return Err(PaymentVerificationError::Expired.into());
-    }
-
-    // Parse and verify validAfter is not in the future (with clock skew tolerance)
-    let valid_after_u64: u64 = auth.witness.valid_after.0.try_into().unwrap_or(u64::MAX);
-    if valid_after_u64 > now.as_secs() + clock_skew_tolerance {
-        return Err(PaymentVerificationError::Early.into());
-    }
-
-    // Verify amount is sufficient
-    let auth_amount: U256 = auth.permitted.amount.into();
     let required_amount: U256 = accepted.amount.into();
-    assert_enough_value(&auth_amount, &required_amount)?;
-
-    // Verify token matches
-    if auth.permitted.token != Address::from(accepted.asset) {
-        return Err(PaymentVerificationError::AssetMismatch.into());
-    }
 
     let token_address: Address = accepted.asset.into();
     let erc20 = IERC20::new(token_address, provider);
@@ -467,27 +800,123 @@ pub(super) async fn assert_valid_permit2_payment<P: Provider>(
         verifying_contract: PERMIT2_ADDRESS,
     };
 
-    let payment = Permit2Payment {
+    Ok((erc20, permit2_payment(permit2), domain))
+}
+
+/// Runs the Permit2 preconditions of a payment that was already verified
+/// and is settled with a verification token.
+///
+/// Skips the allowance, balance and signature checks of
+/// [`assert_valid_permit2_payment`], which the token vouches for.
+///
+/// # Errors
+///
+/// Returns the [`PaymentVerificationError`] of the first failed check.
+pub(super) fn assert_verified_permit2_payment(
+    chain: Eip155ChainReference,
+    permit2: &crate::exact::Permit2Payload,
+    payload: &types::v2::PaymentPayload,
+    requirements: &types::v2::PaymentRequirements,
+    config: &Eip155ExactFacilitatorConfig,
+    expiry_grace: u64,
+) -> Result<Permit2Payment, PaymentVerificationError> {
+    assert_static_permit2_payment(chain, permit2, payload, requirements, config, expiry_grace)?;
+    Ok(permit2_payment(permit2))
+}
+
+/// Builds the settlement parameters of a Permit2 payload.
+fn permit2_payment(permit2: &crate::exact::Permit2Payload) -> Permit2Payment {
+    let auth = &permit2.permit2_authorization;
+    Permit2Payment {
         from: auth.from,
         to: auth.witness.to,
         token: auth.permitted.token,
-        amount: auth_amount,
+        amount: auth.permitted.amount.into(),
         spender: auth.spender,
         nonce: auth.nonce.into(),
         deadline: auth.deadline.into(),
         valid_after: auth.witness.valid_after.into(),
         extra: auth.witness.extra.clone(),
         signature: permit2.signature.clone(),
-    };
+    }
+}
+
+/// Runs the Permit2 preconditions that need no chain access.
+///
+/// # Errors
+///
+/// Returns the [`PaymentVerificationError`] of the first failed check.
+pub(super) fn assert_static_permit2_payment(
+    chain: Eip155ChainReference,
+    permit2: &crate::exact::Permit2Payload,
+    payload: &types::v2::PaymentPayload,
+    requirements: &types::v2::PaymentRequirements,
+    config: &Eip155ExactFacilitatorConfig,
+    expiry_grace: u64,
+) -> Result<(), PaymentVerificationError> {
+    let accepted = &payload.accepted;
+    assert_requirements_match(accepted, requirements)?;
+    requirements.check_required_extensions(payload.extensions.as_ref())?;
+
+    let chain_id: ChainId = chain.into();
+    if accepted.network != chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch);
+    }
+
+    let auth = &permit2.permit2_authorization;
+
+    // Verify spender is x402ExactPermit2Proxy
+    if auth.spender != X402_EXACT_PERMIT2_PROXY {
+        return Err(PaymentVerificationError::InvalidSignature(
+            "invalid Permit2 spender: must be x402ExactPermit2Proxy".into(),
+        ));
+    }
+
+    // Verify witness.to matches payTo
+    if auth.witness.to != Address::from(accepted.pay_to) {
+        return Err(PaymentVerificationError::RecipientMismatch);
+    }
+
+    // Parse and verify deadline not expired (with clock skew tolerance)
+    let now = UnixTimestamp::now();
+    let deadline_u64: u64 = auth.deadline.0.try_into().unwrap_or(u64::MAX);
+    let deadline_threshold = now.as_secs() + config.clock_skew_tolerance;
+    if deadline_u64.saturating_add(expiry_grace) < deadline_threshold {
+        return Err(PaymentVerificationError::Expired);
+    }
+
+    // Parse and verify validAfter is not in the future (with clock skew tolerance)
+    let valid_after_u64: u64 = auth.witness.valid_after.0.try_into().unwrap_or(u64::MAX);
+    if valid_after_u64 > now.as_secs() + config.clock_skew_tolerance {
+        return Err(PaymentVerificationError::Early);
+    }
+    assert_authorization_lifetime(
+        valid_after_u64,
+        deadline_u64,
+        config.max_authorization_lifetime,
+    )?;
 
-    Ok((erc20, payment, domain))
+    // Verify amount is sufficient
+    let auth_amount: U256 = auth.permitted.amount.into();
+    let required_amount: U256 = accepted.amount.into();
+    assert_enough_value(&auth_amount, &required_amount)?;
+    assert_min_settle_amount(&auth_amount, config.min_settle_amount)?;
+
+    // Verify token matches
+    if auth.permitted.token != Address::from(accepted.asset) {
+        return Err(PaymentVerificationError::AssetMismatch);
+    }
+    Ok(())
 }
 
 /// Verifies a Permit2 payment by checking the EIP-712 signature.
 ///
 /// Reconstructs the `PermitWitnessTransferFrom` typed data, computes the
 /// EIP-712 signing hash, and verifies the signature using the EIP-6492
-/// universal validator (supporting EOA, EIP-1271, and counterfactual wallets).
+/// universal `validator` (supporting EOA, EIP-1271, and counterfactual
+/// wallets). Without a validator, EOA signatures are recovered locally and
+/// contract wallets are asked through EIP-1271, while counterfactual wallets
+/// are rejected.
 ///
 /// # Errors
 ///
@@ -496,6 +925,7 @@ pub async fn verify_permit2_payment<P: Provider>(
     provider: &P,
     payment: &Permit2Payment,
     eip712_domain: &Eip712Domain,
+    validator: Option<Address>,
 ) -> Result<Address, Eip155ExactError> {
     let permit_witness = PermitWitnessTransferFrom {
         permitted: SolTokenPermissions {
@@ -516,8 +946,18 @@ pub async fn verify_permit2_payment<P: Provider>(
     let payer = payment.from;
     let signature_bytes = payment.signature.clone();
 
+    let Some(validator) = validator else {
+        return verify_permit2_signature_without_validator(
+            provider,
+            payer,
+            eip712_hash,
+            signature_bytes,
+        )
+        .await;
+    };
+
     // Use universal signature verification (EIP-6492 validator)
-    let validator6492 = Validator6492::new(VALIDATOR_ADDRESS, provider);
+    let validator6492 = Validator6492::new(validator, provider);
     let is_valid_call =
         validator6492.isValidSigWithSideEffects(payer, eip712_hash, signature_bytes);
     let is_valid_fut = is_valid_call.call().into_future();
@@ -540,3 +980,256 @@ pub async fn verify_permit2_payment<P: Provider>(
 
     Ok(payer)
 }
+
+/// `IERC1271.isValidSignature` return value of a valid signature.
+const EIP1271_MAGIC_VALUE: FixedBytes<4> = FixedBytes([0x16, 0x26, 0xba, 0x7e]);
+
+/// Verifies a Permit2 signature on a chain without the EIP-6492 validator.
+async fn verify_permit2_signature_without_validator<P: Provider>(
+    provider: &P,
+    payer: Address,
+    hash: B256,
+    signature: alloy_primitives::Bytes,
+) -> Result<Address, Eip155ExactError> {
+    let signature = match StructuredSignature::try_from_bytes(signature, payer, &hash)? {
+        StructuredSignature::EOA(_) => return Ok(payer),
+        StructuredSignature::EIP6492 { .. } => return Err(eip6492_unsupported()),
+        StructuredSignature::EIP1271(signature) => signature,
+    };
+    let wallet = IERC1271::new(payer, provider);
+    let magic = wallet
+        .isValidSignature(hash, signature)
+        .call()
+        .await
+        .map_err(|e| PaymentVerificationError::InvalidSignature(e.to_string()))?;
+    if magic != EIP1271_MAGIC_VALUE {
+        return Err(
+            PaymentVerificationError::InvalidSignature("invalid Permit2 signature".into()).into(),
+        );
+    }
+    Ok(payer)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::Bytes;
+    use alloy_provider::{MULTICALL3_ADDRESS, RootProvider};
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+    use alloy_sol_types::SolValue;
+    use serde_json::json;
+    use url::Url;
+
+    use super::*;
+    use crate::exact::TransferWithAuthorization;
+    use crate::mock::mock_rpc;
+
+    #[test]
+    fn test_authorization_lifetime_is_capped() {
+        let start = UnixTimestamp::now().as_secs() + 100;
+        assert_authorization_lifetime(start, start + 600, 600).unwrap();
+        assert!(matches!(
+            assert_authorization_lifetime(start, start + 601, 600),
+            Err(PaymentVerificationError::AuthorizationTooLong {
+                lifetime: 601,
+                max: 600
+            })
+        ));
+        // A backdated start only counts from now.
+        let now = UnixTimestamp::now().as_secs();
+        assert_authorization_lifetime(now - 3_000, now + 500, 600).unwrap();
+    }
+
+    #[test]
+    fn test_remaining_validity_counts_down_to_valid_before() {
+        let now = UnixTimestamp::from_secs(1_700_000_000);
+        assert_eq!(remaining_validity(1_700_000_090, now), 90);
+        assert_eq!(remaining_validity(1_700_000_000, now), 0);
+        assert_eq!(remaining_validity(1_600_000_000, now), 0);
+    }
+
+    #[test]
+    fn test_settle_grace_accepts_authorization_expiring_during_settlement() {
+        let now = UnixTimestamp::now().as_secs();
+        let valid_after = UnixTimestamp::from_secs(0);
+        // Inside the clock skew margin, so rejected by verification.
+        let expiring = UnixTimestamp::from_secs(now + 2);
+        assert!(matches!(
+            assert_time(valid_after, expiring, 5, 0),
+            Err(PaymentVerificationError::Expired)
+        ));
+        assert_time(valid_after, expiring, 5, 10).unwrap();
+        // Expired beyond the grace window.
+        let expired = UnixTimestamp::from_secs(now - 20);
+        assert!(assert_time(valid_after, expired, 5, 10).is_err());
+    }
+
+    fn domain(name: &'static str) -> Eip712Domain {
+        eip712_domain! {
+            name: name,
+            version: "2",
+            chain_id: 8453,
+            verifying_contract: Address::repeat_byte(0x11),
+        }
+    }
+
+    fn signed_payment(signer: &PrivateKeySigner, domain: &Eip712Domain) -> Eip3009Payment {
+        let mut payment = Eip3009Payment {
+            from: signer.address(),
+            to: Address::repeat_byte(0x22),
+            value: U256::from(1_000u64),
+            valid_after: UnixTimestamp::from_secs(0),
+            valid_before: UnixTimestamp::from_secs(u64::from(u32::MAX)),
+            nonce: B256::repeat_byte(0x33),
+            signature: Bytes::new(),
+            transfer_selector: None,
+        };
+        let hash = TransferWithAuthorization {
+            from: payment.from,
+            to: payment.to,
+            value: payment.value,
+            validAfter: U256::from(payment.valid_after.as_secs()),
+            validBefore: U256::from(payment.valid_before.as_secs()),
+            nonce: payment.nonce,
+        }
+        .eip712_signing_hash(domain);
+        let signature = signer.sign_hash_sync(&hash).unwrap();
+        payment.signature = signature.as_bytes().into();
+        payment
+    }
+
+    #[test]
+    fn test_select_signing_domain_falls_back_to_onchain() {
+        let signer = PrivateKeySigner::random();
+        let advertised = domain("USD Coin");
+        let onchain = domain("USDC");
+        let payment = signed_payment(&signer, &onchain);
+
+        assert!(!recovers_under(&payment, &advertised));
+        let selected = select_signing_domain(&payment, advertised, Some(onchain.clone()));
+        assert_eq!(selected, onchain);
+    }
+
+    #[test]
+    fn test_salted_domain_verifies_only_with_salt() {
+        let signer = PrivateKeySigner::random();
+        let chain = Eip155ChainReference::new(8453);
+        let asset = Address::repeat_byte(0x11);
+        let salt = B256::repeat_byte(0x44);
+        let salted = build_eip712_domain(chain, asset, "Salted".into(), "1".into(), Some(salt));
+        let payment = signed_payment(&signer, &salted);
+
+        let extra: PaymentRequirementsExtra = serde_json::from_value(serde_json::json!({
+            "name": "Salted",
+            "version": "1",
+            "salt": salt,
+        }))
+        .unwrap();
+        let domain = build_eip712_domain(chain, asset, extra.name, extra.version, extra.salt);
+        assert!(recovers_under(&payment, &domain));
+
+        let unsalted = build_eip712_domain(chain, asset, "Salted".into(), "1".into(), None);
+        assert!(!recovers_under(&payment, &unsalted));
+    }
+
+    #[test]
+    fn test_min_settle_amount() {
+        let minimum = Some(TokenAmount(U256::from(10_000u64)));
+        assert!(assert_min_settle_amount(&U256::from(10_000u64), minimum).is_ok());
+        assert!(matches!(
+            assert_min_settle_amount(&U256::from(9_999u64), minimum),
+            Err(PaymentVerificationError::BelowMinimumAmount(min)) if min == "10000"
+        ));
+        assert!(assert_min_settle_amount(&U256::from(1u64), None).is_ok());
+    }
+
+    #[test]
+    fn test_zero_amount_is_rejected() {
+        let one = U256::from(1u64);
+        assert!(assert_enough_value(&one, &one).is_ok());
+        for (sent, required) in [
+            (U256::ZERO, U256::ZERO),
+            (one, U256::ZERO),
+            (U256::ZERO, one),
+        ] {
+            assert!(matches!(
+                assert_enough_value(&sent, &required),
+                Err(PaymentVerificationError::InvalidPaymentAmount)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_amounts_beyond_u128_compare_exactly() {
+        // 2^128 base units: ~3.4e20 of an 18-decimal token.
+        let required: TokenAmount = "340282366920938463463374607431768211456".parse().unwrap();
+        let sent: TokenAmount = "340282366920938463463374607431768211457".parse().unwrap();
+        assert!(assert_enough_value(&sent.into(), &required.into()).is_ok());
+
+        let short = TokenAmount(U256::from(u128::MAX));
+        assert!(matches!(
+            assert_enough_value(&short.into(), &required.into()),
+            Err(PaymentVerificationError::InvalidPaymentAmount)
+        ));
+    }
+
+    #[test]
+    fn test_select_signing_domain_keeps_advertised_without_match() {
+        let signer = PrivateKeySigner::random();
+        let advertised = domain("USD Coin");
+        let payment = signed_payment(&signer, &domain("Other"));
+
+        let selected = select_signing_domain(&payment, advertised.clone(), Some(domain("USDC")));
+        assert_eq!(selected, advertised);
+        let selected = select_signing_domain(&payment, advertised.clone(), None);
+        assert_eq!(selected, advertised);
+    }
+
+    #[tokio::test]
+    async fn test_payment_state_prefetched_in_single_multicall() {
+        // Multicall3 `aggregate` returns (blockNumber, returnData[]): the nonce
+        // is unused and the payer holds 5_000 units.
+        let return_data = vec![
+            Bytes::from(false.abi_encode()),
+            Bytes::from(U256::from(5_000u64).abi_encode()),
+        ];
+        let result = Bytes::from((U256::from(1u64), return_data).abi_encode_params());
+        let server = mock_rpc([("eth_call", json!(result))]).await;
+        let provider: RootProvider = RootProvider::new_http(Url::parse(&server.uri()).unwrap());
+        let contract = IEIP3009::new(Address::repeat_byte(0x11), provider);
+
+        assert_payment_state(
+            &contract,
+            &Address::repeat_byte(0x33),
+            &B256::ZERO,
+            U256::from(1_000u64),
+            Some(MULTICALL3_ADDRESS),
+        )
+        .await
+        .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let body: serde_json::Value = requests[0].body_json().unwrap();
+        assert!(
+            body["params"][0]["to"]
+                .as_str()
+                .is_some_and(|to| to.eq_ignore_ascii_case(&MULTICALL3_ADDRESS.to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_asset_without_decimals_is_rejected() {
+        // An EOA answers every call with empty return data.
+        let server = mock_rpc([("eth_call", json!("0x"))]).await;
+        let provider: RootProvider = RootProvider::new_http(Url::parse(&server.uri()).unwrap());
+
+        let result = assert_asset_is_erc20(&provider, Address::repeat_byte(0x44)).await;
+        assert!(matches!(
+            result,
+            Err(Eip155ExactError::PaymentVerification(
+                PaymentVerificationError::AssetMismatch
+            ))
+        ));
+    }
+}
@@ -17,6 +17,9 @@ impl Eip155Exact {
     /// resource. Uses CAIP-2 chain IDs (e.g., `eip155:8453`) and embeds the
     /// requirements directly in the price tag.
     ///
+    /// The timeout defaults to [`v2::DEFAULT_MAX_TIMEOUT_SECONDS`]; override it
+    /// with [`v2::PriceTag::with_max_timeout_seconds`].
+    ///
     /// # Transfer method
     ///
     /// - `None` or `Some(Eip3009)` — EIP-3009 `transferWithAuthorization` (default)
@@ -26,16 +29,46 @@ pub fn price_tag<A: Into<ChecksummedAddress>>(
         asset: DeployedTokenAmount<U256, Eip155TokenDeployment>,
         transfer_method: Option<AssetTransferMethod>,
     ) -> v2::PriceTag {
-        let chain_id: ChainId = asset.token.chain_reference.into();
-        let extra = PaymentRequirementsExtra::from_deployment(asset.token.eip712, transfer_method);
+        let mut tag = Self::build_price_tag(pay_to.into(), &asset.token, asset.amount, None);
+        tag.requirements.extra =
+            PaymentRequirementsExtra::from_deployment(asset.token.eip712, transfer_method);
+        tag
+    }
+
+    /// Creates a price tag with an explicit `extra` payload.
+    ///
+    /// Unlike [`Self::price_tag`], the EIP-712 domain `name`/`version` are taken
+    /// from `extra` instead of the token deployment. Use this for token variants
+    /// whose on-chain domain differs from the built-in registry, otherwise
+    /// clients sign against the wrong domain and verification fails with
+    /// `InvalidSignature`.
+    pub fn price_tag_with_extra<A: Into<ChecksummedAddress>>(
+        pay_to: A,
+        token: &Eip155TokenDeployment,
+        amount: U256,
+        extra: PaymentRequirementsExtra,
+    ) -> v2::PriceTag {
+        let extra = serde_json::to_value(extra).ok();
+        Self::build_price_tag(pay_to.into(), token, amount, extra)
+    }
+
+    /// Assembles the V2 requirements shared by all price tag constructors.
+    fn build_price_tag(
+        pay_to: ChecksummedAddress,
+        token: &Eip155TokenDeployment,
+        amount: U256,
+        extra: Option<serde_json::Value>,
+    ) -> v2::PriceTag {
+        let chain_id: ChainId = token.chain_reference.into();
         let requirements = v2::PaymentRequirements {
             scheme: ExactScheme.to_string(),
-            pay_to: pay_to.into().to_string(),
-            asset: asset.token.address.to_string(),
+            pay_to: pay_to.to_string(),
+            asset: token.address.to_string(),
             network: chain_id,
-            amount: asset.amount.to_string(),
-            max_timeout_seconds: 300,
+            amount: amount.to_string(),
+            max_timeout_seconds: v2::DEFAULT_MAX_TIMEOUT_SECONDS,
             extra,
+            required_extensions: None,
         };
         v2::PriceTag {
             requirements,
@@ -43,3 +76,89 @@ pub fn price_tag<A: Into<ChecksummedAddress>>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::Address;
+
+    use super::*;
+    use crate::chain::{Eip155ChainReference, TokenDeploymentEip712};
+
+    fn create_test_deployment() -> Eip155TokenDeployment {
+        Eip155TokenDeployment {
+            chain_reference: Eip155ChainReference::new(8453),
+            address: Address::repeat_byte(0x11),
+            decimals: 6,
+            eip712: Some(TokenDeploymentEip712 {
+                name: "USD Coin".into(),
+                version: "2".into(),
+            }),
+            transfer_with_auth_selector: None,
+        }
+    }
+
+    #[test]
+    fn test_price_tag_with_extra_overrides_domain() {
+        let token = create_test_deployment();
+        let extra = PaymentRequirementsExtra {
+            name: "USDC".into(),
+            version: "1".into(),
+            salt: None,
+            asset_transfer_method: None,
+        };
+        let tag = Eip155Exact::price_tag_with_extra(
+            Address::repeat_byte(0x22),
+            &token,
+            U256::from(1_000u64),
+            extra,
+        );
+        let extra = tag.requirements.extra.unwrap();
+        assert_eq!(extra["name"], "USDC");
+        assert_eq!(extra["version"], "1");
+        assert!(extra.get("assetTransferMethod").is_none());
+        assert_eq!(tag.requirements.amount, "1000");
+        assert_eq!(tag.requirements.network.to_string(), "eip155:8453");
+    }
+
+    #[test]
+    fn test_price_tag_with_extra_keeps_transfer_method() {
+        let token = create_test_deployment();
+        let extra = PaymentRequirementsExtra {
+            name: "Custom".into(),
+            version: "3".into(),
+            salt: None,
+            asset_transfer_method: Some(AssetTransferMethod::Permit2),
+        };
+        let tag = Eip155Exact::price_tag_with_extra(
+            Address::repeat_byte(0x22),
+            &token,
+            U256::from(1u64),
+            extra,
+        );
+        let extra = tag.requirements.extra.unwrap();
+        assert_eq!(extra["name"], "Custom");
+        assert_eq!(extra["assetTransferMethod"], "permit2");
+    }
+
+    #[test]
+    fn test_price_tag_max_timeout() {
+        let token = create_test_deployment();
+        let tag = Eip155Exact::price_tag(
+            Address::repeat_byte(0x22),
+            DeployedTokenAmount {
+                amount: U256::from(1u64),
+                token,
+            },
+            None,
+        );
+        assert_eq!(
+            tag.requirements.max_timeout_seconds,
+            v2::DEFAULT_MAX_TIMEOUT_SECONDS
+        );
+
+        let err = tag.clone().with_max_timeout_seconds(10, 30).unwrap_err();
+        assert_eq!(err.seconds, 10);
+        let tag = tag.with_max_timeout_seconds(120, 30).unwrap();
+        assert_eq!(tag.requirements.max_timeout_seconds, 120);
+    }
+}
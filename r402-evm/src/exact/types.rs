@@ -183,6 +183,20 @@ pub struct Eip3009Authorization {
     pub nonce: B256,
 }
 
+/// Extra fields advertised for the EVM exact scheme in `/supported`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedPaymentKindExtra {
+    /// The asset the facilitator advertises as the network's default, if
+    /// it overrides the built-in registry choice.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_asset: Option<Address>,
+    /// Whether counterfactual smart wallet (EIP-6492) signatures are
+    /// accepted on the network, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eip6492: Option<bool>,
+}
+
 /// Extra payment requirements data for the EVM exact scheme.
 ///
 /// Contains optional EIP-712 domain parameters and the asset transfer method.
@@ -195,6 +209,12 @@ pub struct PaymentRequirementsExtra {
     /// The token version as used in the EIP-712 domain (required for EIP-3009).
     pub version: String,
 
+    /// The EIP-712 domain salt, for tokens whose domain includes one.
+    ///
+    /// Serialized as a `0x`-prefixed 32-byte hex string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<B256>,
+
     /// Which on-chain transfer mechanism to use.
     ///
     /// - `Some(Eip3009)` or `None` → EIP-3009 `transferWithAuthorization`
@@ -218,6 +238,7 @@ pub fn from_deployment(
             (None, Some(m)) => Self {
                 name: String::new(),
                 version: String::new(),
+                salt: None,
                 asset_transfer_method: Some(m),
             },
             (None, None) => return None,
@@ -238,6 +259,7 @@ fn from(eip712: crate::chain::TokenDeploymentEip712) -> Self {
         Self {
             name: eip712.name,
             version: eip712.version,
+            salt: None,
             asset_transfer_method: None,
         }
     }
@@ -326,3 +348,36 @@ pub mod v2 {
         PaymentRequirementsExtra,
     >;
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_extra_salt_round_trip() {
+        let extra = PaymentRequirementsExtra {
+            name: "Salted".into(),
+            version: "1".into(),
+            salt: Some(B256::repeat_byte(0xab)),
+            asset_transfer_method: None,
+        };
+        let value = serde_json::to_value(&extra).unwrap();
+        assert_eq!(value["salt"], format!("0x{}", "ab".repeat(32)));
+        assert_eq!(
+            serde_json::from_value::<PaymentRequirementsExtra>(value).unwrap(),
+            extra
+        );
+
+        let unsalted: PaymentRequirementsExtra =
+            serde_json::from_value(json!({ "name": "USD Coin", "version": "2" })).unwrap();
+        assert_eq!(unsalted.salt, None);
+        assert!(
+            serde_json::to_value(&unsalted)
+                .unwrap()
+                .get("salt")
+                .is_none()
+        );
+    }
+}
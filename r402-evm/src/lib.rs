@@ -31,6 +31,10 @@
 pub mod exact;
 
 mod networks;
+
+#[cfg(test)]
+#[cfg(any(feature = "facilitator", feature = "client-provider"))]
+mod mock;
 pub use exact::Eip155Exact;
 #[cfg(feature = "client")]
 pub use exact::client::{Eip155ExactClient, Eip155ExactClientBuilder, Permit2Approver};
@@ -0,0 +1,85 @@
+//! JSON-RPC mocks shared by the unit tests.
+
+use serde_json::{Value, json};
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+/// Results of the methods every mock endpoint answers unless overridden.
+const DEFAULT_RESULTS: &[(&str, &str)] = &[("eth_chainId", "0x2105")];
+
+/// Wraps `result` in a JSON-RPC response to the request `body`.
+pub fn rpc_response(body: &Value, result: &Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": body["id"],
+        "result": result,
+    })
+}
+
+/// Starts a Base mainnet JSON-RPC endpoint answering each method of
+/// `overrides` with its result.
+///
+/// `eth_chainId` answers `0x2105` unless overridden; other methods fail with
+/// HTTP 500.
+pub async fn mock_rpc<I>(overrides: I) -> MockServer
+where
+    I: IntoIterator<Item = (&'static str, Value)>,
+{
+    let mut results: Vec<(&str, Value)> = DEFAULT_RESULTS
+        .iter()
+        .map(|(rpc_method, result)| (*rpc_method, json!(result)))
+        .collect();
+    for (rpc_method, result) in overrides {
+        results.retain(|(known, _)| *known != rpc_method);
+        results.push((rpc_method, result));
+    }
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(move |request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            results
+                .iter()
+                .find(|(rpc_method, _)| body["method"] == *rpc_method)
+                .map_or_else(
+                    || ResponseTemplate::new(500),
+                    |(_, result)| {
+                        ResponseTemplate::new(200).set_body_json(rpc_response(&body, result))
+                    },
+                )
+        })
+        .mount(&server)
+        .await;
+    server
+}
+
+/// Counts the requests `server` received for `rpc_method`.
+#[cfg(feature = "facilitator")]
+pub async fn rpc_calls(server: &MockServer, rpc_method: &str) -> usize {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| {
+            serde_json::from_slice::<Value>(&request.body)
+                .is_ok_and(|body| body["method"] == rpc_method)
+        })
+        .count()
+}
+
+/// Creates a Base mainnet provider with a random signer, backed by `server`.
+#[cfg(feature = "facilitator")]
+pub fn provider(server: &MockServer) -> crate::chain::Eip155ChainProvider {
+    use alloy_network::EthereumWallet;
+    use alloy_signer_local::PrivateKeySigner;
+
+    crate::chain::Eip155ChainProvider::new(
+        crate::chain::Eip155ChainReference::new(8453),
+        EthereumWallet::new(PrivateKeySigner::random()),
+        &[(url::Url::parse(&server.uri()).unwrap(), None)],
+        true,
+        false,
+        30,
+    )
+    .unwrap()
+}
@@ -1,10 +1,14 @@
 //! Well-known EVM network definitions and token deployments.
 //!
 //! This module provides static network metadata and USDC/USDM token deployment
-//! information for all supported EIP-155 chains.
+//! information for all supported EIP-155 chains, plus a [`TokenRegistry`] for
+//! resolving tokens by symbol at runtime.
 
+use std::collections::HashMap;
 use std::sync::LazyLock;
 
+use alloy_primitives::Address;
+use r402::chain::ChainId;
 use r402::networks::NetworkInfo;
 
 use crate::chain::{Eip155ChainReference, Eip155TokenDeployment, TokenDeploymentEip712};
@@ -246,6 +250,7 @@
                 name: "USD Coin".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Ethereum Sepolia — native Circle USDC testnet
         // Verify: https://sepolia.etherscan.io/address/0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238
@@ -257,6 +262,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Base mainnet — native Circle USDC
         // Verify: https://basescan.org/token/0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913
@@ -268,6 +274,7 @@
                 name: "USD Coin".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Base Sepolia — native Circle USDC testnet
         // Verify: https://base-sepolia.blockscout.com/address/0x036CbD53842c5426634e7929541eC2318f3dCF7e
@@ -279,6 +286,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Arbitrum One — native Circle USDC
         // Verify: https://arbiscan.io/token/0xaf88d065e77c8cC2239327C5EDb3A432268e5831
@@ -290,6 +298,7 @@
                 name: "USD Coin".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Arbitrum Sepolia — native Circle USDC testnet
         // Verify: https://sepolia.arbiscan.io/address/0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d
@@ -301,6 +310,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // OP Mainnet — native Circle USDC
         // Verify: https://optimistic.etherscan.io/token/0x0b2c639c533813f4aa9d7837caf62653d097ff85
@@ -312,6 +322,7 @@
                 name: "USD Coin".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // OP Sepolia — native Circle USDC testnet
         // Verify: https://sepolia-optimism.etherscan.io/address/0x5fd84259d66Cd46123540766Be93DFE6D43130D7
@@ -323,6 +334,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Polygon PoS — native Circle USDC (not the old bridged USDC.e at 0x2791...)
         // Verify: https://polygonscan.com/token/0x3c499c542cef5e3811e1192ce70d8cc03d5c3359
@@ -334,6 +346,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Polygon Amoy — native Circle USDC testnet
         // Verify: https://amoy.polygonscan.com/address/0x41e94eb019c0762f9bfcf9fb1e58725bfb0e7582
@@ -345,6 +358,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Avalanche C-Chain — native Circle USDC
         // Verify: https://snowtrace.io/token/0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E
@@ -356,6 +370,7 @@
                 name: "USD Coin".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Avalanche Fuji — native Circle USDC testnet
         // Verify: https://testnet.snowtrace.io/token/0x5425890298aed601595a70ab815c96711a31bc65
@@ -367,6 +382,7 @@
                 name: "USD Coin".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Celo — native Circle USDC
         // Verify: https://celoscan.io/token/0xcebA9300f2b948710d2653dD7B07f33A8B32118C
@@ -378,6 +394,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Celo Sepolia — native Circle USDC testnet
         // Verify: https://celo-sepolia.blockscout.com/token/0x01C5C0122039549AD1493B8220cABEdD739BC44E
@@ -389,6 +406,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Sei — native Circle USDC
         // Verify: https://seitrace.com/address/0xe15fC38F6D8c56aF07bbCBe3BAf5708A2Bf42392?chain=pacific-1
@@ -400,6 +418,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Sei Testnet — native Circle USDC testnet
         // Verify: https://seitrace.com/address/0x4fCF1784B31630811181f670Aea7A7bEF803eaED?chain=atlantic-2
@@ -411,6 +430,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Sonic — native Circle USDC
         // Verify: https://sonicscan.org/token/0x29219dd400f2bf60e5a23d13be72b486d4038894
@@ -422,6 +442,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Sonic Blaze Testnet — native Circle USDC testnet
         // Verify: https://blaze.soniclabs.com/address/0xA4879Fed32Ecbef99399e5cbC247E533421C4eC6
@@ -433,6 +454,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Unichain — native Circle USDC
         // Verify: https://uniscan.xyz/token/0x078d782b760474a361dda0af3839290b0ef57ad6
@@ -444,6 +466,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Unichain Sepolia — native Circle USDC testnet
         // Verify: https://unichain-sepolia.blockscout.com/token/0x31d0220469e10c4E71834a79b1f276d740d3768F
@@ -455,6 +478,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // World Chain — native Circle USDC
         // Verify: https://worldscan.org/address/0x79A02482A880bCe3F13E09da970dC34dB4cD24D1
@@ -466,6 +490,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // World Chain Sepolia — native Circle USDC testnet
         // Verify: https://sepolia.worldscan.org/address/0x66145f38cBAC35Ca6F1Dfb4914dF98F1614aeA88
@@ -477,6 +502,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // ZKsync Era — native Circle USDC
         // Verify: https://explorer.zksync.io/address/0x1d17CBcF0D6D143135aE902365D2E5e2A16538D4
@@ -488,6 +514,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // ZKsync Era Sepolia — native Circle USDC testnet
         // Verify: https://sepolia.explorer.zksync.io/address/0xAe045DE5638162fa134807Cb558E15A3F5A7F853
@@ -499,6 +526,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Linea — Circle USDC (upgraded from bridged to native via CCTP)
         // Verify: https://lineascan.build/token/0x176211869ca2b568f2a7d4ee941e073a821ee1ff
@@ -510,6 +538,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Linea Sepolia — Circle USDC testnet
         // Verify: https://sepolia.lineascan.build/address/0xFEce4462D57bD51A6A552365A011b95f0E16d9B7
@@ -521,6 +550,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Ink (by Kraken) — native Circle USDC
         // Verify: https://explorer.inkonchain.com/address/0x2D270e6886d130D724215A266106e6832161EAEd
@@ -532,6 +562,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Ink Sepolia — native Circle USDC testnet
         // Verify: https://explorer-sepolia.inkonchain.com/address/0xFabab97dCE620294D2B0b0e46C68964e326300Ac
@@ -543,6 +574,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // HyperEVM (Hyperliquid) — native Circle USDC
         // Verify: https://hyperscan.com/token/0xb88339CB7199b77E23DB6E890353E22632Ba630f
@@ -554,6 +586,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // HyperEVM Testnet — native Circle USDC testnet
         // Verify: https://testnet.purrsec.com/address/0x2B3370eE501B4a559b57D449569354196457D8Ab
@@ -565,6 +598,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Monad — native Circle USDC
         // Verify: https://monadvision.com/token/0x754704Bc059F8C67012fEd69BC8A327a5aafb603
@@ -576,6 +610,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Monad Testnet — native Circle USDC testnet
         // Verify: https://testnet.monadvision.com/token/0x534b2f3A21130d7a60830c2Df862319e593943A3
@@ -587,6 +622,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Plume — native Circle USDC
         // Verify: https://explorer.plume.org/address/0x222365EF19F7947e5484218551B56bb3965Aa7aF
@@ -598,6 +634,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Plume Testnet — native Circle USDC testnet
         // Verify: https://testnet-explorer.plume.org/address/0xcB5f30e335672893c7eb944B374c196392C19D18
@@ -609,6 +646,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Codex — native Circle USDC
         // Verify: https://explorer.codex.xyz/address/0xd996633a415985DBd7D6D12f4A4343E31f5037cf
@@ -620,6 +658,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // Codex Testnet — native Circle USDC testnet
         // Verify: https://explorer.codex-stg.xyz/address/0x6d7f141b6819C2c9CC2f818e6ad549E7Ca090F8f
@@ -631,6 +670,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // XDC Network — native Circle USDC
         // Verify: https://xdcscan.com/address/0xfA2958CB79b0491CC627c1557F441eF849Ca8eb1
@@ -642,6 +682,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // XDC Apothem Testnet — native Circle USDC testnet
         // Verify: https://testnet.xdcscan.com/address/0xb5AB69F7bBada22B28e79C8FFAECe55eF1c771D4
@@ -653,6 +694,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // XRPL EVM sidechain — community deployment, not on Circle official page
         // EIP-3009 support unverified (eip712: None)
@@ -661,6 +703,7 @@
             address: alloy_primitives::address!("0xDaF4556169c4F3f2231d8ab7BC8772Ddb7D4c84C"),
             decimals: 6,
             eip712: None,
+            transfer_with_auth_selector: None,
         },
         // Peaq — community deployment, not on Circle official page
         // EIP-3009 support unverified
@@ -672,6 +715,7 @@
                 name: "USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
         // IoTeX — community deployment, not on Circle official page
         // EIP-3009 support unverified
@@ -683,6 +727,7 @@
                 name: "Bridged USDC".into(),
                 version: "2".into(),
             }),
+            transfer_with_auth_selector: None,
         },
     ]
 });
@@ -702,6 +747,7 @@
                 name: "MegaUSD".into(),
                 version: "1".into(),
             }),
+            transfer_with_auth_selector: None,
         },
     ]
 });
@@ -1062,3 +1108,110 @@ pub fn megaeth() -> &'static Eip155TokenDeployment {
             .expect("built-in USDM deployment for MegaETH missing")
     }
 }
+
+/// Well-known token deployments indexed by chain and symbol.
+///
+/// Built lazily from [`USDC`] and [`USDM`] on first access.
+pub static TOKEN_REGISTRY: LazyLock<TokenRegistry> = LazyLock::new(TokenRegistry::well_known);
+
+/// Runtime lookup table mapping `(chain, symbol)` to a token deployment.
+///
+/// Useful when a token is named by string, e.g. `asset = "USDC"` in a
+/// configuration file, rather than through the typed accessors on [`USDC`]
+/// and [`USDM`]. Symbols are matched case-insensitively.
+#[derive(Debug, Clone, Default)]
+pub struct TokenRegistry {
+    tokens: HashMap<(Eip155ChainReference, String), Eip155TokenDeployment>,
+}
+
+impl TokenRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry holding every built-in USDC and USDM deployment.
+    #[must_use]
+    pub fn well_known() -> Self {
+        let mut registry = Self::new();
+        for deployment in USDC::all() {
+            registry.register("USDC", deployment.clone());
+        }
+        for deployment in USDM::all() {
+            registry.register("USDM", deployment.clone());
+        }
+        registry
+    }
+
+    /// Adds or replaces the deployment of `symbol` on the deployment's chain.
+    pub fn register(&mut self, symbol: &str, deployment: Eip155TokenDeployment) {
+        self.tokens.insert(
+            (deployment.chain_reference, symbol.to_ascii_uppercase()),
+            deployment,
+        );
+    }
+
+    /// Looks up the deployment of `symbol` on `chain_id`.
+    ///
+    /// Returns `None` for unknown symbols and for non-EIP-155 chains.
+    #[must_use]
+    pub fn lookup(&self, chain_id: &ChainId, symbol: &str) -> Option<Eip155TokenDeployment> {
+        let chain = Eip155ChainReference::try_from(chain_id).ok()?;
+        self.tokens
+            .get(&(chain, symbol.to_ascii_uppercase()))
+            .cloned()
+    }
+
+    /// Returns the symbol of the token deployed at `address` on `chain_id`.
+    #[must_use]
+    pub fn symbol_of(&self, chain_id: &ChainId, address: Address) -> Option<&str> {
+        let chain = Eip155ChainReference::try_from(chain_id).ok()?;
+        self.tokens
+            .iter()
+            .find(|((c, _), d)| *c == chain && d.address == address)
+            .map(|((_, symbol), _)| symbol.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_well_known_tokens() {
+        let base = ChainId::new("eip155", "8453");
+        let usdc = TOKEN_REGISTRY.lookup(&base, "USDC").unwrap();
+        assert_eq!(usdc, *USDC::base());
+        assert_eq!(TOKEN_REGISTRY.lookup(&base, "usdc"), Some(usdc));
+
+        let sepolia = ChainId::new("eip155", "11155111");
+        assert_eq!(
+            TOKEN_REGISTRY.lookup(&sepolia, "USDC").as_ref(),
+            Some(USDC::ethereum_sepolia())
+        );
+
+        let megaeth = ChainId::new("eip155", "4326");
+        assert_eq!(
+            TOKEN_REGISTRY.lookup(&megaeth, "USDM").as_ref(),
+            Some(USDM::megaeth())
+        );
+        assert!(TOKEN_REGISTRY.lookup(&base, "USDM").is_none());
+    }
+
+    #[test]
+    fn test_lookup_rejects_non_evm_chain() {
+        let solana = ChainId::new("solana", "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp");
+        assert!(TOKEN_REGISTRY.lookup(&solana, "USDC").is_none());
+    }
+
+    #[test]
+    fn test_symbol_of_reverse_lookup() {
+        let arbitrum = ChainId::new("eip155", "42161");
+        let address = USDC::arbitrum().address;
+        assert_eq!(TOKEN_REGISTRY.symbol_of(&arbitrum, address), Some("USDC"));
+
+        let base = ChainId::new("eip155", "8453");
+        assert_eq!(TOKEN_REGISTRY.symbol_of(&base, address), None);
+    }
+}
@@ -0,0 +1,183 @@
+//! Verification of facilitator attestations on settlement receipts.
+//!
+//! Facilitators may sign each successful settlement (see
+//! [`r402::attestation`]). An [`AttestationPolicy`] checks that signature
+//! against the facilitator's advertised public key so a client can tell a
+//! genuine receipt from one forged by an intermediary.
+
+use std::fmt;
+use std::sync::Arc;
+
+use http::{HeaderMap, StatusCode};
+use r402::attestation::{AttestationVerifier, FacilitatorAttestation, attestation_message};
+use r402::proto;
+use r402::scheme::ClientError;
+
+use crate::headers::{PAYMENT_RESPONSE_HEADER, decode_json_header};
+
+/// Checks the facilitator attestation on settlement receipts.
+///
+/// A receipt carrying an attestation that does not verify against the expected
+/// key is always rejected. Receipts without an attestation are accepted unless
+/// strict mode is enabled with [`AttestationPolicy::strict`].
+#[derive(Clone)]
+pub struct AttestationPolicy {
+    verifier: Arc<dyn AttestationVerifier>,
+    public_key: String,
+    strict: bool,
+}
+
+impl fmt::Debug for AttestationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttestationPolicy")
+            .field("public_key", &self.public_key)
+            .field("strict", &self.strict)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AttestationPolicy {
+    /// Creates a policy verifying attestations by `public_key` with `verifier`.
+    ///
+    /// `public_key` is the key the facilitator advertises in
+    /// [`proto::SupportedResponse::attestation_key`].
+    pub fn new(
+        verifier: impl AttestationVerifier + 'static,
+        public_key: impl Into<String>,
+    ) -> Self {
+        Self {
+            verifier: Arc::new(verifier),
+            public_key: public_key.into(),
+            strict: false,
+        }
+    }
+
+    /// Sets whether receipts without an attestation are rejected.
+    #[must_use]
+    pub const fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Checks the attestation on a settlement receipt.
+    ///
+    /// Failed settlements are never attested and always pass.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::InvalidAttestation`] if the attestation does not
+    /// verify, or if it is missing and the policy is strict.
+    pub fn check(&self, receipt: &proto::SettleResponse) -> Result<(), ClientError> {
+        let Some(message) = attestation_message(receipt) else {
+            return Ok(());
+        };
+        match FacilitatorAttestation::from_response(receipt) {
+            Some(attestation) => {
+                if self
+                    .verifier
+                    .verify(&self.public_key, &message, &attestation.signature)
+                {
+                    Ok(())
+                } else {
+                    Err(ClientError::InvalidAttestation(
+                        "signature does not match the facilitator key".to_owned(),
+                    ))
+                }
+            }
+            None => self.missing(),
+        }
+    }
+
+    /// Checks the receipt in the `Payment-Response` header of a paid response.
+    ///
+    /// Unsuccessful responses are not settled and always pass.
+    pub(super) fn check_paid_response(
+        &self,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Result<(), ClientError> {
+        if !status.is_success() {
+            return Ok(());
+        }
+        headers
+            .get(PAYMENT_RESPONSE_HEADER)
+            .and_then(|h| decode_json_header(h.as_bytes(), false))
+            .map_or_else(|| self.missing(), |r| self.check(&r))
+    }
+
+    fn missing(&self) -> Result<(), ClientError> {
+        if self.strict {
+            Err(ClientError::InvalidAttestation(
+                "receipt is not attested by the facilitator".to_owned(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r402::attestation::ATTESTATION_EXTENSION;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Toy scheme: the signature is the key followed by the message.
+    struct ConcatVerifier;
+
+    impl AttestationVerifier for ConcatVerifier {
+        fn verify(&self, public_key: &str, message: &[u8], signature: &str) -> bool {
+            signature == format!("{public_key}|{}", String::from_utf8_lossy(message))
+        }
+    }
+
+    fn receipt(transaction: &str, attestation: Option<&str>) -> proto::SettleResponse {
+        let signed = proto::SettleResponse::Success {
+            payer: "0xpayer".into(),
+            transaction: "0xtx".into(),
+            network: "eip155:8453".into(),
+            extensions: None,
+        };
+        let message = attestation_message(&signed).unwrap();
+        let extensions = attestation.map(|key| {
+            let signature = format!("{key}|{}", String::from_utf8_lossy(&message));
+            proto::Extensions::from([(
+                ATTESTATION_EXTENSION.to_owned(),
+                json!({ "publicKey": key, "signature": signature }),
+            )])
+        });
+        proto::SettleResponse::Success {
+            payer: "0xpayer".into(),
+            transaction: transaction.into(),
+            network: "eip155:8453".into(),
+            extensions,
+        }
+    }
+
+    #[test]
+    fn test_valid_attestation_is_accepted() {
+        let policy = AttestationPolicy::new(ConcatVerifier, "key").strict(true);
+        assert!(policy.check(&receipt("0xtx", Some("key"))).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_attestation_is_rejected() {
+        let policy = AttestationPolicy::new(ConcatVerifier, "key");
+        let tampered = receipt("0xforged", Some("key"));
+        assert!(matches!(
+            policy.check(&tampered),
+            Err(ClientError::InvalidAttestation(_))
+        ));
+        let wrong_key = receipt("0xtx", Some("mitm"));
+        assert!(policy.check(&wrong_key).is_err());
+    }
+
+    #[test]
+    fn test_missing_attestation_depends_on_strict_mode() {
+        let unsigned = receipt("0xtx", None);
+        let policy = AttestationPolicy::new(ConcatVerifier, "key");
+        assert!(policy.check(&unsigned).is_ok());
+        assert!(policy.strict(true).check(&unsigned).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! Settlement confirmation tracking for the x402 client.
+//!
+//! A successful settlement only tells the client that the facilitator
+//! broadcast a transaction and saw it mined. Clients that need stronger
+//! finality guarantees can wait for the transaction to reach a given depth
+//! with [`X402Client::confirm_settlement`](super::X402Client::confirm_settlement).
+//!
+//! Chain access is abstracted behind [`ConfirmationProvider`] so this crate
+//! stays independent of any particular RPC client.
+
+use std::time::Duration;
+
+use r402::facilitator::BoxFuture;
+use r402::scheme::ClientError;
+
+/// Default upper bound on how long to wait for a settlement to confirm.
+pub const DEFAULT_CONFIRMATION_TIMEOUT: Duration = Duration::from_mins(2);
+
+/// Default delay between two confirmation polls.
+pub const DEFAULT_CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Read-only chain access needed to track settlement confirmations.
+///
+/// Implementations typically wrap an RPC provider for the network named in
+/// the settlement proof. This trait is dyn-compatible.
+pub trait ConfirmationProvider: Send + Sync {
+    /// Returns the latest block number of `network`.
+    fn block_number<'a>(&'a self, network: &'a str) -> BoxFuture<'a, Result<u64, ClientError>>;
+
+    /// Returns the number of the block that included `transaction`, or
+    /// `None` if the transaction is not mined yet.
+    fn transaction_block<'a>(
+        &'a self,
+        network: &'a str,
+        transaction: &'a str,
+    ) -> BoxFuture<'a, Result<Option<u64>, ClientError>>;
+}
+
+/// Polling options applied by [`X402Client::confirm_settlement`](super::X402Client::confirm_settlement).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfirmationOptions {
+    /// Maximum time to wait for the requested confirmation depth.
+    pub timeout: Duration,
+    /// Delay between two polls of the provider.
+    pub poll_interval: Duration,
+}
+
+impl Default for ConfirmationOptions {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_CONFIRMATION_TIMEOUT,
+            poll_interval: DEFAULT_CONFIRMATION_POLL_INTERVAL,
+        }
+    }
+}
+
+/// A settlement that reached the requested confirmation depth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementConfirmation {
+    /// The settlement transaction hash.
+    pub transaction: String,
+    /// The network where the transaction was mined (CAIP-2 chain ID).
+    pub network: String,
+    /// The block that included the transaction.
+    pub block_number: u64,
+    /// The number of confirmations observed, counting the inclusion block.
+    pub confirmations: u64,
+}
+
+/// Polls `provider` until `transaction` has at least `required` confirmations.
+///
+/// Gives up with [`ClientError::ConfirmationTimeout`] once `options.timeout`
+/// has elapsed.
+pub(super) async fn wait_for_confirmations<P>(
+    provider: &P,
+    network: &str,
+    transaction: &str,
+    required: u64,
+    options: ConfirmationOptions,
+) -> Result<SettlementConfirmation, ClientError>
+where
+    P: ConfirmationProvider + ?Sized,
+{
+    let mut observed = 0;
+    let poll = async {
+        loop {
+            if let Some(block_number) = provider.transaction_block(network, transaction).await? {
+                let head = provider.block_number(network).await?;
+                observed = head.saturating_sub(block_number).saturating_add(1);
+                if observed >= required {
+                    return Ok(SettlementConfirmation {
+                        transaction: transaction.to_owned(),
+                        network: network.to_owned(),
+                        block_number,
+                        confirmations: observed,
+                    });
+                }
+            }
+            tokio::time::sleep(options.poll_interval).await;
+        }
+    };
+    let result = tokio::time::timeout(options.timeout, poll).await;
+    result.unwrap_or_else(|_| {
+        Err(ClientError::ConfirmationTimeout {
+            transaction: transaction.to_owned(),
+            confirmations: observed,
+            required,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use r402::proto::SettleResponse;
+
+    use super::*;
+    use crate::client::X402Client;
+
+    /// Mock chain whose head advances by one block on every poll.
+    struct AdvancingChain {
+        head: AtomicU64,
+        mined_at: u64,
+    }
+
+    impl ConfirmationProvider for AdvancingChain {
+        fn block_number<'a>(
+            &'a self,
+            _network: &'a str,
+        ) -> BoxFuture<'a, Result<u64, ClientError>> {
+            Box::pin(async { Ok(self.head.fetch_add(1, Ordering::SeqCst)) })
+        }
+
+        fn transaction_block<'a>(
+            &'a self,
+            _network: &'a str,
+            _transaction: &'a str,
+        ) -> BoxFuture<'a, Result<Option<u64>, ClientError>> {
+            let head = self.head.load(Ordering::SeqCst);
+            Box::pin(async move { Ok((head >= self.mined_at).then_some(self.mined_at)) })
+        }
+    }
+
+    fn success_proof() -> SettleResponse {
+        SettleResponse::Success {
+            payer: "0xpayer".to_owned(),
+            transaction: "0xabc".to_owned(),
+            network: "eip155:8453".to_owned(),
+            extensions: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_confirm_settlement_waits_for_depth() {
+        let chain = AdvancingChain {
+            head: AtomicU64::new(100),
+            mined_at: 100,
+        };
+        let client = X402Client::new().with_confirmation_poll_interval(Duration::from_millis(1));
+
+        let confirmation = client
+            .confirm_settlement(&success_proof(), &chain, 3)
+            .await
+            .unwrap();
+
+        assert_eq!(confirmation.block_number, 100);
+        assert_eq!(confirmation.confirmations, 3);
+        assert_eq!(confirmation.transaction, "0xabc");
+    }
+
+    #[tokio::test]
+    async fn test_confirm_settlement_times_out() {
+        let chain = AdvancingChain {
+            head: AtomicU64::new(0),
+            mined_at: u64::MAX,
+        };
+        let client = X402Client::new()
+            .with_confirmation_poll_interval(Duration::from_millis(1))
+            .with_confirmation_timeout(Duration::from_millis(20));
+
+        let err = client
+            .confirm_settlement(&success_proof(), &chain, 1)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ClientError::ConfirmationTimeout {
+                confirmations: 0,
+                required: 1,
+                ..
+            }
+        ));
+    }
+}
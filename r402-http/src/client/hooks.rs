@@ -8,9 +8,15 @@
 //! ## Hook Lifecycle
 //!
 //! 1. **`before_payment_creation`** — Run before payment creation; can abort it.
-//! 2. **Payment signing executes**
-//! 3. **`after_payment_creation`** (on success) — Observes the result.
-//! 4. **`on_payment_creation_failure`** (on error) — Can recover with substitute headers.
+//! 2. **`before_sign`** — Inspects the selected payment option; can veto it.
+//! 3. **Payment signing executes**
+//! 4. **`after_payment_creation`** (on success) — Observes the result.
+//! 5. **`on_payment_creation_failure`** (on error) — Can recover with substitute headers.
+//! 6. **`after_pay`** — Observes the settlement returned with the paid response.
+//!
+//! An abort from `before_payment_creation` or `before_sign` fails the request
+//! with [`ClientError::Aborted`](r402::scheme::ClientError::Aborted). Failure
+//! hooks do not run for aborts.
 //!
 //! ## Usage
 //!
@@ -21,6 +27,7 @@
 use r402::facilitator::BoxFuture;
 use r402::hooks::{FailureRecovery, HookDecision};
 use r402::proto;
+use r402::scheme::PaymentCandidate;
 
 /// Context passed to client payment creation lifecycle hooks.
 #[derive(Debug, Clone)]
@@ -37,14 +44,20 @@ pub struct PaymentCreationContext {
 /// The hook lifecycle mirrors [`r402::hooks::FacilitatorHooks`]:
 ///
 /// 1. **`before_payment_creation`** — Can abort with [`HookDecision::Abort`].
-/// 2. **Payment signing executes**
-/// 3. **`after_payment_creation`** (on success) — Observes the signed headers.
-/// 4. **`on_payment_creation_failure`** (on error) — Can recover with [`FailureRecovery::Recovered`].
+/// 2. **`before_sign`** — Can veto the selected payment with [`HookDecision::Abort`].
+/// 3. **Payment signing executes**
+/// 4. **`after_payment_creation`** (on success) — Observes the signed headers.
+/// 5. **`on_payment_creation_failure`** (on error) — Can recover with [`FailureRecovery::Recovered`].
+/// 6. **`after_pay`** — Observes the settlement of the paid request.
+///
+/// Hooks run in registration order. For the "before" hooks, the first abort
+/// wins and the remaining hooks are not called.
 pub trait ClientHooks: Send + Sync {
     /// Called before payment creation.
     ///
     /// If any hook returns [`HookDecision::Abort`], payment creation is skipped
-    /// and the original 402 response is returned to the caller.
+    /// and [`ClientError::Aborted`](r402::scheme::ClientError::Aborted) is
+    /// returned to the caller.
     fn before_payment_creation<'a>(
         &'a self,
         _ctx: &'a PaymentCreationContext,
@@ -52,6 +65,21 @@ fn before_payment_creation<'a>(
         Box::pin(async { HookDecision::Continue })
     }
 
+    /// Called with the selected payment option, right before it is signed.
+    ///
+    /// This is the policy checkpoint for agents, e.g. to refuse amounts above
+    /// a budget or untrusted recipients. If any hook returns
+    /// [`HookDecision::Abort`], nothing is signed and
+    /// [`ClientError::Aborted`](r402::scheme::ClientError::Aborted) is
+    /// returned to the caller.
+    fn before_sign<'a>(
+        &'a self,
+        _ctx: &'a PaymentCreationContext,
+        _candidate: &'a PaymentCandidate,
+    ) -> BoxFuture<'a, HookDecision> {
+        Box::pin(async { HookDecision::Continue })
+    }
+
     /// Called after successful payment creation.
     ///
     /// Receives the signed payment headers. Cannot affect the outcome.
@@ -74,4 +102,11 @@ fn on_payment_creation_failure<'a>(
     ) -> BoxFuture<'a, FailureRecovery<HeaderMap>> {
         Box::pin(async { FailureRecovery::Propagate })
     }
+
+    /// Called when the paid request returns a `Payment-Response` settlement.
+    ///
+    /// Useful for logging and accounting. Cannot affect the outcome.
+    fn after_pay<'a>(&'a self, _settlement: &'a proto::SettleResponse) -> BoxFuture<'a, ()> {
+        Box::pin(async {})
+    }
 }
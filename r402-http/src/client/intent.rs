@@ -0,0 +1,372 @@
+//! Payment intents: reserving spend before paying, for multi-step agents.
+//!
+//! An agent planning several paid calls can turn each [`probe`] result into a
+//! [`PaymentIntent`] up front. The intent selects the candidate it would pay
+//! with and leases its amount from a shared [`PaymentBudget`], so later steps
+//! see the reduced headroom before anything is signed. The intent is then
+//! either [executed](PaymentIntent::execute), which signs the payment and
+//! commits the spend, or [cancelled](PaymentIntent::cancel), which releases
+//! the lease.
+//!
+//! Leases expire after the time-to-live given at creation. An expired intent
+//! can no longer be executed, and its amount returns to the budget even if
+//! the intent is never cancelled, so abandoned plans do not leak budget.
+//!
+//! [`probe`]: X402Client::probe
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use http::HeaderMap;
+use r402::hooks::HookDecision;
+use r402::proto;
+use r402::scheme::{ClientError, PaymentCandidate, PaymentSelector};
+
+use super::hooks::PaymentCreationContext;
+use super::middleware::X402Client;
+
+/// A spend limit shared by the intents of an agent, in token base units.
+///
+/// Amounts are compared as raw integers, so a budget should only cover
+/// payments in a single asset. Clones share the same limit.
+#[derive(Debug, Clone)]
+pub struct PaymentBudget {
+    state: Arc<Mutex<BudgetState>>,
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    limit: u128,
+    spent: u128,
+    next_id: u64,
+    leases: HashMap<u64, Lease>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Lease {
+    amount: u128,
+    expires_at: Instant,
+}
+
+impl BudgetState {
+    /// Drops expired leases, returning their amounts to the budget.
+    fn purge_expired(&mut self, now: Instant) {
+        self.leases.retain(|_, lease| lease.expires_at > now);
+    }
+
+    fn reserved(&self) -> u128 {
+        self.leases.values().map(|lease| lease.amount).sum()
+    }
+}
+
+impl PaymentBudget {
+    /// Creates a budget allowing `limit` base units of spend.
+    #[must_use]
+    pub fn new(limit: u128) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BudgetState {
+                limit,
+                spent: 0,
+                next_id: 0,
+                leases: HashMap::new(),
+            })),
+        }
+    }
+
+    fn with_state<R>(&self, f: impl FnOnce(&mut BudgetState) -> R) -> R {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.purge_expired(Instant::now());
+        f(&mut state)
+    }
+
+    /// Returns the amount spent by executed intents.
+    #[must_use]
+    pub fn spent(&self) -> u128 {
+        self.with_state(|state| state.spent)
+    }
+
+    /// Returns the amount held by pending, unexpired intents.
+    #[must_use]
+    pub fn reserved(&self) -> u128 {
+        self.with_state(|state| state.reserved())
+    }
+
+    /// Returns the amount that can still be leased.
+    #[must_use]
+    pub fn available(&self) -> u128 {
+        self.with_state(|state| {
+            state
+                .limit
+                .saturating_sub(state.spent)
+                .saturating_sub(state.reserved())
+        })
+    }
+
+    /// Reserves `amount` for `ttl`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Aborted`] with reason `"budget_exceeded"` if
+    /// less than `amount` is available.
+    pub fn lease(&self, amount: u128, ttl: Duration) -> Result<BudgetLease, ClientError> {
+        let now = Instant::now();
+        let expires_at = now.checked_add(ttl).unwrap_or(now);
+        self.with_state(|state| {
+            let available = state
+                .limit
+                .saturating_sub(state.spent)
+                .saturating_sub(state.reserved());
+            if amount > available {
+                return Err(ClientError::Aborted {
+                    reason: "budget_exceeded".to_owned(),
+                    message: format!("payment of {amount} exceeds the {available} left in budget"),
+                });
+            }
+            let id = state.next_id;
+            state.next_id += 1;
+            state.leases.insert(id, Lease { amount, expires_at });
+            Ok(BudgetLease {
+                budget: self.clone(),
+                id,
+                amount,
+                expires_at,
+            })
+        })
+    }
+}
+
+/// An amount reserved from a [`PaymentBudget`] until committed or released.
+///
+/// Dropping the lease releases it.
+#[derive(Debug)]
+pub struct BudgetLease {
+    budget: PaymentBudget,
+    id: u64,
+    amount: u128,
+    expires_at: Instant,
+}
+
+impl BudgetLease {
+    /// Returns the reserved amount.
+    #[must_use]
+    pub const fn amount(&self) -> u128 {
+        self.amount
+    }
+
+    /// Returns when the reservation lapses.
+    #[must_use]
+    pub const fn expires_at(&self) -> Instant {
+        self.expires_at
+    }
+
+    /// Returns `true` once the reservation has lapsed.
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+
+    /// Turns the reservation into spend.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Aborted`] with reason `"lease_expired"` if the
+    /// reservation lapsed, since its amount may have been leased again.
+    pub fn commit(self) -> Result<(), ClientError> {
+        self.budget.with_state(|state| {
+            if state.leases.remove(&self.id).is_none() {
+                return Err(lease_expired());
+            }
+            state.spent = state.spent.saturating_add(self.amount);
+            Ok(())
+        })
+    }
+
+    /// Returns the reserved amount to the budget.
+    pub fn release(self) {
+        drop(self);
+    }
+}
+
+impl Drop for BudgetLease {
+    fn drop(&mut self) {
+        self.budget
+            .with_state(|state| state.leases.remove(&self.id));
+    }
+}
+
+fn lease_expired() -> ClientError {
+    ClientError::Aborted {
+        reason: "lease_expired".to_owned(),
+        message: "payment intent expired before execution".to_owned(),
+    }
+}
+
+/// A selected payment, with its amount reserved, awaiting execution.
+///
+/// Created by [`X402Client::intent`]; see the [module documentation](self).
+#[allow(missing_debug_implementations)] // borrows the client, which is not Debug
+pub struct PaymentIntent<'c, TSelector> {
+    client: &'c X402Client<TSelector>,
+    context: PaymentCreationContext,
+    candidate: PaymentCandidate,
+    lease: BudgetLease,
+}
+
+impl<TSelector> PaymentIntent<'_, TSelector>
+where
+    TSelector: PaymentSelector,
+{
+    /// Returns the payment requirements the intent was created from.
+    #[must_use]
+    pub const fn payment_required(&self) -> &proto::PaymentRequired {
+        &self.context.payment_required
+    }
+
+    /// Returns the candidate that will be paid with.
+    #[must_use]
+    pub const fn candidate(&self) -> &PaymentCandidate {
+        &self.candidate
+    }
+
+    /// Returns the lease held against the budget.
+    #[must_use]
+    pub const fn lease(&self) -> &BudgetLease {
+        &self.lease
+    }
+
+    /// Signs the payment and commits the reserved amount as spent.
+    ///
+    /// Returns the headers to attach to the retried request. If signing
+    /// fails, the reservation is released.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Aborted`] with reason `"lease_expired"` if the
+    /// intent expired, or the error raised by the hooks or while signing.
+    pub async fn execute(self) -> Result<HeaderMap, ClientError> {
+        if self.lease.is_expired() {
+            return Err(lease_expired());
+        }
+        let headers = self
+            .client
+            .sign_candidate(&self.context, &self.candidate)
+            .await?;
+        self.lease.commit()?;
+        for hook in self.client.hooks.iter() {
+            hook.after_payment_creation(&self.context, &headers).await;
+        }
+        Ok(headers)
+    }
+
+    /// Abandons the payment, releasing its reservation.
+    pub fn cancel(self) {
+        self.lease.release();
+    }
+}
+
+impl<TSelector> X402Client<TSelector>
+where
+    TSelector: PaymentSelector,
+{
+    /// Selects how `payment_required` would be paid and reserves its amount
+    /// from `budget` for `ttl`, without signing.
+    ///
+    /// Runs the `before_payment_creation` hooks now; the `before_sign` hooks
+    /// run when the intent is executed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::NoMatchingPaymentOption`] if no registered scheme
+    /// can handle the payment requirements, [`ClientError::ParseError`] if the
+    /// selected amount is not an integer, and [`ClientError::Aborted`] if a
+    /// hook vetoes the payment or the budget cannot cover it.
+    pub async fn intent(
+        &self,
+        payment_required: proto::PaymentRequired,
+        budget: &PaymentBudget,
+        ttl: Duration,
+    ) -> Result<PaymentIntent<'_, TSelector>, ClientError> {
+        let context = PaymentCreationContext { payment_required };
+        for hook in self.hooks.iter() {
+            if let HookDecision::Abort { reason, message } =
+                hook.before_payment_creation(&context).await
+            {
+                return Err(ClientError::Aborted { reason, message });
+            }
+        }
+
+        let mut candidates = self.schemes.candidates(&context.payment_required);
+        let selected = self.select_candidate(&candidates)?;
+        let candidate = candidates.swap_remove(selected);
+        let amount = candidate
+            .amount
+            .parse::<u128>()
+            .map_err(|e| ClientError::ParseError(format!("invalid amount: {e}")))?;
+        let lease = budget.lease(amount, ttl)?;
+        Ok(PaymentIntent {
+            client: self,
+            context,
+            candidate,
+            lease,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{StaticScheme, payment_required};
+    use crate::headers::PAYMENT_SIGNATURE_HEADER;
+
+    #[tokio::test]
+    async fn test_execute_commits_and_cancel_releases() {
+        let client = X402Client::new().register(StaticScheme);
+        let budget = PaymentBudget::new(1_000);
+
+        let first = client
+            .intent(payment_required("600"), &budget, Duration::from_mins(1))
+            .await
+            .unwrap();
+        assert_eq!(budget.available(), 400);
+        assert!(
+            client
+                .intent(payment_required("600"), &budget, Duration::from_mins(1))
+                .await
+                .is_err()
+        );
+
+        let headers = first.execute().await.unwrap();
+        assert_eq!(headers[PAYMENT_SIGNATURE_HEADER], "signed-payment");
+        assert_eq!(budget.spent(), 600);
+
+        let second = client
+            .intent(payment_required("300"), &budget, Duration::from_mins(1))
+            .await
+            .unwrap();
+        assert_eq!(budget.reserved(), 300);
+        second.cancel();
+        assert_eq!(budget.reserved(), 0);
+        assert_eq!(budget.available(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_expired_intent_releases_budget() {
+        let client = X402Client::new().register(StaticScheme);
+        let budget = PaymentBudget::new(1_000);
+
+        let intent = client
+            .intent(payment_required("800"), &budget, Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert_eq!(budget.available(), 200);
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(budget.available(), 1_000);
+        assert!(matches!(
+            intent.execute().await,
+            Err(ClientError::Aborted { reason, .. }) if reason == "lease_expired"
+        ));
+        assert_eq!(budget.spent(), 0);
+    }
+}
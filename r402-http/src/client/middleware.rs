@@ -4,12 +4,13 @@
 //! and payment selection for automatic payment handling.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use http::{Extensions, HeaderMap, StatusCode};
 use r402::hooks::{FailureRecovery, HookDecision};
 use r402::proto;
-use r402::proto::Base64Bytes;
-use r402::proto::v2;
+use r402::proto::split::{self, SplitPaymentPayload};
+use r402::proto::{Base64Bytes, Extension, v2};
 use r402::scheme::{
     ClientError, FirstMatch, PaymentCandidate, PaymentPolicy, PaymentSelector, SchemeClient,
 };
@@ -18,7 +19,14 @@
 #[cfg(feature = "telemetry")]
 use tracing::{debug, info, instrument, trace};
 
+use super::attestation::AttestationPolicy;
+use super::confirm::{
+    ConfirmationOptions, ConfirmationProvider, SettlementConfirmation, wait_for_confirmations,
+};
 use super::hooks::{ClientHooks, PaymentCreationContext};
+use crate::headers::{
+    PAYMENT_REQUIRED_HEADER, PAYMENT_RESPONSE_HEADER, PAYMENT_SIGNATURE_HEADER, decode_json_header,
+};
 
 /// The main x402 client that orchestrates scheme clients and selection.
 ///
@@ -27,10 +35,13 @@
 /// payments, and retrying requests.
 #[allow(missing_debug_implementations)] // ClientSchemes contains dyn trait objects
 pub struct X402Client<TSelector> {
-    schemes: ClientSchemes,
+    pub(super) schemes: ClientSchemes,
     selector: TSelector,
     policies: Vec<Arc<dyn PaymentPolicy>>,
-    hooks: Arc<[Arc<dyn ClientHooks>]>,
+    pub(super) hooks: Arc<[Arc<dyn ClientHooks>]>,
+    confirmation: ConfirmationOptions,
+    pub(super) attestation: Option<AttestationPolicy>,
+    extensions: proto::Extensions,
 }
 
 impl X402Client<FirstMatch> {
@@ -51,6 +62,9 @@ fn default() -> Self {
             selector: FirstMatch,
             policies: Vec::new(),
             hooks: Arc::from([]),
+            confirmation: ConfirmationOptions::default(),
+            attestation: None,
+            extensions: proto::Extensions::new(),
         }
     }
 }
@@ -87,6 +101,9 @@ pub fn with_selector<P: PaymentSelector + 'static>(self, selector: P) -> X402Cli
             schemes: self.schemes,
             policies: self.policies,
             hooks: self.hooks,
+            confirmation: self.confirmation,
+            attestation: self.attestation,
+            extensions: self.extensions,
         }
     }
 
@@ -104,8 +121,8 @@ pub fn with_policy<P: PaymentPolicy + 'static>(mut self, policy: P) -> Self {
     /// Adds a lifecycle hook for payment creation.
     ///
     /// Hooks allow intercepting the payment creation pipeline for logging,
-    /// custom validation, or error recovery. Multiple hooks are executed
-    /// in registration order.
+    /// custom validation such as vetoing a payment before it is signed, or
+    /// error recovery. Multiple hooks are executed in registration order.
     #[must_use]
     pub fn with_hook(mut self, hook: impl ClientHooks + 'static) -> Self {
         let mut hooks = (*self.hooks).to_vec();
@@ -113,12 +130,142 @@ pub fn with_hook(mut self, hook: impl ClientHooks + 'static) -> Self {
         self.hooks = Arc::from(hooks);
         self
     }
+
+    /// Sets the maximum time [`confirm_settlement`](Self::confirm_settlement)
+    /// waits for a settlement to reach the requested depth.
+    ///
+    /// Defaults to [`DEFAULT_CONFIRMATION_TIMEOUT`](super::DEFAULT_CONFIRMATION_TIMEOUT).
+    #[must_use]
+    pub const fn with_confirmation_timeout(mut self, timeout: Duration) -> Self {
+        self.confirmation.timeout = timeout;
+        self
+    }
+
+    /// Sets the delay between two polls in
+    /// [`confirm_settlement`](Self::confirm_settlement).
+    ///
+    /// Defaults to [`DEFAULT_CONFIRMATION_POLL_INTERVAL`](super::DEFAULT_CONFIRMATION_POLL_INTERVAL).
+    #[must_use]
+    pub const fn with_confirmation_poll_interval(mut self, interval: Duration) -> Self {
+        self.confirmation.poll_interval = interval;
+        self
+    }
+
+    /// Verifies the facilitator attestation on the receipt of every paid request.
+    ///
+    /// A paid response whose receipt fails the `policy` is turned into a
+    /// [`ClientError::InvalidAttestation`] middleware error.
+    #[must_use]
+    pub fn with_attestation(mut self, policy: AttestationPolicy) -> Self {
+        self.attestation = Some(policy);
+        self
+    }
+
+    /// Declares support for a protocol extension.
+    ///
+    /// When a 402 response advertises the extension's id in its `extensions`,
+    /// the declared value is attached to the payment payload. Extensions the
+    /// server does not advertise are never sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::JsonError`] if the extension cannot be serialized.
+    pub fn with_extension<E: Extension>(mut self, extension: &E) -> Result<Self, ClientError> {
+        proto::insert_extension(&mut self.extensions, extension)?;
+        Ok(self)
+    }
 }
 
 impl<TSelector> X402Client<TSelector>
 where
     TSelector: PaymentSelector,
 {
+    /// Waits until a settled payment reaches `confirmations` blocks of depth.
+    ///
+    /// `proof` is the settlement returned by the resource server, usually
+    /// obtained with [`parse_payment_response`]. The chain is polled through
+    /// `provider` until the transaction is buried under enough blocks or the
+    /// configured confirmation timeout elapses.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::SettlementFailed`] if `proof` reports a failed
+    /// settlement, [`ClientError::ConfirmationTimeout`] if the depth is not
+    /// reached in time, and any error raised by `provider`.
+    #[cfg_attr(
+        feature = "telemetry",
+        instrument(name = "x402.reqwest.confirm_settlement", skip_all, err)
+    )]
+    pub async fn confirm_settlement<P>(
+        &self,
+        proof: &proto::SettleResponse,
+        provider: &P,
+        confirmations: u64,
+    ) -> Result<SettlementConfirmation, ClientError>
+    where
+        P: ConfirmationProvider + ?Sized,
+    {
+        match proof {
+            proto::SettleResponse::Success {
+                transaction,
+                network,
+                ..
+            } => {
+                wait_for_confirmations(
+                    provider,
+                    network,
+                    transaction,
+                    confirmations,
+                    self.confirmation,
+                )
+                .await
+            }
+            proto::SettleResponse::Error {
+                reason, message, ..
+            } => Err(ClientError::SettlementFailed(
+                message.clone().unwrap_or_else(|| reason.clone()),
+            )),
+            _ => Err(ClientError::SettlementFailed(
+                "unsupported settlement response".to_owned(),
+            )),
+        }
+    }
+
+    /// Fetches the payment requirements of a resource without paying for it.
+    ///
+    /// Sends `request` once through `client`, which should not carry this
+    /// middleware. A 402 response is parsed like in the paying flow and its
+    /// requirements returned; any other successful response means the
+    /// resource is free and yields `None`. Nothing is signed and the request
+    /// is never retried.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`rqm::Error::Reqwest`] if the request fails or the response
+    /// has an error status other than 402, and [`ClientError::ParseError`]
+    /// if a 402 response carries no readable payment requirements.
+    #[allow(clippy::unused_self)] // a probe is made on behalf of a paying client
+    #[cfg_attr(
+        feature = "telemetry",
+        instrument(name = "x402.reqwest.probe", skip_all, err)
+    )]
+    pub async fn probe(
+        &self,
+        client: &reqwest::Client,
+        request: Request,
+    ) -> rqm::Result<Option<proto::PaymentRequired>> {
+        let res = client.execute(request).await?;
+        if res.status() != StatusCode::PAYMENT_REQUIRED {
+            res.error_for_status()?;
+            return Ok(None);
+        }
+        parse_payment_required(res).await.map(Some).ok_or_else(|| {
+            rqm::Error::Middleware(
+                ClientError::ParseError("Invalid 402 response".to_string()).into(),
+            )
+        })
+    }
+
     /// Creates payment headers from a 402 response.
     ///
     /// This method extracts the payment requirements from the response,
@@ -138,6 +285,7 @@ impl<TSelector> X402Client<TSelector>
     /// Returns [`ClientError::ParseError`] if the response cannot be parsed.
     /// Returns [`ClientError::NoMatchingPaymentOption`] if no registered scheme
     /// can handle the payment requirements.
+    /// Returns [`ClientError::Aborted`] if a hook vetoes the payment.
     ///
     /// # Panics
     ///
@@ -150,21 +298,38 @@ pub async fn make_payment_headers(&self, res: Response) -> Result<HeaderMap, Cli
         let payment_required = parse_payment_required(res)
             .await
             .ok_or_else(|| ClientError::ParseError("Invalid 402 response".to_string()))?;
+        self.payment_headers(payment_required).await
+    }
 
+    /// Creates payment headers for already parsed payment requirements.
+    ///
+    /// Runs the payment creation hooks around selection and signing, like
+    /// [`make_payment_headers`](Self::make_payment_headers), for transports
+    /// that parse the 402 response themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::NoMatchingPaymentOption`] if no registered scheme
+    /// can handle the payment requirements, or the error raised while signing.
+    /// Returns [`ClientError::Aborted`] if a hook vetoes the payment.
+    pub async fn payment_headers(
+        &self,
+        payment_required: proto::PaymentRequired,
+    ) -> Result<HeaderMap, ClientError> {
         let hook_ctx = PaymentCreationContext {
             payment_required: payment_required.clone(),
         };
 
         // Phase 1: Before hooks — first abort wins
         for hook in self.hooks.iter() {
-            if let HookDecision::Abort { reason, .. } =
+            if let HookDecision::Abort { reason, message } =
                 hook.before_payment_creation(&hook_ctx).await
             {
-                return Err(ClientError::ParseError(reason));
+                return Err(ClientError::Aborted { reason, message });
             }
         }
 
-        let creation_result = self.create_payment_headers_inner(&payment_required).await;
+        let creation_result = self.create_payment_headers_inner(&hook_ctx).await;
 
         match creation_result {
             Ok(headers) => {
@@ -174,6 +339,8 @@ pub async fn make_payment_headers(&self, res: Response) -> Result<HeaderMap, Cli
                 }
                 Ok(headers)
             }
+            // A veto is final, not a failure to recover from.
+            Err(err @ ClientError::Aborted { .. }) => Err(err),
             Err(err) => {
                 // Phase 3b: Failure hooks — first recovery wins
                 let err_msg = err.to_string();
@@ -192,10 +359,59 @@ pub async fn make_payment_headers(&self, res: Response) -> Result<HeaderMap, Cli
     /// Internal helper that performs the actual payment header creation.
     async fn create_payment_headers_inner(
         &self,
-        payment_required: &proto::PaymentRequired,
+        hook_ctx: &PaymentCreationContext,
     ) -> Result<HeaderMap, ClientError> {
-        let candidates = self.schemes.candidates(payment_required);
+        let candidates = self.schemes.candidates(&hook_ctx.payment_required);
+        if split::is_split(&hook_ctx.payment_required) {
+            return self.sign_split(hook_ctx, &candidates).await;
+        }
+        let selected = self.select_candidate(&candidates)?;
+        self.sign_candidate(hook_ctx, &candidates[selected]).await
+    }
 
+    /// Signs one payment per requirement of a split 402 response.
+    ///
+    /// Every requirement must be paid, so the selector is not consulted; each
+    /// one needs a candidate the policies allow.
+    async fn sign_split(
+        &self,
+        hook_ctx: &PaymentCreationContext,
+        candidates: &[PaymentCandidate],
+    ) -> Result<HeaderMap, ClientError> {
+        let mut allowed: Vec<&PaymentCandidate> = candidates.iter().collect();
+        for policy in &self.policies {
+            allowed = policy.apply(allowed);
+        }
+
+        let accepts = &hook_ctx.payment_required.accepts;
+        let mut payments = Vec::with_capacity(accepts.len());
+        for requirement in accepts {
+            let candidate = allowed
+                .iter()
+                .find(|candidate| pays(candidate, requirement))
+                .ok_or(ClientError::NoMatchingPaymentOption)?;
+            let signed_payload = self.sign_payload(hook_ctx, candidate).await?;
+            let bytes = Base64Bytes::from(signed_payload.as_bytes())
+                .decode()
+                .map_err(|e| ClientError::SigningError(e.to_string()))?;
+            payments.push(serde_json::from_slice(&bytes)?);
+        }
+
+        let split = SplitPaymentPayload::<serde_json::Value> {
+            x402_version: v2::V2,
+            payments,
+        };
+        Ok(signature_headers(
+            &Base64Bytes::encode(serde_json::to_vec(&split)?).to_string(),
+        ))
+    }
+
+    /// Filters candidates by the policies and returns the index of the
+    /// one picked by the selector.
+    pub(super) fn select_candidate(
+        &self,
+        candidates: &[PaymentCandidate],
+    ) -> Result<usize, ClientError> {
         // Apply policies to filter candidates
         let mut filtered: Vec<&PaymentCandidate> = candidates.iter().collect();
         for policy in &self.policies {
@@ -218,19 +434,98 @@ async fn create_payment_headers_inner(
             "Selected payment scheme"
         );
 
+        candidates
+            .iter()
+            .position(|candidate| std::ptr::eq(candidate, selected))
+            .ok_or(ClientError::NoMatchingPaymentOption)
+    }
+
+    /// Signs the selected candidate and builds the payment headers.
+    pub(super) async fn sign_candidate(
+        &self,
+        hook_ctx: &PaymentCreationContext,
+        selected: &PaymentCandidate,
+    ) -> Result<HeaderMap, ClientError> {
+        let signed_payload = self.sign_payload(hook_ctx, selected).await?;
+        Ok(signature_headers(&signed_payload))
+    }
+
+    /// Runs the `before_sign` hooks, then signs `selected` and attaches the
+    /// negotiated extensions, returning the encoded payload.
+    async fn sign_payload(
+        &self,
+        hook_ctx: &PaymentCreationContext,
+        selected: &PaymentCandidate,
+    ) -> Result<String, ClientError> {
+        // Phase 2: Before sign hooks — first abort wins
+        for hook in self.hooks.iter() {
+            if let HookDecision::Abort { reason, message } =
+                hook.before_sign(hook_ctx, selected).await
+            {
+                return Err(ClientError::Aborted { reason, message });
+            }
+        }
+
         let signed_payload = selected.sign().await?;
-        let headers = {
-            let mut headers = HeaderMap::new();
-            headers.insert(
-                "Payment-Signature",
-                signed_payload
-                    .parse()
-                    .expect("signed payload is valid header value"),
-            );
-            headers
+        self.attach_extensions(signed_payload, &hook_ctx.payment_required)
+    }
+
+    /// Attaches the declared extensions advertised by the server to a signed payload.
+    ///
+    /// Extensions are not covered by the payment signature, so they can be
+    /// added after signing. Declared values replace any echoed by the scheme client.
+    fn attach_extensions(
+        &self,
+        signed_payload: String,
+        payment_required: &proto::PaymentRequired,
+    ) -> Result<String, ClientError> {
+        let Some(advertised) = payment_required.extensions.as_ref() else {
+            return Ok(signed_payload);
         };
+        let negotiated: Vec<_> = self
+            .extensions
+            .iter()
+            .filter(|(id, _)| advertised.contains_key(*id))
+            .collect();
+        if negotiated.is_empty() {
+            return Ok(signed_payload);
+        }
 
-        Ok(headers)
+        let bytes = Base64Bytes::from(signed_payload.as_bytes())
+            .decode()
+            .map_err(|e| ClientError::SigningError(e.to_string()))?;
+        let mut payload: serde_json::Value = serde_json::from_slice(&bytes)?;
+        let object = payload
+            .as_object_mut()
+            .ok_or_else(|| ClientError::SigningError("payload is not a JSON object".to_owned()))?;
+        let extensions = object
+            .entry("extensions")
+            .or_insert(serde_json::Value::Null);
+        if !extensions.is_object() {
+            *extensions = serde_json::Value::Object(serde_json::Map::new());
+        }
+        if let Some(extensions) = extensions.as_object_mut() {
+            for (id, value) in negotiated {
+                extensions.insert(id.clone(), value.clone());
+            }
+        }
+        Ok(Base64Bytes::encode(serde_json::to_vec(&payload)?).to_string())
+    }
+
+    /// Runs the `after_pay` hooks if a paid response carries a settlement.
+    pub(super) async fn observe_settlement(&self, headers: &HeaderMap) {
+        if self.hooks.is_empty() {
+            return;
+        }
+        let Some(settlement) = headers
+            .get(PAYMENT_RESPONSE_HEADER)
+            .and_then(|h| decode_json_header::<proto::SettleResponse>(h.as_bytes(), false))
+        else {
+            return;
+        };
+        for hook in self.hooks.iter() {
+            hook.after_pay(&settlement).await;
+        }
     }
 }
 
@@ -257,6 +552,28 @@ pub fn candidates(&self, payment_required: &proto::PaymentRequired) -> Vec<Payme
     }
 }
 
+/// Builds the `Payment-Signature` header carrying `signed_payload`.
+fn signature_headers(signed_payload: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        PAYMENT_SIGNATURE_HEADER,
+        signed_payload
+            .parse()
+            .expect("signed payload is valid header value"),
+    );
+    headers
+}
+
+/// Returns `true` if `candidate` pays exactly what `requirement` asks for.
+fn pays(candidate: &PaymentCandidate, requirement: &v2::PaymentRequirements) -> bool {
+    let network = &requirement.network;
+    candidate.chain_id == *network
+        && candidate.scheme == requirement.scheme
+        && candidate.amount == requirement.amount
+        && candidate.pay_to.eq_ignore_ascii_case(&requirement.pay_to)
+        && candidate.asset.eq_ignore_ascii_case(&requirement.asset)
+}
+
 /// Runs the next middleware or HTTP client with optional telemetry instrumentation.
 #[cfg_attr(
     feature = "telemetry",
@@ -325,7 +642,16 @@ async fn handle(
         #[cfg(feature = "telemetry")]
         trace!(url = ?retry.url(), "Retrying request with payment headers");
 
-        run_next(next, retry, extensions).await
+        let res = run_next(next, retry, extensions).await?;
+
+        if let Some(policy) = &self.attestation {
+            policy
+                .check_paid_response(res.status(), res.headers())
+                .map_err(|e| rqm::Error::Middleware(e.into()))?;
+        }
+        self.observe_settlement(res.headers()).await;
+
+        Ok(res)
     }
 }
 
@@ -342,9 +668,8 @@ async fn handle(
 pub async fn parse_payment_required(response: Response) -> Option<proto::PaymentRequired> {
     let v2_from_header = response
         .headers()
-        .get("Payment-Required")
-        .and_then(|h| Base64Bytes::from(h.as_bytes()).decode().ok())
-        .and_then(|b| serde_json::from_slice::<v2::PaymentRequired>(&b).ok());
+        .get(PAYMENT_REQUIRED_HEADER)
+        .and_then(|h| decode_json_header::<v2::PaymentRequired>(h.as_bytes(), false));
 
     if let Some(v2_payment_required) = v2_from_header {
         #[cfg(feature = "telemetry")]
@@ -366,3 +691,64 @@ pub async fn parse_payment_required(response: Response) -> Option<proto::Payment
 
     None
 }
+
+/// Extracts the settlement proof from the `Payment-Response` header of a paid
+/// response.
+///
+/// Returns `None` if the header is missing or cannot be decoded.
+#[must_use]
+pub fn parse_payment_response(response: &Response) -> Option<proto::SettleResponse> {
+    response
+        .headers()
+        .get(PAYMENT_RESPONSE_HEADER)
+        .and_then(|h| decode_json_header(h.as_bytes(), false))
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::fixtures::payment_required;
+    use crate::headers::encode_json_header;
+
+    async fn probe(
+        status: u16,
+        headers: &[(&str, &str)],
+    ) -> rqm::Result<Option<proto::PaymentRequired>> {
+        let server = MockServer::start().await;
+        let mut response = ResponseTemplate::new(status);
+        for (name, value) in headers {
+            response = response.insert_header(*name, *value);
+        }
+        Mock::given(method("GET"))
+            .respond_with(response)
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = reqwest::Client::new();
+        let request = client.get(server.uri()).build().unwrap();
+        X402Client::new().probe(&client, request).await
+    }
+
+    #[tokio::test]
+    async fn test_probe_returns_requirements_of_402() {
+        let payment_required = payment_required("10000");
+        let header = encode_json_header(&payment_required).unwrap();
+        let header = std::str::from_utf8(header.as_ref()).unwrap();
+
+        let probed = probe(402, &[(PAYMENT_REQUIRED_HEADER, header)])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(probed.accepts, payment_required.accepts);
+        assert!(probe(402, &[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_probe_of_free_resource_returns_none() {
+        assert!(probe(200, &[]).await.unwrap().is_none());
+        assert!(probe(500, &[]).await.is_err());
+    }
+}
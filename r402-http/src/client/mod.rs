@@ -22,14 +22,54 @@
 //! matching scheme. You can implement custom selection logic by providing your own selector.
 //!
 //! See [`X402Client::with_selector`] for custom payment selection.
+//!
+//! ## Probing Prices
+//!
+//! [`X402Client::probe`] fetches the payment requirements of a resource
+//! without paying, so agents can compare prices before committing.
+//!
+//! ## Payment Intents
+//!
+//! [`X402Client::intent`] turns a probe result into a [`PaymentIntent`]:
+//! the selected candidate with its amount leased from a shared
+//! [`PaymentBudget`]. Multi-step agents reserve spend for a whole plan, then
+//! execute or cancel each intent; unexecuted leases expire on their own.
+//!
+//! ## Settlement Confirmations
+//!
+//! After a paid request succeeds, [`parse_payment_response`] extracts the
+//! settlement proof and [`X402Client::confirm_settlement`] waits for the
+//! transaction to reach a given confirmation depth, bounded by
+//! [`X402Client::with_confirmation_timeout`].
+//!
+//! ## Receipt Attestations
+//!
+//! [`X402Client::with_attestation`] checks the facilitator's signature on each
+//! settlement receipt against its advertised public key, see [`AttestationPolicy`].
+//!
+//! ## Other HTTP Stacks
+//!
+//! [`X402ClientLayer`] applies the same flow to any `tower` HTTP client
+//! service, for stacks not built on reqwest.
 
+mod attestation;
+mod confirm;
 pub mod hooks;
+mod intent;
 mod middleware;
+mod service;
 
+pub use attestation::AttestationPolicy;
+pub use confirm::{
+    ConfirmationOptions, ConfirmationProvider, DEFAULT_CONFIRMATION_POLL_INTERVAL,
+    DEFAULT_CONFIRMATION_TIMEOUT, SettlementConfirmation,
+};
 pub use hooks::ClientHooks;
-pub use middleware::{X402Client, parse_payment_required};
+pub use intent::{BudgetLease, PaymentBudget, PaymentIntent};
+pub use middleware::{X402Client, parse_payment_required, parse_payment_response};
 use reqwest::{Client, ClientBuilder};
 use reqwest_middleware as rqm;
+pub use service::{ReplayableBody, X402ClientLayer, X402ClientService};
 
 /// Trait for adding x402 payment handling to reqwest clients.
 ///
@@ -0,0 +1,344 @@
+//! x402 payment handling for any [`tower`] HTTP client stack.
+//!
+//! [`X402ClientLayer`] wraps a `tower::Service<http::Request<B>>` with the
+//! same pay-and-retry flow as the reqwest middleware: on a 402 response the
+//! payment requirements are read from the `Payment-Required` header, a
+//! payment is signed with the [`X402Client`], and the request is sent again
+//! with the payment header. Request bodies must implement [`ReplayableBody`]
+//! so the request can be sent twice.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http::{Request, Response, StatusCode};
+use r402::scheme::{ClientError, PaymentSelector};
+use tower::{BoxError, Layer, Service};
+
+use super::X402Client;
+use crate::headers::{PAYMENT_REQUIRED_HEADER, parse_payment_required_header};
+
+/// A request body that can be sent again after a 402 response.
+pub trait ReplayableBody: Sized {
+    /// Returns a copy of the body, or `None` if it cannot be replayed, e.g.
+    /// because it is a stream.
+    fn try_clone_body(&self) -> Option<Self>;
+}
+
+impl ReplayableBody for () {
+    fn try_clone_body(&self) -> Option<Self> {
+        Some(())
+    }
+}
+
+impl ReplayableBody for String {
+    fn try_clone_body(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+impl ReplayableBody for Vec<u8> {
+    fn try_clone_body(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+/// Copies a request so it can be retried, or returns `None` if its body
+/// cannot be replayed.
+fn try_clone_request<B: ReplayableBody>(request: &Request<B>) -> Option<Request<B>> {
+    let mut clone = Request::new(request.body().try_clone_body()?);
+    clone.method_mut().clone_from(request.method());
+    clone.uri_mut().clone_from(request.uri());
+    *clone.version_mut() = request.version();
+    clone.headers_mut().clone_from(request.headers());
+    clone.extensions_mut().clone_from(request.extensions());
+    Some(clone)
+}
+
+/// Tower layer adding x402 payment handling to an HTTP client service.
+#[allow(missing_debug_implementations)] // X402Client contains dyn trait objects
+pub struct X402ClientLayer<TSelector> {
+    client: Arc<X402Client<TSelector>>,
+}
+
+impl<TSelector> X402ClientLayer<TSelector> {
+    /// Creates a layer paying with `client`.
+    pub fn new(client: X402Client<TSelector>) -> Self {
+        Self {
+            client: Arc::new(client),
+        }
+    }
+}
+
+impl<TSelector> Clone for X402ClientLayer<TSelector> {
+    fn clone(&self) -> Self {
+        Self {
+            client: Arc::clone(&self.client),
+        }
+    }
+}
+
+impl<S, TSelector> Layer<S> for X402ClientLayer<TSelector> {
+    type Service = X402ClientService<S, TSelector>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        X402ClientService {
+            inner,
+            client: Arc::clone(&self.client),
+        }
+    }
+}
+
+/// Tower service that pays for 402 responses of the wrapped HTTP client.
+///
+/// If the request body cannot be replayed, the 402 response is returned
+/// as-is so the caller can handle it manually.
+#[allow(missing_debug_implementations)] // X402Client contains dyn trait objects
+pub struct X402ClientService<S, TSelector> {
+    inner: S,
+    client: Arc<X402Client<TSelector>>,
+}
+
+impl<S: Clone, TSelector> Clone for X402ClientService<S, TSelector> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            client: Arc::clone(&self.client),
+        }
+    }
+}
+
+impl<S, B, ResBody, TSelector> Service<Request<B>> for X402ClientService<S, TSelector>
+where
+    S: Service<Request<B>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+    B: ReplayableBody + Send + 'static,
+    ResBody: Send + 'static,
+    TSelector: PaymentSelector + Send + Sync + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        // Keep the service that was polled ready for this call.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let client = Arc::clone(&self.client);
+        Box::pin(async move {
+            let retry = try_clone_request(&req);
+            let res = inner.call(req).await.map_err(Into::into)?;
+            if res.status() != StatusCode::PAYMENT_REQUIRED {
+                return Ok(res);
+            }
+            let Some(mut retry) = retry else {
+                return Ok(res);
+            };
+
+            let header = res
+                .headers()
+                .get(PAYMENT_REQUIRED_HEADER)
+                .ok_or_else(|| ClientError::ParseError("Invalid 402 response".to_owned()))?;
+            let payment_required = parse_payment_required_header(header)
+                .map_err(|e| ClientError::ParseError(e.to_string()))?;
+            let headers = client.payment_headers(payment_required).await?;
+            retry.headers_mut().extend(headers);
+
+            std::future::poll_fn(|cx| inner.poll_ready(cx))
+                .await
+                .map_err(Into::into)?;
+            let res = inner.call(retry).await.map_err(Into::into)?;
+            if let Some(policy) = &client.attestation {
+                policy.check_paid_response(res.status(), res.headers())?;
+            }
+            client.observe_settlement(res.headers()).await;
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use r402::facilitator::BoxFuture;
+    use r402::hooks::HookDecision;
+    use r402::proto;
+    use r402::scheme::{FirstMatch, PaymentCandidate};
+
+    use super::*;
+    use crate::client::hooks::{ClientHooks, PaymentCreationContext};
+    use crate::fixtures::{StaticScheme, payment_required};
+    use crate::headers::{PAYMENT_RESPONSE_HEADER, PAYMENT_SIGNATURE_HEADER, encode_json_header};
+
+    /// Answers 402 until a payment header is sent, recording each request.
+    #[derive(Clone, Default)]
+    struct PaywalledService {
+        payments: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    impl Service<Request<String>> for PaywalledService {
+        type Response = Response<String>;
+        type Error = BoxError;
+        type Future = Pin<Box<dyn Future<Output = Result<Response<String>, BoxError>> + Send>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: Request<String>) -> Self::Future {
+            let payment = req
+                .headers()
+                .get(PAYMENT_SIGNATURE_HEADER)
+                .map(|h| h.to_str().unwrap().to_owned());
+            self.payments.lock().unwrap().push(payment.clone());
+            Box::pin(async move {
+                if payment.is_some() {
+                    let settlement = proto::SettleResponse::Success {
+                        payer: "0xpayer".into(),
+                        transaction: "0xtx".into(),
+                        network: "eip155:8453".into(),
+                        extensions: None,
+                    };
+                    let header = encode_json_header(&settlement)?;
+                    let res = Response::builder()
+                        .header(PAYMENT_RESPONSE_HEADER, header.as_ref())
+                        .body("weather".to_owned())?;
+                    return Ok(res);
+                }
+                let header = encode_json_header(&payment_required("10000"))?;
+                let res = Response::builder()
+                    .status(StatusCode::PAYMENT_REQUIRED)
+                    .header(PAYMENT_REQUIRED_HEADER, header.as_ref())
+                    .body(String::new())?;
+                Ok(res)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tower_service_pays_and_retries() {
+        let paywalled = PaywalledService::default();
+        let layer = X402ClientLayer::new(X402Client::new().register(StaticScheme));
+        let mut service = layer.layer(paywalled.clone());
+
+        let req = Request::get("https://api.example.com/weather")
+            .body(String::new())
+            .unwrap();
+        std::future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .unwrap();
+        let res = service.call(req).await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body(), "weather");
+        let payments = paywalled.payments.lock().unwrap();
+        assert_eq!(*payments, [None, Some("signed-payment".to_owned())]);
+    }
+
+    /// Vetoes payments above `max_amount`, recording each candidate seen.
+    struct Budget {
+        max_amount: u64,
+        seen: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ClientHooks for Budget {
+        fn before_sign<'a>(
+            &'a self,
+            _ctx: &'a PaymentCreationContext,
+            candidate: &'a PaymentCandidate,
+        ) -> BoxFuture<'a, HookDecision> {
+            Box::pin(async move {
+                self.seen.lock().unwrap().push(candidate.amount.clone());
+                if candidate.amount.parse::<u64>().unwrap() > self.max_amount {
+                    return HookDecision::Abort {
+                        reason: "amount_too_high".to_owned(),
+                        message: format!("{} exceeds the budget", candidate.amount),
+                    };
+                }
+                HookDecision::Continue
+            })
+        }
+    }
+
+    /// Records the settlements observed by `after_pay`.
+    struct RecordSettled(Arc<Mutex<Vec<proto::SettleResponse>>>);
+
+    impl ClientHooks for RecordSettled {
+        fn after_pay<'a>(&'a self, settlement: &'a proto::SettleResponse) -> BoxFuture<'a, ()> {
+            Box::pin(async move {
+                self.0.lock().unwrap().push(settlement.clone());
+            })
+        }
+    }
+
+    async fn send(
+        service: &mut X402ClientService<PaywalledService, FirstMatch>,
+    ) -> Result<Response<String>, BoxError> {
+        let req = Request::get("https://api.example.com/weather")
+            .body(String::new())
+            .unwrap();
+        std::future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .unwrap();
+        service.call(req).await
+    }
+
+    #[tokio::test]
+    async fn test_before_sign_veto_aborts_payment() {
+        let paywalled = PaywalledService::default();
+        let vetoed = Arc::new(Mutex::new(Vec::new()));
+        let later = Arc::new(Mutex::new(Vec::new()));
+        let client = X402Client::new()
+            .register(StaticScheme)
+            .with_hook(Budget {
+                max_amount: 5000,
+                seen: Arc::clone(&vetoed),
+            })
+            .with_hook(Budget {
+                max_amount: u64::MAX,
+                seen: Arc::clone(&later),
+            });
+        let mut service = X402ClientLayer::new(client).layer(paywalled.clone());
+
+        let err = send(&mut service).await.unwrap_err();
+        let err = err.downcast::<ClientError>().unwrap();
+        assert!(
+            matches!(*err, ClientError::Aborted { ref reason, .. } if reason == "amount_too_high")
+        );
+
+        // The first abort wins: later hooks are skipped and nothing is paid.
+        assert_eq!(*vetoed.lock().unwrap(), ["10000"]);
+        assert!(later.lock().unwrap().is_empty());
+        assert_eq!(*paywalled.payments.lock().unwrap(), [None]);
+    }
+
+    #[tokio::test]
+    async fn test_passthrough_hook_observes_payment() {
+        let paywalled = PaywalledService::default();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let settled = Arc::new(Mutex::new(Vec::new()));
+        let client = X402Client::new()
+            .register(StaticScheme)
+            .with_hook(Budget {
+                max_amount: 10_000,
+                seen: Arc::clone(&seen),
+            })
+            .with_hook(RecordSettled(Arc::clone(&settled)));
+        let mut service = X402ClientLayer::new(client).layer(paywalled.clone());
+
+        let res = send(&mut service).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(*seen.lock().unwrap(), ["10000"]);
+        assert!(matches!(
+            settled.lock().unwrap().as_slice(),
+            [proto::SettleResponse::Success { transaction, .. }] if transaction == "0xtx"
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+//! Fixtures shared by the client and header tests.
+
+use r402::proto;
+#[cfg(feature = "client")]
+pub use scheme::StaticScheme;
+use serde_json::json;
+
+/// A 402 document for a weather report costing `amount` of USDC on Base
+/// Sepolia.
+pub fn payment_required(amount: &str) -> proto::PaymentRequired {
+    serde_json::from_value(json!({
+        "x402Version": 2,
+        "resource": {
+            "description": "Weather report",
+            "mimeType": "application/json",
+            "url": "https://api.example.com/weather"
+        },
+        "accepts": [{
+            "scheme": "exact",
+            "network": "eip155:84532",
+            "amount": amount,
+            "payTo": "0x209693Bc6afc0C5328bA36FaF03C514EF312287C",
+            "maxTimeoutSeconds": 60,
+            "asset": "0x036CbD53842c5426634e7929541eC2318f3dCF7e"
+        }]
+    }))
+    .unwrap()
+}
+
+#[cfg(feature = "client")]
+mod scheme {
+    use r402::facilitator::BoxFuture;
+    use r402::proto;
+    use r402::scheme::{
+        ClientError, PaymentCandidate, PaymentCandidateSigner, SchemeClient, SchemeId,
+    };
+
+    /// Signer returning the fixed payment `signed-payment`.
+    pub struct StaticSigner;
+
+    impl PaymentCandidateSigner for StaticSigner {
+        fn sign_payment(&self) -> BoxFuture<'_, Result<String, ClientError>> {
+            Box::pin(async { Ok("signed-payment".to_owned()) })
+        }
+    }
+
+    /// `exact` EVM scheme accepting every requirement with a [`StaticSigner`].
+    pub struct StaticScheme;
+
+    impl SchemeId for StaticScheme {
+        fn namespace(&self) -> &'static str {
+            "eip155"
+        }
+
+        fn scheme(&self) -> &'static str {
+            "exact"
+        }
+    }
+
+    impl SchemeClient for StaticScheme {
+        fn accept(&self, payment_required: &proto::PaymentRequired) -> Vec<PaymentCandidate> {
+            payment_required
+                .accepts
+                .iter()
+                .map(|req| PaymentCandidate {
+                    chain_id: req.network.clone(),
+                    asset: req.asset.clone(),
+                    amount: req.amount.clone(),
+                    scheme: req.scheme.clone(),
+                    pay_to: req.pay_to.clone(),
+                    signer: Box::new(StaticSigner),
+                })
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! x402 V2 HTTP header names and base64 JSON header codecs.
+//!
+//! Header values are JSON documents encoded as base64. The spec mandates the
+//! standard alphabet, which is always used for encoding. Decoding is tolerant
+//! by default and also accepts the URL-safe alphabet emitted by some SDKs;
+//! pass `strict = true` to accept only standard base64.
+
+use http::HeaderValue;
+use r402::networks::NetworkInfo;
+use r402::proto::v1::PaymentRequiredV1;
+use r402::proto::{Base64Bytes, PaymentRequired};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Header carrying the client's signed payment payload.
+pub const PAYMENT_SIGNATURE_HEADER: &str = "Payment-Signature";
+
+/// Header carrying the server's payment requirements on a 402 response.
+pub const PAYMENT_REQUIRED_HEADER: &str = "Payment-Required";
+
+/// Header carrying the settlement result on a successful paid response.
+pub const PAYMENT_RESPONSE_HEADER: &str = "Payment-Response";
+
+/// Decodes a base64-encoded JSON header value.
+///
+/// Returns `None` if the value is not valid base64 (see
+/// [`Base64Bytes::decode_with`]) or not valid JSON for `T`.
+#[must_use]
+pub fn decode_json_header<T: DeserializeOwned>(value: &[u8], strict: bool) -> Option<T> {
+    let bytes = Base64Bytes::from(value).decode_with(strict).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Encodes a value as a standard base64 JSON header value.
+///
+/// # Errors
+///
+/// Returns an error if `value` cannot be serialized to JSON.
+pub fn encode_json_header<T: Serialize + ?Sized>(
+    value: &T,
+) -> Result<Base64Bytes, serde_json::Error> {
+    let json = serde_json::to_vec(value)?;
+    Ok(Base64Bytes::encode(json))
+}
+
+/// Errors that can occur when parsing a 402 Payment Required response.
+#[derive(Debug, thiserror::Error)]
+pub enum HttpError {
+    /// The header value is not valid base64, for example because it was
+    /// truncated.
+    #[error("invalid base64 in payment header: {0}")]
+    InvalidBase64(String),
+    /// The decoded document is not valid UTF-8.
+    #[error("payment document is not valid UTF-8: {0}")]
+    InvalidUtf8(#[from] std::str::Utf8Error),
+    /// The document is not a valid payment required JSON document.
+    #[error("invalid payment required JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    /// The document declares an x402 version that cannot be read.
+    #[error("unsupported x402 version: {0}")]
+    UnsupportedVersion(u64),
+}
+
+/// Parses the V2 `Payment-Required` header of a 402 response.
+///
+/// Unlike the client middleware, this does not retry the request, so callers
+/// can inspect what a resource costs before deciding to pay. Decoding is
+/// tolerant of the URL-safe alphabet, as with [`decode_json_header`].
+///
+/// # Errors
+///
+/// Returns [`HttpError`] if the value is not base64-encoded UTF-8 JSON
+/// describing V2 payment requirements.
+pub fn parse_payment_required_header(value: &HeaderValue) -> Result<PaymentRequired, HttpError> {
+    let bytes = Base64Bytes::from(value.as_bytes())
+        .decode_tolerant()
+        .map_err(|e| HttpError::InvalidBase64(e.to_string()))?;
+    let json = std::str::from_utf8(&bytes)?;
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Parses the JSON body of a 402 response.
+///
+/// Accepts both the V2 body and the V1 body sent by older servers. V1
+/// requirements are converted to V2 using `networks` to map network names
+/// to chain IDs; requirements on unknown networks are dropped.
+///
+/// # Errors
+///
+/// Returns [`HttpError`] if the body is not UTF-8 JSON describing V1 or V2
+/// payment requirements.
+pub fn parse_payment_required_body(
+    body: &[u8],
+    networks: &[NetworkInfo],
+) -> Result<PaymentRequired, HttpError> {
+    let json: serde_json::Value = serde_json::from_str(std::str::from_utf8(body)?)?;
+    let version = json
+        .get("x402Version")
+        .and_then(serde_json::Value::as_u64)
+        .ok_or_else(|| <serde_json::Error as serde::de::Error>::missing_field("x402Version"))?;
+    match version {
+        1 => Ok(serde_json::from_value::<PaymentRequiredV1>(json)?.to_payment_required(networks)),
+        2 => Ok(serde_json::from_value(json)?),
+        other => Err(HttpError::UnsupportedVersion(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::fixtures::payment_required;
+
+    const NETWORKS: &[NetworkInfo] = &[NetworkInfo {
+        name: "base-sepolia",
+        namespace: "eip155",
+        reference: "84532",
+    }];
+
+    #[test]
+    fn test_parse_v2_header() {
+        let encoded = encode_json_header(&payment_required("10000")).unwrap();
+        let value = HeaderValue::from_bytes(encoded.as_ref()).unwrap();
+        let parsed = parse_payment_required_header(&value).unwrap();
+        assert_eq!(parsed.resource.url, "https://api.example.com/weather");
+        assert_eq!(parsed.accepts[0].amount, "10000");
+    }
+
+    #[test]
+    fn test_parse_v1_body() {
+        let body = json!({
+            "x402Version": 1,
+            "error": "X-PAYMENT header is required",
+            "accepts": [{
+                "scheme": "exact",
+                "network": "base-sepolia",
+                "maxAmountRequired": "10000",
+                "resource": "https://api.example.com/weather",
+                "description": "Weather report",
+                "mimeType": "application/json",
+                "payTo": "0x209693Bc6afc0C5328bA36FaF03C514EF312287C",
+                "maxTimeoutSeconds": 60,
+                "asset": "0x036CbD53842c5426634e7929541eC2318f3dCF7e",
+                "extra": { "name": "USDC", "version": "2" }
+            }]
+        });
+        let parsed =
+            parse_payment_required_body(&serde_json::to_vec(&body).unwrap(), NETWORKS).unwrap();
+        assert_eq!(
+            parsed.error.as_deref(),
+            Some("X-PAYMENT header is required")
+        );
+        assert_eq!(parsed.resource.description, "Weather report");
+        assert_eq!(parsed.accepts[0].network.to_string(), "eip155:84532");
+        assert_eq!(parsed.accepts[0].amount, "10000");
+
+        let v2_body = serde_json::to_vec(&payment_required("10000")).unwrap();
+        assert!(parse_payment_required_body(&v2_body, NETWORKS).is_ok());
+    }
+
+    #[test]
+    fn test_malformed_payment_required_is_rejected() {
+        let encoded = encode_json_header(&payment_required("10000")).unwrap();
+        let truncated = HeaderValue::from_bytes(&encoded.as_ref()[..13]).unwrap();
+        assert!(matches!(
+            parse_payment_required_header(&truncated),
+            Err(HttpError::InvalidBase64(_))
+        ));
+
+        let latin1 = Base64Bytes::encode(b"{\"x402Version\": \"\xe9\"}");
+        let value = HeaderValue::from_bytes(latin1.as_ref()).unwrap();
+        assert!(matches!(
+            parse_payment_required_header(&value),
+            Err(HttpError::InvalidUtf8(_))
+        ));
+
+        let not_requirements = encode_json_header(&json!({ "x402Version": 2 })).unwrap();
+        let value = HeaderValue::from_bytes(not_requirements.as_ref()).unwrap();
+        assert!(matches!(
+            parse_payment_required_header(&value),
+            Err(HttpError::InvalidJson(_))
+        ));
+
+        assert!(matches!(
+            parse_payment_required_body(br#"{"x402Version": 3}"#, NETWORKS),
+            Err(HttpError::UnsupportedVersion(3))
+        ));
+    }
+}
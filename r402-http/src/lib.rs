@@ -11,8 +11,15 @@
 //! - `client` — reqwest-middleware for automatic 402 handling
 //! - `telemetry` — Tracing instrumentation
 
+#[cfg(any(feature = "client", feature = "server"))]
+pub mod headers;
+
 #[cfg(feature = "server")]
 pub mod server;
 
 #[cfg(feature = "client")]
 pub mod client;
+
+#[cfg(test)]
+#[cfg(any(feature = "client", feature = "server"))]
+mod fixtures;
@@ -0,0 +1,115 @@
+//! Response body that settles the payment once it has been streamed.
+//!
+//! Used by [`PaygateBuilder::settle_after_body`](super::paygate::PaygateBuilder::settle_after_body)
+//! for large or long-lived responses (downloads, server-sent events) that
+//! should not be buffered before settlement.
+
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use axum_core::body::Body;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use http_body::{Body as HttpBody, Frame};
+
+use super::PaygateError;
+
+/// Settlement of a verified payment, resolving to the `Payment-Response` value.
+pub type SettleFuture = Pin<Box<dyn Future<Output = Result<HeaderValue, PaygateError>> + Send>>;
+
+/// Progress of a [`SettlingBody`].
+enum State {
+    /// Forwarding frames of the inner body; settlement has not started.
+    Streaming(SettleFuture),
+    /// The inner body completed; waiting for settlement.
+    Settling(SettleFuture),
+    /// The trailers were sent, or the stream failed.
+    Done,
+}
+
+/// Passes the inner body through frame by frame, then settles the payment
+/// and ends with a `Payment-Response` trailer.
+///
+/// If the inner body fails, or the body is dropped before it completes
+/// (e.g. the client disconnected), the payment is not settled.
+pub struct SettlingBody {
+    inner: Body,
+    /// Name of the trailer carrying the settlement result.
+    trailer: HeaderName,
+    trailers: Option<HeaderMap>,
+    state: State,
+}
+
+impl SettlingBody {
+    /// Wraps `inner`, running `settle` once it has been streamed to the end
+    /// and sending its result as the `trailer`.
+    pub fn new(inner: Body, trailer: HeaderName, settle: SettleFuture) -> Self {
+        Self {
+            inner,
+            trailer,
+            trailers: None,
+            state: State::Streaming(settle),
+        }
+    }
+}
+
+impl HttpBody for SettlingBody {
+    type Data = <Body as HttpBody>::Data;
+    type Error = axum_core::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                State::Streaming(_) => match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+                    Some(Ok(frame)) => match frame.into_trailers() {
+                        // Inner trailers are merged into the final trailers.
+                        Ok(trailers) => {
+                            this.trailers
+                                .get_or_insert_with(HeaderMap::new)
+                                .extend(trailers);
+                        }
+                        Err(frame) => return Poll::Ready(Some(Ok(frame))),
+                    },
+                    Some(Err(err)) => {
+                        this.state = State::Done;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    None => {
+                        if let State::Streaming(settle) = mem::replace(&mut this.state, State::Done)
+                        {
+                            this.state = State::Settling(settle);
+                        }
+                    }
+                },
+                State::Settling(settle) => {
+                    let result = ready!(settle.as_mut().poll(cx));
+                    this.state = State::Done;
+                    return match result {
+                        // A partially settled split payment lists only the
+                        // parts settled before the failing one.
+                        Ok(header_value)
+                        | Err(PaygateError::PartialSettlement {
+                            settled: header_value,
+                            ..
+                        }) => {
+                            let mut trailers = this.trailers.take().unwrap_or_default();
+                            trailers.insert(this.trailer.clone(), header_value);
+                            Poll::Ready(Some(Ok(Frame::trailers(trailers))))
+                        }
+                        Err(err) => Poll::Ready(Some(Err(axum_core::Error::new(err)))),
+                    };
+                }
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+}
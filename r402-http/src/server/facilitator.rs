@@ -8,6 +8,7 @@
 //!
 //! - Uses `reqwest` for async HTTP requests
 //! - Supports optional timeout and headers
+//! - Notifies an optional callback when the cached `/supported` response changes
 //! - Integrates with `tracing` if the `telemetry` feature is enabled
 //!
 //! ## Error Handling
@@ -19,14 +20,17 @@
 //! - Unexpected HTTP status responses
 //!
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Display;
 use std::sync::Arc;
 use std::time::Duration;
 
 use http::{HeaderMap, StatusCode};
+use r402::chain::ChainId;
 use r402::facilitator::{BoxFuture, Facilitator, FacilitatorError};
 use r402::proto::{
-    SettleRequest, SettleResponse, SupportedResponse, VerifyRequest, VerifyResponse,
+    SettleRequest, SettleResponse, SupportedPaymentKind, SupportedResponse, VerifyRequest,
+    VerifyResponse,
 };
 use reqwest::Client;
 use tokio::sync::RwLock;
@@ -79,11 +83,19 @@ pub async fn get(&self) -> Option<SupportedResponse> {
 
     /// Stores a response in the cache with the configured TTL.
     pub async fn set(&self, response: SupportedResponse) {
+        let _ = self.replace(response).await;
+    }
+
+    /// Stores a response in the cache with the configured TTL, returning the
+    /// previously stored one, expired or not.
+    pub async fn replace(&self, response: SupportedResponse) -> Option<SupportedResponse> {
         let mut guard = self.state.write().await;
-        *guard = Some(SupportedCacheState {
-            response,
-            expires_at: std::time::Instant::now() + self.ttl,
-        });
+        guard
+            .replace(SupportedCacheState {
+                response,
+                expires_at: std::time::Instant::now() + self.ttl,
+            })
+            .map(|previous| previous.response)
     }
 
     /// Clears the cache.
@@ -93,10 +105,76 @@ pub async fn clear(&self) {
     }
 }
 
+/// A change in the facilitator's capabilities between two `/supported` fetches.
+#[derive(Debug, Clone)]
+pub struct SupportedChange {
+    /// The previously cached response.
+    pub previous: SupportedResponse,
+    /// The newly fetched response.
+    pub current: SupportedResponse,
+}
+
+impl SupportedChange {
+    /// Returns the change from `previous` to `current`, or `None` if they
+    /// advertise the same payment kinds and signers.
+    ///
+    /// Ordering of kinds and of each chain's signers is ignored.
+    #[must_use]
+    pub fn between(previous: SupportedResponse, current: SupportedResponse) -> Option<Self> {
+        let change = Self { previous, current };
+        (change.kinds_changed() || change.signers_changed()).then_some(change)
+    }
+
+    /// Returns `true` if a payment kind was added or removed.
+    #[must_use]
+    pub fn kinds_changed(&self) -> bool {
+        let contains_all = |a: &[SupportedPaymentKind], b: &[SupportedPaymentKind]| {
+            a.iter()
+                .all(|kind| b.iter().any(|other| same_kind(kind, other)))
+        };
+        !contains_all(&self.previous.kinds, &self.current.kinds)
+            || !contains_all(&self.current.kinds, &self.previous.kinds)
+    }
+
+    /// Returns `true` if a signer address was added, removed or rotated.
+    #[must_use]
+    pub fn signers_changed(&self) -> bool {
+        signer_sets(&self.previous) != signer_sets(&self.current)
+    }
+}
+
+fn same_kind(a: &SupportedPaymentKind, b: &SupportedPaymentKind) -> bool {
+    a.x402_version == b.x402_version
+        && a.scheme == b.scheme
+        && a.network == b.network
+        && a.extra == b.extra
+}
+
+fn signer_sets(response: &SupportedResponse) -> BTreeMap<&str, BTreeSet<&str>> {
+    response
+        .signers
+        .iter()
+        .filter(|(_, signers)| !signers.is_empty())
+        .map(|(pattern, signers)| {
+            (
+                pattern.as_str(),
+                signers.iter().map(String::as_str).collect(),
+            )
+        })
+        .collect()
+}
+
+/// Callback invoked when a refreshed `/supported` response differs from the
+/// cached one.
+///
+/// Lets a long-running server invalidate state derived from the facilitator's
+/// capabilities, such as pre-enriched price tags.
+pub type SupportedChangeCallback = Arc<dyn Fn(&SupportedChange) + Send + Sync>;
+
 /// A client for communicating with a remote x402 facilitator.
 ///
 /// Handles `/verify`, `/settle`, and `/supported` endpoints via JSON HTTP.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct FacilitatorClient {
     /// Base URL of the facilitator (e.g. `https://facilitator.example/`)
     base_url: Url,
@@ -112,8 +190,28 @@ pub struct FacilitatorClient {
     headers: HeaderMap,
     /// Optional request timeout
     timeout: Option<Duration>,
+    /// Optional timeout for `/verify`, overriding `timeout`
+    verify_timeout: Option<Duration>,
+    /// Optional timeout for `/settle`, overriding `timeout`
+    settle_timeout: Option<Duration>,
     /// Cache for the supported endpoint response
     supported_cache: SupportedCache,
+    /// Optional callback for changes in the supported endpoint response
+    on_supported_change: Option<SupportedChangeCallback>,
+}
+
+impl std::fmt::Debug for FacilitatorClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FacilitatorClient")
+            .field("base_url", &self.base_url)
+            .field("headers", &self.headers)
+            .field("timeout", &self.timeout)
+            .field("verify_timeout", &self.verify_timeout)
+            .field("settle_timeout", &self.settle_timeout)
+            .field("supported_cache", &self.supported_cache)
+            .field("on_supported_change", &self.on_supported_change.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 impl Facilitator for FacilitatorClient {
@@ -125,7 +223,10 @@ fn verify(
             #[cfg(feature = "telemetry")]
             let result = with_span(
                 Self::verify(self, &request),
-                tracing::info_span!("x402.facilitator_client.verify", timeout = ?self.timeout),
+                tracing::info_span!(
+                    "x402.facilitator_client.verify",
+                    timeout = ?self.verify_timeout()
+                ),
             )
             .await;
             #[cfg(not(feature = "telemetry"))]
@@ -142,7 +243,10 @@ fn settle(
             #[cfg(feature = "telemetry")]
             let result = with_span(
                 Self::settle(self, &request),
-                tracing::info_span!("x402.facilitator_client.settle", timeout = ?self.timeout),
+                tracing::info_span!(
+                    "x402.facilitator_client.settle",
+                    timeout = ?self.settle_timeout()
+                ),
             )
             .await;
             #[cfg(not(feature = "telemetry"))]
@@ -251,6 +355,18 @@ pub const fn timeout(&self) -> &Option<Duration> {
         &self.timeout
     }
 
+    /// Returns the timeout applied to `/verify` calls, if any.
+    #[must_use]
+    pub fn verify_timeout(&self) -> Option<Duration> {
+        self.verify_timeout.or(self.timeout)
+    }
+
+    /// Returns the timeout applied to `/settle` calls, if any.
+    #[must_use]
+    pub fn settle_timeout(&self) -> Option<Duration> {
+        self.settle_timeout.or(self.timeout)
+    }
+
     /// Returns a reference to the supported cache.
     #[must_use]
     pub const fn supported_cache(&self) -> &SupportedCache {
@@ -295,7 +411,10 @@ pub fn try_new(base_url: Url) -> Result<Self, FacilitatorClientError> {
             supported_url,
             headers: HeaderMap::new(),
             timeout: None,
+            verify_timeout: None,
+            settle_timeout: None,
             supported_cache: SupportedCache::new(Self::DEFAULT_SUPPORTED_CACHE_TTL),
+            on_supported_change: None,
         })
     }
 
@@ -313,6 +432,23 @@ pub const fn with_timeout(mut self, timeout: Duration) -> Self {
         self
     }
 
+    /// Sets the timeout for `/verify` calls, overriding [`Self::with_timeout`].
+    #[must_use]
+    pub const fn with_verify_timeout(mut self, timeout: Duration) -> Self {
+        self.verify_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for `/settle` calls, overriding [`Self::with_timeout`].
+    ///
+    /// Settling waits for the transaction to be mined, so it usually needs a
+    /// larger budget than verifying.
+    #[must_use]
+    pub const fn with_settle_timeout(mut self, timeout: Duration) -> Self {
+        self.settle_timeout = Some(timeout);
+        self
+    }
+
     /// Sets the TTL for caching the supported endpoint response.
     ///
     /// Default is 10 minutes. Use [`Self::without_supported_cache()`] to disable caching.
@@ -328,6 +464,19 @@ pub fn without_supported_cache(self) -> Self {
         self.with_supported_cache_ttl(Duration::ZERO)
     }
 
+    /// Sets a callback invoked when a refreshed `/supported` response
+    /// advertises different payment kinds or signers than the cached one.
+    ///
+    /// The first fetch only populates the cache and does not invoke it.
+    #[must_use]
+    pub fn with_on_supported_change<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&SupportedChange) + Send + Sync + 'static,
+    {
+        self.on_supported_change = Some(Arc::new(callback));
+        self
+    }
+
     /// Sends a `POST /verify` request to the facilitator.
     ///
     /// # Errors
@@ -337,8 +486,13 @@ pub async fn verify(
         &self,
         request: &VerifyRequest,
     ) -> Result<VerifyResponse, FacilitatorClientError> {
-        self.post_json(&self.verify_url, "POST /verify", request)
-            .await
+        self.post_json(
+            &self.verify_url,
+            "POST /verify",
+            request,
+            self.verify_timeout(),
+        )
+        .await
     }
 
     /// Sends a `POST /settle` request to the facilitator.
@@ -350,8 +504,13 @@ pub async fn settle(
         &self,
         request: &SettleRequest,
     ) -> Result<SettleResponse, FacilitatorClientError> {
-        self.post_json(&self.settle_url, "POST /settle", request)
-            .await
+        self.post_json(
+            &self.settle_url,
+            "POST /settle",
+            request,
+            self.settle_timeout(),
+        )
+        .await
     }
 
     /// Sends a `GET /supported` request to the facilitator.
@@ -382,11 +541,60 @@ pub async fn supported(&self) -> Result<SupportedResponse, FacilitatorClientErro
         tracing::info!("x402.facilitator_client.supported_cache_miss");
 
         let response = self.supported_inner().await?;
-        self.supported_cache.set(response.clone()).await;
+        let previous = self.supported_cache.replace(response.clone()).await;
+        if let Some(callback) = &self.on_supported_change
+            && let Some(previous) = previous
+            && let Some(change) = SupportedChange::between(previous, response.clone())
+        {
+            #[cfg(feature = "telemetry")]
+            tracing::info!(
+                kinds_changed = change.kinds_changed(),
+                signers_changed = change.signers_changed(),
+                "x402.facilitator_client.supported_changed"
+            );
+            callback(&change);
+        }
 
         Ok(response)
     }
 
+    /// Returns `true` if the facilitator settles `scheme` payments on
+    /// `chain_id`, according to its (cached) `/supported` response.
+    ///
+    /// Lets a client check a facilitator can handle a payment before
+    /// signing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FacilitatorClientError`] if `/supported` cannot be fetched.
+    pub async fn supports(
+        &self,
+        chain_id: &ChainId,
+        scheme: &str,
+    ) -> Result<bool, FacilitatorClientError> {
+        Ok(self.supported().await?.supports(chain_id, scheme))
+    }
+
+    /// Returns the facilitator's signer addresses on `chain_id`, according to
+    /// its (cached) `/supported` response.
+    ///
+    /// See [`SupportedResponse::signers_for_chain`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FacilitatorClientError`] if `/supported` cannot be fetched.
+    pub async fn signers_for_chain(
+        &self,
+        chain_id: &ChainId,
+    ) -> Result<Vec<String>, FacilitatorClientError> {
+        let supported = self.supported().await?;
+        Ok(supported
+            .signers_for_chain(chain_id)
+            .into_iter()
+            .map(str::to_owned)
+            .collect())
+    }
+
     /// Generic POST helper that handles JSON serialization, error mapping,
     /// timeout application, and telemetry integration.
     ///
@@ -397,13 +605,14 @@ async fn post_json<T, R>(
         url: &Url,
         context: &'static str,
         payload: &T,
+        timeout: Option<Duration>,
     ) -> Result<R, FacilitatorClientError>
     where
         T: serde::Serialize + Sync + ?Sized,
         R: serde::de::DeserializeOwned,
     {
         let req = self.client.post(url.clone()).json(payload);
-        self.send_and_parse(req, context).await
+        self.send_and_parse(req, context, timeout).await
     }
 
     /// Generic GET helper that handles error mapping, timeout application,
@@ -419,7 +628,7 @@ async fn get_json<R>(
         R: serde::de::DeserializeOwned,
     {
         let req = self.client.get(url.clone());
-        self.send_and_parse(req, context).await
+        self.send_and_parse(req, context, self.timeout).await
     }
 
     /// Applies headers, timeout, sends the request, and parses the JSON response.
@@ -427,6 +636,7 @@ async fn send_and_parse<R>(
         &self,
         mut req: reqwest::RequestBuilder,
         context: &'static str,
+        timeout: Option<Duration>,
     ) -> Result<R, FacilitatorClientError>
     where
         R: serde::de::DeserializeOwned,
@@ -434,7 +644,17 @@ async fn send_and_parse<R>(
         for (key, value) in &self.headers {
             req = req.header(key, value);
         }
-        if let Some(timeout) = self.timeout {
+        #[cfg(feature = "telemetry")]
+        if !self.headers.contains_key(super::trace::TRACEPARENT)
+            && let Some(trace) = super::trace::TraceContext::outbound()
+        {
+            let mut trace_headers = HeaderMap::new();
+            trace.inject(&mut trace_headers);
+            for (key, value) in &trace_headers {
+                req = req.header(key, value);
+            }
+        }
+        if let Some(timeout) = timeout {
             req = req.timeout(timeout);
         }
         let http_response = req
@@ -538,6 +758,7 @@ fn create_test_supported_response() -> SupportedResponse {
             }],
             extensions: vec![],
             signers: HashMap::new(),
+            attestation_key: None,
         }
     }
 
@@ -670,4 +891,175 @@ async fn test_supported_inner_bypasses_cache() {
         let result = client.supported_inner().await.unwrap();
         assert_eq!(result.kinds.len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_supports_and_signers_follow_supported_response() {
+        let mock_server = MockServer::start().await;
+        let test_response = SupportedResponse {
+            kinds: vec![SupportedPaymentKind {
+                x402_version: 2,
+                scheme: "exact".to_string(),
+                network: "eip155:8453".to_string(),
+                extra: None,
+            }],
+            extensions: vec![],
+            signers: HashMap::from([("eip155:*".to_string(), vec!["0xsigner".to_string()])]),
+            attestation_key: None,
+        };
+        Mock::given(method("GET"))
+            .and(path("/supported"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&test_response))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = FacilitatorClient::try_new(mock_server.uri().parse::<Url>().unwrap()).unwrap();
+        let base = ChainId::new("eip155", "8453");
+
+        assert!(client.supports(&base, "exact").await.unwrap());
+        assert!(!client.supports(&base, "upto").await.unwrap());
+        assert!(
+            !client
+                .supports(&ChainId::new("eip155", "1"), "exact")
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            client.signers_for_chain(&base).await.unwrap(),
+            vec!["0xsigner"]
+        );
+        assert!(
+            client
+                .signers_for_chain(&ChainId::new("solana", "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp"))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[cfg(feature = "telemetry")]
+    #[tokio::test]
+    async fn test_verify_forwards_traceparent() {
+        use wiremock::matchers::header;
+
+        use crate::server::trace::TraceContext;
+
+        const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+        let mock_server = MockServer::start().await;
+        // Same trace, continued under a new parent span id.
+        let continues_trace = |request: &wiremock::Request| {
+            request
+                .headers
+                .get("traceparent")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| TraceContext::parse(value, None))
+                .is_some_and(|trace| {
+                    trace.trace_id() == "4bf92f3577b34da6a3ce929d0e0e4736"
+                        && trace.parent_id() != "00f067aa0ba902b7"
+                })
+        };
+        Mock::given(method("POST"))
+            .and(path("/verify"))
+            .and(continues_trace)
+            .and(header("tracestate", "congo=t61rcWkgMzE"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(VerifyResponse::valid("0xpayer".into())),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = FacilitatorClient::try_new(mock_server.uri().parse::<Url>().unwrap()).unwrap();
+        let request = VerifyRequest::from(serde_json::json!({}));
+        let context = TraceContext::parse(TRACEPARENT, Some("congo=t61rcWkgMzE")).unwrap();
+
+        context.scope(client.verify(&request)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_on_supported_change_fires_once_per_change() {
+        let mock_server = MockServer::start().await;
+        let initial = create_test_supported_response();
+        let rotated = SupportedResponse {
+            signers: HashMap::from([("eip155:*".to_string(), vec!["0xrotated".to_string()])]),
+            ..create_test_supported_response()
+        };
+        Mock::given(method("GET"))
+            .and(path("/supported"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&initial))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/supported"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&rotated))
+            .mount(&mock_server)
+            .await;
+
+        let changes = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&changes);
+        let client = FacilitatorClient::try_new(mock_server.uri().parse::<Url>().unwrap())
+            .unwrap()
+            .without_supported_cache()
+            .with_on_supported_change(move |change| {
+                assert!(change.signers_changed());
+                assert!(!change.kinds_changed());
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            });
+
+        for _ in 0..3 {
+            let _ = client.supported().await.unwrap();
+        }
+        assert_eq!(changes.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_verify_and_settle_have_separate_timeouts() {
+        let mock_server = MockServer::start().await;
+        let delay = Duration::from_millis(200);
+        Mock::given(method("POST"))
+            .and(path("/verify"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(VerifyResponse::valid("0xpayer".into()))
+                    .set_delay(delay),
+            )
+            .mount(&mock_server)
+            .await;
+        let settlement = SettleResponse::Success {
+            payer: "0xpayer".into(),
+            transaction: "0xtx".into(),
+            network: "eip155:8453".into(),
+            extensions: None,
+        };
+        Mock::given(method("POST"))
+            .and(path("/settle"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(&settlement)
+                    .set_delay(delay),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let middleware = crate::server::X402Middleware::new(&mock_server.uri())
+            .with_verify_timeout(Duration::from_millis(50))
+            .with_settle_timeout(Duration::from_secs(5));
+        let client: &FacilitatorClient = middleware.facilitator();
+
+        let err = client
+            .verify(&VerifyRequest::from(serde_json::json!({})))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            FacilitatorClientError::Http { ref source, .. } if source.is_timeout()
+        ));
+        let response = client
+            .settle(&SettleRequest::from(serde_json::json!({})))
+            .await
+            .unwrap();
+        assert!(response.is_success());
+    }
 }
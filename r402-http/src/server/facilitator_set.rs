@@ -0,0 +1,343 @@
+//! Routing payments across several remote facilitators.
+//!
+//! A [`FacilitatorSet`] wraps [`FacilitatorClient`]s run by different
+//! operators or for different chains, and implements
+//! [`Facilitator`] so it can stand in for a single one:
+//!
+//! - `/supported` is the union of the members' responses.
+//! - Each verify and settle goes to the first member, in priority order, that
+//!   advertises the payment's chain and scheme, failing over to the next
+//!   supporting member on error.
+//!
+//! Priority is the order members were added in: when two facilitators
+//! support the same chain and scheme, the earlier one is tried first.
+//!
+//! Settlements only fail over when the facilitator could not be reached at
+//! all. Any other error may come after the settlement was submitted, and
+//! settling again elsewhere could charge the payer twice.
+
+use std::collections::HashMap;
+
+use r402::facilitator::{BoxFuture, Facilitator, FacilitatorError};
+use r402::proto::{
+    PaymentVerificationError, SettleRequest, SettleResponse, SupportedResponse, VerifyRequest,
+    VerifyResponse,
+};
+use r402::scheme::SchemeSlug;
+
+use super::facilitator::{FacilitatorClient, FacilitatorClientError};
+
+/// A prioritized set of remote facilitators acting as one.
+///
+/// See the [module documentation](self) for routing and failover rules.
+#[derive(Debug, Clone, Default)]
+pub struct FacilitatorSet {
+    members: Vec<FacilitatorClient>,
+}
+
+impl FacilitatorSet {
+    /// Creates a set from facilitators in priority order, highest first.
+    #[must_use]
+    pub fn new(members: impl IntoIterator<Item = FacilitatorClient>) -> Self {
+        Self {
+            members: members.into_iter().collect(),
+        }
+    }
+
+    /// Adds a facilitator with a lower priority than those already added.
+    #[must_use]
+    pub fn with_facilitator(mut self, facilitator: FacilitatorClient) -> Self {
+        self.members.push(facilitator);
+        self
+    }
+
+    /// Returns the facilitators in priority order.
+    #[must_use]
+    pub fn facilitators(&self) -> &[FacilitatorClient] {
+        &self.members
+    }
+
+    /// Returns the facilitators advertising `slug`, in priority order.
+    ///
+    /// Facilitators whose `/supported` cannot be fetched are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PaymentVerificationError::UnsupportedScheme`] if no
+    /// facilitator supports `slug` but one supports its chain, and
+    /// [`PaymentVerificationError::UnsupportedChain`] otherwise.
+    pub async fn route(
+        &self,
+        slug: &SchemeSlug,
+    ) -> Result<Vec<&FacilitatorClient>, PaymentVerificationError> {
+        let network = slug.chain_id.to_string();
+        let mut chain_supported = false;
+        let mut candidates = Vec::new();
+        for member in &self.members {
+            let Ok(supported) = member.supported().await else {
+                continue;
+            };
+            if supported.supports(&slug.chain_id, &slug.name) {
+                candidates.push(member);
+            } else {
+                chain_supported |= supported.kinds.iter().any(|kind| kind.network == network);
+            }
+        }
+        match (candidates.is_empty(), chain_supported) {
+            (false, _) => Ok(candidates),
+            (true, true) => Err(PaymentVerificationError::UnsupportedScheme),
+            (true, false) => Err(PaymentVerificationError::UnsupportedChain),
+        }
+    }
+
+    async fn candidates(
+        &self,
+        slug: Option<SchemeSlug>,
+    ) -> Result<Vec<&FacilitatorClient>, FacilitatorError> {
+        let slug = slug.ok_or_else(|| {
+            PaymentVerificationError::InvalidFormat(
+                "payment payload does not name a V2 scheme and network".into(),
+            )
+        })?;
+        Ok(self.route(&slug).await?)
+    }
+
+    async fn settle_with(
+        &self,
+        request: SettleRequest,
+        token: Option<String>,
+    ) -> Result<SettleResponse, FacilitatorError> {
+        let candidates = self.candidates(request.scheme_slug()).await?;
+        let mut last_error = None;
+        for member in candidates {
+            let result = match &token {
+                Some(token) => member.settle_verified(request.clone(), token.clone()).await,
+                None => Facilitator::settle(member, request.clone()).await,
+            };
+            match result {
+                Err(e) if is_unreachable(&e) => {
+                    #[cfg(feature = "telemetry")]
+                    tracing::warn!(
+                        facilitator = %member.base_url(),
+                        error = %e,
+                        "x402.facilitator_set.settle_failover"
+                    );
+                    last_error = Some(e);
+                }
+                result => return result,
+            }
+        }
+        Err(last_error.unwrap_or_else(|| PaymentVerificationError::UnsupportedChain.into()))
+    }
+}
+
+impl Facilitator for FacilitatorSet {
+    fn verify(
+        &self,
+        request: VerifyRequest,
+    ) -> BoxFuture<'_, Result<VerifyResponse, FacilitatorError>> {
+        Box::pin(async move {
+            let candidates = self.candidates(request.scheme_slug()).await?;
+            let mut last_error = None;
+            for member in candidates {
+                match Facilitator::verify(member, request.clone()).await {
+                    Ok(response) => return Ok(response),
+                    Err(e) => {
+                        #[cfg(feature = "telemetry")]
+                        tracing::warn!(
+                            facilitator = %member.base_url(),
+                            error = %e,
+                            "x402.facilitator_set.verify_failover"
+                        );
+                        last_error = Some(e);
+                    }
+                }
+            }
+            Err(last_error.unwrap_or_else(|| PaymentVerificationError::UnsupportedChain.into()))
+        })
+    }
+
+    fn settle(
+        &self,
+        request: SettleRequest,
+    ) -> BoxFuture<'_, Result<SettleResponse, FacilitatorError>> {
+        Box::pin(self.settle_with(request, None))
+    }
+
+    fn settle_verified(
+        &self,
+        request: SettleRequest,
+        token: String,
+    ) -> BoxFuture<'_, Result<SettleResponse, FacilitatorError>> {
+        Box::pin(self.settle_with(request, Some(token)))
+    }
+
+    /// Returns the union of the members' `/supported` responses.
+    ///
+    /// Members whose `/supported` cannot be fetched are skipped; the first
+    /// error is returned only if all of them fail.
+    fn supported(&self) -> BoxFuture<'_, Result<SupportedResponse, FacilitatorError>> {
+        Box::pin(async move {
+            let mut merged = SupportedResponse::default();
+            let mut signers: HashMap<String, Vec<String>> = HashMap::new();
+            let mut first_error = None;
+            let mut any_ok = false;
+            for member in &self.members {
+                let supported = match member.supported().await {
+                    Ok(supported) => supported,
+                    Err(e) => {
+                        first_error.get_or_insert(e);
+                        continue;
+                    }
+                };
+                any_ok = true;
+                for kind in supported.kinds {
+                    let duplicate = merged.kinds.iter().any(|known| {
+                        known.x402_version == kind.x402_version
+                            && known.scheme == kind.scheme
+                            && known.network == kind.network
+                    });
+                    if !duplicate {
+                        merged.kinds.push(kind);
+                    }
+                }
+                for extension in supported.extensions {
+                    if !merged.extensions.contains(&extension) {
+                        merged.extensions.push(extension);
+                    }
+                }
+                for (pattern, addresses) in supported.signers {
+                    let known = signers.entry(pattern).or_default();
+                    for address in addresses {
+                        if !known.contains(&address) {
+                            known.push(address);
+                        }
+                    }
+                }
+                // Receipts are signed by whichever member settles, so no
+                // single key applies to the whole set.
+            }
+            if let (false, Some(e)) = (any_ok, first_error) {
+                return Err(FacilitatorError::Other(Box::new(e)));
+            }
+            merged.signers = signers;
+            Ok(merged)
+        })
+    }
+}
+
+/// Returns `true` if `error` means the request never reached the facilitator.
+fn is_unreachable(error: &FacilitatorError) -> bool {
+    let FacilitatorError::Other(source) = error else {
+        return false;
+    };
+    matches!(
+        source.downcast_ref::<FacilitatorClientError>(),
+        Some(FacilitatorClientError::Http { source, .. }) if source.is_connect()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use r402::proto::SupportedPaymentKind;
+    use serde_json::json;
+    use url::Url;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    async fn facilitator_for(network: &str) -> MockServer {
+        let server = MockServer::start().await;
+        let supported = SupportedResponse {
+            kinds: vec![SupportedPaymentKind {
+                x402_version: 2,
+                scheme: "exact".to_string(),
+                network: network.to_string(),
+                extra: None,
+            }],
+            ..SupportedResponse::default()
+        };
+        Mock::given(method("GET"))
+            .and(path("/supported"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&supported))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn client(server: &MockServer) -> FacilitatorClient {
+        FacilitatorClient::try_new(server.uri().parse::<Url>().unwrap()).unwrap()
+    }
+
+    fn evm_request() -> VerifyRequest {
+        VerifyRequest::from(json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "accepted": { "scheme": "exact", "network": "eip155:8453" }
+            }
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_routes_to_facilitator_supporting_chain() {
+        let solana = facilitator_for("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp").await;
+        let evm = facilitator_for("eip155:8453").await;
+        Mock::given(method("POST"))
+            .and(path("/verify"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&solana)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/verify"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(VerifyResponse::valid("0xpayer".into())),
+            )
+            .expect(1)
+            .mount(&evm)
+            .await;
+
+        let set = FacilitatorSet::new([client(&solana), client(&evm)]);
+        let response = set.verify(evm_request()).await.unwrap();
+        assert!(response.is_valid());
+        assert_eq!(set.supported().await.unwrap().kinds.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_when_first_facilitator_errors() {
+        let primary = facilitator_for("eip155:8453").await;
+        let backup = facilitator_for("eip155:8453").await;
+        Mock::given(method("POST"))
+            .and(path("/verify"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&primary)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/verify"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(VerifyResponse::valid("0xpayer".into())),
+            )
+            .expect(1)
+            .mount(&backup)
+            .await;
+
+        let set = FacilitatorSet::new([client(&primary)]).with_facilitator(client(&backup));
+        let response = set.verify(evm_request()).await.unwrap();
+        assert!(response.is_valid());
+
+        let unsupported = VerifyRequest::from(json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "accepted": { "scheme": "upto", "network": "eip155:8453" }
+            }
+        }));
+        assert!(matches!(
+            set.verify(unsupported).await,
+            Err(FacilitatorError::PaymentVerification(
+                PaymentVerificationError::UnsupportedScheme
+            ))
+        ));
+    }
+}
@@ -0,0 +1,39 @@
+//! Fixtures shared by the server tests.
+
+use axum_core::body::Body;
+use r402::chain::ChainId;
+use r402::proto::v2;
+use serde::Serialize;
+
+use crate::headers::{PAYMENT_SIGNATURE_HEADER, encode_json_header};
+
+/// Price tag charging `amount` of `asset` on Base mainnet.
+pub fn asset_tag(amount: &str, asset: &str) -> v2::PriceTag {
+    v2::PriceTag {
+        requirements: v2::PaymentRequirements {
+            scheme: "exact".into(),
+            network: ChainId::new("eip155", "8453"),
+            amount: amount.into(),
+            pay_to: "0xmerchant".into(),
+            max_timeout_seconds: 60,
+            asset: asset.into(),
+            extra: None,
+            required_extensions: None,
+        },
+        enricher: None,
+    }
+}
+
+/// Price tag charging 1000 units of USDC on Base mainnet.
+pub fn price_tag() -> v2::PriceTag {
+    asset_tag("1000", "0xusdc")
+}
+
+/// Request carrying `payload` in the standard payment header.
+pub fn paid_request(payload: &impl Serialize) -> http::Request<Body> {
+    let header = encode_json_header(payload).unwrap();
+    http::Request::builder()
+        .header(PAYMENT_SIGNATURE_HEADER, header.as_ref())
+        .body(Body::empty())
+        .unwrap()
+}
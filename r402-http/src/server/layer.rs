@@ -8,6 +8,7 @@
 //!
 //! ## Configuration Notes
 //!
+//! - **[`X402Middleware::from_facilitator`]** uses an in-process facilitator instead of a remote one.
 //! - **[`X402Middleware::with_price_tag`]** sets the assets and amounts accepted for payment (static pricing).
 //! - **[`X402Middleware::with_dynamic_price`]** sets a callback for dynamic pricing based on request context.
 //! - **[`X402Middleware::with_base_url`]** sets the base URL for computing full resource URLs.
@@ -15,6 +16,15 @@
 //! - **[`X402LayerBuilder::with_description`]** is optional but helps the payer understand what is being paid for.
 //! - **[`X402LayerBuilder::with_mime_type`]** sets the MIME type of the protected resource (default: `application/json`).
 //! - **[`X402LayerBuilder::with_resource`]** explicitly sets the full URI of the protected resource.
+//! - **[`X402LayerBuilder::with_required_extension`]** rejects payments that lack a protocol extension.
+//! - **[`X402LayerBuilder::with_replay_store`]** rejects replayed payment headers with `409 Conflict`.
+//! - **[`X402LayerBuilder::with_extra_402_headers`]** adds custom headers, e.g. a docs `Link`, to 402 responses.
+//! - **[`X402LayerBuilder::with_settle_after_body`]** streams the response body before settling, sending
+//!   `Payment-Response` as a trailer.
+//! - **[`X402LayerBuilder::with_split`]** requires every price tag to be paid, splitting the price across
+//!   several recipients.
+//! - **[`X402LayerBuilder::with_payment_header`]** and **[`X402LayerBuilder::with_payment_response_header`]**
+//!   rename the payment headers for proxies that strip the standard ones (breaks standard clients).
 //!
 
 use std::convert::Infallible;
@@ -26,16 +36,17 @@
 
 use axum_core::extract::Request;
 use axum_core::response::Response;
-use http::{HeaderMap, Uri};
+use http::{HeaderMap, HeaderName, Uri};
 use r402::facilitator::Facilitator;
-use r402::proto::v2;
+use r402::proto::{self, v2};
 use tower::util::BoxCloneSyncService;
 use tower::{Layer, Service};
 use url::Url;
 
 use super::facilitator::FacilitatorClient;
-use super::paygate::{Paygate, ResourceInfoBuilder};
+use super::paygate::{Paygate, PaymentHeaderNames, ResourceInfoBuilder};
 use super::pricing::{DynamicPriceTags, PriceTagSource, StaticPriceTags};
+use super::replay::ReplayStore;
 
 /// The main X402 middleware instance for enforcing x402 payments on routes.
 ///
@@ -71,6 +82,25 @@ pub const fn facilitator(&self) -> &F {
     }
 }
 
+impl<F> X402Middleware<F>
+where
+    F: Facilitator + Clone,
+{
+    /// Creates a new middleware instance backed by an in-process facilitator.
+    ///
+    /// The layer accepts any `F: Facilitator + Clone`. Wrapping a local
+    /// [`SchemeRegistry`](r402::scheme::SchemeRegistry) in an `Arc` lets an
+    /// all-in-one deployment verify and settle payments directly, without a
+    /// loopback HTTP round-trip through [`FacilitatorClient`].
+    #[must_use]
+    pub const fn from_facilitator(facilitator: F) -> Self {
+        Self {
+            facilitator,
+            base_url: None,
+        }
+    }
+}
+
 impl X402Middleware<Arc<FacilitatorClient>> {
     /// Creates a new middleware instance with a default facilitator URL.
     ///
@@ -135,6 +165,36 @@ pub fn with_facilitator_timeout(&self, timeout: Duration) -> Self {
             base_url: self.base_url.clone(),
         }
     }
+
+    /// Sets a per-request timeout for facilitator `/verify` calls,
+    /// overriding [`Self::with_facilitator_timeout`] for them.
+    ///
+    /// Verification only checks signatures and balances, so it can be held
+    /// to a tighter budget than settlement.
+    #[must_use]
+    pub fn with_verify_timeout(&self, timeout: Duration) -> Self {
+        let inner = Arc::unwrap_or_clone(Arc::clone(&self.facilitator));
+        let facilitator = Arc::new(inner.with_verify_timeout(timeout));
+        Self {
+            facilitator,
+            base_url: self.base_url.clone(),
+        }
+    }
+
+    /// Sets a per-request timeout for facilitator `/settle` calls,
+    /// overriding [`Self::with_facilitator_timeout`] for them.
+    ///
+    /// Settlement waits for the transaction to be mined, so it needs a larger
+    /// budget than verification.
+    #[must_use]
+    pub fn with_settle_timeout(&self, timeout: Duration) -> Self {
+        let inner = Arc::unwrap_or_clone(Arc::clone(&self.facilitator));
+        let facilitator = Arc::new(inner.with_settle_timeout(timeout));
+        Self {
+            facilitator,
+            base_url: self.base_url.clone(),
+        }
+    }
 }
 
 impl TryFrom<&str> for X402Middleware<Arc<FacilitatorClient>> {
@@ -179,23 +239,37 @@ impl<TFacilitator> X402Middleware<TFacilitator>
     ///
     /// Creates a layer builder that can be further configured with additional
     /// price tags and resource information.
+    ///
+    /// The amount must be non-zero; leave free routes unprotected so they
+    /// answer `200` instead of a zero-price `402`.
     #[must_use]
     pub fn with_price_tag(
         &self,
         price_tag: v2::PriceTag,
     ) -> X402LayerBuilder<StaticPriceTags, TFacilitator> {
-        X402LayerBuilder {
-            facilitator: self.facilitator.clone(),
-            price_source: StaticPriceTags::new(vec![price_tag]),
-            base_url: self.base_url.clone().map(Arc::new),
-            resource: Arc::new(ResourceInfoBuilder::default()),
-        }
+        self.layer_builder(StaticPriceTags::new(vec![price_tag]))
+    }
+
+    /// Sets a custom price source for the protected route.
+    ///
+    /// Use this with [`DynamicPriceTags::with_extensions`] to price requests
+    /// by context stored in the request extensions.
+    #[must_use]
+    pub fn with_price_source<TSource: PriceTagSource>(
+        &self,
+        price_source: TSource,
+    ) -> X402LayerBuilder<TSource, TFacilitator> {
+        self.layer_builder(price_source)
     }
 
     /// Sets a dynamic price source for the protected route.
     ///
     /// The `callback` receives request headers, URI, and base URL, and returns
     /// a vector of V2 price tags.
+    ///
+    /// To serve a request for free, return no price tags: the request is passed
+    /// through to the handler. Never advertise a zero amount; clients and
+    /// facilitators reject zero-amount payments.
     #[must_use]
     pub fn with_dynamic_price<F, Fut>(
         &self,
@@ -205,11 +279,26 @@ pub fn with_dynamic_price<F, Fut>(
         F: Fn(&HeaderMap, &Uri, Option<&Url>) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = Vec<v2::PriceTag>> + Send + 'static,
     {
+        self.layer_builder(DynamicPriceTags::new(callback))
+    }
+
+    /// Creates a layer builder with default settings pricing by `price_source`.
+    fn layer_builder<TSource>(
+        &self,
+        price_source: TSource,
+    ) -> X402LayerBuilder<TSource, TFacilitator> {
         X402LayerBuilder {
             facilitator: self.facilitator.clone(),
-            price_source: DynamicPriceTags::new(callback),
+            price_source,
             base_url: self.base_url.clone().map(Arc::new),
             resource: Arc::new(ResourceInfoBuilder::default()),
+            strict_base64: false,
+            required_extensions: Arc::new(proto::Extensions::new()),
+            replay_store: None,
+            extra_402_headers: Arc::new(HeaderMap::new()),
+            settle_after_body: false,
+            split: false,
+            header_names: PaymentHeaderNames::default(),
         }
     }
 }
@@ -225,6 +314,13 @@ pub struct X402LayerBuilder<TSource, TFacilitator> {
     base_url: Option<Arc<Url>>,
     price_source: TSource,
     resource: Arc<ResourceInfoBuilder>,
+    strict_base64: bool,
+    required_extensions: Arc<proto::Extensions>,
+    replay_store: Option<Arc<dyn ReplayStore>>,
+    extra_402_headers: Arc<HeaderMap>,
+    settle_after_body: bool,
+    split: bool,
+    header_names: PaymentHeaderNames,
 }
 
 impl<TFacilitator> X402LayerBuilder<StaticPriceTags, TFacilitator> {
@@ -276,6 +372,97 @@ pub fn with_resource(mut self, resource: Url) -> Self {
         self.resource = Arc::new(new_resource);
         self
     }
+
+    /// Only accepts `Payment-Signature` headers encoded with the standard base64 alphabet.
+    ///
+    /// By default, URL-safe base64 is tolerated as well since some SDKs and
+    /// intermediaries emit it.
+    #[must_use]
+    pub const fn with_strict_base64(mut self) -> Self {
+        self.strict_base64 = true;
+        self
+    }
+
+    /// Requires payment payloads to carry the extension `id`.
+    ///
+    /// `info` is advertised under `id` in 402 responses. See
+    /// [`PaygateBuilder::require_extension`](super::paygate::PaygateBuilder::require_extension).
+    #[must_use]
+    pub fn with_required_extension(
+        mut self,
+        id: impl Into<String>,
+        info: serde_json::Value,
+    ) -> Self {
+        Arc::make_mut(&mut self.required_extensions).insert(id.into(), info);
+        self
+    }
+
+    /// Rejects replayed payment headers with `409 Conflict`.
+    ///
+    /// A payment that does not settle, e.g. because verification or the
+    /// handler failed, is released from the store and may be retried.
+    ///
+    /// While the store is full of unexpired ids, new payments are answered
+    /// with `503 Service Unavailable` rather than evicting live ids.
+    ///
+    /// The `store` is shared by every request through this layer. Use
+    /// [`InMemoryReplayStore`](super::replay::InMemoryReplayStore) for a single
+    /// instance, or an external store when running several.
+    #[must_use]
+    pub fn with_replay_store(mut self, store: impl ReplayStore + 'static) -> Self {
+        self.replay_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Adds `headers` to every 402 response, e.g. `Link: <docs>; rel="help"`.
+    ///
+    /// Protocol headers such as `Payment-Required` are never overwritten. See
+    /// [`PaygateBuilder::extra_402_headers`](super::paygate::PaygateBuilder::extra_402_headers).
+    #[must_use]
+    pub fn with_extra_402_headers(mut self, headers: HeaderMap) -> Self {
+        Arc::make_mut(&mut self.extra_402_headers).extend(headers);
+        self
+    }
+
+    /// Streams the response body before settling, instead of settling first.
+    ///
+    /// `Payment-Response` is then sent as an HTTP trailer. See
+    /// [`PaygateBuilder::settle_after_body`](super::paygate::PaygateBuilder::settle_after_body).
+    #[must_use]
+    pub const fn with_settle_after_body(mut self) -> Self {
+        self.settle_after_body = true;
+        self
+    }
+
+    /// Requires every price tag to be paid, e.g. a seller's price plus a
+    /// platform fee to another address, instead of any one of them.
+    ///
+    /// See [`PaygateBuilder::split`](super::paygate::PaygateBuilder::split).
+    #[must_use]
+    pub const fn with_split(mut self) -> Self {
+        self.split = true;
+        self
+    }
+
+    /// Reads the payment from `name` instead of `Payment-Signature`, e.g.
+    /// when a reverse proxy strips the standard header.
+    ///
+    /// Standard clients keep sending `Payment-Signature`, so they can only pay
+    /// if the proxy renames it. See [`PaymentHeaderNames`].
+    #[must_use]
+    pub fn with_payment_header(mut self, name: HeaderName) -> Self {
+        self.header_names.payment = name;
+        self
+    }
+
+    /// Sends the settlement result in `name` instead of `Payment-Response`.
+    ///
+    /// Standard clients only read `Payment-Response`. See [`PaymentHeaderNames`].
+    #[must_use]
+    pub fn with_payment_response_header(mut self, name: HeaderName) -> Self {
+        self.header_names.response = name;
+        self
+    }
 }
 
 impl<S, TSource, TFacilitator> Layer<S> for X402LayerBuilder<TSource, TFacilitator>
@@ -293,6 +480,13 @@ fn layer(&self, inner: S) -> Self::Service {
             base_url: self.base_url.clone(),
             price_source: self.price_source.clone(),
             resource: Arc::clone(&self.resource),
+            strict_base64: self.strict_base64,
+            required_extensions: Arc::clone(&self.required_extensions),
+            replay_store: self.replay_store.clone(),
+            extra_402_headers: Arc::clone(&self.extra_402_headers),
+            settle_after_body: self.settle_after_body,
+            split: self.split,
+            header_names: self.header_names.clone(),
             inner: BoxCloneSyncService::new(inner),
         }
     }
@@ -313,6 +507,20 @@ pub struct X402MiddlewareService<TSource, TFacilitator> {
     price_source: TSource,
     /// Resource information
     resource: Arc<ResourceInfoBuilder>,
+    /// Whether to reject URL-safe base64 payment headers
+    strict_base64: bool,
+    /// Extensions every payment payload must carry
+    required_extensions: Arc<proto::Extensions>,
+    /// Store of payment ids already seen, if replay protection is enabled
+    replay_store: Option<Arc<dyn ReplayStore>>,
+    /// Headers added to every 402 response
+    extra_402_headers: Arc<HeaderMap>,
+    /// Whether to settle once the response body has been streamed
+    settle_after_body: bool,
+    /// Whether every price tag must be paid, as a split payment
+    split: bool,
+    /// Names of the payment and settlement headers
+    header_names: PaymentHeaderNames,
     /// The inner Axum service being wrapped
     inner: BoxCloneSyncService<Request, Response, Infallible>,
 }
@@ -337,12 +545,26 @@ fn call(&mut self, req: Request) -> Self::Future {
         let facilitator = self.facilitator.clone();
         let base_url = self.base_url.clone();
         let resource_builder = Arc::clone(&self.resource);
+        let strict_base64 = self.strict_base64;
+        let required_extensions = Arc::clone(&self.required_extensions);
+        let replay_store = self.replay_store.clone();
+        let extra_402_headers = Arc::clone(&self.extra_402_headers);
+        let settle_after_body = self.settle_after_body;
+        let split = self.split;
+        let header_names = self.header_names.clone();
         let mut inner = self.inner.clone();
+        #[cfg(feature = "telemetry")]
+        let trace_context = super::trace::TraceContext::from_headers(req.headers());
 
-        Box::pin(async move {
+        let handle = async move {
             // Resolve price tags from the source
             let accepts = price_source
-                .resolve(req.headers(), req.uri(), base_url.as_deref())
+                .resolve_with_extensions(
+                    req.headers(),
+                    req.uri(),
+                    base_url.as_deref(),
+                    req.extensions(),
+                )
                 .await;
 
             // If no price tags are configured, bypass payment enforcement
@@ -353,14 +575,183 @@ fn call(&mut self, req: Request) -> Self::Future {
             let resource = resource_builder.as_resource_info(base_url.as_deref(), &req);
 
             let gate = {
-                let mut gate = Paygate::builder(facilitator)
+                let mut builder = Paygate::builder(facilitator)
                     .accepts(accepts)
                     .resource(resource)
-                    .build();
+                    .strict_base64(strict_base64)
+                    .require_extensions((*required_extensions).clone())
+                    .extra_402_headers(extra_402_headers)
+                    .settle_after_body(settle_after_body)
+                    .split(split)
+                    .header_names(header_names);
+                if let Some(store) = replay_store {
+                    builder = builder.replay_store(store);
+                }
+                let mut gate = builder.build();
                 gate.enrich_accepts().await;
                 gate
             };
             gate.handle_request(inner, req).await
-        })
+        };
+        #[cfg(feature = "telemetry")]
+        let handle = super::trace::in_scope(trace_context, handle);
+        Box::pin(handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum_core::body::Body;
+    use r402::facilitator::{BoxFuture, FacilitatorError};
+    use r402::proto;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::headers::{
+        PAYMENT_REQUIRED_HEADER, PAYMENT_RESPONSE_HEADER, PAYMENT_SIGNATURE_HEADER,
+        decode_json_header,
+    };
+    use crate::server::fixtures::{paid_request, price_tag};
+
+    /// In-process facilitator counting the calls it receives.
+    #[derive(Default)]
+    struct LocalFacilitator {
+        verified: AtomicUsize,
+        settled: AtomicUsize,
+    }
+
+    impl Facilitator for LocalFacilitator {
+        fn verify(
+            &self,
+            _request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            self.verified.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Ok(proto::VerifyResponse::valid("0xpayer".into())) })
+        }
+
+        fn settle(
+            &self,
+            request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            self.settled.fetch_add(1, Ordering::SeqCst);
+            let network = request.network().to_owned();
+            Box::pin(async move {
+                Ok(proto::SettleResponse::Success {
+                    payer: "0xpayer".into(),
+                    transaction: "0xtx".into(),
+                    network,
+                    extensions: None,
+                })
+            })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::SupportedResponse::default()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_process_facilitator_verifies_and_settles() {
+        let local = Arc::new(LocalFacilitator::default());
+        let price_tag = price_tag();
+        let layer =
+            X402Middleware::from_facilitator(Arc::clone(&local)).with_price_tag(price_tag.clone());
+        let service = layer.layer(tower::service_fn(|_req: Request| async {
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        }));
+
+        let payload = v2::PaymentPayload {
+            accepted: price_tag.requirements,
+            payload: serde_json::json!({}),
+            resource: None,
+            x402_version: v2::V2,
+            extensions: None,
+        };
+        let req = paid_request(&payload);
+
+        let response = service.oneshot(req).await.unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert!(response.headers().contains_key(PAYMENT_RESPONSE_HEADER));
+        assert_eq!(local.verified.load(Ordering::SeqCst), 1);
+        assert_eq!(local.settled.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_extra_headers_are_added_to_402() {
+        let mut extra = HeaderMap::new();
+        extra.insert(
+            http::header::LINK,
+            "<https://docs.example.com>; rel=\"help\"".parse().unwrap(),
+        );
+        extra.insert(http::header::CONTENT_TYPE, "text/plain".parse().unwrap());
+        extra.insert(
+            HeaderName::from_static("payment-required"),
+            "forged".parse().unwrap(),
+        );
+        let layer = X402Middleware::from_facilitator(Arc::new(LocalFacilitator::default()))
+            .with_price_tag(price_tag())
+            .with_extra_402_headers(extra);
+        let service = layer.layer(tower::service_fn(|_req: Request| async {
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        }));
+
+        let req = http::Request::builder().body(Body::empty()).unwrap();
+        let response = service.oneshot(req).await.unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::PAYMENT_REQUIRED);
+        let headers = response.headers();
+        assert_eq!(
+            headers[http::header::LINK],
+            "<https://docs.example.com>; rel=\"help\""
+        );
+        assert_eq!(headers[http::header::CONTENT_TYPE], "application/json");
+        let payment_required: v2::PaymentRequired =
+            decode_json_header(headers[PAYMENT_REQUIRED_HEADER].as_bytes(), true).unwrap();
+        assert_eq!(payment_required.accepts.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_custom_payment_header_names_are_honored() {
+        let price_tag = price_tag();
+        let layer = X402Middleware::from_facilitator(Arc::new(LocalFacilitator::default()))
+            .with_price_tag(price_tag.clone())
+            .with_payment_header(HeaderName::from_static("x-payment"))
+            .with_payment_response_header(HeaderName::from_static("x-payment-response"));
+        let service = layer.layer(tower::service_fn(|_req: Request| async {
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        }));
+        let payload = v2::PaymentPayload {
+            accepted: price_tag.requirements,
+            payload: serde_json::json!({}),
+            resource: None,
+            x402_version: v2::V2,
+            extensions: None,
+        };
+
+        // The standard header is ignored, and the 402 names the expected one.
+        let response = service
+            .clone()
+            .oneshot(paid_request(&payload))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), http::StatusCode::PAYMENT_REQUIRED);
+        let payment_required: v2::PaymentRequired =
+            decode_json_header(response.headers()[PAYMENT_REQUIRED_HEADER].as_bytes(), true)
+                .unwrap();
+        assert_eq!(
+            payment_required.error.as_deref(),
+            Some("x-payment header is required")
+        );
+
+        let mut req = paid_request(&payload);
+        let header = req.headers_mut().remove(PAYMENT_SIGNATURE_HEADER).unwrap();
+        req.headers_mut().insert("x-payment", header);
+        let response = service.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert!(response.headers().contains_key("x-payment-response"));
+        assert!(!response.headers().contains_key(PAYMENT_RESPONSE_HEADER));
     }
 }
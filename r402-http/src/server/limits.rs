@@ -0,0 +1,386 @@
+//! Request size and processing time limits for facilitator endpoints.
+//!
+//! [`proto::VerifyRequest`] and [`proto::SettleRequest`] wrap arbitrary JSON,
+//! so an HTTP facilitator must bound both the request body it accepts and the
+//! time it spends on a single request. [`RequestLimits`] captures both limits,
+//! [`RequestLimits::parse_body`] enforces the body size while reading, and
+//! [`LimitedFacilitator`] enforces the processing timeout around any
+//! [`Facilitator`]. A [`LimitError`] converts into a JSON response of the form
+//! `{ "error": "invalid_format", "message": "..." }`, so a rejected body gets
+//! the same structured error as a rejected payment.
+
+use std::time::Duration;
+
+use axum_core::body::Body;
+use axum_core::response::{IntoResponse, Response};
+use http::StatusCode;
+use http_body::Body as HttpBody;
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use r402::facilitator::{BoxFuture, Facilitator, FacilitatorError};
+use r402::proto;
+use r402::proto::{AsPaymentProblem, ErrorReason, PaymentProblem};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Default maximum request body size: 64 `KiB`.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Default per-request processing timeout.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Abort reason reported when a request exceeds its processing timeout.
+pub const TIMEOUT_REASON: &str = "request_timeout";
+
+/// Limits applied to each facilitator request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestLimits {
+    /// Maximum accepted request body size in bytes.
+    /// Default: 65536
+    #[serde(default = "default_max_body_size")]
+    pub max_body_size: usize,
+
+    /// Maximum time spent processing a single verify or settle request.
+    /// Default: 30 seconds
+    #[serde(default = "default_request_timeout", with = "duration_secs")]
+    pub timeout: Duration,
+}
+
+const fn default_max_body_size() -> usize {
+    DEFAULT_MAX_BODY_SIZE
+}
+
+const fn default_request_timeout() -> Duration {
+    DEFAULT_REQUEST_TIMEOUT
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_body_size: default_max_body_size(),
+            timeout: default_request_timeout(),
+        }
+    }
+}
+
+impl RequestLimits {
+    /// Reads and parses a request body, rejecting it once it exceeds
+    /// [`Self::max_body_size`].
+    ///
+    /// A body whose declared length (`Content-Length`) is over the limit is
+    /// rejected before any of it is read; any other body is read only up to
+    /// the limit, so an oversized body is never buffered in full.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LimitError::PayloadTooLarge`] for oversized bodies,
+    /// [`LimitError::Unreadable`] if reading the body fails, and
+    /// [`LimitError::Malformed`] if the body is not valid JSON for `T`.
+    pub async fn parse_body<T, B>(&self, body: B) -> Result<T, LimitError>
+    where
+        T: DeserializeOwned,
+        B: HttpBody,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let limit = self.max_body_size;
+        if body.size_hint().lower() > u64::try_from(limit).unwrap_or(u64::MAX) {
+            return Err(LimitError::PayloadTooLarge { limit });
+        }
+        let body = Limited::new(body, limit)
+            .collect()
+            .await
+            .map_err(|error| {
+                if error.is::<LengthLimitError>() {
+                    LimitError::PayloadTooLarge { limit }
+                } else {
+                    LimitError::Unreadable(error.to_string())
+                }
+            })?
+            .to_bytes();
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+/// Errors raised when a request violates the configured [`RequestLimits`].
+#[derive(Debug, thiserror::Error)]
+pub enum LimitError {
+    /// The request body exceeds the maximum size.
+    #[error("Request body exceeds the {limit} byte limit")]
+    PayloadTooLarge {
+        /// Configured maximum body size in bytes.
+        limit: usize,
+    },
+    /// The request body could not be read.
+    #[error("Failed to read request body: {0}")]
+    Unreadable(String),
+    /// The request body is not valid JSON.
+    #[error("Malformed request body: {0}")]
+    Malformed(#[from] serde_json::Error),
+}
+
+impl LimitError {
+    /// Returns the HTTP status code to answer with.
+    #[must_use]
+    pub const fn status_code(&self) -> StatusCode {
+        match self {
+            Self::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Unreadable(_) | Self::Malformed(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    /// Returns the [`PaymentProblem`] response body, `{ error, message }`.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self.as_payment_problem()).unwrap_or_default()
+    }
+}
+
+impl AsPaymentProblem for LimitError {
+    fn as_payment_problem(&self) -> PaymentProblem {
+        PaymentProblem::new(ErrorReason::InvalidFormat, self.to_string())
+    }
+}
+
+impl IntoResponse for LimitError {
+    fn into_response(self) -> Response {
+        #[cfg(feature = "telemetry")]
+        tracing::warn!(error = %self, "Rejected facilitator request body");
+        Response::builder()
+            .status(self.status_code())
+            .header("Content-Type", "application/json")
+            .body(Body::from(self.to_json().to_string()))
+            .expect("Fail to construct response")
+    }
+}
+
+/// Returns `true` if `error` was produced by a [`LimitedFacilitator`] timeout.
+///
+/// HTTP handlers should answer such errors with `504 Gateway Timeout`.
+#[must_use]
+pub fn is_timeout(error: &FacilitatorError) -> bool {
+    matches!(error, FacilitatorError::Aborted { reason, .. } if reason == TIMEOUT_REASON)
+}
+
+/// A [`Facilitator`] wrapper that bounds the processing time of each request.
+///
+/// When a verify or settle call exceeds the timeout, the in-flight future,
+/// including any pending RPC call, is dropped and a
+/// [`FacilitatorError::Aborted`] with reason [`TIMEOUT_REASON`] is returned.
+#[derive(Debug, Clone)]
+pub struct LimitedFacilitator<F> {
+    inner: F,
+    timeout: Duration,
+}
+
+impl<F> LimitedFacilitator<F> {
+    /// Wraps `inner`, aborting requests that run longer than `timeout`.
+    pub const fn new(inner: F, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+
+    /// Returns a reference to the wrapped facilitator.
+    pub const fn inner(&self) -> &F {
+        &self.inner
+    }
+}
+
+/// Runs `future`, aborting it if it does not complete within `timeout`.
+async fn with_timeout<T>(
+    timeout: Duration,
+    operation: &'static str,
+    network: String,
+    future: BoxFuture<'_, Result<T, FacilitatorError>>,
+) -> Result<T, FacilitatorError> {
+    tokio::time::timeout(timeout, future)
+        .await
+        .unwrap_or_else(|_| {
+            #[cfg(feature = "telemetry")]
+            tracing::warn!(
+                operation,
+                network = %network,
+                timeout = ?timeout,
+                "Abandoned facilitator request after timeout"
+            );
+            Err(FacilitatorError::Aborted {
+                reason: TIMEOUT_REASON.to_owned(),
+                message: format!("{operation} on {network} did not complete within {timeout:?}"),
+            })
+        })
+}
+
+impl<F: Facilitator> Facilitator for LimitedFacilitator<F> {
+    fn verify(
+        &self,
+        request: proto::VerifyRequest,
+    ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+        let network = request.network().to_owned();
+        Box::pin(with_timeout(
+            self.timeout,
+            "verify",
+            network,
+            self.inner.verify(request),
+        ))
+    }
+
+    fn settle(
+        &self,
+        request: proto::SettleRequest,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        let network = request.network().to_owned();
+        Box::pin(with_timeout(
+            self.timeout,
+            "settle",
+            network,
+            self.inner.settle(request),
+        ))
+    }
+
+    fn settle_verified(
+        &self,
+        request: proto::SettleRequest,
+        token: String,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        let network = request.network().to_owned();
+        let settle = self.inner.settle_verified(request, token);
+        Box::pin(with_timeout(self.timeout, "settle", network, settle))
+    }
+
+    fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+        self.inner.supported()
+    }
+
+    fn pre_validate(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<(), proto::PaymentVerificationError> {
+        self.inner.pre_validate(request)
+    }
+}
+
+/// Serializes a [`Duration`] as whole seconds.
+mod duration_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(value.as_secs())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use http_body::Frame;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Facilitator whose verify call never completes in time.
+    struct SlowFacilitator;
+
+    impl Facilitator for SlowFacilitator {
+        fn verify(
+            &self,
+            _request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_mins(1)).await;
+                Ok(proto::VerifyResponse::valid("0xpayer".into()))
+            })
+        }
+
+        fn settle(
+            &self,
+            _request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            Box::pin(async { Err(FacilitatorError::OnchainFailure("unreachable".into())) })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Err(FacilitatorError::OnchainFailure("unreachable".into())) })
+        }
+    }
+
+    /// Body streamed in chunks without a declared length.
+    struct Chunked(Vec<&'static [u8]>);
+
+    impl HttpBody for Chunked {
+        type Data = &'static [u8];
+        type Error = std::convert::Infallible;
+
+        fn poll_frame(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+            let chunk = (!self.0.is_empty()).then(|| self.0.remove(0));
+            Poll::Ready(chunk.map(|chunk| Ok(Frame::data(chunk))))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_is_rejected() {
+        let limits = RequestLimits {
+            max_body_size: 16,
+            ..RequestLimits::default()
+        };
+        let body = json!({ "paymentPayload": "x".repeat(32) }).to_string();
+
+        let err = limits
+            .parse_body::<proto::VerifyRequest, _>(Body::from(body.clone()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LimitError::PayloadTooLarge { limit: 16 }));
+        assert_eq!(err.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let streamed = Chunked(vec![b"{\"paymentPayload\": ", b"\"xxxxxxxxxxxxxxxx\"}"]);
+        let err = limits
+            .parse_body::<proto::VerifyRequest, _>(streamed)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LimitError::PayloadTooLarge { limit: 16 }));
+
+        assert!(
+            RequestLimits::default()
+                .parse_body::<proto::VerifyRequest, _>(Body::from(body))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_body_is_structured_400() {
+        let err = RequestLimits::default()
+            .parse_body::<proto::VerifyRequest, _>(Body::from("{\"paymentPayload\": "))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LimitError::Malformed(_)));
+
+        let body = err.to_json();
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "invalid_format");
+        assert!(
+            body["message"]
+                .as_str()
+                .unwrap()
+                .starts_with("Malformed request body")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slow_verify_times_out() {
+        let facilitator = LimitedFacilitator::new(SlowFacilitator, Duration::from_millis(20));
+        let request = proto::VerifyRequest::from(json!({
+            "paymentRequirements": { "network": "eip155:8453" }
+        }));
+
+        let err = facilitator.verify(request).await.unwrap_err();
+        assert!(is_timeout(&err));
+    }
+}
@@ -8,33 +8,65 @@
 //!
 //! See [`X402Middleware`] for full configuration options.
 //! For low-level interaction with the facilitator, see [`facilitator::FacilitatorClient`].
+//! To route payments across several facilitators, see [`FacilitatorSet`].
+//! For request size and timeout limits when hosting a facilitator, see [`limits`].
+//! For the HTTP status codes of facilitator errors, see [`problem`].
+//! To update facilitator payer screening at runtime, see [`screening::ScreeningAdmin`].
+//! For W3C trace context propagation to the facilitator, see `trace` (`telemetry` feature).
 //!
 //! ## Configuration Notes
 //!
 //! - **[`X402Middleware::with_price_tag`]** sets the assets and amounts accepted for payment (static pricing).
 //! - **[`X402Middleware::with_dynamic_price`]** sets a callback for dynamic pricing based on request context.
+//! - **[`X402Middleware::with_price_source`]** accepts any [`PriceTagSource`], e.g. [`OraclePricedTags`]
+//!   to charge a USD price in assets priced by a live exchange rate.
 //! - **[`X402Middleware::with_base_url`]** sets the base URL for computing full resource URLs.
 //!   If not set, defaults to `http://localhost/` (avoid in production).
 //! - **[`X402Middleware::with_supported_cache_ttl`]** configures the TTL for caching facilitator capabilities.
 //! - **[`X402Middleware::with_facilitator_timeout`]** sets a per-request timeout for facilitator HTTP calls.
+//! - **[`X402Middleware::with_verify_timeout`]** and **[`X402Middleware::with_settle_timeout`]** give verify
+//!   and settle separate budgets, settle usually needing more as it waits for the chain.
 //! - **[`X402LayerBuilder::with_description`]** is optional but helps the payer understand what is being paid for.
 //! - **[`X402LayerBuilder::with_mime_type`]** sets the MIME type of the protected resource (default: `application/json`).
 //! - **[`X402LayerBuilder::with_resource`]** explicitly sets the full URI of the protected resource.
+//! - **[`X402LayerBuilder::with_strict_base64`]** rejects payment headers not encoded with standard base64.
+//! - **[`X402LayerBuilder::with_replay_store`]** rejects replayed payment headers with `409 Conflict`.
+//! - **[`X402LayerBuilder::with_extra_402_headers`]** adds custom headers, e.g. a docs `Link`, to 402 responses.
+//! - **[`X402LayerBuilder::with_settle_after_body`]** streams the response body before settling, sending
+//!   `Payment-Response` as a trailer.
+//! - **[`X402LayerBuilder::with_split`]** requires every price tag to be paid, splitting the price across
+//!   several recipients.
+//! - **[`X402LayerBuilder::with_payment_header`]** and **[`X402LayerBuilder::with_payment_response_header`]**
+//!   rename the payment headers for proxies that strip the standard ones (breaks standard clients).
 
+mod body;
 pub mod facilitator;
+pub mod facilitator_set;
+#[cfg(test)]
+mod fixtures;
 pub mod layer;
+pub mod limits;
 pub mod paygate;
 pub mod pricing;
+pub mod problem;
+pub mod replay;
+pub mod screening;
+#[cfg(feature = "telemetry")]
+pub mod trace;
 
+pub use facilitator_set::FacilitatorSet;
 pub use layer::{X402LayerBuilder, X402Middleware};
-pub use pricing::{DynamicPriceTags, PriceTagSource, StaticPriceTags};
+pub use paygate::{PaymentHeaderNames, SelectedPayment};
+pub use pricing::{DynamicPriceTags, OraclePricedTags, PriceTagSource, StaticPriceTags};
+pub use replay::{InMemoryReplayStore, ReplayCheck, ReplayStore};
+pub use screening::ScreeningAdmin;
 
 /// Common verification errors shared between protocol versions.
 #[derive(Debug, thiserror::Error)]
 pub enum VerificationError {
     /// Required payment header is missing.
     #[error("{0} header is required")]
-    PaymentHeaderRequired(&'static str),
+    PaymentHeaderRequired(String),
     /// Payment header is present but malformed.
     #[error("Invalid or malformed payment header")]
     InvalidPaymentHeader,
@@ -52,7 +84,22 @@ pub enum PaygateError {
     /// Payment verification failed.
     #[error(transparent)]
     Verification(#[from] VerificationError),
+    /// The payment header was already submitted to this server.
+    #[error("Payment has already been submitted")]
+    Replayed,
+    /// The replay store is full, so the payment cannot be checked for replay.
+    #[error("Too many pending payments, retry later")]
+    ReplayStoreFull,
     /// On-chain settlement failed.
     #[error("Settlement failed: {0}")]
     Settlement(String),
+    /// A part of a split payment failed to settle after earlier parts were
+    /// settled. Those settlements stand.
+    #[error("Settlement partially failed: {reason}")]
+    PartialSettlement {
+        /// `Payment-Response` value listing the parts that were settled.
+        settled: http::HeaderValue,
+        /// Why the failing part was not settled.
+        reason: String,
+    },
 }
@@ -6,22 +6,26 @@
 
 use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum_core::body::Body;
 use axum_core::extract::Request;
 use axum_core::response::{IntoResponse, Response};
-use http::{HeaderMap, HeaderValue, StatusCode};
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
 use r402::facilitator::Facilitator;
 use r402::proto;
-use r402::proto::Base64Bytes;
+use r402::proto::split::{SPLIT_EXTENSION, SplitPaymentPayload, split_info};
 use r402::proto::v2;
-use serde_json::json;
+use r402::proto::{Base64Bytes, ErrorReason, PaymentProblem};
 use tower::Service;
 #[cfg(feature = "telemetry")]
 use tracing::{Instrument, instrument};
 use url::Url;
 
+use super::body::{SettleFuture, SettlingBody};
+use super::replay::{ReplayCheck, ReplayStore};
 use super::{PaygateError, VerificationError};
+use crate::headers::{PAYMENT_REQUIRED_HEADER, decode_json_header, encode_json_header};
 
 /// Builder for resource information that can be used with both V1 and V2 protocols.
 #[derive(Debug, Clone)]
@@ -47,8 +51,9 @@ fn default() -> Self {
 impl ResourceInfoBuilder {
     /// Determines the resource URL (static or dynamic).
     ///
-    /// If `url` is set, returns it directly. Otherwise, constructs a URL by combining
-    /// the base URL with the request URI's path and query.
+    /// If `url` is set, returns it directly. Otherwise, constructs a URL by appending
+    /// the request URI's path and query to the base URL, keeping any path prefix
+    /// the base URL has (e.g. `/api` when mounted behind a reverse proxy).
     ///
     /// # Panics
     ///
@@ -56,7 +61,7 @@ impl ResourceInfoBuilder {
     #[allow(clippy::unwrap_used)]
     pub fn as_resource_info(&self, base_url: Option<&Url>, req: &Request) -> v2::ResourceInfo {
         let url = self.url.clone().unwrap_or_else(|| {
-            let mut url = base_url.cloned().unwrap_or_else(|| {
+            let url = base_url.cloned().unwrap_or_else(|| {
                 let host = req.headers().get("host").and_then(|h| h.to_str().ok()).unwrap_or("localhost");
                 let origin = format!("http://{host}");
                 let url = Url::parse(&origin).unwrap_or_else(|_| Url::parse("http://localhost").unwrap());
@@ -67,9 +72,7 @@ pub fn as_resource_info(&self, base_url: Option<&Url>, req: &Request) -> v2::Res
                 url
             });
             let request_uri = req.uri();
-            url.set_path(request_uri.path());
-            url.set_query(request_uri.query());
-            url.to_string()
+            join_resource_url(url, request_uri.path(), request_uri.query()).to_string()
         });
         v2::ResourceInfo {
             description: self.description.clone(),
@@ -79,6 +82,70 @@ pub fn as_resource_info(&self, base_url: Option<&Url>, req: &Request) -> v2::Res
     }
 }
 
+/// Appends a request path and query to `base`, keeping its path prefix.
+///
+/// Characters not allowed in a URL are percent-encoded; existing escapes are
+/// kept as-is.
+fn join_resource_url(mut base: Url, path: &str, query: Option<&str>) -> Url {
+    let prefix = base.path().trim_end_matches('/');
+    let path = path.strip_prefix('/').unwrap_or(path);
+    let joined = format!("{prefix}/{path}");
+    base.set_path(&joined);
+    base.set_query(query);
+    base
+}
+
+/// The payment option a client paid with.
+///
+/// Inserted into the request extensions after a successful verification, so
+/// the protected handler can tell which of several offered price tags was
+/// chosen. Facilitator hooks see the same requirements through
+/// [`VerifyRequest::payment_requirements`](proto::VerifyRequest::payment_requirements).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectedPayment(pub v2::PaymentRequirements);
+
+/// Names of the headers a [`Paygate`] reads the payment from and writes the
+/// settlement to.
+///
+/// Defaults to the standard `Payment-Signature` and `Payment-Response`.
+/// Override them only when infrastructure in front of the server strips or
+/// renames these headers: standard x402 clients send and read the standard
+/// names, so they can only pay through a gate using other names if a proxy
+/// translates the headers for them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentHeaderNames {
+    /// Request header carrying the signed payment payload.
+    pub payment: HeaderName,
+    /// Response header (or trailer) carrying the settlement result.
+    pub response: HeaderName,
+}
+
+impl PaymentHeaderNames {
+    /// Returns the standard `Payment-Signature` and `Payment-Response` names.
+    #[must_use]
+    pub const fn standard() -> Self {
+        Self {
+            payment: HeaderName::from_static("payment-signature"),
+            response: HeaderName::from_static("payment-response"),
+        }
+    }
+
+    /// Returns the V1 `X-PAYMENT` and `X-PAYMENT-RESPONSE` names.
+    #[must_use]
+    pub const fn v1() -> Self {
+        Self {
+            payment: HeaderName::from_static("x-payment"),
+            response: HeaderName::from_static("x-payment-response"),
+        }
+    }
+}
+
+impl Default for PaymentHeaderNames {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
 /// V2-only payment gate for enforcing x402 payments.
 ///
 /// Handles the full payment lifecycle: header extraction, verification,
@@ -94,6 +161,13 @@ pub struct Paygate<TFacilitator> {
     pub(crate) facilitator: TFacilitator,
     pub(crate) accepts: Arc<Vec<v2::PriceTag>>,
     pub(crate) resource: v2::ResourceInfo,
+    pub(crate) strict_base64: bool,
+    pub(crate) extension_info: Arc<proto::Extensions>,
+    pub(crate) replay_store: Option<Arc<dyn ReplayStore>>,
+    pub(crate) extra_402_headers: Arc<HeaderMap>,
+    pub(crate) settle_after_body: bool,
+    pub(crate) header_names: PaymentHeaderNames,
+    pub(crate) split: bool,
 }
 
 /// Builder for constructing a [`Paygate`] with validated configuration.
@@ -111,6 +185,13 @@ pub struct PaygateBuilder<TFacilitator> {
     facilitator: TFacilitator,
     accepts: Vec<v2::PriceTag>,
     resource: Option<v2::ResourceInfo>,
+    strict_base64: bool,
+    required_extensions: Vec<(String, serde_json::Value)>,
+    replay_store: Option<Arc<dyn ReplayStore>>,
+    extra_402_headers: Option<Arc<HeaderMap>>,
+    settle_after_body: bool,
+    header_names: PaymentHeaderNames,
+    split: bool,
 }
 
 impl<TFacilitator> Paygate<TFacilitator> {
@@ -120,6 +201,13 @@ pub const fn builder(facilitator: TFacilitator) -> PaygateBuilder<TFacilitator>
             facilitator,
             accepts: Vec::new(),
             resource: None,
+            strict_base64: false,
+            required_extensions: Vec::new(),
+            replay_store: None,
+            extra_402_headers: None,
+            settle_after_body: false,
+            header_names: PaymentHeaderNames::standard(),
+            split: false,
         }
     }
 
@@ -132,6 +220,34 @@ pub fn accepts(&self) -> &[v2::PriceTag] {
     pub const fn resource(&self) -> &v2::ResourceInfo {
         &self.resource
     }
+
+    /// Returns the info advertised for each required extension, keyed by id.
+    ///
+    /// The ids themselves are listed in the `requiredExtensions` of every
+    /// accepted price tag.
+    pub fn extension_info(&self) -> &proto::Extensions {
+        &self.extension_info
+    }
+
+    /// Returns the headers added to every 402 response.
+    pub fn extra_402_headers(&self) -> &HeaderMap {
+        &self.extra_402_headers
+    }
+
+    /// Returns the names of the payment and settlement headers.
+    pub const fn header_names(&self) -> &PaymentHeaderNames {
+        &self.header_names
+    }
+
+    /// Returns the extensions advertised in 402 responses: the required
+    /// ones, plus the split marker for a split gate.
+    fn advertised_extensions(&self) -> proto::Extensions {
+        let mut extensions = (*self.extension_info).clone();
+        if self.split {
+            extensions.insert(SPLIT_EXTENSION.to_owned(), split_info(self.accepts.len()));
+        }
+        extensions
+    }
 }
 
 impl<TFacilitator> PaygateBuilder<TFacilitator> {
@@ -156,10 +272,120 @@ pub fn resource(mut self, resource: v2::ResourceInfo) -> Self {
         self
     }
 
+    /// Only accepts payment headers encoded with the standard base64 alphabet.
+    ///
+    /// By default, URL-safe base64 is tolerated as well for cross-SDK interop.
+    #[must_use]
+    pub const fn strict_base64(mut self, strict: bool) -> Self {
+        self.strict_base64 = strict;
+        self
+    }
+
+    /// Requires payment payloads to carry the extension `id`.
+    ///
+    /// `id` is added to the `requiredExtensions` of every accepted price tag,
+    /// and `info` is advertised under `id` in the `extensions` of 402
+    /// responses so clients know what to send, typically the extension's
+    /// version or schema. Payloads without the extension are rejected before
+    /// verification, and again by the facilitator.
+    #[must_use]
+    pub fn require_extension(mut self, id: impl Into<String>, info: serde_json::Value) -> Self {
+        self.required_extensions.push((id.into(), info));
+        self
+    }
+
+    /// Requires every extension in `extensions`, keyed by id.
+    #[must_use]
+    pub fn require_extensions(
+        mut self,
+        extensions: impl IntoIterator<Item = (String, serde_json::Value)>,
+    ) -> Self {
+        self.required_extensions.extend(extensions);
+        self
+    }
+
+    /// Rejects payments whose id was already recorded in `store`.
+    ///
+    /// Each payment id is recorded for the selected option's
+    /// `maxTimeoutSeconds`, the validity window of its authorization.
+    #[must_use]
+    pub fn replay_store(mut self, store: Arc<dyn ReplayStore>) -> Self {
+        self.replay_store = Some(store);
+        self
+    }
+
+    /// Adds `headers` to every 402 response, e.g. a `Link` to documentation.
+    ///
+    /// Headers the response already carries, such as `Payment-Required` and
+    /// `Content-Type`, are never overwritten.
+    #[must_use]
+    pub fn extra_402_headers(mut self, headers: Arc<HeaderMap>) -> Self {
+        self.extra_402_headers = Some(headers);
+        self
+    }
+
+    /// Settles once the response body has been streamed, not before the
+    /// response is returned.
+    ///
+    /// The body is passed through without buffering, which suits downloads
+    /// and server-sent events. The `Payment-Response` header is then sent
+    /// as an HTTP trailer. If the body fails mid-stream, the payment is not
+    /// settled. If settlement fails, the body ends with an error instead of
+    /// the trailer.
+    #[must_use]
+    pub const fn settle_after_body(mut self, enabled: bool) -> Self {
+        self.settle_after_body = enabled;
+        self
+    }
+
+    /// Requires a payment to every accepted price tag instead of any one of
+    /// them, e.g. a platform fee and the seller's price.
+    ///
+    /// Clients send one payment per price tag in a single header, see
+    /// [`r402::proto::split`]. Each payment is verified before the handler
+    /// runs and settled after it, one after another.
+    ///
+    /// Settlement is not atomic: if a part fails after earlier parts landed
+    /// on-chain, those settlements stand. The client then gets the error
+    /// response with a `Payment-Response` listing the settled parts only
+    /// (see [`PaygateError::PartialSettlement`]); with
+    /// [`settle_after_body`](Self::settle_after_body), that list is sent as
+    /// the trailer. A list shorter than the price tags means the payment
+    /// was not completed.
+    #[must_use]
+    pub const fn split(mut self, enabled: bool) -> Self {
+        self.split = enabled;
+        self
+    }
+
+    /// Reads the payment from and writes the settlement to non-standard
+    /// headers. See [`PaymentHeaderNames`] for the interoperability caveat.
+    #[must_use]
+    pub fn header_names(mut self, names: PaymentHeaderNames) -> Self {
+        self.header_names = names;
+        self
+    }
+
     /// Consumes the builder and produces a configured [`Paygate`].
     ///
     /// Uses empty resource info if none was provided.
-    pub fn build(self) -> Paygate<TFacilitator> {
+    pub fn build(mut self) -> Paygate<TFacilitator> {
+        let extension_info: proto::Extensions = self.required_extensions.into_iter().collect();
+        if !extension_info.is_empty() {
+            let mut ids: Vec<_> = extension_info.keys().cloned().collect();
+            ids.sort_unstable();
+            for price_tag in &mut self.accepts {
+                let required = price_tag
+                    .requirements
+                    .required_extensions
+                    .get_or_insert_with(Vec::new);
+                for id in &ids {
+                    if !required.contains(id) {
+                        required.push(id.clone());
+                    }
+                }
+            }
+        }
         Paygate {
             facilitator: self.facilitator,
             accepts: Arc::new(self.accepts),
@@ -168,16 +394,39 @@ pub fn build(self) -> Paygate<TFacilitator> {
                 mime_type: "application/json".to_owned(),
                 url: String::new(),
             }),
+            strict_base64: self.strict_base64,
+            extension_info: Arc::new(extension_info),
+            replay_store: self.replay_store,
+            extra_402_headers: self.extra_402_headers.unwrap_or_default(),
+            settle_after_body: self.settle_after_body,
+            header_names: self.header_names,
+            split: self.split,
         }
     }
 }
 
-/// The V2 payment header name.
-const PAYMENT_HEADER_NAME: &str = "Payment-Signature";
-
 /// The V2 payment payload type.
 type V2PaymentPayload = v2::PaymentPayload<v2::PaymentRequirements, serde_json::Value>;
 
+/// Outcome of verifying a payment and running the protected handler.
+enum Handled {
+    /// The handler failed; its response is returned without settlement.
+    Unsettled(Response),
+    /// The handler succeeded; the payments remain to be settled.
+    Pending(Response, Vec<Verified>),
+}
+
+/// A verified payment awaiting settlement.
+struct Verified {
+    request: proto::VerifyRequest,
+    /// Token of the verify response, letting the facilitator skip
+    /// re-verification when it settles.
+    token: Option<String>,
+    /// Payment id reserved in the replay store, released unless the
+    /// payment settles.
+    payment_id: Option<String>,
+}
+
 impl<TFacilitator> Paygate<TFacilitator> {
     /// Calls the inner service with proper telemetry instrumentation.
     async fn call_inner<
@@ -209,38 +458,6 @@ impl<TFacilitator> Paygate<TFacilitator>
 where
     TFacilitator: Facilitator + Sync,
 {
-    /// Handles an incoming request, processing payment if required.
-    ///
-    /// Returns 402 response if payment fails.
-    /// Otherwise, returns the response from the inner service.
-    ///
-    /// # Errors
-    ///
-    /// This method is infallible (`Infallible` error type).
-    #[cfg_attr(
-        feature = "telemetry",
-        instrument(name = "x402.handle_request", skip_all)
-    )]
-    pub async fn handle_request<
-        ReqBody,
-        ResBody,
-        S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
-    >(
-        self,
-        inner: S,
-        req: http::Request<ReqBody>,
-    ) -> Result<Response, Infallible>
-    where
-        S::Response: IntoResponse,
-        S::Error: IntoResponse,
-        S::Future: Send,
-    {
-        match self.handle_request_fallible(inner, req).await {
-            Ok(response) => Ok(response),
-            Err(err) => Ok(error_into_response(err, &self.accepts, &self.resource)),
-        }
-    }
-
     /// Enriches price tags with facilitator capabilities (e.g., fee payer address).
     pub async fn enrich_accepts(&mut self) {
         let capabilities = self.facilitator.supported().await.unwrap_or_default();
@@ -278,38 +495,188 @@ pub async fn handle_request_fallible<
         S::Error: IntoResponse,
         S::Future: Send,
     {
-        let header = extract_payment_header(req.headers(), PAYMENT_HEADER_NAME).ok_or(
-            VerificationError::PaymentHeaderRequired(PAYMENT_HEADER_NAME),
-        )?;
-        let payment_payload = extract_payment_payload::<V2PaymentPayload>(header)
-            .ok_or(VerificationError::InvalidPaymentHeader)?;
-
-        let verify_request = make_verify_request(payment_payload, &self.accepts)?;
-
-        // Step 1: Verify the payment before executing the request.
-        let verify_response = self
-            .facilitator
-            .verify(verify_request.clone())
-            .await
-            .map_err(|e| VerificationError::VerificationFailed(format!("{e}")))?;
+        let handled = self.verify_and_call(inner, req).await?;
+        self.settle_handled(handled).await
+    }
+
+    /// Verifies the payment and runs the inner handler, leaving settlement to
+    /// the caller.
+    async fn verify_and_call<
+        ReqBody,
+        ResBody,
+        S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    >(
+        &self,
+        inner: S,
+        mut req: http::Request<ReqBody>,
+    ) -> Result<Handled, PaygateError>
+    where
+        S::Response: IntoResponse,
+        S::Error: IntoResponse,
+        S::Future: Send,
+    {
+        let payment_header = &self.header_names.payment;
+        let header = extract_payment_header(req.headers(), payment_header).ok_or_else(|| {
+            VerificationError::PaymentHeaderRequired(payment_header.as_str().to_owned())
+        })?;
+        let payments = if self.split {
+            let split = decode_json_header::<SplitPaymentPayload>(header, self.strict_base64)
+                .ok_or(VerificationError::InvalidPaymentHeader)?;
+            let parts: Vec<_> = self
+                .accepts
+                .iter()
+                .map(|pt| pt.requirements.clone())
+                .collect();
+            split
+                .into_parts(&parts)
+                .map_err(|e| VerificationError::VerificationFailed(e.to_string()))?
+        } else {
+            vec![
+                decode_json_header::<V2PaymentPayload>(header, self.strict_base64)
+                    .ok_or(VerificationError::InvalidPaymentHeader)?,
+            ]
+        };
 
-        validate_verify_response(verify_response)?;
+        // Step 1: Verify the payments before executing the request.
+        let mut verified = Vec::with_capacity(payments.len());
+        let mut selected = None;
+        for payment_payload in payments {
+            match self.reserve_and_verify(payment_payload).await {
+                Ok((payment, requirements)) => {
+                    verified.push(payment);
+                    selected = Some(requirements);
+                }
+                Err(err) => {
+                    self.release(verified.into_iter().filter_map(|v| v.payment_id))
+                        .await;
+                    return Err(err);
+                }
+            }
+        }
+        if !self.split
+            && let Some(selected) = selected
+        {
+            req.extensions_mut().insert(SelectedPayment(selected));
+        }
 
         // Step 2: Execute the inner handler.
         let response = match Self::call_inner(inner, req).await {
             Ok(response) => response,
-            Err(err) => return Ok(err.into_response()),
+            Err(err) => {
+                self.release(verified.into_iter().filter_map(|v| v.payment_id))
+                    .await;
+                return Ok(Handled::Unsettled(err.into_response()));
+            }
         };
 
         // Step 3: Skip settlement if the handler returned an error response.
         if response.status().is_client_error() || response.status().is_server_error() {
-            return Ok(response.into_response());
+            self.release(verified.into_iter().filter_map(|v| v.payment_id))
+                .await;
+            return Ok(Handled::Unsettled(response.into_response()));
+        }
+        Ok(Handled::Pending(response.into_response(), verified))
+    }
+
+    /// Reserves the payment id of a payment and verifies it, releasing the
+    /// id again if verification fails.
+    async fn reserve_and_verify(
+        &self,
+        payment_payload: V2PaymentPayload,
+    ) -> Result<(Verified, v2::PaymentRequirements), PaygateError> {
+        let (request, requirements) = make_verify_request(payment_payload, &self.accepts)?;
+        let payment_id = self.check_replay(&request, &requirements).await?;
+        let verify_response = match self.facilitator.verify(request.clone()).await {
+            Ok(response) => validate_verify_response(response),
+            Err(e) => Err(VerificationError::VerificationFailed(format!("{e}"))),
+        };
+        match verify_response {
+            Ok(token) => {
+                let verified = Verified {
+                    request,
+                    token,
+                    payment_id,
+                };
+                Ok((verified, requirements))
+            }
+            Err(err) => {
+                self.release(payment_id).await;
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Settles the payment of a successful response, before returning it.
+    async fn settle_handled(&self, handled: Handled) -> Result<Response, PaygateError> {
+        match handled {
+            Handled::Unsettled(response) => Ok(response),
+            Handled::Pending(mut response, verified) => {
+                // Step 4: Settle the payment on-chain.
+                let header_value = self.settle(verified).await?;
+                response
+                    .headers_mut()
+                    .insert(self.header_names.response.clone(), header_value);
+                Ok(response)
+            }
         }
+    }
 
-        // Step 4: Settle the payment on-chain.
-        let settlement = self
-            .facilitator
-            .settle(verify_request.into())
+    /// Settles verified payments, returning the `Payment-Response` header value.
+    ///
+    /// A single payment is answered with its settlement, a split payment with
+    /// the array of its settlements, in the order of the price tags.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PaygateError::PartialSettlement`] if a part of a split
+    /// payment fails after others were settled.
+    async fn settle(&self, verified: Vec<Verified>) -> Result<HeaderValue, PaygateError> {
+        let mut settlements = Vec::with_capacity(verified.len());
+        let mut pending = verified.into_iter();
+        while let Some(verified) = pending.next() {
+            let payment_id = verified.payment_id.clone();
+            let err = match self.settle_payment(verified).await {
+                Ok(settlement) => {
+                    settlements.push(settlement);
+                    continue;
+                }
+                Err(err) => err,
+            };
+            // The failed payment and those after it were not settled.
+            let unsettled = pending.map(|verified| verified.payment_id);
+            self.release(std::iter::once(payment_id).chain(unsettled).flatten())
+                .await;
+            return match err {
+                PaygateError::Settlement(reason) if !settlements.is_empty() => {
+                    Err(PaygateError::PartialSettlement {
+                        settled: settlements_to_header(&settlements)?,
+                        reason,
+                    })
+                }
+                err => Err(err),
+            };
+        }
+        if !self.split
+            && let Some(settlement) = settlements.pop()
+        {
+            return settlement_to_header(settlement);
+        }
+        settlements_to_header(&settlements)
+    }
+
+    /// Settles a single verified payment.
+    ///
+    /// The verification token is presented if the facilitator issued one.
+    async fn settle_payment(
+        &self,
+        verified: Verified,
+    ) -> Result<proto::SettleResponse, PaygateError> {
+        let request = verified.request.into();
+        let settlement = match verified.token {
+            Some(token) => self.facilitator.settle_verified(request, token),
+            None => self.facilitator.settle(request),
+        };
+        let settlement = settlement
             .await
             .map_err(|e| PaygateError::Settlement(format!("{e}")))?;
 
@@ -321,27 +688,148 @@ pub async fn handle_request_fallible<
             return Err(PaygateError::Settlement(detail.to_owned()));
         }
 
-        let header_value = settlement_to_header(settlement)?;
-
-        let mut res = response;
-        res.headers_mut().insert("Payment-Response", header_value);
-        Ok(res.into_response())
+        Ok(settlement)
     }
 }
 
-/// Extracts the payment header value from the header map.
-fn extract_payment_header<'a>(header_map: &'a HeaderMap, header_name: &'a str) -> Option<&'a [u8]> {
-    header_map.get(header_name).map(HeaderValue::as_bytes)
+impl<TFacilitator> Paygate<TFacilitator>
+where
+    TFacilitator: Facilitator + Sync + 'static,
+{
+    /// Handles an incoming request, processing payment if required.
+    ///
+    /// Returns 402 response if payment fails.
+    /// Otherwise, returns the response from the inner service, with the
+    /// payment settled either before the response is returned or, with
+    /// [`PaygateBuilder::settle_after_body`], once its body has been sent.
+    ///
+    /// # Errors
+    ///
+    /// This method is infallible (`Infallible` error type).
+    #[cfg_attr(
+        feature = "telemetry",
+        instrument(name = "x402.handle_request", skip_all)
+    )]
+    pub async fn handle_request<
+        ReqBody,
+        ResBody,
+        S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    >(
+        self,
+        inner: S,
+        req: http::Request<ReqBody>,
+    ) -> Result<Response, Infallible>
+    where
+        S::Response: IntoResponse,
+        S::Error: IntoResponse,
+        S::Future: Send,
+    {
+        let result = match self.verify_and_call(inner, req).await {
+            Ok(Handled::Pending(response, verified)) if self.settle_after_body => {
+                return Ok(self.stream_then_settle(response, verified));
+            }
+            Ok(handled) => self.settle_handled(handled).await,
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(response) => Ok(response),
+            Err(err) => {
+                let settled = match &err {
+                    PaygateError::PartialSettlement { settled, .. } => Some(settled.clone()),
+                    _ => None,
+                };
+                let mut response = error_into_response(
+                    err,
+                    &self.accepts,
+                    &self.resource,
+                    &self.advertised_extensions(),
+                    &self.extra_402_headers,
+                );
+                if let Some(settled) = settled {
+                    response
+                        .headers_mut()
+                        .insert(self.header_names.response.clone(), settled);
+                }
+                Ok(response)
+            }
+        }
+    }
+
+    /// Streams the response body, settling the payment once it completes
+    /// and sending the `Payment-Response` header as a trailer.
+    fn stream_then_settle(self, response: Response, verified: Vec<Verified>) -> Response {
+        let (mut parts, body) = response.into_parts();
+        // Trailers require chunked encoding, so a known length is dropped.
+        parts.headers.remove(http::header::CONTENT_LENGTH);
+        let trailer = self.header_names.response.clone();
+        parts
+            .headers
+            .insert(http::header::TRAILER, HeaderValue::from(trailer.clone()));
+        let settle = async move { self.settle(verified).await };
+        // The body is polled outside the request's trace scope.
+        #[cfg(feature = "telemetry")]
+        let settle = super::trace::in_scope(super::trace::TraceContext::current(), settle);
+        let settle: SettleFuture = Box::pin(settle);
+        Response::from_parts(parts, Body::new(SettlingBody::new(body, trailer, settle)))
+    }
 }
 
-/// Extracts and deserializes the payment payload from base64-encoded header bytes.
-fn extract_payment_payload<T>(header_bytes: &[u8]) -> Option<T>
+impl<TFacilitator> Paygate<TFacilitator>
 where
-    T: serde::de::DeserializeOwned,
+    TFacilitator: Sync,
 {
-    let base64 = Base64Bytes::from(header_bytes).decode().ok()?;
-    let value = serde_json::from_slice(base64.as_ref()).ok()?;
-    Some(value)
+    /// Reserves the payment id, failing if it was already seen or cannot be
+    /// recorded, and returns the reserved id.
+    ///
+    /// Requests without a recognizable payment id are let through; the
+    /// facilitator still rejects them if they are invalid.
+    async fn check_replay(
+        &self,
+        verify_request: &proto::VerifyRequest,
+        selected: &v2::PaymentRequirements,
+    ) -> Result<Option<String>, PaygateError> {
+        let (Some(store), Some(payment_id)) = (&self.replay_store, verify_request.payment_id())
+        else {
+            return Ok(None);
+        };
+        let ttl = Duration::from_secs(selected.max_timeout_seconds);
+        match store.check_and_insert(&payment_id, ttl).await {
+            ReplayCheck::Fresh => Ok(Some(payment_id)),
+            ReplayCheck::Replayed => {
+                #[cfg(feature = "telemetry")]
+                tracing::warn!(payment_id = %payment_id, "Rejected replayed payment header");
+                Err(PaygateError::Replayed)
+            }
+            ReplayCheck::Full => {
+                #[cfg(feature = "telemetry")]
+                tracing::warn!(payment_id = %payment_id, "Replay store is full");
+                Err(PaygateError::ReplayStoreFull)
+            }
+        }
+    }
+
+    /// Releases the payment ids reserved for payments that will not settle,
+    /// so the client can retry them.
+    async fn release<I>(&self, payment_ids: I)
+    where
+        I: IntoIterator<Item = String>,
+        I::IntoIter: Send,
+    {
+        let Some(store) = &self.replay_store else {
+            return;
+        };
+        for payment_id in payment_ids {
+            store.release(&payment_id).await;
+        }
+    }
+}
+
+/// Extracts the payment header value from the header map.
+fn extract_payment_header<'a>(
+    header_map: &'a HeaderMap,
+    header_name: &HeaderName,
+) -> Option<&'a [u8]> {
+    header_map.get(header_name).map(HeaderValue::as_bytes)
 }
 
 /// Converts a [`proto::SettleResponse`] into an HTTP header value.
@@ -349,24 +837,47 @@ fn extract_payment_payload<T>(header_bytes: &[u8]) -> Option<T>
 /// Returns an error response if conversion fails.
 #[allow(clippy::needless_pass_by_value)] // settlement is consumed by serialization
 fn settlement_to_header(settlement: proto::SettleResponse) -> Result<HeaderValue, PaygateError> {
-    let json =
-        serde_json::to_vec(&settlement).map_err(|err| PaygateError::Settlement(err.to_string()))?;
-    let payment_header = Base64Bytes::encode(json);
+    let payment_header =
+        encode_json_header(&settlement).map_err(|err| PaygateError::Settlement(err.to_string()))?;
     HeaderValue::from_bytes(payment_header.as_ref())
         .map_err(|err| PaygateError::Settlement(err.to_string()))
 }
 
+/// Encodes the settlements of a split payment as an HTTP header value.
+fn settlements_to_header(
+    settlements: &[proto::SettleResponse],
+) -> Result<HeaderValue, PaygateError> {
+    let encoded = encode_json_header(&settlements)
+        .map_err(|err| PaygateError::Settlement(err.to_string()))?;
+    HeaderValue::from_bytes(encoded.as_ref())
+        .map_err(|err| PaygateError::Settlement(err.to_string()))
+}
+
 /// Constructs a V2 verify request from the payment payload and accepted requirements.
+///
+/// Also returns the offered requirements the payload was matched against.
 fn make_verify_request(
     payment_payload: V2PaymentPayload,
     accepts: &[v2::PriceTag],
-) -> Result<proto::VerifyRequest, VerificationError> {
+) -> Result<(proto::VerifyRequest, v2::PaymentRequirements), VerificationError> {
     let accepted = &payment_payload.accepted;
 
-    let selected = accepts
-        .iter()
-        .find(|price_tag| **price_tag == *accepted)
-        .ok_or(VerificationError::NoPaymentMatching)?;
+    let Some(selected) = accepts.iter().find(|price_tag| **price_tag == *accepted) else {
+        #[cfg(feature = "telemetry")]
+        for (index, price_tag) in accepts.iter().enumerate() {
+            let diff = price_tag.requirements.diff(accepted);
+            tracing::debug!(
+                index,
+                diff = %diff.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "),
+                "Accepted requirements do not match offered option"
+            );
+        }
+        return Err(VerificationError::NoPaymentMatching);
+    };
+    selected
+        .requirements
+        .check_required_extensions(payment_payload.extensions.as_ref())
+        .map_err(|e| VerificationError::VerificationFailed(e.to_string()))?;
 
     let verify_request = v2::VerifyRequest {
         x402_version: v2::V2,
@@ -377,15 +888,22 @@ fn make_verify_request(
     let json = serde_json::to_value(&verify_request)
         .map_err(|e| VerificationError::VerificationFailed(format!("{e}")))?;
 
-    Ok(proto::VerifyRequest::from(json))
+    Ok((
+        proto::VerifyRequest::from(json),
+        selected.requirements.clone(),
+    ))
 }
 
 /// Validates a verify response, rejecting invalid or unknown variants.
+///
+/// Returns the verification token of a valid response, if any.
 fn validate_verify_response(
     verify_response: proto::VerifyResponse,
-) -> Result<(), VerificationError> {
+) -> Result<Option<String>, VerificationError> {
     match verify_response {
-        proto::VerifyResponse::Valid { .. } => Ok(()),
+        proto::VerifyResponse::Valid {
+            verification_token, ..
+        } => Ok(verification_token),
         proto::VerifyResponse::Invalid { reason, .. } => {
             Err(VerificationError::VerificationFailed(reason))
         }
@@ -400,15 +918,17 @@ fn error_into_response(
     err: PaygateError,
     accepts: &[v2::PriceTag],
     resource: &v2::ResourceInfo,
+    extensions: &proto::Extensions,
+    extra_402_headers: &HeaderMap,
 ) -> Response {
-    match err {
+    let mut response = match err {
         PaygateError::Verification(err) => {
             let payment_required_response = v2::PaymentRequired {
                 error: Some(err.to_string()),
                 accepts: accepts.iter().map(|pt| pt.requirements.clone()).collect(),
                 x402_version: v2::V2,
                 resource: resource.clone(),
-                extensions: None,
+                extensions: (!extensions.is_empty()).then(|| extensions.clone()),
             };
             let payment_required_bytes =
                 serde_json::to_vec(&payment_required_response).expect("serialization failed");
@@ -418,22 +938,658 @@ fn error_into_response(
 
             Response::builder()
                 .status(StatusCode::PAYMENT_REQUIRED)
-                .header("Payment-Required", header_value)
+                .header(PAYMENT_REQUIRED_HEADER, header_value)
                 .header("Content-Type", "application/json")
                 .body(Body::from(payment_required_bytes))
                 .expect("Fail to construct response")
         }
-        PaygateError::Settlement(ref err) => {
+        PaygateError::Replayed => problem_response(
+            StatusCode::CONFLICT,
+            &PaymentProblem::new(ErrorReason::NonceAlreadyUsed, err.to_string()),
+        ),
+        PaygateError::ReplayStoreFull => problem_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &PaymentProblem::new(ErrorReason::UnexpectedError, err.to_string()),
+        ),
+        PaygateError::Settlement(_) | PaygateError::PartialSettlement { .. } => {
             #[cfg(feature = "telemetry")]
-            tracing::error!(details = %err, "Settlement failed");
-            let body = Body::from(
-                json!({ "error": "Settlement failed", "details": err.clone() }).to_string(),
-            );
-            Response::builder()
-                .status(StatusCode::PAYMENT_REQUIRED)
-                .header("Content-Type", "application/json")
-                .body(body)
-                .expect("Fail to construct response")
+            tracing::error!(error = %err, "Settlement failed");
+            problem_response(
+                StatusCode::PAYMENT_REQUIRED,
+                &PaymentProblem::new(ErrorReason::UnexpectedError, err.to_string()),
+            )
+        }
+    };
+    if response.status() == StatusCode::PAYMENT_REQUIRED {
+        append_missing_headers(response.headers_mut(), extra_402_headers);
+    }
+    response
+}
+
+/// Builds a JSON error response with a [`PaymentProblem`] body.
+fn problem_response(status: StatusCode, problem: &PaymentProblem) -> Response {
+    let body = serde_json::to_vec(problem).expect("serialization failed");
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))
+        .expect("Fail to construct response")
+}
+
+/// Appends the headers in `extra` whose names are not yet in `headers`.
+fn append_missing_headers(headers: &mut HeaderMap, extra: &HeaderMap) {
+    for name in extra.keys() {
+        if headers.contains_key(name) {
+            continue;
+        }
+        for value in extra.get_all(name) {
+            headers.append(name.clone(), value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::{Ready, poll_fn, ready};
+    use std::pin::Pin;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::{Context, Poll};
+
+    use http_body::{Body as HttpBody, Frame};
+
+    use r402::facilitator::{BoxFuture, FacilitatorError};
+    use r402::hooks::{FacilitatorHooks, HookedFacilitator, VerifyContext};
+    use r402::verification::VerificationTokens;
+    use serde_json::json;
+
+    use super::*;
+    use crate::headers::PAYMENT_RESPONSE_HEADER;
+    use crate::server::fixtures::{asset_tag, paid_request, price_tag};
+    use crate::server::replay::InMemoryReplayStore;
+
+    struct AcceptingFacilitator;
+
+    impl Facilitator for AcceptingFacilitator {
+        fn verify(
+            &self,
+            _request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::VerifyResponse::valid("0xpayer".into())) })
+        }
+
+        fn settle(
+            &self,
+            _request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            Box::pin(async {
+                Ok(proto::SettleResponse::Success {
+                    payer: "0xpayer".into(),
+                    transaction: "0xtx".into(),
+                    network: "eip155:8453".into(),
+                    extensions: None,
+                })
+            })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::SupportedResponse::default()) })
+        }
+    }
+
+    /// Records the requirements seen by `after_verify`.
+    struct RecordPaid(Arc<Mutex<Option<v2::PaymentRequirements>>>);
+
+    impl FacilitatorHooks for RecordPaid {
+        fn after_verify<'a>(
+            &'a self,
+            ctx: &'a VerifyContext,
+            _result: &'a proto::VerifyResponse,
+        ) -> BoxFuture<'a, ()> {
+            Box::pin(async move {
+                *self.0.lock().unwrap() = ctx.request.payment_requirements();
+            })
+        }
+    }
+
+    /// Inner service recording the [`SelectedPayment`] extension.
+    #[derive(Clone)]
+    struct RecordSelected(Arc<Mutex<Option<SelectedPayment>>>);
+
+    impl Service<http::Request<Body>> for RecordSelected {
+        type Response = http::Response<Body>;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+            *self.0.lock().unwrap() = req.extensions().get::<SelectedPayment>().cloned();
+            ready(Ok(http::Response::new(Body::empty())))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_paid_option_reaches_hook_and_handler() {
+        let usdc = price_tag();
+        let usdm = asset_tag("1000000000000000", "0xusdm");
+
+        let hooked = Arc::new(Mutex::new(None));
+        let facilitator =
+            HookedFacilitator::new(AcceptingFacilitator).with_hook(RecordPaid(Arc::clone(&hooked)));
+        let gate = Paygate::builder(facilitator)
+            .accepts([usdc, usdm.clone()])
+            .build();
+
+        let payload = V2PaymentPayload {
+            accepted: usdm.requirements.clone(),
+            payload: json!({}),
+            resource: None,
+            x402_version: v2::V2,
+            extensions: None,
+        };
+        let req = paid_request(&payload);
+
+        let selected = Arc::new(Mutex::new(None));
+        let response = gate
+            .handle_request_fallible(RecordSelected(Arc::clone(&selected)), req)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(*hooked.lock().unwrap(), Some(usdm.requirements.clone()));
+        assert_eq!(
+            *selected.lock().unwrap(),
+            Some(SelectedPayment(usdm.requirements))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_required_extension_is_enforced() {
+        let usdc = price_tag();
+        let gate = Paygate::builder(AcceptingFacilitator)
+            .accept(usdc.clone())
+            .require_extension("kyc", json!({ "version": 1 }))
+            .build();
+
+        let request = |extensions: Option<proto::Extensions>| {
+            let payload = V2PaymentPayload {
+                accepted: usdc.requirements.clone(),
+                payload: json!({}),
+                resource: None,
+                x402_version: v2::V2,
+                extensions,
+            };
+            paid_request(&payload)
+        };
+        let inner = || RecordSelected(Arc::new(Mutex::new(None)));
+
+        let err = gate
+            .handle_request_fallible(inner(), request(None))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            PaygateError::Verification(VerificationError::VerificationFailed(ref reason)) if reason.contains("kyc")
+        ));
+
+        let response = error_into_response(
+            err,
+            gate.accepts(),
+            gate.resource(),
+            gate.extension_info(),
+            gate.extra_402_headers(),
+        );
+        let header = response.headers().get(PAYMENT_REQUIRED_HEADER).unwrap();
+        let payment_required: v2::PaymentRequired =
+            decode_json_header(header.as_bytes(), true).unwrap();
+        assert_eq!(
+            payment_required.extensions.unwrap()["kyc"],
+            json!({ "version": 1 })
+        );
+        assert_eq!(
+            payment_required.accepts[0].required_extensions,
+            Some(vec!["kyc".to_owned()])
+        );
+
+        let kyc = proto::Extensions::from([("kyc".to_owned(), json!({ "token": "abc" }))]);
+        let response = gate
+            .handle_request_fallible(inner(), request(Some(kyc)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    /// Fails as many verify calls as its counter holds, then accepts.
+    struct FlakyFacilitator(AtomicUsize);
+
+    impl Facilitator for FlakyFacilitator {
+        fn verify(
+            &self,
+            request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            let failing = self
+                .0
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if failing {
+                return Box::pin(async {
+                    Err(FacilitatorError::OnchainFailure("RPC unavailable".into()))
+                });
+            }
+            AcceptingFacilitator.verify(request)
+        }
+
+        fn settle(
+            &self,
+            request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            AcceptingFacilitator.settle(request)
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            AcceptingFacilitator.supported()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_payment_can_be_retried_after_failed_verify() {
+        let usdc = price_tag();
+        let gate = Paygate::builder(FlakyFacilitator(AtomicUsize::new(1)))
+            .accept(usdc.clone())
+            .replay_store(Arc::new(InMemoryReplayStore::default()))
+            .build();
+        let request = || {
+            let payload = V2PaymentPayload {
+                accepted: usdc.requirements.clone(),
+                payload: json!({ "authorization": { "nonce": "0x01" } }),
+                resource: None,
+                x402_version: v2::V2,
+                extensions: None,
+            };
+            paid_request(&payload)
+        };
+        let inner = || RecordSelected(Arc::new(Mutex::new(None)));
+
+        let err = gate
+            .handle_request_fallible(inner(), request())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PaygateError::Verification(_)));
+
+        let retry = gate.handle_request_fallible(inner(), request());
+        assert_eq!(retry.await.unwrap().status(), StatusCode::OK);
+
+        let err = gate
+            .handle_request_fallible(inner(), request())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PaygateError::Replayed));
+    }
+
+    #[tokio::test]
+    async fn test_replayed_header_is_rejected() {
+        let usdc = price_tag();
+        let gate = Paygate::builder(AcceptingFacilitator)
+            .accept(usdc.clone())
+            .replay_store(Arc::new(InMemoryReplayStore::default()))
+            .build();
+
+        let request = |nonce: &str| {
+            let payload = V2PaymentPayload {
+                accepted: usdc.requirements.clone(),
+                payload: json!({ "authorization": { "nonce": nonce } }),
+                resource: None,
+                x402_version: v2::V2,
+                extensions: None,
+            };
+            paid_request(&payload)
+        };
+        let inner = || RecordSelected(Arc::new(Mutex::new(None)));
+
+        let first = gate.handle_request_fallible(inner(), request("0x01"));
+        assert_eq!(first.await.unwrap().status(), StatusCode::OK);
+
+        let err = gate
+            .handle_request_fallible(inner(), request("0x01"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PaygateError::Replayed));
+        let response = error_into_response(
+            err,
+            gate.accepts(),
+            gate.resource(),
+            &proto::Extensions::new(),
+            &HeaderMap::new(),
+        );
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let fresh = gate.handle_request_fallible(inner(), request("0x02"));
+        assert_eq!(fresh.await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_resource_url_keeps_base_path() {
+        let join = |base: &str, path: &str, query: Option<&str>| {
+            join_resource_url(Url::parse(base).unwrap(), path, query).to_string()
+        };
+        assert_eq!(
+            join("https://host/api/", "/weather", Some("q=a b")),
+            "https://host/api/weather?q=a%20b"
+        );
+        assert_eq!(
+            join("https://host/api", "/weather", Some("q=a%26b&c=d/e")),
+            "https://host/api/weather?q=a%26b&c=d/e"
+        );
+        assert_eq!(
+            join("https://host/", "/caf%C3%A9 menu", None),
+            "https://host/caf%C3%A9%20menu"
+        );
+        assert_eq!(join("https://host/api/", "", None), "https://host/api/");
+        assert_eq!(join("https://host", "/", None), "https://host/");
+    }
+
+    /// Counts settlements, failing those after the first `.1`.
+    struct CountSettled(Arc<AtomicUsize>, usize);
+
+    impl Facilitator for CountSettled {
+        fn verify(
+            &self,
+            _request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::VerifyResponse::valid("0xpayer".into())) })
+        }
+
+        fn settle(
+            &self,
+            request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            Box::pin(async move {
+                if self.0.fetch_add(1, Ordering::SeqCst) >= self.1 {
+                    return Err(FacilitatorError::TransactionFailed("reverted".into()));
+                }
+                AcceptingFacilitator.settle(request).await
+            })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::SupportedResponse::default()) })
         }
     }
+
+    #[tokio::test]
+    async fn test_split_payment_settles_every_part() {
+        let mut platform = asset_tag("100", "0xusdc");
+        platform.requirements.pay_to = "0xplatform".into();
+        let seller = asset_tag("900", "0xusdc");
+        let settled = Arc::new(AtomicUsize::new(0));
+        let gate = Paygate::builder(CountSettled(Arc::clone(&settled), usize::MAX))
+            .accepts([platform.clone(), seller.clone()])
+            .split(true)
+            .build();
+        assert!(gate.advertised_extensions().contains_key(SPLIT_EXTENSION));
+
+        let request = |amounts: [&str; 2]| split_request([&platform, &seller], amounts);
+        let inner = || RecordSelected(Arc::new(Mutex::new(None)));
+
+        let response = gate
+            .handle_request_fallible(inner(), request(["100", "900"]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(settled.load(Ordering::SeqCst), 2);
+        let header = response.headers().get(PAYMENT_RESPONSE_HEADER).unwrap();
+        let settlements =
+            decode_json_header::<Vec<serde_json::Value>>(header.as_bytes(), true).unwrap();
+        assert_eq!(settlements.len(), 2);
+
+        let short = gate
+            .handle_request_fallible(inner(), request(["100", "800"]))
+            .await;
+        assert!(matches!(
+            short,
+            Err(PaygateError::Verification(
+                VerificationError::VerificationFailed(_)
+            ))
+        ));
+        assert_eq!(settled.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_partial_split_settlement_is_reported() {
+        let mut platform = asset_tag("100", "0xusdc");
+        platform.requirements.pay_to = "0xplatform".into();
+        let seller = asset_tag("900", "0xusdc");
+        let gate = Paygate::builder(CountSettled(Arc::new(AtomicUsize::new(0)), 1))
+            .accepts([platform.clone(), seller.clone()])
+            .split(true)
+            .build();
+
+        let response = gate
+            .handle_request(
+                RecordSelected(Arc::new(Mutex::new(None))),
+                split_request([&platform, &seller], ["100", "900"]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYMENT_REQUIRED);
+        let header = response.headers().get(PAYMENT_RESPONSE_HEADER).unwrap();
+        let settlements =
+            decode_json_header::<Vec<proto::SettleResponse>>(header.as_bytes(), true).unwrap();
+        assert_eq!(settlements.len(), 1);
+        assert!(settlements[0].is_success());
+    }
+
+    /// Builds a request paying each of `tags` the matching amount.
+    fn split_request(tags: [&v2::PriceTag; 2], amounts: [&str; 2]) -> http::Request<Body> {
+        let payments: Vec<_> = tags
+            .into_iter()
+            .zip(amounts)
+            .map(|(tag, amount)| {
+                let mut accepted = tag.requirements.clone();
+                accepted.amount = amount.into();
+                json!({ "accepted": accepted, "payload": {}, "x402Version": 2 })
+            })
+            .collect();
+        paid_request(&json!({ "x402Version": 2, "payments": payments }))
+    }
+
+    /// Body of `remaining` 64 `KiB` chunks, optionally failing after the last.
+    struct Chunks {
+        remaining: usize,
+        produced: Arc<AtomicUsize>,
+        fail: bool,
+    }
+
+    impl HttpBody for Chunks {
+        type Data = <Body as HttpBody>::Data;
+        type Error = std::io::Error;
+
+        fn poll_frame(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+            if self.remaining == 0 {
+                let end = self
+                    .fail
+                    .then(|| Err(std::io::Error::other("upstream failed")));
+                return Poll::Ready(end);
+            }
+            self.remaining -= 1;
+            self.produced.fetch_add(1, Ordering::SeqCst);
+            Poll::Ready(Some(Ok(Frame::data(vec![0; 64 * 1024].into()))))
+        }
+    }
+
+    /// Inner service streaming a [`Chunks`] body.
+    struct ServeChunks {
+        chunks: usize,
+        produced: Arc<AtomicUsize>,
+        fail: bool,
+    }
+
+    impl Service<http::Request<Body>> for ServeChunks {
+        type Response = http::Response<Chunks>;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _req: http::Request<Body>) -> Self::Future {
+            ready(Ok(http::Response::new(Chunks {
+                remaining: self.chunks,
+                produced: Arc::clone(&self.produced),
+                fail: self.fail,
+            })))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamed_body_is_settled_after_completion() {
+        let usdc = price_tag();
+        let settled = Arc::new(AtomicUsize::new(0));
+        let gate = || {
+            Paygate::builder(CountSettled(Arc::clone(&settled), usize::MAX))
+                .accept(usdc.clone())
+                .settle_after_body(true)
+                .build()
+        };
+        let request = || {
+            let payload = V2PaymentPayload {
+                accepted: usdc.requirements.clone(),
+                payload: json!({}),
+                resource: None,
+                x402_version: v2::V2,
+                extensions: None,
+            };
+            paid_request(&payload)
+        };
+
+        let produced = Arc::new(AtomicUsize::new(0));
+        let inner = ServeChunks {
+            chunks: 256,
+            produced: Arc::clone(&produced),
+            fail: false,
+        };
+        let response = gate().handle_request(inner, request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response.headers()[http::header::TRAILER]
+                .to_str()
+                .unwrap()
+                .eq_ignore_ascii_case(PAYMENT_RESPONSE_HEADER)
+        );
+        assert!(!response.headers().contains_key(PAYMENT_RESPONSE_HEADER));
+
+        let mut body = response.into_body();
+        let mut chunks = 0;
+        let trailers = loop {
+            let frame = poll_fn(|cx| Pin::new(&mut body).poll_frame(cx))
+                .await
+                .unwrap()
+                .unwrap();
+            if let Ok(trailers) = frame.into_trailers() {
+                break trailers;
+            }
+            chunks += 1;
+            // Chunks are pulled one at a time, never buffered ahead.
+            assert_eq!(produced.load(Ordering::SeqCst), chunks);
+            assert_eq!(settled.load(Ordering::SeqCst), 0);
+        };
+        assert_eq!(chunks, 256);
+        assert_eq!(settled.load(Ordering::SeqCst), 1);
+        assert!(trailers.contains_key(PAYMENT_RESPONSE_HEADER));
+        assert!(
+            poll_fn(|cx| Pin::new(&mut body).poll_frame(cx))
+                .await
+                .is_none()
+        );
+
+        // A body failing mid-stream is not settled.
+        let inner = ServeChunks {
+            chunks: 4,
+            produced,
+            fail: true,
+        };
+        let mut body = gate()
+            .handle_request(inner, request())
+            .await
+            .unwrap()
+            .into_body();
+        while let Some(frame) = poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await {
+            if frame.is_err() {
+                break;
+            }
+        }
+        assert!(
+            poll_fn(|cx| Pin::new(&mut body).poll_frame(cx))
+                .await
+                .is_none()
+        );
+        assert_eq!(settled.load(Ordering::SeqCst), 1);
+    }
+
+    /// Issues verification tokens, and settles only payments presenting one.
+    struct TokenIssuing(VerificationTokens);
+
+    impl Facilitator for TokenIssuing {
+        fn verify(
+            &self,
+            request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            let token = self.0.issue(&request, "0xpayer");
+            let response = proto::VerifyResponse::valid("0xpayer".into());
+            Box::pin(async move { Ok(response.with_verification_token(token)) })
+        }
+
+        fn settle(
+            &self,
+            _request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            Box::pin(async { Err(FacilitatorError::OnchainFailure("re-verified".into())) })
+        }
+
+        fn settle_verified(
+            &self,
+            request: proto::SettleRequest,
+            token: String,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            Box::pin(async move {
+                self.0.check(&token, &request)?;
+                AcceptingFacilitator.settle(request).await
+            })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::SupportedResponse::default()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verification_token_is_presented_at_settlement() {
+        let usdc = price_tag();
+        let gate = Paygate::builder(TokenIssuing(VerificationTokens::new("secret")))
+            .accept(usdc.clone())
+            .build();
+
+        let payload = V2PaymentPayload {
+            accepted: usdc.requirements,
+            payload: json!({}),
+            resource: None,
+            x402_version: v2::V2,
+            extensions: None,
+        };
+        let req = paid_request(&payload);
+
+        let selected = Arc::new(Mutex::new(None));
+        let response = gate
+            .handle_request_fallible(RecordSelected(selected), req)
+            .await
+            .unwrap();
+        assert!(response.headers().contains_key(PAYMENT_RESPONSE_HEADER));
+    }
 }
@@ -1,15 +1,18 @@
 //! Price tag sources for the x402 payment gate.
 //!
-//! Abstracts over static and dynamic pricing strategies via the
-//! [`PriceTagSource`] trait. All sources produce [`v2::PriceTag`] values
+//! Abstracts over static, dynamic and oracle-based pricing strategies via
+//! the [`PriceTagSource`] trait. All sources produce [`v2::PriceTag`] values
 //! (V2-only server layer).
 
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
-use http::{HeaderMap, Uri};
+use http::{Extensions, HeaderMap, Uri};
 use r402::proto::v2;
+use rust_decimal::Decimal;
 use url::Url;
 
 /// Trait for types that can provide V2 price tags for a request.
@@ -26,6 +29,22 @@ fn resolve(
         uri: &Uri,
         base_url: Option<&Url>,
     ) -> impl Future<Output = Vec<v2::PriceTag>> + Send;
+
+    /// Resolves price tags with access to the request extensions.
+    ///
+    /// Upstream middleware (authentication, routing) can store context such
+    /// as the caller's tier or matched route parameters in the extensions.
+    /// The default implementation ignores them and calls [`Self::resolve`].
+    fn resolve_with_extensions(
+        &self,
+        headers: &HeaderMap,
+        uri: &Uri,
+        base_url: Option<&Url>,
+        extensions: &Extensions,
+    ) -> impl Future<Output = Vec<v2::PriceTag>> + Send {
+        let _ = extensions;
+        self.resolve(headers, uri, base_url)
+    }
 }
 
 /// Static price tag source - returns the same price tags for every request.
@@ -78,6 +97,7 @@ async fn resolve(
         &'a HeaderMap,
         &'a Uri,
         Option<&'a Url>,
+        &'a Extensions,
     ) -> Pin<Box<dyn Future<Output = Vec<v2::PriceTag>> + Send + 'a>>
     + Send
     + Sync;
@@ -85,7 +105,7 @@ async fn resolve(
 /// Dynamic price tag source - computes price tags per-request via callback.
 ///
 /// This implementation allows computing different prices based on request
-/// headers, URI, or other runtime factors.
+/// headers, URI, request extensions, or other runtime factors.
 pub struct DynamicPriceTags {
     callback: Arc<BoxedDynamicPriceCallback>,
 }
@@ -116,11 +136,28 @@ pub fn new<F, Fut>(callback: F) -> Self
         Fut: Future<Output = Vec<v2::PriceTag>> + Send + 'static,
     {
         Self {
-            callback: Arc::new(move |headers, uri, base_url| {
+            callback: Arc::new(move |headers, uri, base_url, _extensions| {
                 Box::pin(callback(headers, uri, base_url))
             }),
         }
     }
+
+    /// Creates a new dynamic price source from an async closure that also
+    /// receives the request extensions.
+    ///
+    /// Use this to price requests by context injected by upstream middleware,
+    /// e.g. an authenticated user's tier or matched route parameters.
+    pub fn with_extensions<F, Fut>(callback: F) -> Self
+    where
+        F: Fn(&HeaderMap, &Uri, Option<&Url>, &Extensions) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Vec<v2::PriceTag>> + Send + 'static,
+    {
+        Self {
+            callback: Arc::new(move |headers, uri, base_url, extensions| {
+                Box::pin(callback(headers, uri, base_url, extensions))
+            }),
+        }
+    }
 }
 
 impl PriceTagSource for DynamicPriceTags {
@@ -130,6 +167,261 @@ async fn resolve(
         uri: &Uri,
         base_url: Option<&Url>,
     ) -> Vec<v2::PriceTag> {
-        (self.callback)(headers, uri, base_url).await
+        let extensions = Extensions::new();
+        (self.callback)(headers, uri, base_url, &extensions).await
+    }
+
+    async fn resolve_with_extensions(
+        &self,
+        headers: &HeaderMap,
+        uri: &Uri,
+        base_url: Option<&Url>,
+        extensions: &Extensions,
+    ) -> Vec<v2::PriceTag> {
+        (self.callback)(headers, uri, base_url, extensions).await
+    }
+}
+
+/// Default time an exchange rate fetched by [`OraclePricedTags`] is reused.
+pub const DEFAULT_RATE_TTL: Duration = Duration::from_secs(10);
+
+/// Internal type alias for the boxed exchange rate callback.
+type BoxedRateCallback = dyn for<'a> Fn(&'a str) -> Pin<Box<dyn Future<Output = Option<Decimal>> + Send + 'a>>
+    + Send
+    + Sync;
+
+/// An asset whose amount [`OraclePricedTags`] computes from an exchange rate.
+#[derive(Clone, Debug)]
+struct OracleAsset {
+    tag: v2::PriceTag,
+    decimals: u32,
+}
+
+/// Oracle-priced price tag source - charges a fixed USD price in assets
+/// whose amount follows a live exchange rate.
+///
+/// For every request, the USD price is converted into base units of each
+/// asset at the rate returned by the oracle callback, which gives the USD
+/// value of one whole token. Rates are cached for a short time
+/// ([`DEFAULT_RATE_TTL`] by default) so that most requests do not call the
+/// oracle.
+///
+/// Assets whose rate cannot be fetched are left out, and a configured
+/// stablecoin price tag is offered instead, so a failing oracle never leaves
+/// a request without payment options.
+#[derive(Clone)]
+pub struct OraclePricedTags {
+    usd_price: Decimal,
+    assets: Arc<Vec<OracleAsset>>,
+    fallback: v2::PriceTag,
+    rates: Arc<BoxedRateCallback>,
+    cache: Arc<Mutex<HashMap<String, (Decimal, Instant)>>>,
+    rate_ttl: Duration,
+}
+
+impl std::fmt::Debug for OraclePricedTags {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OraclePricedTags")
+            .field("usd_price", &self.usd_price)
+            .field("assets", &self.assets)
+            .field("fallback", &self.fallback)
+            .field("rates", &"<callback>")
+            .field("rate_ttl", &self.rate_ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OraclePricedTags {
+    /// Creates an oracle-priced source charging `usd_price`.
+    ///
+    /// `rates` receives an asset address and returns the USD value of one
+    /// whole token, or `None` if the rate is unavailable. `fallback` is a
+    /// stablecoin price tag offered when a rate cannot be fetched.
+    pub fn new<F, Fut>(usd_price: Decimal, fallback: v2::PriceTag, rates: F) -> Self
+    where
+        F: Fn(&str) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<Decimal>> + Send + 'static,
+    {
+        Self {
+            usd_price,
+            assets: Arc::new(Vec::new()),
+            fallback,
+            rates: Arc::new(move |asset| Box::pin(rates(asset))),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            rate_ttl: DEFAULT_RATE_TTL,
+        }
+    }
+
+    /// Adds an asset with `decimals` decimal places, charged according to its
+    /// rate.
+    ///
+    /// The amount of `tag` is replaced by the converted price on every request.
+    #[must_use]
+    pub fn with_asset(mut self, tag: v2::PriceTag, decimals: u32) -> Self {
+        let mut assets = (*self.assets).clone();
+        assets.push(OracleAsset { tag, decimals });
+        self.assets = Arc::new(assets);
+        self
+    }
+
+    /// Sets how long a fetched rate is reused before the oracle is called again.
+    #[must_use]
+    pub const fn with_rate_ttl(mut self, ttl: Duration) -> Self {
+        self.rate_ttl = ttl;
+        self
+    }
+
+    /// Returns the price tag of `asset` at its current rate, if available.
+    async fn price(&self, asset: &OracleAsset) -> Option<v2::PriceTag> {
+        let rate = self.rate(&asset.tag.requirements.asset).await?;
+        let amount = base_units(self.usd_price, rate, asset.decimals)?;
+        let mut tag = asset.tag.clone();
+        tag.requirements.amount = amount;
+        Some(tag)
+    }
+
+    /// Returns the rate of `asset`, from the cache if it is fresh.
+    async fn rate(&self, asset: &str) -> Option<Decimal> {
+        let cached = self
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(asset)
+            .copied();
+        if let Some((rate, fetched_at)) = cached
+            && fetched_at.elapsed() < self.rate_ttl
+        {
+            return Some(rate);
+        }
+        let rate = (self.rates)(asset)
+            .await
+            .filter(|rate| rate.is_sign_positive() && !rate.is_zero())?;
+        self.cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(asset.to_owned(), (rate, Instant::now()));
+        Some(rate)
+    }
+}
+
+/// Converts `usd_price` into base units of a token worth `rate` USD with
+/// `decimals` decimal places, rounding up.
+fn base_units(usd_price: Decimal, rate: Decimal, decimals: u32) -> Option<String> {
+    let unit = Decimal::try_from_i128_with_scale(10_i128.checked_pow(decimals)?, 0).ok()?;
+    let amount = usd_price.checked_div(rate)?.checked_mul(unit)?.ceil();
+    Some(amount.to_string())
+}
+
+impl PriceTagSource for OraclePricedTags {
+    async fn resolve(
+        &self,
+        _headers: &HeaderMap,
+        _uri: &Uri,
+        _base_url: Option<&Url>,
+    ) -> Vec<v2::PriceTag> {
+        let mut tags = Vec::with_capacity(self.assets.len() + 1);
+        let mut fallback = self.assets.is_empty();
+        for asset in self.assets.iter() {
+            match self.price(asset).await {
+                Some(tag) => tags.push(tag),
+                None => fallback = true,
+            }
+        }
+        if fallback {
+            tags.push(self.fallback.clone());
+        }
+        tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use r402::chain::ChainId;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct Premium;
+
+    fn price_tag(amount: u64) -> v2::PriceTag {
+        asset_tag(amount, "0xusdc")
+    }
+
+    fn asset_tag(amount: u64, asset: &str) -> v2::PriceTag {
+        v2::PriceTag {
+            requirements: v2::PaymentRequirements {
+                scheme: "exact".into(),
+                network: ChainId::new("eip155", "8453"),
+                amount: amount.to_string(),
+                pay_to: "0xmerchant".into(),
+                max_timeout_seconds: 60,
+                asset: asset.into(),
+                extra: None,
+                required_extensions: None,
+            },
+            enricher: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extension_aware_pricing() {
+        let source = DynamicPriceTags::with_extensions(|_, _, _, extensions| {
+            let amount = if extensions.get::<Premium>().is_some() {
+                500
+            } else {
+                1_000
+            };
+            async move { vec![price_tag(amount)] }
+        });
+        let headers = HeaderMap::new();
+        let uri = Uri::from_static("/weather");
+
+        let mut extensions = Extensions::new();
+        let tags = source
+            .resolve_with_extensions(&headers, &uri, None, &extensions)
+            .await;
+        assert_eq!(tags[0].requirements.amount, "1000");
+
+        extensions.insert(Premium);
+        let tags = source
+            .resolve_with_extensions(&headers, &uri, None, &extensions)
+            .await;
+        assert_eq!(tags[0].requirements.amount, "500");
+    }
+
+    #[tokio::test]
+    async fn test_oracle_priced_amounts_follow_rate_and_decimals() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        // One cent, paid in WETH at $2000 or in a token whose oracle is down.
+        let source = OraclePricedTags::new(Decimal::new(1, 2), price_tag(10_000), move |asset| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let rate = (asset == "0xweth").then(|| Decimal::from(2_000));
+            async move { rate }
+        })
+        .with_asset(asset_tag(0, "0xweth"), 18)
+        .with_asset(asset_tag(0, "0xdown"), 6);
+        let headers = HeaderMap::new();
+        let uri = Uri::from_static("/weather");
+
+        let tags = source.resolve(&headers, &uri, None).await;
+        let amounts = tags
+            .iter()
+            .map(|tag| {
+                (
+                    tag.requirements.asset.as_str(),
+                    tag.requirements.amount.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        // $0.01 / $2000 = 0.000005 WETH = 5 * 10^12 wei; USDC replaces the
+        // unpriced asset.
+        assert_eq!(amounts, [("0xweth", "5000000000000"), ("0xusdc", "10000")]);
+
+        // The WETH rate is cached; the failed rate is fetched again.
+        source.resolve(&headers, &uri, None).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
     }
 }
@@ -0,0 +1,143 @@
+//! HTTP status codes and JSON bodies for facilitator errors.
+//!
+//! An HTTP facilitator answers a failed verify or settle call with the
+//! `{ "error": "...", "message": "..." }` body of its [`PaymentProblem`],
+//! under a status code telling the client what to do next:
+//!
+//! | Failure | Status |
+//! |---|---|
+//! | Unsupported chain or scheme | `422 Unprocessable Entity` |
+//! | Insufficient funds or Permit2 allowance | `402 Payment Required` |
+//! | Any other invalid payment (format, signature, amount, ...) | `400 Bad Request` |
+//! | RPC or provider failure, failed transaction | `502 Bad Gateway` |
+//! | [`LimitedFacilitator`](super::limits::LimitedFacilitator) timeout | `504 Gateway Timeout` |
+//! | Anything else | `500 Internal Server Error` |
+//!
+//! A `422` means the payment can never succeed this way, while a `502` or
+//! `504` is worth retrying later.
+
+use axum_core::body::Body;
+use axum_core::response::{IntoResponse, Response};
+use http::StatusCode;
+use r402::facilitator::FacilitatorError;
+use r402::proto::{AsPaymentProblem, ErrorReason, PaymentProblem};
+
+use super::limits::is_timeout;
+
+/// A [`FacilitatorError`] answered as an HTTP response.
+#[derive(Debug)]
+pub struct FacilitatorErrorResponse(pub FacilitatorError);
+
+impl From<FacilitatorError> for FacilitatorErrorResponse {
+    fn from(error: FacilitatorError) -> Self {
+        Self(error)
+    }
+}
+
+impl FacilitatorErrorResponse {
+    /// Returns the HTTP status code to answer with.
+    #[must_use]
+    pub fn status_code(&self) -> StatusCode {
+        match &self.0 {
+            FacilitatorError::PaymentVerification(error) => {
+                match error.as_payment_problem().reason() {
+                    ErrorReason::UnsupportedChain | ErrorReason::UnsupportedScheme => {
+                        StatusCode::UNPROCESSABLE_ENTITY
+                    }
+                    ErrorReason::InsufficientFunds | ErrorReason::Permit2AllowanceInsufficient => {
+                        StatusCode::PAYMENT_REQUIRED
+                    }
+                    _ => StatusCode::BAD_REQUEST,
+                }
+            }
+            FacilitatorError::OnchainFailure(_) | FacilitatorError::TransactionFailed(_) => {
+                StatusCode::BAD_GATEWAY
+            }
+            error if is_timeout(error) => StatusCode::GATEWAY_TIMEOUT,
+            FacilitatorError::Aborted { .. } => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Returns the payment problem of the error.
+    #[must_use]
+    pub fn problem(&self) -> PaymentProblem {
+        self.0.as_payment_problem()
+    }
+
+    /// Returns the [`PaymentProblem`] response body, `{ error, message }`.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self.problem()).unwrap_or_default()
+    }
+}
+
+impl IntoResponse for FacilitatorErrorResponse {
+    fn into_response(self) -> Response {
+        let status = self.status_code();
+        #[cfg(feature = "telemetry")]
+        if status.is_server_error() {
+            tracing::warn!(error = %self.0, %status, "Facilitator request failed");
+        }
+        Response::builder()
+            .status(status)
+            .header("Content-Type", "application/json")
+            .body(Body::from(self.to_json().to_string()))
+            .expect("Fail to construct response")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use r402::proto::PaymentVerificationError;
+
+    use super::*;
+    use crate::server::limits::TIMEOUT_REASON;
+
+    fn status(error: FacilitatorError) -> StatusCode {
+        FacilitatorErrorResponse(error).status_code()
+    }
+
+    #[test]
+    fn test_errors_map_to_status_codes() {
+        assert_eq!(
+            status(PaymentVerificationError::UnsupportedChain.into()),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            status(PaymentVerificationError::UnsupportedScheme.into()),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            status(PaymentVerificationError::InsufficientFunds.into()),
+            StatusCode::PAYMENT_REQUIRED
+        );
+        assert_eq!(
+            status(PaymentVerificationError::InvalidFormat("bad".into()).into()),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(FacilitatorError::OnchainFailure("rpc down".into())),
+            StatusCode::BAD_GATEWAY
+        );
+        assert_eq!(
+            status(FacilitatorError::Aborted {
+                reason: TIMEOUT_REASON.into(),
+                message: "slow".into(),
+            }),
+            StatusCode::GATEWAY_TIMEOUT
+        );
+    }
+
+    #[test]
+    fn test_response_body_carries_the_reason() {
+        let response = FacilitatorErrorResponse(PaymentVerificationError::UnsupportedChain.into());
+        let body = response.to_json();
+        assert_eq!(body["error"], "unsupported_chain");
+        assert!(body["message"].is_string());
+        assert_eq!(
+            response.into_response().status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Replay protection for payment headers.
+//!
+//! On-chain nonces already prevent a payment from settling twice, but a
+//! captured `Payment-Signature` header can still be replayed to make the
+//! server run its protected handler again before settlement fails. A
+//! [`ReplayStore`] remembers the [payment id](r402::proto::payment_id) of each
+//! payment the gate has seen so that a second request carrying the same
+//! authorization is rejected with `409 Conflict` before the facilitator is
+//! contacted.
+//!
+//! [`InMemoryReplayStore`] keeps a bounded set of ids in process memory.
+//! Implement [`ReplayStore`] to share the set across instances, e.g. in Redis.
+//!
+//! Ids are reserved before the payment is verified and released again if
+//! the payment does not settle, e.g. because verification failed or the
+//! handler answered with an error, so the client can retry the same payment.
+//!
+//! As ids are recorded before the payment is verified, a store must never
+//! evict an unexpired id to make room: a flood of bogus headers would
+//! otherwise flush the ids of real payments and re-enable their replay. A
+//! full store reports [`ReplayCheck::Full`] instead, and the gate answers
+//! `503 Service Unavailable` until entries expire.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use r402::facilitator::BoxFuture;
+
+/// Default number of payment ids kept by an [`InMemoryReplayStore`].
+pub const DEFAULT_REPLAY_CAPACITY: usize = 10_000;
+
+/// Outcome of [`ReplayStore::check_and_insert`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayCheck {
+    /// The id was not seen before and is now recorded.
+    Fresh,
+    /// The id was already recorded and has not expired.
+    Replayed,
+    /// The id is new, but the store has no room left to record it.
+    Full,
+}
+
+/// Records payment ids seen by the payment gate.
+///
+/// This trait is dyn-compatible.
+pub trait ReplayStore: Send + Sync {
+    /// Records `payment_id` for `ttl`, unless it is already recorded or the
+    /// store is full.
+    ///
+    /// The check and the insertion must be atomic so that two concurrent
+    /// requests with the same id cannot both observe it as fresh.
+    fn check_and_insert<'a>(
+        &'a self,
+        payment_id: &'a str,
+        ttl: Duration,
+    ) -> BoxFuture<'a, ReplayCheck>;
+
+    /// Forgets `payment_id`, recorded by a payment that did not settle.
+    fn release<'a>(&'a self, payment_id: &'a str) -> BoxFuture<'a, ()>;
+}
+
+/// An in-process [`ReplayStore`] bounded to a fixed number of entries.
+///
+/// Entries expire after their TTL. Once the store holds `capacity` unexpired
+/// entries, new ids are refused with [`ReplayCheck::Full`].
+#[derive(Debug)]
+pub struct InMemoryReplayStore {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    expiries: HashMap<String, Instant>,
+    order: VecDeque<(String, Instant)>,
+}
+
+impl InMemoryReplayStore {
+    /// Creates a store holding at most `capacity` payment ids.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    fn insert(&self, payment_id: &str, ttl: Duration) -> ReplayCheck {
+        let now = Instant::now();
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if entries
+            .expiries
+            .get(payment_id)
+            .is_some_and(|expiry| *expiry > now)
+        {
+            return ReplayCheck::Replayed;
+        }
+
+        let Entries { expiries, order } = &mut *entries;
+        while order.front().is_some_and(|(_, e)| *e <= now) {
+            let Some((evicted, evicted_expiry)) = order.pop_front() else {
+                break;
+            };
+            // Skip stale queue entries for ids that were re-inserted later.
+            if expiries.get(&evicted) == Some(&evicted_expiry) {
+                expiries.remove(&evicted);
+            }
+        }
+        if expiries.len() >= self.capacity {
+            // Entries with a shorter TTL may have expired behind the front.
+            expiries.retain(|_, expiry| *expiry > now);
+            order.retain(|(id, expiry)| expiries.get(id) == Some(expiry));
+            if expiries.len() >= self.capacity {
+                return ReplayCheck::Full;
+            }
+        }
+        let expiry = now + ttl;
+        expiries.insert(payment_id.to_owned(), expiry);
+        order.push_back((payment_id.to_owned(), expiry));
+        ReplayCheck::Fresh
+    }
+}
+
+impl Default for InMemoryReplayStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_CAPACITY)
+    }
+}
+
+impl ReplayStore for InMemoryReplayStore {
+    fn check_and_insert<'a>(
+        &'a self,
+        payment_id: &'a str,
+        ttl: Duration,
+    ) -> BoxFuture<'a, ReplayCheck> {
+        let check = self.insert(payment_id, ttl);
+        Box::pin(async move { check })
+    }
+
+    fn release<'a>(&'a self, payment_id: &'a str) -> BoxFuture<'a, ()> {
+        // The queue entry of the id is skipped once it reaches the front.
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .expiries
+            .remove(payment_id);
+        Box::pin(async {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_memory_store_is_bounded_and_expires() {
+        let store = InMemoryReplayStore::new(2);
+        let ttl = Duration::from_mins(1);
+        assert_eq!(store.check_and_insert("a", ttl).await, ReplayCheck::Fresh);
+        assert_eq!(
+            store.check_and_insert("a", ttl).await,
+            ReplayCheck::Replayed
+        );
+
+        assert_eq!(
+            store.check_and_insert("b", Duration::ZERO).await,
+            ReplayCheck::Fresh
+        );
+        // "b" expired, making room for "c".
+        assert_eq!(store.check_and_insert("c", ttl).await, ReplayCheck::Fresh);
+        // Live entries are never evicted to make room.
+        assert_eq!(store.check_and_insert("d", ttl).await, ReplayCheck::Full);
+        assert_eq!(
+            store.check_and_insert("a", ttl).await,
+            ReplayCheck::Replayed
+        );
+
+        store.release("a").await;
+        assert_eq!(store.check_and_insert("a", ttl).await, ReplayCheck::Fresh);
+    }
+}
@@ -0,0 +1,187 @@
+//! Admin endpoint for updating facilitator payer screening at runtime.
+//!
+//! [`ScreeningAdmin`] is a [`tower::Service`] replacing the payer lists of a
+//! [`PayerScreening`] shared with a
+//! [`ScreeningFacilitator`](r402::screening::ScreeningFacilitator). Mount it
+//! under an admin prefix, e.g. `/admin/screening`; the last path segment names
+//! the chain:
+//!
+//! | Request | Effect |
+//! |---|---|
+//! | `PUT /{chain}` with a [`PayerLists`] JSON body | Replaces the lists of the chain |
+//! | `DELETE /{chain}` | Removes the lists, accepting every payer on the chain |
+//!
+//! Every request must carry `Authorization: Bearer <token>` with the admin
+//! token the endpoint was created with. Successful updates answer
+//! `204 No Content`.
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum_core::body::Body;
+use axum_core::extract::Request;
+use axum_core::response::{IntoResponse, Response};
+use http::{Method, StatusCode, header};
+use r402::chain::ChainId;
+use r402::screening::{PayerLists, PayerScreening};
+use tower::Service;
+
+use super::limits::RequestLimits;
+
+/// Service updating the lists of a [`PayerScreening`] over HTTP.
+///
+/// See the [module documentation](self) for the routes.
+#[derive(Clone)]
+pub struct ScreeningAdmin {
+    screening: PayerScreening,
+    token: Arc<str>,
+    limits: RequestLimits,
+}
+
+impl std::fmt::Debug for ScreeningAdmin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScreeningAdmin")
+            .field("screening", &self.screening)
+            .field("limits", &self.limits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ScreeningAdmin {
+    /// Creates an endpoint updating `screening`, accepting requests that
+    /// present `token` as a bearer token.
+    pub fn new(screening: PayerScreening, token: impl Into<Arc<str>>) -> Self {
+        Self {
+            screening,
+            token: token.into(),
+            limits: RequestLimits::default(),
+        }
+    }
+
+    /// Sets the limits applied to request bodies.
+    #[must_use]
+    pub const fn with_limits(mut self, limits: RequestLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Handles a single admin request.
+    pub async fn handle(&self, request: Request) -> Response {
+        if !self.authorized(&request) {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+        let chain = request
+            .uri()
+            .path()
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .and_then(|segment| segment.parse::<ChainId>().ok());
+        let Some(chain) = chain else {
+            return (
+                StatusCode::BAD_REQUEST,
+                "path does not end in a CAIP-2 chain ID",
+            )
+                .into_response();
+        };
+        match *request.method() {
+            Method::PUT => match self
+                .limits
+                .parse_body::<PayerLists, _>(request.into_body())
+                .await
+            {
+                Ok(lists) => {
+                    self.screening.set_lists(chain, lists);
+                    StatusCode::NO_CONTENT.into_response()
+                }
+                Err(error) => error.into_response(),
+            },
+            Method::DELETE => {
+                self.screening.clear_lists(&chain);
+                StatusCode::NO_CONTENT.into_response()
+            }
+            _ => (
+                StatusCode::METHOD_NOT_ALLOWED,
+                [(header::ALLOW, "PUT, DELETE")],
+                Body::empty(),
+            )
+                .into_response(),
+        }
+    }
+
+    /// Checks the bearer token of `request` in constant time.
+    fn authorized(&self, request: &Request) -> bool {
+        let Some(presented) = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            return false;
+        };
+        presented.len() == self.token.len()
+            && presented
+                .bytes()
+                .zip(self.token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+impl Service<Request> for ScreeningAdmin {
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let admin = self.clone();
+        Box::pin(async move { Ok(admin.handle(request).await) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: Method, token: &str, body: &str) -> Request {
+        http::Request::builder()
+            .method(method)
+            .uri("/admin/screening/eip155:8453")
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .body(Body::from(body.to_owned()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_admin_route_updates_lists() {
+        let base = ChainId::new("eip155", "8453");
+        let payer = "0x1111111111111111111111111111111111111111";
+        let screening = PayerScreening::default();
+        let admin = ScreeningAdmin::new(screening.clone(), "secret");
+        let block = format!(r#"{{"block":["{payer}"]}}"#);
+
+        let response = admin.handle(request(Method::PUT, "wrong", &block)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(screening.check(&base, payer).is_ok());
+
+        let response = admin.handle(request(Method::PUT, "secret", &block)).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(screening.check(&base, payer).is_err());
+
+        let response = admin
+            .handle(request(Method::PUT, "secret", "not json"))
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = admin.handle(request(Method::DELETE, "secret", "")).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(screening.check(&base, payer).is_ok());
+    }
+}
@@ -0,0 +1,233 @@
+//! W3C Trace Context propagation between servers and facilitators.
+//!
+//! Links the trace of a paid request to the verify and settle calls it causes
+//! on a remote facilitator, without an `OpenTelemetry` dependency:
+//!
+//! - [`X402Middleware`](super::X402Middleware) extracts the `traceparent` and
+//!   `tracestate` headers of each paid request and runs its handling in a
+//!   [`TraceContext::scope`].
+//! - [`FacilitatorClient`](super::facilitator::FacilitatorClient) injects the
+//!   context of the current scope into its `/verify` and `/settle` calls,
+//!   under a fresh span id for each call.
+//! - A facilitator server continues the trace the same way, running each
+//!   handler in the scope of [`TraceContext::from_headers`].
+//!
+//! Only the context is propagated; exporting spans under it is left to the
+//! `tracing` subscriber.
+
+use std::fmt::Write as _;
+
+use http::{HeaderMap, HeaderValue};
+
+/// Name of the header carrying the trace id and parent span id.
+pub const TRACEPARENT: &str = "traceparent";
+
+/// Name of the header carrying vendor-specific trace state.
+pub const TRACESTATE: &str = "tracestate";
+
+tokio::task_local! {
+    static CURRENT: TraceContext;
+}
+
+/// A W3C Trace Context: the `traceparent` fields and the opaque `tracestate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    trace_id: String,
+    parent_id: String,
+    flags: u8,
+    state: Option<String>,
+}
+
+impl TraceContext {
+    /// Parses a `traceparent` header value, with the optional `tracestate`.
+    ///
+    /// Returns `None` if `traceparent` is malformed or has an all-zero id, as
+    /// the specification requires such headers to be ignored.
+    #[must_use]
+    pub fn parse(traceparent: &str, tracestate: Option<&str>) -> Option<Self> {
+        let mut fields = traceparent.trim().split('-');
+        let version = fields.next()?;
+        let trace_id = fields.next()?;
+        let parent_id = fields.next()?;
+        let flags = fields.next()?;
+        // Future versions may append fields; version 00 may not.
+        if version == "00" && fields.next().is_some() {
+            return None;
+        }
+        let valid = is_lower_hex(version, 2)
+            && version != "ff"
+            && is_lower_hex(trace_id, 32)
+            && is_lower_hex(parent_id, 16)
+            && is_lower_hex(flags, 2)
+            && trace_id.bytes().any(|b| b != b'0')
+            && parent_id.bytes().any(|b| b != b'0');
+        if !valid {
+            return None;
+        }
+        Some(Self {
+            trace_id: trace_id.to_owned(),
+            parent_id: parent_id.to_owned(),
+            flags: u8::from_str_radix(flags, 16).ok()?,
+            state: tracestate
+                .map(str::trim)
+                .filter(|state| !state.is_empty())
+                .map(str::to_owned),
+        })
+    }
+
+    /// Extracts the context from the `traceparent` and `tracestate` headers.
+    #[must_use]
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let traceparent = headers.get(TRACEPARENT)?.to_str().ok()?;
+        let tracestate = headers.get(TRACESTATE).and_then(|v| v.to_str().ok());
+        Self::parse(traceparent, tracestate)
+    }
+
+    /// Returns the 32 hex digit trace id.
+    #[must_use]
+    pub fn trace_id(&self) -> &str {
+        &self.trace_id
+    }
+
+    /// Returns the 16 hex digit id of the parent span.
+    #[must_use]
+    pub fn parent_id(&self) -> &str {
+        &self.parent_id
+    }
+
+    /// Returns the `tracestate` header value, if any.
+    #[must_use]
+    pub fn state(&self) -> Option<&str> {
+        self.state.as_deref()
+    }
+
+    /// Returns the context continued under the span `span_id`.
+    #[must_use]
+    pub fn child(&self, span_id: u64) -> Self {
+        Self {
+            parent_id: format!("{span_id:016x}"),
+            ..self.clone()
+        }
+    }
+
+    /// Formats the `traceparent` header value.
+    #[must_use]
+    pub fn traceparent(&self) -> String {
+        let mut value = String::with_capacity(55);
+        let _ = write!(
+            value,
+            "00-{}-{}-{:02x}",
+            self.trace_id, self.parent_id, self.flags
+        );
+        value
+    }
+
+    /// Adds the `traceparent` and `tracestate` headers to `headers`, unless
+    /// a `traceparent` is already set.
+    pub fn inject(&self, headers: &mut HeaderMap) {
+        if headers.contains_key(TRACEPARENT) {
+            return;
+        }
+        if let Ok(value) = HeaderValue::from_str(&self.traceparent()) {
+            headers.insert(TRACEPARENT, value);
+        }
+        if let Some(value) = self.state().and_then(|s| HeaderValue::from_str(s).ok()) {
+            headers.insert(TRACESTATE, value);
+        }
+    }
+
+    /// Runs `future` with this context as the current one.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+
+    /// Returns the context of the enclosing [`scope`](Self::scope), if any.
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+
+    /// Returns the context to send with an outbound call: the current one,
+    /// continued under a new random span id.
+    ///
+    /// `tracing` span ids are process-local and reused, so they cannot serve
+    /// as W3C parent ids, which must be unique across services.
+    #[must_use]
+    pub fn outbound() -> Option<Self> {
+        Some(Self::current()?.child(new_span_id()))
+    }
+}
+
+/// Generates a random, non-zero span id.
+fn new_span_id() -> u64 {
+    loop {
+        let id = rand::random::<u64>();
+        if id != 0 {
+            return id;
+        }
+    }
+}
+
+/// Runs `future` in the scope of `context`, or as is without one.
+pub(crate) async fn in_scope<F: Future>(context: Option<TraceContext>, future: F) -> F::Output {
+    match context {
+        Some(context) => context.scope(future).await,
+        None => future.await,
+    }
+}
+
+fn is_lower_hex(field: &str, len: usize) -> bool {
+    field.len() == len
+        && field
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACEPARENT_VALUE: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_traceparent_round_trips() {
+        let context = TraceContext::parse(TRACEPARENT_VALUE, Some("congo=t61rcWkgMzE")).unwrap();
+        assert_eq!(context.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(context.traceparent(), TRACEPARENT_VALUE);
+
+        let child = context.child(0x2a);
+        assert_eq!(child.parent_id(), "000000000000002a");
+        assert_eq!(child.state(), Some("congo=t61rcWkgMzE"));
+
+        assert!(
+            TraceContext::parse(
+                "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+                None
+            )
+            .is_none()
+        );
+        assert!(
+            TraceContext::parse(
+                "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+                None
+            )
+            .is_none()
+        );
+        assert!(TraceContext::parse(&format!("{TRACEPARENT_VALUE}-extra"), None).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_outbound_calls_get_fresh_span_ids() {
+        assert!(TraceContext::outbound().is_none());
+        let context = TraceContext::parse(TRACEPARENT_VALUE, None).unwrap();
+        let (first, second) = context
+            .clone()
+            .scope(async { (TraceContext::outbound(), TraceContext::outbound()) })
+            .await;
+        let (first, second) = (first.unwrap(), second.unwrap());
+        assert_eq!(first.trace_id(), context.trace_id());
+        assert_ne!(first.parent_id(), context.parent_id());
+        assert_ne!(first.parent_id(), second.parent_id());
+        assert!(TraceContext::parse(&first.traceparent(), None).is_some());
+    }
+}
@@ -1,8 +1,9 @@
 //! Error types for MCP x402 payment integration.
 //!
 //! This module defines [`McpPaymentError`] for all failure modes during
-//! MCP payment flows, and [`PaymentRequiredError`] for representing
-//! 402 payment required responses as typed errors.
+//! MCP payment flows, [`MetaPaymentError`] for malformed payments in `_meta`
+//! fields, and [`PaymentRequiredError`] for representing 402 payment required
+//! responses as typed errors.
 
 use r402::proto;
 
@@ -53,6 +54,37 @@ pub enum McpPaymentError {
     /// Client-side scheme error.
     #[error("Client error: {0}")]
     Client(#[from] r402::scheme::ClientError),
+
+    /// The payment in a request's `_meta` is malformed.
+    #[error(transparent)]
+    InvalidPaymentMeta(#[from] MetaPaymentError),
+}
+
+/// Reasons the payment in a `_meta` field cannot be read.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum MetaPaymentError {
+    /// The payment is not a JSON object.
+    #[error("x402 payment in _meta is not a JSON object")]
+    NotAnObject,
+
+    /// The payment has no numeric `x402Version`.
+    #[error("x402 payment in _meta has no numeric x402Version")]
+    MissingVersion,
+
+    /// The payment is written in a protocol version this crate does not know.
+    #[error("x402 payment in _meta has unsupported x402Version {0}")]
+    UnsupportedVersion(u64),
+
+    /// The payment does not match the shape of its declared version.
+    #[error("malformed {version} x402 payment in _meta: {source}")]
+    Malformed {
+        /// The declared protocol version.
+        version: proto::ProtocolVersion,
+        /// The deserialization failure.
+        #[source]
+        source: serde_json::Error,
+    },
 }
 
 /// Represents a 402 payment required response from an MCP tool call.
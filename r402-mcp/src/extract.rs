@@ -6,14 +6,23 @@
 use r402::proto;
 use serde_json::Value;
 
-use crate::types::{CallToolResult, ContentItem};
+use crate::error::MetaPaymentError;
+use crate::types::{CallToolResult, ContentItem, PaymentPayload};
 use crate::{
     PAYMENT_ERROR_KEY, PAYMENT_META_KEY, PAYMENT_REQUIRED_CODE, PAYMENT_RESPONSE_META_KEY,
 };
 
 /// Extracts an x402 payment payload from an MCP request's `_meta` field.
 ///
-/// Returns `None` if no payment is present or the data is malformed.
+/// The protocol version is detected from the payload's `x402Version`, and the
+/// payload is parsed into the shape of that version.
+///
+/// Returns `Ok(None)` if no payment is present.
+///
+/// # Errors
+///
+/// Returns a [`MetaPaymentError`] if a payment is present but malformed, so a
+/// client and server disagreeing on the version fail loudly.
 ///
 /// # Examples
 ///
@@ -21,29 +30,30 @@
 /// use r402_mcp::extract::extract_payment_from_meta;
 ///
 /// let meta = serde_json::Map::new();
-/// assert!(extract_payment_from_meta(&meta).is_none());
+/// assert!(extract_payment_from_meta(&meta).unwrap().is_none());
 /// ```
-#[must_use]
-pub fn extract_payment_from_meta(meta: &serde_json::Map<String, Value>) -> Option<Value> {
-    let payment = meta.get(PAYMENT_META_KEY)?;
-
-    // Validate basic structure: must have x402Version and payload
-    let obj = payment.as_object()?;
-    let version = obj.get("x402Version")?.as_u64()?;
-    if version == 0 {
-        return None;
-    }
-    obj.get("payload")?;
-
-    Some(payment.clone())
+pub fn extract_payment_from_meta(
+    meta: &serde_json::Map<String, Value>,
+) -> Result<Option<PaymentPayload>, MetaPaymentError> {
+    meta.get(PAYMENT_META_KEY)
+        .map(|payment| PaymentPayload::from_value(payment.clone()))
+        .transpose()
 }
 
 /// Attaches an x402 payment payload to an MCP request's `_meta` field.
 ///
-/// Creates the `_meta` map if it doesn't exist. Overwrites any existing
-/// payment data under the [`PAYMENT_META_KEY`].
-pub fn attach_payment_to_meta(meta: &mut serde_json::Map<String, Value>, payment: Value) {
-    meta.insert(PAYMENT_META_KEY.to_owned(), payment);
+/// Overwrites any existing payment data under the [`PAYMENT_META_KEY`].
+///
+/// # Errors
+///
+/// Returns `Err` if the payload cannot be serialized.
+pub fn attach_payment_to_meta(
+    meta: &mut serde_json::Map<String, Value>,
+    payment: &PaymentPayload,
+) -> Result<(), serde_json::Error> {
+    let value = serde_json::to_value(payment)?;
+    meta.insert(PAYMENT_META_KEY.to_owned(), value);
+    Ok(())
 }
 
 /// Extracts an x402 settlement response from an MCP result's `_meta` field.
@@ -197,3 +207,87 @@ pub fn wrap_x402_error_envelope(pr: &proto::PaymentRequired) -> Option<Value> {
         }
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn round_trip(payment: Value) -> PaymentPayload {
+        let payload = PaymentPayload::from_value(payment).unwrap();
+        let mut meta = serde_json::Map::new();
+        attach_payment_to_meta(&mut meta, &payload).unwrap();
+        extract_payment_from_meta(&meta).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_v1_and_v2_payloads_round_trip() {
+        let v1 = json!({
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "base-sepolia",
+            "payload": { "signature": "0xsig" }
+        });
+        let payload = round_trip(v1.clone());
+        let PaymentPayload::V1(payload) = payload else {
+            panic!("expected a V1 payload");
+        };
+        assert_eq!(payload.network, "base-sepolia");
+        assert_eq!(serde_json::to_value(&payload).unwrap(), v1);
+
+        let v2 = json!({
+            "x402Version": 2,
+            "accepted": {
+                "scheme": "exact",
+                "network": "eip155:8453",
+                "amount": "10000",
+                "payTo": "0xpayee",
+                "maxTimeoutSeconds": 60,
+                "asset": "0xusdc"
+            },
+            "payload": { "signature": "0xsig" },
+            "resource": null
+        });
+        let payload = round_trip(v2);
+        assert_eq!(payload.version(), proto::ProtocolVersion::V2);
+        let PaymentPayload::V2(payload) = payload else {
+            panic!("expected a V2 payload");
+        };
+        assert_eq!(payload.accepted.amount, "10000");
+    }
+
+    #[test]
+    fn test_malformed_meta_is_an_error() {
+        let mut meta = serde_json::Map::new();
+        meta.insert(PAYMENT_META_KEY.to_owned(), json!("0xdeadbeef"));
+        assert!(matches!(
+            extract_payment_from_meta(&meta),
+            Err(MetaPaymentError::NotAnObject)
+        ));
+
+        meta.insert(PAYMENT_META_KEY.to_owned(), json!({ "x402Version": 3 }));
+        assert!(matches!(
+            extract_payment_from_meta(&meta),
+            Err(MetaPaymentError::UnsupportedVersion(3))
+        ));
+
+        // A V1-shaped payload declared as V2.
+        meta.insert(
+            PAYMENT_META_KEY.to_owned(),
+            json!({
+                "x402Version": 2,
+                "scheme": "exact",
+                "network": "base-sepolia",
+                "payload": {}
+            }),
+        );
+        assert!(matches!(
+            extract_payment_from_meta(&meta),
+            Err(MetaPaymentError::Malformed {
+                version: proto::ProtocolVersion::V2,
+                ..
+            })
+        ));
+    }
+}
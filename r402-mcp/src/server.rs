@@ -14,6 +14,11 @@
 //! 7. Settle payment via facilitator
 //! 8. `on_after_settlement` hook
 //! 9. Return result with settlement info in `_meta`
+//!
+//! The [`SettlementPolicy`] of the config moves settlement before step 5
+//! ([`SettleEach`](SettlementPolicy::SettleEach)), or replaces steps 7 and 8
+//! with a callback receiving the verified authorization for later batch
+//! settlement ([`VerifyOnly`](SettlementPolicy::VerifyOnly)).
 
 use std::future::Future;
 use std::sync::Arc;
@@ -28,7 +33,8 @@
 use crate::extract::{self, wrap_x402_error_envelope};
 use crate::types::{
     AfterExecutionContext, CallToolParams, CallToolResult, ContentItem, NoServerHooks,
-    PaymentWrapperConfig, ServerHookContext, ServerHooks, SettlementContext,
+    PaymentPayload, PaymentWrapperConfig, ServerHookContext, ServerHooks, SettlementContext,
+    SettlementPolicy, VerifiedPayment,
 };
 
 /// Wraps MCP tool handlers with x402 payment verification and settlement.
@@ -82,7 +88,8 @@ pub fn new(facilitator: Arc<dyn Facilitator>, config: PaymentWrapperConfig) -> S
     /// Processes a tool call request with payment enforcement.
     ///
     /// The `handler` closure is called only after payment verification succeeds.
-    /// Settlement occurs after the handler returns a successful result.
+    /// Whether and when the payment is settled depends on the configured
+    /// [`SettlementPolicy`].
     ///
     /// # Errors
     ///
@@ -97,10 +104,23 @@ pub async fn process<H, Fut>(&self, request: CallToolParams, handler: H) -> Call
         let payment_data = request
             .meta
             .as_ref()
-            .and_then(extract::extract_payment_from_meta);
+            .map(extract::extract_payment_from_meta)
+            .transpose()
+            .map(Option::flatten);
 
-        let Some(payment_value) = payment_data else {
-            return self.payment_required_result("Payment Required");
+        let payment_value = match payment_data {
+            Ok(Some(PaymentPayload::V2(payload))) => match serde_json::to_value(payload) {
+                Ok(value) => value,
+                Err(e) => return self.payment_required_result(&format!("Invalid payment: {e}")),
+            },
+            Ok(Some(payload)) => {
+                return self.payment_required_result(&format!(
+                    "Unsupported payment version {}, expected v2",
+                    payload.version()
+                ));
+            }
+            Ok(None) => return self.payment_required_result("Payment Required"),
+            Err(e) => return self.payment_required_result(&e.to_string()),
         };
 
         // Deserialize to create verify request
@@ -150,21 +170,35 @@ pub async fn process<H, Fut>(&self, request: CallToolParams, handler: H) -> Call
             }
         }
 
+        let settle_request = proto::SettleRequest::from(verify_request);
+
+        // Settle before executing, so the tool only runs once paid
+        let settlement = match &self.config.settlement {
+            SettlementPolicy::SettleEach => {
+                match self.settle(settle_request.clone(), &hook_ctx).await {
+                    Ok(settlement) => Some(settlement),
+                    Err(result) => return result,
+                }
+            }
+            _ => None,
+        };
+
         // Execute the original handler
         let result = match handler(request).await {
             Ok(r) => r,
-            Err(e) => {
-                return CallToolResult {
-                    content: vec![ContentItem::text(e.to_string())],
-                    is_error: true,
-                    ..Default::default()
-                };
-            }
+            Err(e) => CallToolResult {
+                content: vec![ContentItem::text(e.to_string())],
+                is_error: true,
+                ..Default::default()
+            },
         };
 
         // If handler returned an error, don't settle
         if result.is_error {
-            return result;
+            return match settlement {
+                Some(settlement) => attach_settlement(result, &settlement),
+                None => result,
+            };
         }
 
         // on_after_execution hook (non-fatal)
@@ -174,13 +208,39 @@ pub async fn process<H, Fut>(&self, request: CallToolParams, handler: H) -> Call
         };
         let _ = hooks.on_after_execution(&after_exec_ctx).await;
 
-        // Settle payment
-        let settle_request = proto::SettleRequest::from(verify_request);
-        let settle_result = self.facilitator.settle(settle_request).await;
-        let settle_response = match settle_result {
+        match (settlement, &self.config.settlement) {
+            (Some(settlement), _) => attach_settlement(result, &settlement),
+            (None, SettlementPolicy::VerifyOnly(on_verified)) => {
+                let payer = match verify_response {
+                    proto::VerifyResponse::Valid { payer, .. } => payer,
+                    _ => String::new(),
+                };
+                on_verified(VerifiedPayment {
+                    tool_name: hook_ctx.tool_name,
+                    payer,
+                    settle_request,
+                });
+                result
+            }
+            (None, _) => match self.settle(settle_request, &hook_ctx).await {
+                Ok(settlement) => attach_settlement(result, &settlement),
+                Err(result) => result,
+            },
+        }
+    }
+
+    /// Settles a verified payment and runs the `on_after_settlement` hook.
+    ///
+    /// Returns the 402 result to send back if settlement fails.
+    async fn settle(
+        &self,
+        settle_request: proto::SettleRequest,
+        hook_ctx: &ServerHookContext,
+    ) -> Result<proto::SettleResponse, CallToolResult> {
+        let settle_response = match self.facilitator.settle(settle_request).await {
             Ok(resp) => resp,
             Err(e) => {
-                return self.payment_required_result(&format!("Settlement error: {e}"));
+                return Err(self.payment_required_result(&format!("Settlement error: {e}")));
             }
         };
 
@@ -189,28 +249,17 @@ pub async fn process<H, Fut>(&self, request: CallToolParams, handler: H) -> Call
                 proto::SettleResponse::Error { reason, .. } => reason.as_str(),
                 _ => "unknown",
             };
-            return self.payment_required_result(&format!("Settlement failed: {reason}"));
+            return Err(self.payment_required_result(&format!("Settlement failed: {reason}")));
         }
 
         // on_after_settlement hook (non-fatal)
         let settle_ctx = SettlementContext {
-            server_ctx: hook_ctx,
+            server_ctx: hook_ctx.clone(),
             settlement: settle_response.clone(),
         };
-        let _ = hooks.on_after_settlement(&settle_ctx).await;
-
-        // Attach settlement response to result _meta
-        let mut result_meta = result.meta.unwrap_or_default();
-        if let Ok(settle_value) = serde_json::to_value(&settle_response) {
-            result_meta.insert(PAYMENT_RESPONSE_META_KEY.to_owned(), settle_value);
-        }
+        let _ = self.hooks().on_after_settlement(&settle_ctx).await;
 
-        CallToolResult {
-            content: result.content,
-            is_error: result.is_error,
-            meta: Some(result_meta),
-            structured_content: result.structured_content,
-        }
+        Ok(settle_response)
     }
 
     /// Creates a 402 payment required error result.
@@ -257,6 +306,21 @@ fn hooks(&self) -> &dyn ServerHooks {
     }
 }
 
+/// Attaches a settlement response to a tool result's `_meta`.
+fn attach_settlement(result: CallToolResult, settlement: &proto::SettleResponse) -> CallToolResult {
+    let mut result_meta = result.meta.unwrap_or_default();
+    if let Ok(settle_value) = serde_json::to_value(settlement) {
+        result_meta.insert(PAYMENT_RESPONSE_META_KEY.to_owned(), settle_value);
+    }
+
+    CallToolResult {
+        content: result.content,
+        is_error: result.is_error,
+        meta: Some(result_meta),
+        structured_content: result.structured_content,
+    }
+}
+
 /// Builds a [`proto::VerifyRequest`] from a payment payload and requirements.
 fn build_verify_request(
     payment_value: &Value,
@@ -273,3 +337,155 @@ fn build_verify_request(
 
     Ok(proto::VerifyRequest::from(verify_json))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use r402::facilitator::{BoxFuture, FacilitatorError};
+    use serde_json::json;
+
+    use super::*;
+    use crate::PAYMENT_META_KEY;
+
+    #[derive(Default)]
+    struct CountingFacilitator {
+        settled: AtomicUsize,
+    }
+
+    impl Facilitator for CountingFacilitator {
+        fn verify(
+            &self,
+            _request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::VerifyResponse::valid("0xpayer".into())) })
+        }
+
+        fn settle(
+            &self,
+            _request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            self.settled.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async {
+                Ok(proto::SettleResponse::Success {
+                    payer: "0xpayer".into(),
+                    transaction: "0xtx".into(),
+                    network: "eip155:8453".into(),
+                    extensions: None,
+                })
+            })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::SupportedResponse::default()) })
+        }
+    }
+
+    fn requirements() -> v2::PaymentRequirements {
+        serde_json::from_value(json!({
+            "scheme": "exact",
+            "network": "eip155:8453",
+            "amount": "10000",
+            "payTo": "0xpayee",
+            "maxTimeoutSeconds": 60,
+            "asset": "0xusdc"
+        }))
+        .unwrap()
+    }
+
+    fn paid_request() -> CallToolParams {
+        let mut meta = serde_json::Map::new();
+        meta.insert(
+            PAYMENT_META_KEY.to_owned(),
+            json!({
+                "x402Version": 2,
+                "accepted": requirements(),
+                "payload": { "signature": "0xsig" }
+            }),
+        );
+        CallToolParams {
+            name: "weather".to_owned(),
+            meta: Some(meta),
+            ..Default::default()
+        }
+    }
+
+    async fn call(
+        settlement: SettlementPolicy,
+        tool_fails: bool,
+    ) -> (CallToolResult, Arc<CountingFacilitator>) {
+        let facilitator = Arc::new(CountingFacilitator::default());
+        let wrapper = PaymentWrapper::new(
+            Arc::<CountingFacilitator>::clone(&facilitator),
+            PaymentWrapperConfig {
+                accepts: vec![requirements()],
+                settlement,
+                ..Default::default()
+            },
+        );
+        let result = wrapper
+            .process(paid_request(), |_| async move {
+                if tool_fails {
+                    return Err(McpPaymentError::ToolCallFailed("upstream down".into()));
+                }
+                Ok(CallToolResult {
+                    content: vec![ContentItem::text("sunny")],
+                    ..Default::default()
+                })
+            })
+            .await;
+        (result, facilitator)
+    }
+
+    fn settled_in_meta(result: &CallToolResult) -> bool {
+        result
+            .meta
+            .as_ref()
+            .is_some_and(|meta| meta.contains_key(PAYMENT_RESPONSE_META_KEY))
+    }
+
+    #[tokio::test]
+    async fn test_settle_after_success_skips_failed_tools() {
+        let (result, facilitator) = call(SettlementPolicy::SettleAfterSuccess, false).await;
+        assert!(!result.is_error);
+        assert!(settled_in_meta(&result));
+        assert_eq!(facilitator.settled.load(Ordering::SeqCst), 1);
+
+        let (result, facilitator) = call(SettlementPolicy::SettleAfterSuccess, true).await;
+        assert!(result.is_error);
+        assert_eq!(facilitator.settled.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_settle_each_settles_before_the_tool_runs() {
+        let (result, facilitator) = call(SettlementPolicy::SettleEach, true).await;
+        assert!(result.is_error);
+        assert!(settled_in_meta(&result));
+        assert_eq!(facilitator.settled.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_verify_only_hands_authorization_to_callback() {
+        let verified = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&verified);
+        let policy = SettlementPolicy::VerifyOnly(Arc::new(move |payment: VerifiedPayment| {
+            sink.lock().unwrap().push(payment);
+        }));
+
+        let (result, facilitator) = call(policy.clone(), false).await;
+        assert!(!result.is_error);
+        assert!(!settled_in_meta(&result));
+        assert_eq!(facilitator.settled.load(Ordering::SeqCst), 0);
+        {
+            let verified = verified.lock().unwrap();
+            assert_eq!(verified.len(), 1);
+            assert_eq!(verified[0].tool_name, "weather");
+            assert_eq!(verified[0].payer, "0xpayer");
+            assert_eq!(verified[0].settle_request.network(), "eip155:8453");
+        }
+
+        let _ = call(policy, true).await;
+        assert_eq!(verified.lock().unwrap().len(), 1);
+    }
+}
@@ -5,12 +5,62 @@
 //! and configuration structures.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use r402::facilitator::BoxFuture;
 use r402::proto;
+use r402::proto::{ProtocolVersion, v1, v2};
 use serde::{Deserialize, Serialize};
 
-use crate::error::McpPaymentError;
+use crate::error::{McpPaymentError, MetaPaymentError};
+
+/// A payment payload carried in a `_meta` field, typed by protocol version.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum PaymentPayload {
+    /// A V1 payload, naming its scheme and network.
+    V1(v1::PaymentPayloadV1),
+    /// A V2 payload, echoing the accepted requirements.
+    V2(Box<v2::PaymentPayload<v2::PaymentRequirements, serde_json::Value>>),
+}
+
+impl PaymentPayload {
+    /// Parses a payload, detecting its version from `x402Version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MetaPaymentError`] naming what is wrong with `value`.
+    pub fn from_value(value: serde_json::Value) -> Result<Self, MetaPaymentError> {
+        let raw = value
+            .as_object()
+            .ok_or(MetaPaymentError::NotAnObject)?
+            .get("x402Version")
+            .and_then(serde_json::Value::as_u64)
+            .ok_or(MetaPaymentError::MissingVersion)?;
+        let version = u8::try_from(raw)
+            .ok()
+            .and_then(ProtocolVersion::from_u8)
+            .ok_or(MetaPaymentError::UnsupportedVersion(raw))?;
+        let malformed = |source| MetaPaymentError::Malformed { version, source };
+        match version {
+            ProtocolVersion::V1 => serde_json::from_value(value)
+                .map(Self::V1)
+                .map_err(malformed),
+            ProtocolVersion::V2 => serde_json::from_value(value)
+                .map(|payload| Self::V2(Box::new(payload)))
+                .map_err(malformed),
+        }
+    }
+
+    /// Returns the protocol version of the payload.
+    #[must_use]
+    pub const fn version(&self) -> ProtocolVersion {
+        match self {
+            Self::V1(_) => ProtocolVersion::V1,
+            Self::V2(_) => ProtocolVersion::V2,
+        }
+    }
+}
 
 /// Parameters for calling an MCP tool.
 ///
@@ -212,7 +262,7 @@ pub struct ServerHookContext {
     /// The arguments passed to the tool.
     pub arguments: serde_json::Map<String, serde_json::Value>,
     /// The payment requirements matched.
-    pub payment_requirements: proto::v2::PaymentRequirements,
+    pub payment_requirements: v2::PaymentRequirements,
     /// The payment payload from the client.
     pub payment_payload: serde_json::Value,
 }
@@ -273,16 +323,61 @@ fn on_after_settlement(
 
 impl ServerHooks for NoServerHooks {}
 
+/// A verified payment handed over for later settlement.
+///
+/// Passed to the [`SettlementPolicy::VerifyOnly`] callback after the tool
+/// succeeds.
+#[derive(Debug, Clone)]
+pub struct VerifiedPayment {
+    /// The tool that was paid for.
+    pub tool_name: String,
+    /// The payer reported by the facilitator.
+    pub payer: String,
+    /// The verified authorization, ready to be sent to `/settle`.
+    pub settle_request: proto::SettleRequest,
+}
+
+/// Callback receiving verified payments under [`SettlementPolicy::VerifyOnly`].
+pub type VerifiedPaymentCallback = Arc<dyn Fn(VerifiedPayment) + Send + Sync>;
+
+/// Whether and when the [`PaymentWrapper`](crate::server::PaymentWrapper)
+/// settles a verified payment, relative to running the tool.
+#[derive(Clone, Default)]
+pub enum SettlementPolicy {
+    /// Settle before running the tool, which only runs once paid.
+    ///
+    /// The payment is kept even if the tool then fails.
+    SettleEach,
+    /// Run the tool and settle only if it succeeds.
+    #[default]
+    SettleAfterSuccess,
+    /// Never settle; hand each authorization whose tool succeeded to the
+    /// callback, to be settled later in a batch.
+    VerifyOnly(VerifiedPaymentCallback),
+}
+
+impl std::fmt::Debug for SettlementPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SettleEach => f.write_str("SettleEach"),
+            Self::SettleAfterSuccess => f.write_str("SettleAfterSuccess"),
+            Self::VerifyOnly(_) => f.write_str("VerifyOnly(<callback>)"),
+        }
+    }
+}
+
 /// Configuration for the server-side [`PaymentWrapper`](crate::server::PaymentWrapper).
 pub struct PaymentWrapperConfig {
     /// Acceptable payment methods for the wrapped tool.
-    pub accepts: Vec<proto::v2::PaymentRequirements>,
+    pub accepts: Vec<v2::PaymentRequirements>,
     /// Optional resource metadata.
-    pub resource: Option<proto::v2::ResourceInfo>,
+    pub resource: Option<v2::ResourceInfo>,
     /// Optional server-side hooks.
     pub hooks: Option<Box<dyn ServerHooks>>,
     /// Optional protocol extensions.
     pub extensions: Option<HashMap<String, serde_json::Value>>,
+    /// Whether and when payments are settled.
+    pub settlement: SettlementPolicy,
 }
 
 #[allow(clippy::derivable_impls)]
@@ -293,6 +388,7 @@ fn default() -> Self {
             resource: None,
             hooks: None,
             extensions: None,
+            settlement: SettlementPolicy::default(),
         }
     }
 }
@@ -304,6 +400,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             .field("resource", &self.resource)
             .field("hooks", &self.hooks.as_ref().map(|_| "<dyn ServerHooks>"))
             .field("extensions", &self.extensions)
+            .field("settlement", &self.settlement)
             .finish()
     }
 }
@@ -53,7 +53,12 @@ fn from(value: ClientError) -> Self {
 
 impl From<SolanaChainProviderError> for FacilitatorError {
     fn from(value: SolanaChainProviderError) -> Self {
-        Self::OnchainFailure(value.to_string())
+        match value {
+            SolanaChainProviderError::InvalidTransaction(_) => {
+                Self::TransactionFailed(value.to_string())
+            }
+            _ => Self::OnchainFailure(value.to_string()),
+        }
     }
 }
 
@@ -1,7 +1,12 @@
 //! Trait abstraction for Solana RPC client operations.
 //!
 //! This module provides a trait that abstracts common RPC operations,
-//! allowing for easier testing and mocking of Solana RPC interactions.
+//! allowing for easier testing and mocking of Solana RPC interactions, and
+//! [`PriorityFeeEstimator`] to price compute units from recent fees.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
 use solana_account::Account;
 use solana_client::client_error::ClientError;
@@ -64,3 +69,174 @@ fn get_latest_blockhash(&self) -> impl Future<Output = Result<Hash, ClientError>
         RpcClient::get_latest_blockhash(self.as_ref())
     }
 }
+
+/// Default percentile of recent prioritization fees recommended as the
+/// compute unit price.
+pub const DEFAULT_PRIORITY_FEE_PERCENTILE: u8 = 75;
+
+/// Default time an estimated compute unit price is reused for.
+pub const DEFAULT_PRIORITY_FEE_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Compute unit price, in micro-lamports, used when no recent fee sample is
+/// available.
+const FALLBACK_COMPUTE_UNIT_PRICE: u64 = 1;
+
+/// Recommends a compute unit price from recent prioritization fees.
+///
+/// Fees are sampled with `getRecentPrioritizationFees` over the accounts a
+/// transaction writes to, so the estimate reflects contention on those
+/// accounts rather than the whole cluster. The recommendation is a
+/// percentile of the samples, high enough to land during congestion without
+/// paying the outliers. Estimates are cached per account set for a short
+/// time, as fees move slowly compared to payment rates.
+#[derive(Debug)]
+pub struct PriorityFeeEstimator {
+    percentile: u8,
+    ttl: Duration,
+    cache: Mutex<HashMap<Vec<Pubkey>, (u64, Instant)>>,
+}
+
+impl Default for PriorityFeeEstimator {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_PRIORITY_FEE_PERCENTILE,
+            DEFAULT_PRIORITY_FEE_CACHE_TTL,
+        )
+    }
+}
+
+impl PriorityFeeEstimator {
+    /// Creates an estimator recommending the given `percentile` (capped at
+    /// 100) of recent fees, caching estimates for `ttl`.
+    #[must_use]
+    pub fn new(percentile: u8, ttl: Duration) -> Self {
+        Self {
+            percentile: percentile.min(100),
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the recommended compute unit price, in micro-lamports, for a
+    /// transaction writing to `accounts`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError`] if recent fees cannot be fetched.
+    pub async fn estimate<R: RpcClientLike>(
+        &self,
+        rpc_client: &R,
+        accounts: &[Pubkey],
+    ) -> Result<u64, ClientError> {
+        let mut key = accounts.to_vec();
+        key.sort_unstable();
+        key.dedup();
+        if let Some(fee) = self.cached(&key) {
+            return Ok(fee);
+        }
+        let samples = rpc_client.get_recent_prioritization_fees(&key).await?;
+        let fee = fee_percentile(&samples, self.percentile);
+        let now = Instant::now();
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        cache.retain(|_, (_, fetched)| now.duration_since(*fetched) < self.ttl);
+        cache.insert(key, (fee, now));
+        Ok(fee)
+    }
+
+    fn cached(&self, key: &[Pubkey]) -> Option<u64> {
+        let cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        let (fee, fetched) = cache.get(key)?;
+        (fetched.elapsed() < self.ttl).then_some(*fee)
+    }
+}
+
+/// Returns the `percentile` (nearest-rank) of the prioritization fees in
+/// `samples`, or 1 micro-lamport if there are none.
+#[must_use]
+pub fn fee_percentile(samples: &[RpcPrioritizationFee], percentile: u8) -> u64 {
+    let mut fees: Vec<u64> = samples.iter().map(|s| s.prioritization_fee).collect();
+    if fees.is_empty() {
+        return FALLBACK_COMPUTE_UNIT_PRICE;
+    }
+    fees.sort_unstable();
+    let rank = (fees.len() * usize::from(percentile.min(100))).div_ceil(100);
+    fees[rank.saturating_sub(1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use solana_client::client_error::ClientErrorKind;
+    use solana_client::rpc_response::RpcSimulateTransactionResult;
+
+    use super::*;
+
+    /// RPC answering `getRecentPrioritizationFees` with fixed samples.
+    struct FeeRpc {
+        fees: Vec<u64>,
+        calls: AtomicUsize,
+    }
+
+    /// Error returned by the RPC calls fee estimation never makes.
+    fn unused() -> ClientError {
+        ClientErrorKind::Custom("not served by the fee RPC".into()).into()
+    }
+
+    impl RpcClientLike for FeeRpc {
+        async fn get_account(&self, _pubkey: &Pubkey) -> Result<Account, ClientError> {
+            Err(unused())
+        }
+
+        async fn simulate_transaction_with_config(
+            &self,
+            _transaction: &VersionedTransaction,
+            _config: RpcSimulateTransactionConfig,
+        ) -> RpcResult<RpcSimulateTransactionResult> {
+            Err(unused())
+        }
+
+        async fn get_recent_prioritization_fees(
+            &self,
+            _addresses: &[Pubkey],
+        ) -> Result<Vec<RpcPrioritizationFee>, ClientError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(self
+                .fees
+                .iter()
+                .zip(1..)
+                .map(|(&prioritization_fee, slot)| RpcPrioritizationFee {
+                    slot,
+                    prioritization_fee,
+                })
+                .collect())
+        }
+
+        async fn get_latest_blockhash(&self) -> Result<Hash, ClientError> {
+            Err(unused())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_estimate_uses_percentile_and_caches() {
+        let rpc = FeeRpc {
+            fees: vec![0, 0, 500, 100, 10_000, 200, 300, 0],
+            calls: AtomicUsize::new(0),
+        };
+        let estimator = PriorityFeeEstimator::default();
+        let accounts = [
+            Pubkey::new_from_array([1; 32]),
+            Pubkey::new_from_array([2; 32]),
+        ];
+
+        assert_eq!(estimator.estimate(&rpc, &accounts).await.unwrap(), 300);
+        let reversed = [accounts[1], accounts[0]];
+        assert_eq!(estimator.estimate(&rpc, &reversed).await.unwrap(), 300);
+        assert_eq!(rpc.calls.load(Ordering::Relaxed), 1);
+
+        let other = [Pubkey::new_from_array([3; 32])];
+        estimator.estimate(&rpc, &other).await.unwrap();
+        assert_eq!(rpc.calls.load(Ordering::Relaxed), 2);
+        assert_eq!(fee_percentile(&[], 75), FALLBACK_COMPUTE_UNIT_PRICE);
+    }
+}
@@ -7,14 +7,14 @@
 use std::str::FromStr;
 
 use r402::amount::{MoneyAmount, MoneyAmountParseError};
-use r402::chain::{ChainId, DeployedTokenAmount};
+use r402::chain::{BASE58_ALPHABET, ChainId, DeployedTokenAmount};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use solana_pubkey::Pubkey;
 
 /// The CAIP-2 namespace for Solana chains.
 pub const SOLANA_NAMESPACE: &str = "solana";
 
-/// A Solana chain reference consisting of 32 ASCII characters.
+/// A Solana chain reference consisting of 32 base58 characters.
 ///
 /// The reference is the first 32 characters of the base58-encoded genesis block hash,
 /// which uniquely identifies a Solana network. This follows the CAIP-2 standard for
@@ -24,6 +24,7 @@
 ///
 /// - Mainnet: `5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp`
 /// - Devnet: `EtWTRABZaYq6iMfeYKouRu166VU2xqa1`
+/// - Testnet: `4uhcVJyU9pJkvQyS88uRDiswHXSCkY3z`
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SolanaChainReference([u8; 32]);
 
@@ -34,11 +35,14 @@ impl SolanaChainReference {
     /// Solana devnet (`solana:EtWTRABZaYq6iMfeYKouRu166VU2xqa1`).
     pub const SOLANA_DEVNET: Self = Self::new(*b"EtWTRABZaYq6iMfeYKouRu166VU2xqa1");
 
+    /// Solana testnet (`solana:4uhcVJyU9pJkvQyS88uRDiswHXSCkY3z`).
+    pub const SOLANA_TESTNET: Self = Self::new(*b"4uhcVJyU9pJkvQyS88uRDiswHXSCkY3z");
+
     /// Creates a new [`SolanaChainReference`] from a 32-byte ASCII array.
     ///
     /// # Panics
     ///
-    /// This function does not validate that the bytes are valid ASCII.
+    /// This function does not validate that the bytes are valid base58.
     /// Use [`FromStr`] for validated parsing.
     #[must_use]
     pub const fn new(bytes: [u8; 32]) -> Self {
@@ -76,10 +80,17 @@ impl FromStr for SolanaChainReference {
     type Err = SolanaChainReferenceFormatError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if !(s.is_ascii() && s.len() == 32) {
-            return Err(SolanaChainReferenceFormatError::InvalidReference(
-                s.to_string(),
-            ));
+        if let Some(character) = s.chars().find(|c| !BASE58_ALPHABET.contains(*c)) {
+            return Err(SolanaChainReferenceFormatError::InvalidCharacter {
+                reference: s.to_owned(),
+                character,
+            });
+        }
+        if s.len() != 32 {
+            return Err(SolanaChainReferenceFormatError::InvalidLength {
+                reference: s.to_owned(),
+                length: s.len(),
+            });
         }
         let mut bytes = [0u8; 32];
         bytes.copy_from_slice(s.as_bytes());
@@ -126,9 +137,7 @@ fn try_from(value: ChainId) -> Result<Self, Self::Error> {
         if namespace != SOLANA_NAMESPACE {
             return Err(SolanaChainReferenceFormatError::InvalidNamespace(namespace));
         }
-        let solana_chain_reference = Self::from_str(&reference)
-            .map_err(|_| SolanaChainReferenceFormatError::InvalidReference(reference))?;
-        Ok(solana_chain_reference)
+        Self::from_str(&reference)
     }
 }
 
@@ -138,9 +147,22 @@ pub enum SolanaChainReferenceFormatError {
     /// The namespace was not "solana".
     #[error("Invalid namespace {0}, expected solana")]
     InvalidNamespace(String),
-    /// The reference was not a valid 32-character ASCII string.
-    #[error("Invalid solana chain reference {0}")]
-    InvalidReference(String),
+    /// The reference was not exactly 32 characters long.
+    #[error("Invalid solana chain reference {reference}: expected 32 characters, got {length}")]
+    InvalidLength {
+        /// The rejected reference.
+        reference: String,
+        /// Its length in characters.
+        length: usize,
+    },
+    /// The reference contained a character outside the base58 alphabet.
+    #[error("Invalid solana chain reference {reference}: {character:?} is not base58")]
+    InvalidCharacter {
+        /// The rejected reference.
+        reference: String,
+        /// The first offending character.
+        character: char,
+    },
 }
 
 /// Information about an SPL token deployment on a Solana network.
@@ -309,6 +331,34 @@ fn create_test_deployment(decimals: u8) -> SolanaTokenDeployment {
         SolanaTokenDeployment::new(chain_ref, address, decimals)
     }
 
+    #[test]
+    fn test_chain_reference_parsing() {
+        let mainnet: SolanaChainReference = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp".parse().unwrap();
+        assert_eq!(mainnet, SolanaChainReference::SOLANA);
+        let devnet = ChainId::new(SOLANA_NAMESPACE, "EtWTRABZaYq6iMfeYKouRu166VU2xqa1");
+        assert_eq!(
+            SolanaChainReference::try_from(devnet).unwrap(),
+            SolanaChainReference::SOLANA_DEVNET
+        );
+
+        let err = "5eykt4UsFv8P8NJdTREpY1vzqKqZ".parse::<SolanaChainReference>();
+        assert!(matches!(
+            err,
+            Err(SolanaChainReferenceFormatError::InvalidLength { length: 28, .. })
+        ));
+        // `0` and `l` are not in the base58 alphabet.
+        let err = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvd0".parse::<SolanaChainReference>();
+        assert!(matches!(
+            err,
+            Err(SolanaChainReferenceFormatError::InvalidCharacter { character: '0', .. })
+        ));
+        assert!(
+            "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdl"
+                .parse::<ChainId>()
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_parse_whole_number() {
         let deployment = create_test_deployment(6); // 6 decimals like USDC
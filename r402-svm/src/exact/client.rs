@@ -6,31 +6,76 @@
 //! # Features
 //!
 //! - Automatic compute unit estimation via simulation
-//! - Priority fee calculation from recent fees
+//! - Compute unit price estimated from recent prioritization fees, unless
+//!   fixed with [`SolanaExactClient::with_compute_unit_price`]
 //! - SPL Token and Token-2022 support
 //! - Transaction building with proper instruction ordering
+//! - Signing through [`SolanaSignerLike`], so keys can stay in an HSM, KMS or
+//!   hardware wallet
+
+use std::sync::Arc;
 
 use r402::proto::Base64Bytes;
+use r402::proto::Extensions;
 use r402::proto::PaymentRequired;
 use r402::proto::v2::{self, ResourceInfo};
 use r402::scheme::SchemeId;
 use r402::scheme::{ClientError, PaymentCandidate, PaymentCandidateSigner, SchemeClient};
 use solana_client::rpc_config::RpcSimulateTransactionConfig;
 use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_keypair::Keypair;
 use solana_message::v0::Message as MessageV0;
 use solana_message::{Hash, VersionedMessage};
 use solana_pubkey::Pubkey;
 use solana_signature::Signature;
-use solana_signer::Signer;
+use solana_signer::{Signer, SignerError};
 use solana_transaction::Instruction;
 use solana_transaction::versioned::VersionedTransaction;
 use spl_token::solana_program::program_pack::Pack;
 
 use crate::chain::Address;
-use crate::chain::rpc::RpcClientLike;
+use crate::chain::rpc::{PriorityFeeEstimator, RpcClientLike};
 use crate::exact::types;
 use crate::exact::{ATA_PROGRAM_PUBKEY, ExactSolanaPayload, SolanaExact, TransactionInt};
 
+/// A signer of Solana transaction messages.
+///
+/// Implemented for [`Keypair`]; implement it for signers that keep the key
+/// out of process memory, e.g. an HSM, a KMS or a hardware wallet.
+pub trait SolanaSignerLike: Send + Sync {
+    /// Returns the public key of the signer.
+    fn pubkey(&self) -> Pubkey;
+
+    /// Signs the serialized transaction message.
+    fn sign_message(
+        &self,
+        message: &[u8],
+    ) -> impl Future<Output = Result<Signature, SignerError>> + Send;
+}
+
+impl SolanaSignerLike for Keypair {
+    fn pubkey(&self) -> Pubkey {
+        Signer::pubkey(self)
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.try_sign_message(message)
+    }
+}
+
+impl<T: SolanaSignerLike> SolanaSignerLike for Arc<T> {
+    fn pubkey(&self) -> Pubkey {
+        (**self).pubkey()
+    }
+
+    fn sign_message(
+        &self,
+        message: &[u8],
+    ) -> impl Future<Output = Result<Signature, SignerError>> + Send {
+        (**self).sign_message(message)
+    }
+}
+
 /// Mint information for SPL tokens.
 #[derive(Debug, Clone, Copy)]
 pub enum Mint {
@@ -160,31 +205,14 @@ pub async fn estimate_compute_units<S: RpcClientLike>(
     Ok(units as u32)
 }
 
-/// Get the priority fee in micro-lamports.
-///
-/// # Errors
-///
-/// Returns [`ClientError`] if fee retrieval fails.
-pub async fn get_priority_fee_micro_lamports<S: RpcClientLike>(
-    rpc_client: &S,
-    writeable_accounts: &[Pubkey],
-) -> Result<u64, ClientError> {
-    let recent_fees = rpc_client
-        .get_recent_prioritization_fees(writeable_accounts)
-        .await
-        .map_err(|e| ClientError::SigningError(format!("{e:?}")))?;
-    let fee = recent_fees
-        .iter()
-        .filter_map(|e| {
-            if e.prioritization_fee > 0 {
-                Some(e.prioritization_fee)
-            } else {
-                None
-            }
-        })
-        .min_by(Ord::cmp)
-        .unwrap_or(1);
-    Ok(fee)
+/// How the compute unit price of a payment transaction is chosen.
+#[derive(Debug, Clone, Copy)]
+pub enum ComputeUnitPrice<'a> {
+    /// A fixed price, in micro-lamports.
+    Fixed(u64),
+    /// A price recommended from recent prioritization fees of the accounts
+    /// the transaction writes to.
+    Estimated(&'a PriorityFeeEstimator),
 }
 
 /// Update the first `set_compute_unit_limit` ix if it exists, else append a new one.
@@ -210,13 +238,14 @@ pub fn update_or_append_set_compute_unit_limit(ixs: &mut Vec<Instruction>, units
 /// # Errors
 ///
 /// Returns [`ClientError`] if transaction building or signing fails.
-pub async fn build_signed_transfer_transaction<S: Signer + Sync, R: RpcClientLike>(
+pub async fn build_signed_transfer_transaction<S: SolanaSignerLike, R: RpcClientLike>(
     signer: &S,
     rpc_client: &R,
     fee_payer: &Pubkey,
     pay_to: &Address,
     asset: &Address,
     amount: u64,
+    compute_unit_price: ComputeUnitPrice<'_>,
 ) -> Result<String, ClientError> {
     let mint = fetch_mint(asset, rpc_client).await?;
 
@@ -276,9 +305,13 @@ pub async fn build_signed_transfer_transaction<S: Signer + Sync, R: RpcClientLik
         .await
         .map_err(|e| ClientError::SigningError(format!("{e:?}")))?;
 
-    let fee =
-        get_priority_fee_micro_lamports(rpc_client, &[*fee_payer, destination_ata, source_ata])
-            .await?;
+    let fee = match compute_unit_price {
+        ComputeUnitPrice::Fixed(price) => price,
+        ComputeUnitPrice::Estimated(estimator) => estimator
+            .estimate(rpc_client, &[*fee_payer, destination_ata, source_ata])
+            .await
+            .map_err(|e| ClientError::SigningError(format!("{e:?}")))?,
+    };
 
     let (msg_to_sim, instructions) =
         build_message_to_simulate(*fee_payer, &[transfer_instruction], fee, recent_blockhash)?;
@@ -299,10 +332,7 @@ pub async fn build_signed_transfer_transaction<S: Signer + Sync, R: RpcClientLik
         message: VersionedMessage::V0(msg),
     };
 
-    let tx = TransactionInt::new(tx);
-    let signed = tx
-        .sign_with_keypair(signer)
-        .map_err(|e| ClientError::SigningError(format!("{e:?}")))?;
+    let signed = sign_transaction(TransactionInt::new(tx), signer).await?;
     let tx_b64 = signed
         .as_base64()
         .map_err(|e| ClientError::SigningError(format!("{e:?}")))?;
@@ -310,11 +340,29 @@ pub async fn build_signed_transfer_transaction<S: Signer + Sync, R: RpcClientLik
     Ok(tx_b64)
 }
 
+/// Signs `tx` as `signer`, which must be one of its required signers.
+async fn sign_transaction<S: SolanaSignerLike>(
+    tx: TransactionInt,
+    signer: &S,
+) -> Result<TransactionInt, ClientError> {
+    let signature = signer
+        .sign_message(&tx.message_bytes())
+        .await
+        .map_err(|e| ClientError::SigningError(format!("{e}")))?;
+    tx.with_signature(&signer.pubkey(), signature)
+        .map_err(|e| ClientError::SigningError(format!("{e:?}")))
+}
+
 /// Solana exact scheme client for building and signing payment transactions.
+///
+/// The compute unit price is estimated with a [`PriorityFeeEstimator`]
+/// unless fixed with [`Self::with_compute_unit_price`].
 #[derive(Clone)]
 pub struct SolanaExactClient<S, R> {
     signer: S,
     rpc_client: R,
+    compute_unit_price: Option<u64>,
+    fee_estimator: Arc<PriorityFeeEstimator>,
 }
 
 impl<S, R> std::fmt::Debug for SolanaExactClient<S, R> {
@@ -325,8 +373,29 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 
 impl<S, R> SolanaExactClient<S, R> {
     /// Creates a new Solana exact client.
-    pub const fn new(signer: S, rpc_client: R) -> Self {
-        Self { signer, rpc_client }
+    pub fn new(signer: S, rpc_client: R) -> Self {
+        Self {
+            signer,
+            rpc_client,
+            compute_unit_price: None,
+            fee_estimator: Arc::new(PriorityFeeEstimator::default()),
+        }
+    }
+
+    /// Pays a fixed compute unit price, in micro-lamports, instead of
+    /// estimating it from recent fees.
+    #[must_use]
+    pub const fn with_compute_unit_price(mut self, micro_lamports: u64) -> Self {
+        self.compute_unit_price = Some(micro_lamports);
+        self
+    }
+
+    /// Estimates compute unit prices with `estimator`, e.g. to pick another
+    /// percentile of recent fees.
+    #[must_use]
+    pub fn with_fee_estimator(mut self, estimator: PriorityFeeEstimator) -> Self {
+        self.fee_estimator = Arc::new(estimator);
+        self
     }
 }
 
@@ -342,7 +411,7 @@ fn scheme(&self) -> &str {
 
 impl<S, R> SchemeClient for SolanaExactClient<S, R>
 where
-    S: Signer + Send + Sync + Clone + 'static,
+    S: SolanaSignerLike + Clone + 'static,
     R: RpcClientLike + Send + Sync + Clone + 'static,
 {
     fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
@@ -364,6 +433,10 @@ fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
                     signer: Box::new(V2PayloadSigner {
                         signer: self.signer.clone(),
                         rpc_client: self.rpc_client.clone(),
+                        compute_unit_price: self.compute_unit_price,
+                        fee_estimator: Arc::clone(&self.fee_estimator),
+                        extensions: requirements
+                            .select_required_extensions(payment_required.extensions.as_ref()),
                         requirements,
                         resource: payment_required.resource.clone(),
                     }),
@@ -377,11 +450,16 @@ fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
 struct V2PayloadSigner<S, R> {
     signer: S,
     rpc_client: R,
+    compute_unit_price: Option<u64>,
+    fee_estimator: Arc<PriorityFeeEstimator>,
     requirements: types::v2::PaymentRequirements,
     resource: ResourceInfo,
+    extensions: Option<Extensions>,
 }
 
-impl<S: Signer + Sync, R: RpcClientLike + Sync> PaymentCandidateSigner for V2PayloadSigner<S, R> {
+impl<S: SolanaSignerLike, R: RpcClientLike + Sync> PaymentCandidateSigner
+    for V2PayloadSigner<S, R>
+{
     fn sign_payment(&self) -> r402::facilitator::BoxFuture<'_, Result<String, ClientError>> {
         Box::pin(async move {
             let fee_payer = self
@@ -395,6 +473,10 @@ fn sign_payment(&self) -> r402::facilitator::BoxFuture<'_, Result<String, Client
             let fee_payer_pubkey: Pubkey = fee_payer.into();
 
             let amount = self.requirements.amount.inner();
+            let compute_unit_price = self.compute_unit_price.map_or(
+                ComputeUnitPrice::Estimated(&self.fee_estimator),
+                ComputeUnitPrice::Fixed,
+            );
             let tx_b64 = build_signed_transfer_transaction(
                 &self.signer,
                 &self.rpc_client,
@@ -402,6 +484,7 @@ fn sign_payment(&self) -> r402::facilitator::BoxFuture<'_, Result<String, Client
                 &self.requirements.pay_to,
                 &self.requirements.asset,
                 amount,
+                compute_unit_price,
             )
             .await?;
 
@@ -412,7 +495,7 @@ fn sign_payment(&self) -> r402::facilitator::BoxFuture<'_, Result<String, Client
                 payload: ExactSolanaPayload {
                     transaction: tx_b64,
                 },
-                extensions: None,
+                extensions: self.extensions.clone(),
             };
             let json = serde_json::to_vec(&payload)?;
             let b64 = Base64Bytes::encode(&json);
@@ -421,3 +504,63 @@ fn sign_payment(&self) -> r402::facilitator::BoxFuture<'_, Result<String, Client
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A signer holding its key "remotely", behind an async boundary.
+    struct RemoteSigner {
+        key: Arc<Keypair>,
+    }
+
+    impl SolanaSignerLike for RemoteSigner {
+        fn pubkey(&self) -> Pubkey {
+            Signer::pubkey(self.key.as_ref())
+        }
+
+        async fn sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+            let key = Arc::clone(&self.key);
+            let message = message.to_vec();
+            tokio::task::spawn_blocking(move || key.try_sign_message(&message))
+                .await
+                .map_err(|e| SignerError::Custom(e.to_string()))?
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_signs_transaction_message() {
+        let fee_payer = Pubkey::new_from_array([7; 32]);
+        let key = Arc::new(Keypair::new_from_array([9; 32]));
+        let signer = Arc::new(RemoteSigner {
+            key: Arc::clone(&key),
+        });
+        let owner = SolanaSignerLike::pubkey(&signer);
+        let instruction = spl_token::instruction::transfer(
+            &spl_token::id(),
+            &Pubkey::new_from_array([1; 32]),
+            &Pubkey::new_from_array([2; 32]),
+            &owner,
+            &[],
+            1,
+        )
+        .unwrap();
+        let message =
+            MessageV0::try_compile(&fee_payer, &[instruction], &[], Hash::default()).unwrap();
+        let tx = TransactionInt::new(VersionedTransaction {
+            signatures: vec![],
+            message: VersionedMessage::V0(message),
+        });
+        let message_bytes = tx.message_bytes();
+
+        let signed = sign_transaction(tx, &signer).await.unwrap();
+
+        let signatures = &signed.inner().signatures;
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(signatures[0], Signature::default());
+        assert_eq!(
+            signatures[1],
+            Signer::sign_message(key.as_ref(), &message_bytes)
+        );
+    }
+}
@@ -63,6 +63,12 @@ pub enum SolanaExactError {
     /// Sender account is missing from the transaction.
     #[error("Missing sender account in transaction")]
     MissingSenderAccount,
+    /// Durable nonce `AdvanceNonceAccount` instruction could not be parsed.
+    #[error("Invalid advance nonce account instruction")]
+    InvalidAdvanceNonceInstruction,
+    /// Token-2022 mint account is missing or could not be parsed.
+    #[error("Missing or invalid mint account")]
+    InvalidMintAccount,
 }
 
 impl From<SolanaExactError> for PaymentVerificationError {
@@ -85,6 +91,8 @@ fn from(e: SolanaExactError) -> Self {
             | SolanaExactError::EmptyInstructionAtIndex(_)
             | SolanaExactError::FeePayerTransferringFunds
             | SolanaExactError::MissingSenderAccount
+            | SolanaExactError::InvalidAdvanceNonceInstruction
+            | SolanaExactError::InvalidMintAccount
             | SolanaExactError::InvalidComputePriceInstruction => {
                 Self::TransactionSimulation(e.to_string())
             }
@@ -3,6 +3,7 @@
 //! Controls transaction verification behavior, including support for
 //! additional instructions from third-party wallets like Phantom.
 
+use r402::proto::ParseMode;
 use serde::{Deserialize, Serialize};
 use solana_pubkey::Pubkey;
 
@@ -48,6 +49,62 @@ pub struct SolanaExactFacilitatorConfig {
     /// Default: true - strongly recommended to keep this enabled
     #[serde(default = "default_require_fee_payer_not_in_instructions")]
     pub require_fee_payer_not_in_instructions: bool,
+
+    /// Accept transactions signed against a durable nonce account.
+    ///
+    /// When enabled, a leading `AdvanceNonceAccount` instruction is allowed
+    /// and the required compute budget and transfer instructions are expected
+    /// right after it. The nonce is advanced when the transaction settles, so
+    /// pre-signed payments no longer expire with the recent blockhash.
+    /// Default: false
+    #[serde(default)]
+    pub allow_durable_nonce: bool,
+
+    /// Locate the compute budget and transfer instructions by program and
+    /// instruction type instead of expecting them at fixed positions.
+    ///
+    /// Some wallets prepend or reorder instructions, shifting the compute
+    /// limit, compute price and transfer instructions away from the first
+    /// indices. Every other instruction is still checked against
+    /// `allowed_program_ids` and `blocked_program_ids`, and still counts
+    /// towards `max_instruction_count`.
+    /// Default: false
+    #[serde(default)]
+    pub locate_required_instructions: bool,
+
+    /// Maximum compute unit limit a transaction may request.
+    ///
+    /// The facilitator pays the fees, so this bounds the compute budget a
+    /// client can make it pay for. The provider's own limit still applies;
+    /// the lower of the two is enforced.
+    /// Default: 400000
+    #[serde(default = "default_max_compute_unit_limit")]
+    pub max_compute_unit_limit: u32,
+
+    /// Maximum compute unit price (priority fee) in micro-lamports.
+    ///
+    /// Protects the fee payer from clients that inflate priority fees at its
+    /// expense. The provider's own limit still applies; the lower of the two
+    /// is enforced.
+    /// Default: 5000000
+    #[serde(default = "default_max_compute_unit_price")]
+    pub max_compute_unit_price: u64,
+
+    /// Smallest transfer amount, in atomic token units, accepted for
+    /// settlement.
+    ///
+    /// Rejects dust payments that are not worth the transaction fee. This is
+    /// facilitator policy, independent of the price in the requirements;
+    /// clients paying less than this should batch payments off-band.
+    /// Default: none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_settle_amount: Option<u64>,
+
+    /// Whether verify and settle requests with fields unknown to this
+    /// version are accepted (`tolerant`) or rejected (`strict`).
+    /// Default: tolerant
+    #[serde(default)]
+    pub parse_mode: ParseMode,
 }
 
 const fn default_allow_additional_instructions() -> bool {
@@ -66,6 +123,14 @@ const fn default_require_fee_payer_not_in_instructions() -> bool {
     true
 }
 
+const fn default_max_compute_unit_limit() -> u32 {
+    400_000
+}
+
+const fn default_max_compute_unit_price() -> u64 {
+    5_000_000
+}
+
 impl Default for SolanaExactFacilitatorConfig {
     fn default() -> Self {
         Self {
@@ -74,6 +139,12 @@ fn default() -> Self {
             allowed_program_ids: default_allowed_program_ids(),
             blocked_program_ids: Vec::new(),
             require_fee_payer_not_in_instructions: default_require_fee_payer_not_in_instructions(),
+            allow_durable_nonce: false,
+            locate_required_instructions: false,
+            max_compute_unit_limit: default_max_compute_unit_limit(),
+            max_compute_unit_price: default_max_compute_unit_price(),
+            min_settle_amount: None,
+            parse_mode: ParseMode::Tolerant,
         }
     }
 }
@@ -15,9 +15,12 @@
 use r402::proto::v2;
 use r402::scheme::{SchemeBuilder, SchemeId};
 pub use verify::{
-    TransferCheckedInstruction, TransferRequirement, VerifyTransferResult, settle_transaction,
-    validate_instructions, verify_compute_limit_instruction, verify_compute_price_instruction,
-    verify_transaction, verify_transfer, verify_transfer_instruction,
+    RequiredInstructions, TransferCheckedInstruction, TransferRequirement, VerifyTransferResult,
+    assert_transaction_structure, assert_transfer_preconditions, locate_required_instructions,
+    required_instructions_offset, settle_transaction, validate_instructions,
+    verify_advance_nonce_instruction, verify_compute_limit_instruction,
+    verify_compute_price_instruction, verify_transaction, verify_transfer,
+    verify_transfer_instruction,
 };
 
 use crate::chain::provider::SolanaChainProviderLike;
@@ -71,7 +74,8 @@ fn verify(
         request: proto::VerifyRequest,
     ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
         Box::pin(async move {
-            let request = types::v2::VerifyRequest::from_proto(request)?;
+            let request =
+                types::v2::VerifyRequest::from_proto_with(request, self.config.parse_mode)?;
             let verification = verify_transfer(&self.provider, &request, &self.config).await?;
             Ok(v2::VerifyResponse::valid(verification.payer.to_string()))
         })
@@ -82,19 +86,51 @@ fn settle(
         request: proto::SettleRequest,
     ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
         Box::pin(async move {
-            let request = types::v2::SettleRequest::from_settle(request)?;
+            let request =
+                types::v2::SettleRequest::from_settle_with(request, self.config.parse_mode)?;
             let verification = verify_transfer(&self.provider, &request, &self.config).await?;
             let payer = verification.payer.to_string();
             let tx_sig = settle_transaction(&self.provider, verification).await?;
+            let transaction = tx_sig.to_string();
+            let network = request.payment_requirements.network.to_string();
+            #[cfg(feature = "telemetry")]
+            {
+                let requirements = &request.payment_requirements;
+                r402::facilitator::SettlementRecord {
+                    payer: &payer,
+                    pay_to: &requirements.pay_to.to_string(),
+                    asset: &requirements.asset.to_string(),
+                    amount: &requirements.amount.inner().to_string(),
+                    network: &network,
+                    transaction: &transaction,
+                    signer: Some(&self.provider.fee_payer().to_string()),
+                }
+                .log();
+            }
             Ok(v2::SettleResponse::Success {
                 payer,
-                transaction: tx_sig.to_string(),
-                network: self.provider.chain_id().to_string(),
+                transaction,
+                network,
                 extensions: None,
             })
         })
     }
 
+    fn pre_validate(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<(), proto::PaymentVerificationError> {
+        let request =
+            types::v2::VerifyRequest::from_proto_with(request.clone(), self.config.parse_mode)?;
+        assert_transfer_preconditions(&self.provider, &request)?;
+        assert_transaction_structure(
+            &self.provider,
+            &request.payment_payload.payload.transaction,
+            &self.config,
+        )?;
+        Ok(())
+    }
+
     fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
         Box::pin(async move {
             let chain_id = self.provider.chain_id();
@@ -117,6 +153,7 @@ fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, Facilitato
                 kinds,
                 extensions: Vec::new(),
                 signers,
+                attestation_key: None,
             })
         })
     }
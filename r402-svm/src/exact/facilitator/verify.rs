@@ -13,15 +13,17 @@
 use solana_pubkey::Pubkey;
 use solana_signature::Signature;
 use solana_transaction::versioned::VersionedTransaction;
+use spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
 #[cfg(feature = "telemetry")]
 use tracing_core::Level;
 
 use super::config::SolanaExactFacilitatorConfig;
 use crate::chain::Address;
 use crate::chain::provider::{SolanaChainProviderError, SolanaChainProviderLike};
-use crate::exact::ATA_PROGRAM_PUBKEY;
 use crate::exact::error::SolanaExactError;
 use crate::exact::types::{self, TransactionInt};
+use crate::exact::{ATA_PROGRAM_PUBKEY, SYSTEM_PROGRAM_PUBKEY};
 
 /// Result of a successful transfer verification.
 #[derive(Debug)]
@@ -64,8 +66,9 @@ pub struct TransferRequirement<'a> {
 ///
 /// # Errors
 ///
-/// Returns [`SolanaExactError`] if the instruction is invalid.
+/// Returns [`SolanaExactError`] if the instruction is invalid or the limit exceeds max.
 pub fn verify_compute_limit_instruction(
+    max_compute_unit_limit: u32,
     transaction: &VersionedTransaction,
     instruction_index: usize,
 ) -> Result<u32, SolanaExactError> {
@@ -86,6 +89,9 @@ pub fn verify_compute_limit_instruction(
     let mut buf = [0u8; 4];
     buf.copy_from_slice(&data[1..5]);
     let compute_units = u32::from_le_bytes(buf);
+    if compute_units > max_compute_unit_limit {
+        return Err(SolanaExactError::MaxComputeUnitLimitExceeded);
+    }
 
     Ok(compute_units)
 }
@@ -119,8 +125,167 @@ pub fn verify_compute_price_instruction(
     Ok(())
 }
 
+/// `SystemInstruction::AdvanceNonceAccount` discriminant (bincode `u32`).
+const ADVANCE_NONCE_ACCOUNT_DISCRIMINANT: [u8; 4] = 4u32.to_le_bytes();
+
+/// Verifies the durable nonce `AdvanceNonceAccount` instruction at the given index.
+///
+/// Returns the nonce account the transaction was signed against.
+///
+/// # Errors
+///
+/// Returns [`SolanaExactError`] if the instruction is invalid.
+pub fn verify_advance_nonce_instruction(
+    transaction: &VersionedTransaction,
+    instruction_index: usize,
+) -> Result<Pubkey, SolanaExactError> {
+    let instructions = transaction.message.instructions();
+    let instruction = instructions
+        .get(instruction_index)
+        .ok_or(SolanaExactError::NoInstructionAtIndex(instruction_index))?;
+    let account_keys = transaction.message.static_account_keys();
+    let account = instruction.program_id(account_keys);
+
+    // Accounts: [nonce account, recent blockhashes sysvar, nonce authority]
+    if SYSTEM_PROGRAM_PUBKEY.ne(account)
+        || instruction.data.as_slice() != ADVANCE_NONCE_ACCOUNT_DISCRIMINANT
+        || instruction.accounts.len() != 3
+    {
+        return Err(SolanaExactError::InvalidAdvanceNonceInstruction);
+    }
+
+    let nonce_account_index = instruction.accounts[0];
+    account_keys
+        .get(usize::from(nonce_account_index))
+        .copied()
+        .ok_or(SolanaExactError::NoAccountAtIndex(nonce_account_index))
+}
+
+/// Returns the index of the compute limit instruction, i.e. the number of
+/// instructions preceding the required ones.
+///
+/// This is `1` when durable nonces are enabled and the transaction starts with
+/// a valid `AdvanceNonceAccount` instruction, and `0` otherwise.
+///
+/// # Errors
+///
+/// Returns [`SolanaExactError`] if the transaction starts with a malformed
+/// system program instruction while durable nonces are enabled.
+pub fn required_instructions_offset(
+    transaction: &VersionedTransaction,
+    config: &SolanaExactFacilitatorConfig,
+) -> Result<usize, SolanaExactError> {
+    if !config.allow_durable_nonce || get_program_id(transaction, 0) != Some(SYSTEM_PROGRAM_PUBKEY)
+    {
+        return Ok(0);
+    }
+    verify_advance_nonce_instruction(transaction, 0)?;
+    Ok(1)
+}
+
+/// Positions of the instructions every payment transaction must contain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequiredInstructions {
+    /// Index of the compute unit limit instruction.
+    pub compute_limit: usize,
+    /// Index of the compute unit price instruction.
+    pub compute_price: usize,
+    /// Index of the SPL Token `TransferChecked` instruction.
+    pub transfer: usize,
+}
+
+impl RequiredInstructions {
+    /// Returns the standard layout, starting at `offset`.
+    const fn at_offset(offset: usize) -> Self {
+        Self {
+            compute_limit: offset,
+            compute_price: offset + 1,
+            transfer: offset + 2,
+        }
+    }
+
+    /// Returns `true` if the instruction at `index` is a required one.
+    const fn contains(&self, index: usize) -> bool {
+        index == self.compute_limit || index == self.compute_price || index == self.transfer
+    }
+}
+
+/// `ComputeBudgetInstruction::SetComputeUnitLimit` discriminant.
+const SET_COMPUTE_UNIT_LIMIT_DISCRIMINANT: u8 = 2;
+/// `ComputeBudgetInstruction::SetComputeUnitPrice` discriminant.
+const SET_COMPUTE_UNIT_PRICE_DISCRIMINANT: u8 = 3;
+/// `TokenInstruction::TransferChecked` discriminant.
+const TRANSFER_CHECKED_DISCRIMINANT: u8 = 12;
+
+/// Returns the positions of the required instructions.
+///
+/// They follow the optional durable nonce instruction in a fixed order,
+/// unless [`SolanaExactFacilitatorConfig::locate_required_instructions`] is
+/// set, in which case the first instruction of each type is used wherever it
+/// is.
+///
+/// # Errors
+///
+/// Returns [`SolanaExactError`] if the durable nonce instruction is malformed
+/// or, when locating, a required instruction is missing.
+pub fn locate_required_instructions(
+    transaction: &VersionedTransaction,
+    config: &SolanaExactFacilitatorConfig,
+) -> Result<RequiredInstructions, SolanaExactError> {
+    let offset = required_instructions_offset(transaction, config)?;
+    locate_required_instructions_after(transaction, config, offset)
+}
+
+/// Returns the positions of the required instructions, which follow the
+/// first `offset` instructions.
+fn locate_required_instructions_after(
+    transaction: &VersionedTransaction,
+    config: &SolanaExactFacilitatorConfig,
+    offset: usize,
+) -> Result<RequiredInstructions, SolanaExactError> {
+    if !config.locate_required_instructions {
+        return Ok(RequiredInstructions::at_offset(offset));
+    }
+
+    let account_keys = transaction.message.static_account_keys();
+    let find = |is_required: &dyn Fn(&Pubkey, u8) -> bool| {
+        transaction
+            .message
+            .instructions()
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .find(|(_, instruction)| {
+                let program_id = instruction.program_id(account_keys);
+                instruction
+                    .data
+                    .first()
+                    .is_some_and(|kind| is_required(program_id, *kind))
+            })
+            .map(|(index, _)| index)
+    };
+    let compute_budget = |kind| {
+        move |program_id: &Pubkey, found| ComputeBudgetInstructionId.eq(program_id) && found == kind
+    };
+    Ok(RequiredInstructions {
+        compute_limit: find(&compute_budget(SET_COMPUTE_UNIT_LIMIT_DISCRIMINANT))
+            .ok_or(SolanaExactError::InvalidComputeLimitInstruction)?,
+        compute_price: find(&compute_budget(SET_COMPUTE_UNIT_PRICE_DISCRIMINANT))
+            .ok_or(SolanaExactError::InvalidComputePriceInstruction)?,
+        transfer: find(&|program_id, kind| {
+            (spl_token::ID.eq(program_id) || spl_token_2022::ID.eq(program_id))
+                && kind == TRANSFER_CHECKED_DISCRIMINANT
+        })
+        .ok_or(SolanaExactError::InvalidTokenInstruction)?,
+    })
+}
+
 /// Validates the instruction structure of the transaction.
 ///
+/// Every instruction besides the durable nonce and the
+/// [required ones](locate_required_instructions) must be an allowed
+/// additional instruction.
+///
 /// # Errors
 ///
 /// Returns [`SolanaExactError`] if instruction validation fails.
@@ -129,8 +294,9 @@ pub fn validate_instructions(
     config: &SolanaExactFacilitatorConfig,
 ) -> Result<(), SolanaExactError> {
     let instructions = transaction.message.instructions();
+    let offset = required_instructions_offset(transaction, config)?;
 
-    if instructions.len() < 3 {
+    if instructions.len() <= offset + 2 {
         return Err(SolanaExactError::TooFewInstructions);
     }
 
@@ -140,25 +306,24 @@ pub fn validate_instructions(
         ));
     }
 
-    let ix2_program = get_program_id(transaction, 2);
-    if ix2_program == Some(ATA_PROGRAM_PUBKEY) {
+    let required = locate_required_instructions_after(transaction, config, offset)?;
+    let transfer_program = get_program_id(transaction, required.transfer);
+    if transfer_program == Some(ATA_PROGRAM_PUBKEY) {
         return Err(SolanaExactError::CreateATANotSupported);
     }
 
-    if instructions.len() > 3 {
+    for i in (offset..instructions.len()).filter(|i| !required.contains(*i)) {
         if !config.allow_additional_instructions {
             return Err(SolanaExactError::AdditionalInstructionsNotAllowed);
         }
 
-        for i in 3..instructions.len() {
-            if let Some(program_id) = get_program_id(transaction, i) {
-                if config.is_blocked(&program_id) {
-                    return Err(SolanaExactError::BlockedProgram(program_id));
-                }
+        if let Some(program_id) = get_program_id(transaction, i) {
+            if config.is_blocked(&program_id) {
+                return Err(SolanaExactError::BlockedProgram(program_id));
+            }
 
-                if !config.is_allowed(&program_id) {
-                    return Err(SolanaExactError::ProgramNotAllowed(program_id));
-                }
+            if !config.is_allowed(&program_id) {
+                return Err(SolanaExactError::ProgramNotAllowed(program_id));
             }
         }
     }
@@ -182,6 +347,34 @@ pub async fn verify_transfer<P: SolanaChainProviderLike + ChainProvider>(
     request: &types::v2::VerifyRequest,
     config: &SolanaExactFacilitatorConfig,
 ) -> Result<VerifyTransferResult, PaymentVerificationError> {
+    assert_transfer_preconditions(provider, request)?;
+    let payload = &request.payment_payload;
+    let requirements = &request.payment_requirements;
+    let transaction_b64_string = payload.payload.transaction.clone();
+    let transfer_requirement = TransferRequirement {
+        pay_to: &requirements.pay_to,
+        asset: &requirements.asset,
+        amount: requirements.amount.inner(),
+    };
+    verify_transaction(
+        provider,
+        transaction_b64_string,
+        &transfer_requirement,
+        config,
+    )
+    .await
+}
+
+/// Checks that the payload matches the requirements and targets the
+/// provider's chain.
+///
+/// # Errors
+///
+/// Returns [`PaymentVerificationError`] on the first mismatch.
+pub fn assert_transfer_preconditions<P: ChainProvider>(
+    provider: &P,
+    request: &types::v2::VerifyRequest,
+) -> Result<(), PaymentVerificationError> {
     let payload = &request.payment_payload;
     let requirements = &request.payment_requirements;
 
@@ -194,56 +387,83 @@ pub async fn verify_transfer<P: SolanaChainProviderLike + ChainProvider>(
     {
         return Err(PaymentVerificationError::AcceptedRequirementsMismatch);
     }
+    requirements.check_required_extensions(payload.extensions.as_ref())?;
 
     let chain_id = provider.chain_id();
     let payload_chain_id = &accepted.network;
     if payload_chain_id != &chain_id {
         return Err(PaymentVerificationError::UnsupportedChain);
     }
-    let transaction_b64_string = payload.payload.transaction.clone();
-    let transfer_requirement = TransferRequirement {
-        pay_to: &requirements.pay_to,
-        asset: &requirements.asset,
-        amount: requirements.amount.inner(),
-    };
-    verify_transaction(
-        provider,
-        transaction_b64_string,
-        &transfer_requirement,
-        config,
-    )
-    .await
+    Ok(())
 }
 
-/// Verifies a base64-encoded transaction against requirements.
+/// Decodes a base64-encoded transaction and checks its instruction layout
+/// and compute budget, without any RPC calls.
+///
+/// Returns the transaction and the positions of its required instructions.
 ///
 /// # Errors
 ///
-/// Returns [`PaymentVerificationError`] if verification fails.
-pub async fn verify_transaction<P: SolanaChainProviderLike>(
+/// Returns [`PaymentVerificationError`] if decoding or a check fails.
+pub fn assert_transaction_structure<P: SolanaChainProviderLike>(
     provider: &P,
-    transaction_b64_string: String,
-    transfer_requirement: &TransferRequirement<'_>,
+    transaction_b64_string: &str,
     config: &SolanaExactFacilitatorConfig,
-) -> Result<VerifyTransferResult, PaymentVerificationError> {
+) -> Result<(VersionedTransaction, RequiredInstructions), PaymentVerificationError> {
     let bytes = Base64Bytes::from(transaction_b64_string.as_bytes())
         .decode()
         .map_err(|e| SolanaExactError::TransactionDecoding(e.to_string()))?;
     let transaction = bincode::deserialize::<VersionedTransaction>(bytes.as_slice())
         .map_err(|e| SolanaExactError::TransactionDecoding(e.to_string()))?;
 
-    let compute_units = verify_compute_limit_instruction(&transaction, 0)?;
-    if compute_units > provider.max_compute_unit_limit() {
-        return Err(SolanaExactError::MaxComputeUnitLimitExceeded.into());
-    }
+    let required = locate_required_instructions(&transaction, config)?;
+    let max_compute_unit_limit = config
+        .max_compute_unit_limit
+        .min(provider.max_compute_unit_limit());
+    let compute_units = verify_compute_limit_instruction(
+        max_compute_unit_limit,
+        &transaction,
+        required.compute_limit,
+    )?;
     #[cfg(feature = "telemetry")]
     tracing::debug!(compute_units = compute_units, "Verified compute unit limit");
-    verify_compute_price_instruction(provider.max_compute_unit_price(), &transaction, 1)?;
+    let max_compute_unit_price = config
+        .max_compute_unit_price
+        .min(provider.max_compute_unit_price());
+    verify_compute_price_instruction(max_compute_unit_price, &transaction, required.compute_price)?;
 
     validate_instructions(&transaction, config)?;
+    Ok((transaction, required))
+}
+
+/// Verifies a base64-encoded transaction against requirements.
+///
+/// # Errors
+///
+/// Returns [`PaymentVerificationError`] if verification fails.
+pub async fn verify_transaction<P: SolanaChainProviderLike>(
+    provider: &P,
+    transaction_b64_string: String,
+    transfer_requirement: &TransferRequirement<'_>,
+    config: &SolanaExactFacilitatorConfig,
+) -> Result<VerifyTransferResult, PaymentVerificationError> {
+    let (transaction, required) =
+        assert_transaction_structure(provider, &transaction_b64_string, config)?;
 
-    let transfer_instruction =
-        verify_transfer_instruction(provider, &transaction, 2, transfer_requirement).await?;
+    let transfer_instruction = verify_transfer_instruction(
+        provider,
+        &transaction,
+        required.transfer,
+        transfer_requirement,
+    )
+    .await?;
+    if let Some(minimum) = config.min_settle_amount
+        && transfer_instruction.amount < minimum
+    {
+        return Err(PaymentVerificationError::BelowMinimumAmount(
+            minimum.to_string(),
+        ));
+    }
 
     if config.require_fee_payer_not_in_instructions {
         let fee_payer_pubkey = provider.pubkey();
@@ -343,9 +563,14 @@ pub async fn verify_transfer_instruction<P: SolanaChainProviderLike>(
     if transfer_checked_instruction.destination != ata {
         return Err(PaymentVerificationError::RecipientMismatch);
     }
-    let accounts = provider
-        .get_multiple_accounts(&[transfer_checked_instruction.source, ata])
-        .await?;
+    // Token-2022 mints may charge a transfer fee, so the mint is fetched along
+    // with the token accounts to check the net amount the recipient receives.
+    let is_token_2022 = token_program == spl_token_2022::ID;
+    let mut pubkeys = vec![transfer_checked_instruction.source, ata];
+    if is_token_2022 {
+        pubkeys.push(transfer_checked_instruction.mint);
+    }
+    let accounts = provider.get_multiple_accounts(&pubkeys).await?;
     let is_sender_missing = accounts.first().cloned().is_none_or(|a| a.is_none());
     if is_sender_missing {
         return Err(SolanaExactError::MissingSenderAccount.into());
@@ -355,12 +580,51 @@ pub async fn verify_transfer_instruction<P: SolanaChainProviderLike>(
         return Err(PaymentVerificationError::RecipientMismatch);
     }
     let instruction_amount = transfer_checked_instruction.amount;
-    if instruction_amount < transfer_requirement.amount {
+    let received_amount = if is_token_2022 {
+        let mint = accounts
+            .get(2)
+            .and_then(Option::as_ref)
+            .ok_or(SolanaExactError::InvalidMintAccount)?;
+        net_transfer_amount(&mint.data, instruction_amount)?
+    } else {
+        instruction_amount
+    };
+    // A zero-amount transfer settles nothing, whatever the requirements say.
+    if received_amount == 0
+        || transfer_requirement.amount == 0
+        || received_amount < transfer_requirement.amount
+    {
         return Err(PaymentVerificationError::InvalidPaymentAmount);
     }
     Ok(transfer_checked_instruction)
 }
 
+/// Returns the amount received when `amount` of a Token-2022 mint is
+/// transferred, after deducting the mint's transfer fee, if any.
+///
+/// Which of the mint's two fee schedules applies depends on the current
+/// epoch. Rather than fetching it, the larger of the two fees is deducted, so
+/// the result never overstates what the recipient receives.
+///
+/// # Errors
+///
+/// Returns [`SolanaExactError::InvalidMintAccount`] if `mint_data` is not a
+/// valid mint or the fee cannot be computed.
+pub fn net_transfer_amount(mint_data: &[u8], amount: u64) -> Result<u64, SolanaExactError> {
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(mint_data)
+        .map_err(|_| SolanaExactError::InvalidMintAccount)?;
+    let Ok(fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(amount);
+    };
+    let older_fee = fee_config.older_transfer_fee.calculate_fee(amount);
+    let newer_fee = fee_config.newer_transfer_fee.calculate_fee(amount);
+    let fee = older_fee
+        .zip(newer_fee)
+        .map(|(older, newer)| older.max(newer))
+        .ok_or(SolanaExactError::InvalidMintAccount)?;
+    Ok(amount.saturating_sub(fee))
+}
+
 /// Settles a verified transaction by signing and sending it.
 ///
 /// # Errors
@@ -383,3 +647,370 @@ pub async fn settle_transaction<P: SolanaChainProviderLike>(
         .await?;
     Ok(tx_sig)
 }
+
+#[cfg(test)]
+mod tests {
+    use solana_message::compiled_instruction::CompiledInstruction;
+    use solana_message::v0::Message as MessageV0;
+    use solana_message::{Hash, MessageHeader, VersionedMessage};
+
+    use super::*;
+    use crate::exact::PHANTOM_LIGHTHOUSE_PROGRAM;
+
+    /// Index of the nonce account in the test transactions' account keys.
+    const NONCE_ACCOUNT: u8 = 2;
+
+    const fn key(byte: u8) -> Pubkey {
+        Pubkey::new_from_array([byte; 32])
+    }
+
+    fn transaction(advance_nonce_data: Option<Vec<u8>>) -> (VersionedTransaction, Pubkey) {
+        let account_keys: Vec<Pubkey> = vec![
+            key(1), // fee payer
+            key(2), // payer / nonce authority
+            key(3), // nonce account
+            key(4), // recent blockhashes sysvar
+            SYSTEM_PROGRAM_PUBKEY,
+            ComputeBudgetInstructionId,
+            spl_token::ID,
+            key(5), // source
+            key(6), // mint
+            key(7), // destination
+        ];
+        let nonce_account = account_keys[usize::from(NONCE_ACCOUNT)];
+
+        let mut instructions = Vec::new();
+        if let Some(data) = advance_nonce_data {
+            instructions.push(CompiledInstruction::new_from_raw_parts(
+                4,
+                data,
+                vec![NONCE_ACCOUNT, 3, 1],
+            ));
+        }
+        let mut limit = vec![2];
+        limit.extend(200_000u32.to_le_bytes());
+        let mut price = vec![3];
+        price.extend(1u64.to_le_bytes());
+        instructions.extend([
+            CompiledInstruction::new_from_raw_parts(5, limit, vec![]),
+            CompiledInstruction::new_from_raw_parts(5, price, vec![]),
+            CompiledInstruction::new_from_raw_parts(6, vec![12], vec![7, 8, 9, 1]),
+        ]);
+
+        let message = MessageV0 {
+            header: MessageHeader {
+                num_required_signatures: 2,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 3,
+            },
+            account_keys,
+            recent_blockhash: Hash::default(),
+            instructions,
+            address_table_lookups: vec![],
+        };
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default(); 2],
+            message: VersionedMessage::V0(message),
+        };
+        (transaction, nonce_account)
+    }
+
+    fn durable_nonce_config() -> SolanaExactFacilitatorConfig {
+        SolanaExactFacilitatorConfig {
+            allow_durable_nonce: true,
+            ..SolanaExactFacilitatorConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_durable_nonce_layout_accepted_when_enabled() {
+        let (tx, nonce_account) = transaction(Some(ADVANCE_NONCE_ACCOUNT_DISCRIMINANT.to_vec()));
+        let config = durable_nonce_config();
+
+        assert_eq!(required_instructions_offset(&tx, &config).unwrap(), 1);
+        assert_eq!(
+            verify_advance_nonce_instruction(&tx, 0).unwrap(),
+            nonce_account
+        );
+        assert_eq!(
+            verify_compute_limit_instruction(200_000, &tx, 1).unwrap(),
+            200_000
+        );
+        verify_compute_price_instruction(1, &tx, 2).unwrap();
+        validate_instructions(&tx, &config).unwrap();
+    }
+
+    #[test]
+    fn test_durable_nonce_layout_rejected_when_disabled() {
+        let (tx, _) = transaction(Some(ADVANCE_NONCE_ACCOUNT_DISCRIMINANT.to_vec()));
+        let config = SolanaExactFacilitatorConfig::default();
+
+        assert_eq!(required_instructions_offset(&tx, &config).unwrap(), 0);
+        assert!(matches!(
+            verify_compute_limit_instruction(u32::MAX, &tx, 0),
+            Err(SolanaExactError::InvalidComputeLimitInstruction)
+        ));
+    }
+
+    #[test]
+    fn test_compute_limit_ceiling() {
+        // The test transaction requests 200_000 compute units.
+        let (tx, _) = transaction(None);
+
+        assert_eq!(
+            verify_compute_limit_instruction(200_000, &tx, 0).unwrap(),
+            200_000
+        );
+        assert!(matches!(
+            verify_compute_limit_instruction(199_999, &tx, 0),
+            Err(SolanaExactError::MaxComputeUnitLimitExceeded)
+        ));
+    }
+
+    #[test]
+    fn test_compute_price_ceiling() {
+        // The test transaction sets a price of 1 micro-lamport per compute unit.
+        let (tx, _) = transaction(None);
+
+        verify_compute_price_instruction(1, &tx, 1).unwrap();
+        assert!(matches!(
+            verify_compute_price_instruction(0, &tx, 1),
+            Err(SolanaExactError::MaxComputeUnitPriceExceeded)
+        ));
+    }
+
+    #[test]
+    fn test_durable_nonce_rejects_other_system_instructions() {
+        // SystemInstruction::Transfer (discriminant 2) in the nonce slot
+        let (tx, _) = transaction(Some(2u32.to_le_bytes().to_vec()));
+
+        assert!(matches!(
+            required_instructions_offset(&tx, &durable_nonce_config()),
+            Err(SolanaExactError::InvalidAdvanceNonceInstruction)
+        ));
+    }
+
+    /// Provider serving fixed accounts for transfer verification.
+    struct AccountsProvider {
+        mint: Pubkey,
+        mint_data: Vec<u8>,
+    }
+
+    /// Error returned by the provider calls transfer verification never makes.
+    fn unused() -> SolanaChainProviderError {
+        SolanaChainProviderError::Custom("not served by the accounts provider".into())
+    }
+
+    impl SolanaChainProviderLike for AccountsProvider {
+        async fn simulate_transaction_with_config(
+            &self,
+            _tx: &VersionedTransaction,
+            _cfg: RpcSimulateTransactionConfig,
+        ) -> Result<(), SolanaChainProviderError> {
+            Err(unused())
+        }
+
+        async fn get_multiple_accounts(
+            &self,
+            pubkeys: &[Pubkey],
+        ) -> Result<Vec<Option<solana_account::Account>>, SolanaChainProviderError> {
+            Ok(pubkeys
+                .iter()
+                .map(|pubkey| {
+                    let data = if *pubkey == self.mint {
+                        self.mint_data.clone()
+                    } else {
+                        Vec::new()
+                    };
+                    Some(solana_account::Account {
+                        data,
+                        owner: spl_token_2022::ID,
+                        ..solana_account::Account::default()
+                    })
+                })
+                .collect())
+        }
+
+        fn max_compute_unit_limit(&self) -> u32 {
+            0
+        }
+
+        fn max_compute_unit_price(&self) -> u64 {
+            0
+        }
+
+        fn pubkey(&self) -> Pubkey {
+            key(1)
+        }
+
+        fn fee_payer(&self) -> Address {
+            Address::new(key(1))
+        }
+
+        fn sign(
+            &self,
+            _tx: VersionedTransaction,
+        ) -> Result<VersionedTransaction, SolanaChainProviderError> {
+            Err(unused())
+        }
+
+        async fn send_and_confirm(
+            &self,
+            _tx: &VersionedTransaction,
+            _commitment_config: CommitmentConfig,
+        ) -> Result<Signature, SolanaChainProviderError> {
+            Err(unused())
+        }
+    }
+
+    /// Packs a Token-2022 mint charging `basis_points` on transfers.
+    fn fee_mint_data(basis_points: u16) -> Vec<u8> {
+        use spl_token_2022::extension::transfer_fee::TransferFee;
+        use spl_token_2022::extension::{
+            BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+        };
+        use spl_token_2022::state::Mint;
+
+        let len =
+            ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+                .unwrap();
+        let mut data = vec![0; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: u64::MAX.into(),
+            transfer_fee_basis_points: basis_points.into(),
+        };
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.older_transfer_fee = fee;
+        config.newer_transfer_fee = fee;
+        state.base = Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Mint::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    /// Builds a transaction whose only instruction is a Token-2022
+    /// `TransferChecked` of `amount` to the associated account of `pay_to`.
+    fn token_2022_transfer(amount: u64, pay_to: &Pubkey, mint: &Pubkey) -> VersionedTransaction {
+        let (ata, _) = Pubkey::find_program_address(
+            &[pay_to.as_ref(), spl_token_2022::ID.as_ref(), mint.as_ref()],
+            &ATA_PROGRAM_PUBKEY,
+        );
+        let mut data = vec![12];
+        data.extend(amount.to_le_bytes());
+        data.push(6);
+        let message = MessageV0 {
+            header: MessageHeader {
+                num_required_signatures: 2,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 2,
+            },
+            account_keys: vec![
+                key(1), // fee payer
+                key(2), // authority
+                key(5), // source
+                ata,
+                *mint,
+                spl_token_2022::ID,
+            ],
+            recent_blockhash: Hash::default(),
+            instructions: vec![CompiledInstruction::new_from_raw_parts(
+                5,
+                data,
+                vec![2, 4, 3, 1],
+            )],
+            address_table_lookups: vec![],
+        };
+        VersionedTransaction {
+            signatures: vec![Signature::default(); 2],
+            message: VersionedMessage::V0(message),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_token_2022_transfer_fee_checks_net_amount() {
+        let mint = key(6);
+        let pay_to = key(8);
+        // 1% transfer fee: 1_000_000 gross nets 990_000.
+        let provider = AccountsProvider {
+            mint,
+            mint_data: fee_mint_data(100),
+        };
+        let tx = token_2022_transfer(1_000_000, &pay_to, &mint);
+        let asset = Address::new(mint);
+        let pay_to = Address::new(pay_to);
+        let requirement = |amount| TransferRequirement {
+            asset: &asset,
+            pay_to: &pay_to,
+            amount,
+        };
+
+        verify_transfer_instruction(&provider, &tx, 0, &requirement(990_000))
+            .await
+            .unwrap();
+        assert!(matches!(
+            verify_transfer_instruction(&provider, &tx, 0, &requirement(1_000_000)).await,
+            Err(PaymentVerificationError::InvalidPaymentAmount)
+        ));
+        assert!(matches!(
+            verify_transfer_instruction(&provider, &tx, 0, &requirement(0)).await,
+            Err(PaymentVerificationError::InvalidPaymentAmount)
+        ));
+        let zero = token_2022_transfer(0, &pay_to.pubkey(), &mint);
+        assert!(matches!(
+            verify_transfer_instruction(&provider, &zero, 0, &requirement(0)).await,
+            Err(PaymentVerificationError::InvalidPaymentAmount)
+        ));
+    }
+
+    #[test]
+    fn test_transfer_located_after_prepended_instruction() {
+        let (mut tx, _) = transaction(None);
+        if let VersionedMessage::V0(message) = &mut tx.message {
+            message.account_keys.push(*PHANTOM_LIGHTHOUSE_PROGRAM);
+            let lighthouse = u8::try_from(message.account_keys.len() - 1).unwrap();
+            let instruction = CompiledInstruction::new_from_raw_parts(lighthouse, vec![0], vec![]);
+            message.instructions.insert(0, instruction);
+        }
+
+        let fixed = SolanaExactFacilitatorConfig::default();
+        assert!(validate_instructions(&tx, &fixed).is_err());
+
+        let config = SolanaExactFacilitatorConfig {
+            locate_required_instructions: true,
+            ..SolanaExactFacilitatorConfig::default()
+        };
+        assert_eq!(
+            locate_required_instructions(&tx, &config).unwrap(),
+            RequiredInstructions {
+                compute_limit: 1,
+                compute_price: 2,
+                transfer: 3,
+            }
+        );
+        validate_instructions(&tx, &config).unwrap();
+
+        let unlisted = SolanaExactFacilitatorConfig {
+            allowed_program_ids: vec![],
+            ..config
+        };
+        assert!(matches!(
+            validate_instructions(&tx, &unlisted),
+            Err(SolanaExactError::ProgramNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn test_standard_layout_unchanged_with_durable_nonce_enabled() {
+        let (tx, _) = transaction(None);
+        let config = durable_nonce_config();
+
+        assert_eq!(required_instructions_offset(&tx, &config).unwrap(), 0);
+        validate_instructions(&tx, &config).unwrap();
+    }
+}
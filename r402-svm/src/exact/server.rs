@@ -14,6 +14,9 @@
 
 impl SolanaExact {
     /// Creates a price tag for a Solana SPL token payment.
+    ///
+    /// The timeout defaults to [`v2::DEFAULT_MAX_TIMEOUT_SECONDS`]; override it
+    /// with [`v2::PriceTag::with_max_timeout_seconds`].
     #[allow(clippy::needless_pass_by_value)]
     pub fn price_tag<A: Into<Address>>(
         pay_to: A,
@@ -26,8 +29,9 @@ pub fn price_tag<A: Into<Address>>(
             asset: asset.token.address.to_string(),
             network: chain_id,
             amount: asset.amount.to_string(),
-            max_timeout_seconds: 300,
+            max_timeout_seconds: v2::DEFAULT_MAX_TIMEOUT_SECONDS,
             extra: None,
+            required_extensions: None,
         };
         v2::PriceTag {
             requirements,
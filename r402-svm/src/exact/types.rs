@@ -53,6 +53,9 @@ pub struct SupportedPaymentKindExtra {
 /// Associated Token Account program public key.
 pub const ATA_PROGRAM_PUBKEY: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
 
+/// System program public key, owner of durable nonce accounts.
+pub const SYSTEM_PROGRAM_PUBKEY: Pubkey = pubkey!("11111111111111111111111111111111");
+
 /// Parsed instruction with its index and resolved account keys.
 #[derive(Debug)]
 #[cfg(any(feature = "client", feature = "facilitator"))]
@@ -141,18 +144,35 @@ pub fn sign<P: SolanaChainProviderLike>(
     ///
     /// Returns [`TransactionSignError`] if the signer is not in the required signers list.
     pub fn sign_with_keypair<S: Signer>(self, signer: &S) -> Result<Self, TransactionSignError> {
-        let mut tx = self.inner;
-        let msg_bytes = tx.message.serialize();
         let signature = signer
-            .try_sign_message(msg_bytes.as_slice())
+            .try_sign_message(&self.message_bytes())
             .map_err(|e| TransactionSignError(format!("{e}")))?;
+        self.with_signature(&signer.pubkey(), signature)
+    }
+
+    /// Returns the serialized message that signers sign.
+    #[must_use]
+    pub fn message_bytes(&self) -> Vec<u8> {
+        self.inner.message.serialize()
+    }
 
+    /// Places a signature over [`Self::message_bytes`] in the slot of `signer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransactionSignError`] if `signer` is not in the required signers list.
+    pub fn with_signature(
+        self,
+        signer: &Pubkey,
+        signature: Signature,
+    ) -> Result<Self, TransactionSignError> {
+        let mut tx = self.inner;
         let num_required = tx.message.header().num_required_signatures as usize;
         let static_keys = tx.message.static_account_keys();
 
         let pos = static_keys[..num_required]
             .iter()
-            .position(|k| *k == signer.pubkey())
+            .position(|k| k == signer)
             .ok_or_else(|| {
                 TransactionSignError("Signer not found in required signers".to_string())
             })?;
@@ -34,5 +34,5 @@
 mod networks;
 pub use exact::SolanaExact;
 #[cfg(feature = "client")]
-pub use exact::client::SolanaExactClient;
+pub use exact::client::{SolanaExactClient, SolanaSignerLike};
 pub use networks::*;
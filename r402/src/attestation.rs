@@ -0,0 +1,256 @@
+//! Facilitator attestations over settlement receipts.
+//!
+//! A facilitator can sign each successful [`proto::SettleResponse`] so that
+//! clients can check the receipt really came from the facilitator they expect
+//! and was not forged by an intermediary. The signature is attached to the
+//! response `extensions` under [`ATTESTATION_EXTENSION`], and the facilitator
+//! advertises its public key in [`proto::SupportedResponse::attestation_key`].
+//!
+//! Signing schemes are pluggable: [`AttestationSigner`] produces signatures and
+//! [`AttestationVerifier`] checks them. Chain crates provide implementations
+//! (e.g. EIP-191 signatures in `r402-evm`).
+//!
+//! Wrap any [`Facilitator`] in an [`AttestingFacilitator`] to sign its receipts.
+
+use serde::{Deserialize, Serialize};
+
+use crate::facilitator::{BoxFuture, Facilitator, FacilitatorError};
+use crate::proto;
+
+/// Extension key under which the attestation is attached to a settlement response.
+pub const ATTESTATION_EXTENSION: &str = "facilitatorSignature";
+
+/// A facilitator's signature over a successful settlement response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FacilitatorAttestation {
+    /// Public key (or address) of the signing facilitator.
+    pub public_key: String,
+    /// Encoded signature over [`attestation_message`].
+    pub signature: String,
+}
+
+impl FacilitatorAttestation {
+    /// Extracts the attestation attached to `response`, if any.
+    ///
+    /// Returns `None` for failed settlements and for responses without a
+    /// well-formed [`ATTESTATION_EXTENSION`].
+    #[must_use]
+    pub fn from_response(response: &proto::SettleResponse) -> Option<Self> {
+        let proto::SettleResponse::Success {
+            extensions: Some(extensions),
+            ..
+        } = response
+        else {
+            return None;
+        };
+        let value = extensions.get(ATTESTATION_EXTENSION)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+}
+
+/// Returns the canonical bytes a facilitator signs for `response`.
+///
+/// The message covers the network, transaction hash, and payer of a successful
+/// settlement, one `key:value` pair per line. Extensions are excluded so that
+/// attaching the attestation does not change the signed message.
+///
+/// Returns `None` for failed settlements, which are never attested.
+#[must_use]
+pub fn attestation_message(response: &proto::SettleResponse) -> Option<Vec<u8>> {
+    match response {
+        proto::SettleResponse::Success {
+            payer,
+            transaction,
+            network,
+            ..
+        } => Some(
+            format!("x402 settlement\nnetwork:{network}\ntransaction:{transaction}\npayer:{payer}")
+                .into_bytes(),
+        ),
+        _ => None,
+    }
+}
+
+/// Produces facilitator attestations.
+///
+/// This trait is dyn-compatible.
+pub trait AttestationSigner: Send + Sync {
+    /// Returns the public key (or address) advertised to clients.
+    fn public_key(&self) -> String;
+
+    /// Signs `message`, returning the encoded signature.
+    fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<String, FacilitatorError>>;
+}
+
+/// Checks facilitator attestations.
+pub trait AttestationVerifier: Send + Sync {
+    /// Returns `true` if `signature` is a valid signature of `message` by `public_key`.
+    fn verify(&self, public_key: &str, message: &[u8], signature: &str) -> bool;
+}
+
+/// A [`Facilitator`] wrapper that attests every successful settlement.
+///
+/// Settlement errors and verify responses are passed through unchanged.
+/// [`Facilitator::supported`] advertises the signer's public key.
+#[derive(Debug, Clone)]
+pub struct AttestingFacilitator<F, S> {
+    inner: F,
+    signer: S,
+}
+
+impl<F, S> AttestingFacilitator<F, S> {
+    /// Wraps `inner`, signing its settlement responses with `signer`.
+    pub const fn new(inner: F, signer: S) -> Self {
+        Self { inner, signer }
+    }
+
+    /// Returns a reference to the wrapped facilitator.
+    pub const fn inner(&self) -> &F {
+        &self.inner
+    }
+}
+
+/// Attaches `signer`'s attestation to a successful settlement `response`.
+///
+/// Takes the signer rather than the whole facilitator so the future is `Send`
+/// without requiring the wrapped facilitator to be `Sync`.
+async fn attest<S: AttestationSigner>(
+    signer: &S,
+    mut response: proto::SettleResponse,
+) -> Result<proto::SettleResponse, FacilitatorError> {
+    let Some(message) = attestation_message(&response) else {
+        return Ok(response);
+    };
+    let attestation = FacilitatorAttestation {
+        public_key: signer.public_key(),
+        signature: signer.sign(&message).await?,
+    };
+    if let proto::SettleResponse::Success { extensions, .. } = &mut response {
+        let value =
+            serde_json::to_value(attestation).map_err(|e| FacilitatorError::Other(Box::new(e)))?;
+        extensions
+            .get_or_insert_with(proto::Extensions::new)
+            .insert(ATTESTATION_EXTENSION.to_owned(), value);
+    }
+    Ok(response)
+}
+
+impl<F, S> Facilitator for AttestingFacilitator<F, S>
+where
+    F: Facilitator,
+    S: AttestationSigner,
+{
+    fn verify(
+        &self,
+        request: proto::VerifyRequest,
+    ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+        self.inner.verify(request)
+    }
+
+    fn settle(
+        &self,
+        request: proto::SettleRequest,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        Box::pin(async move {
+            let response = self.inner.settle(request).await?;
+            attest(&self.signer, response).await
+        })
+    }
+
+    fn settle_verified(
+        &self,
+        request: proto::SettleRequest,
+        token: String,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        Box::pin(async move {
+            let response = self.inner.settle_verified(request, token).await?;
+            attest(&self.signer, response).await
+        })
+    }
+
+    fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+        Box::pin(async move {
+            let mut supported = self.inner.supported().await?;
+            supported.attestation_key = Some(self.signer.public_key());
+            Ok(supported)
+        })
+    }
+
+    fn pre_validate(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<(), proto::PaymentVerificationError> {
+        self.inner.pre_validate(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Toy signer whose "signature" is the key followed by the message.
+    struct ConcatSigner;
+
+    impl AttestationSigner for ConcatSigner {
+        fn public_key(&self) -> String {
+            "key".to_owned()
+        }
+
+        fn sign<'a>(
+            &'a self,
+            message: &'a [u8],
+        ) -> BoxFuture<'a, Result<String, FacilitatorError>> {
+            let signature = format!("key|{}", String::from_utf8_lossy(message));
+            Box::pin(async move { Ok(signature) })
+        }
+    }
+
+    struct SettlingFacilitator;
+
+    impl Facilitator for SettlingFacilitator {
+        fn verify(
+            &self,
+            _request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::VerifyResponse::valid("0xpayer".into())) })
+        }
+
+        fn settle(
+            &self,
+            _request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            Box::pin(async {
+                Ok(proto::SettleResponse::Success {
+                    payer: "0xpayer".into(),
+                    transaction: "0xtx".into(),
+                    network: "eip155:8453".into(),
+                    extensions: None,
+                })
+            })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::SupportedResponse::default()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_settlement_is_attested_with_advertised_key() {
+        let facilitator = AttestingFacilitator::new(SettlingFacilitator, ConcatSigner);
+
+        let supported = facilitator.supported().await.unwrap();
+        let response = facilitator
+            .settle(proto::SettleRequest::from(serde_json::json!({})))
+            .await
+            .unwrap();
+
+        let attestation = FacilitatorAttestation::from_response(&response).unwrap();
+        let message = attestation_message(&response).unwrap();
+        assert_eq!(supported.attestation_key, Some(attestation.public_key));
+        assert_eq!(
+            attestation.signature,
+            format!("key|{}", String::from_utf8_lossy(&message))
+        );
+    }
+}
@@ -6,6 +6,7 @@
 //! - [`ChainId`] - A CAIP-2 compliant chain identifier (e.g., `eip155:8453` for Base)
 //! - [`ChainIdPattern`] - Pattern matching for chain IDs (exact, wildcard, or set)
 //! - [`ChainRegistry`] - Registry of configured chain providers
+//! - [`ChainStatus`] - Initialization outcome of a configured chain
 //! - [`ChainProvider`] - Common operations on chain providers
 //! - [`DeployedTokenAmount`] - Token amount paired with deployment info
 
@@ -76,7 +77,8 @@ fn from(value: ChainId) -> Self {
 /// Error returned when parsing an invalid chain ID string.
 ///
 /// A valid chain ID must be in the format `namespace:reference` where both
-/// components are non-empty strings.
+/// components are non-empty strings. References of the `solana` namespace
+/// must be exactly 32 base58 characters (the truncated genesis hash).
 #[derive(Debug, thiserror::Error)]
 #[error("Invalid chain id format {0}")]
 pub struct ChainIdFormatError(String);
@@ -89,6 +91,9 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
         if parts.len() != 2 {
             return Err(ChainIdFormatError(s.into()));
         }
+        if parts[0] == "solana" && !is_solana_reference(parts[1]) {
+            return Err(ChainIdFormatError(s.into()));
+        }
         Ok(Self {
             namespace: parts[0].into(),
             reference: parts[1].into(),
@@ -96,6 +101,15 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
     }
 }
 
+/// The base58 alphabet of Solana references and addresses, which omits `0`,
+/// `O`, `I` and `l`.
+pub const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Checks that `reference` is a Solana genesis hash prefix: 32 base58 characters.
+fn is_solana_reference(reference: &str) -> bool {
+    reference.len() == 32 && reference.chars().all(|c| BASE58_ALPHABET.contains(c))
+}
+
 impl Serialize for ChainId {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -327,24 +341,88 @@ fn chain_id(&self) -> ChainId {
 /// # Type Parameters
 ///
 /// - `P` - The chain provider type (e.g., `Eip155ChainProvider` or `SolanaChainProvider`)
+///
+/// Besides the live providers, the registry remembers chains whose provider
+/// failed to initialize, so a long-running facilitator can keep serving the
+/// healthy chains, report the degraded ones, and retry them later via
+/// [`Self::record_init`].
 #[derive(Debug)]
-pub struct ChainRegistry<P>(HashMap<ChainId, P>);
+pub struct ChainRegistry<P> {
+    providers: HashMap<ChainId, P>,
+    failures: Vec<(ChainId, String)>,
+}
 
 impl<P> ChainRegistry<P> {
     /// Creates a new registry from the given provider map.
     #[must_use]
     pub const fn new(providers: HashMap<ChainId, P>) -> Self {
-        Self(providers)
+        Self {
+            providers,
+            failures: Vec::new(),
+        }
     }
 }
 
 impl<P> ChainRegistry<P> {
+    /// Records the outcome of initializing the provider for `chain_id`.
+    ///
+    /// On success the provider becomes live and any earlier failure is
+    /// cleared. On failure the chain is marked degraded with the error
+    /// message; a previously live provider for the chain is kept.
+    ///
+    /// Returns the live provider for `chain_id`, if any.
+    pub fn record_init<E: fmt::Display>(
+        &mut self,
+        chain_id: &ChainId,
+        result: Result<P, E>,
+    ) -> Option<&P> {
+        self.failures.retain(|(failed, _)| failed != chain_id);
+        match result {
+            Ok(provider) => {
+                self.providers.insert(chain_id.clone(), provider);
+            }
+            Err(error) => {
+                self.failures.push((chain_id.clone(), error.to_string()));
+            }
+        }
+        self.providers.get(chain_id)
+    }
+
+    /// Returns the initialization failure recorded for `chain_id`, if any.
+    #[must_use]
+    pub fn failure(&self, chain_id: &ChainId) -> Option<&str> {
+        self.failures
+            .iter()
+            .find(|(failed, _)| failed == chain_id)
+            .map(|(_, error)| error.as_str())
+    }
+
+    /// Returns the status of every configured chain, live or degraded,
+    /// ordered by chain ID.
+    #[must_use]
+    pub fn statuses(&self) -> Vec<ChainStatus> {
+        let mut statuses: Vec<ChainStatus> = self
+            .providers
+            .keys()
+            .chain(self.failures.iter().map(|(chain_id, _)| chain_id))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(|chain_id| ChainStatus {
+                chain_id: chain_id.clone(),
+                live: self.providers.contains_key(chain_id),
+                error: self.failure(chain_id).map(str::to_owned),
+            })
+            .collect();
+        statuses.sort_by_cached_key(|status| status.chain_id.to_string());
+        statuses
+    }
+
     /// Looks up a provider by exact chain ID.
     ///
     /// Returns `None` if no provider is configured for the given chain.
     #[must_use]
     pub fn by_chain_id(&self, chain_id: &ChainId) -> Option<&P> {
-        self.0.get(chain_id)
+        self.providers.get(chain_id)
     }
 
     /// Looks up providers by chain ID pattern matching.
@@ -356,13 +434,27 @@ pub fn by_chain_id(&self, chain_id: &ChainId) -> Option<&P> {
     /// - Set: Matches any chain from a set of references (e.g., `eip155:{1,8453,137}`)
     #[must_use]
     pub fn by_chain_id_pattern(&self, pattern: &ChainIdPattern) -> Vec<&P> {
-        self.0
+        self.providers
             .iter()
             .filter_map(|(chain_id, provider)| pattern.matches(chain_id).then_some(provider))
             .collect()
     }
 }
 
+/// Initialization status of a configured chain, as reported by
+/// [`ChainRegistry::statuses`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainStatus {
+    /// The configured chain.
+    pub chain_id: ChainId,
+    /// Whether a provider for the chain is currently available.
+    pub live: bool,
+    /// The most recent initialization error, if the chain is degraded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 /// A token amount paired with its deployment information.
 ///
 /// This type associates a numeric amount with the token deployment it refers to,
@@ -383,6 +475,41 @@ pub struct DeployedTokenAmount<TAmount, TToken> {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_chain_registry_reconnects_failed_chain() {
+        let base = ChainId::new("eip155", "8453");
+        let polygon = ChainId::new("eip155", "137");
+        let mut registry = ChainRegistry::new(HashMap::from([(base.clone(), "base-rpc")]));
+
+        assert!(
+            registry
+                .record_init(&polygon, Err("connection refused"))
+                .is_none()
+        );
+        assert_eq!(registry.failure(&polygon), Some("connection refused"));
+        assert_eq!(
+            registry.statuses(),
+            vec![
+                ChainStatus {
+                    chain_id: polygon.clone(),
+                    live: false,
+                    error: Some("connection refused".into()),
+                },
+                ChainStatus {
+                    chain_id: base,
+                    live: true,
+                    error: None,
+                },
+            ]
+        );
+
+        let reconnected = registry.record_init::<&str>(&polygon, Ok("polygon-rpc"));
+        assert_eq!(reconnected, Some(&"polygon-rpc"));
+        assert_eq!(registry.failure(&polygon), None);
+        assert!(registry.statuses().iter().all(|status| status.live));
+    }
+
     #[test]
     fn test_chain_id_serialize_eip155() {
         let chain_id = ChainId::new("eip155", "1");
@@ -422,7 +549,7 @@ fn test_chain_id_roundtrip_eip155() {
 
     #[test]
     fn test_chain_id_roundtrip_solana() {
-        let original = ChainId::new("solana", "devnet");
+        let original = ChainId::new("solana", "EtWTRABZaYq6iMfeYKouRu166VU2xqa1");
         let serialized = serde_json::to_string(&original).unwrap();
         let deserialized: ChainId = serde_json::from_str(&serialized).unwrap();
         assert_eq!(original, deserialized);
@@ -30,6 +30,9 @@ pub enum FacilitatorError {
     /// On-chain operation failed (RPC error, transaction reverted, etc.).
     #[error("Onchain error: {0}")]
     OnchainFailure(String),
+    /// The settlement transaction was included on-chain but failed (e.g. reverted).
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(String),
     /// A lifecycle hook aborted the operation.
     #[error("{reason}: {message}")]
     Aborted {
@@ -48,6 +51,9 @@ fn as_payment_problem(&self) -> PaymentProblem {
         match self {
             Self::PaymentVerification(e) => e.as_payment_problem(),
             Self::OnchainFailure(e) => PaymentProblem::new(ErrorReason::UnexpectedError, e.clone()),
+            Self::TransactionFailed(e) => {
+                PaymentProblem::new(ErrorReason::TransactionFailed, e.clone())
+            }
             Self::Aborted { reason, message } => {
                 PaymentProblem::new(ErrorReason::UnexpectedError, format!("{reason}: {message}"))
             }
@@ -80,8 +86,57 @@ fn settle(
         request: proto::SettleRequest,
     ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>>;
 
+    /// Settles a payment that this facilitator already verified, presenting
+    /// the verification token of its [`proto::VerifyResponse`].
+    ///
+    /// Facilitators that issue tokens check it and skip the verify checks
+    /// that need chain access, rejecting forged or expired tokens with
+    /// [`PaymentVerificationError::InvalidVerificationToken`]. See
+    /// [`crate::verification`].
+    ///
+    /// The default implementation ignores the token and calls
+    /// [`settle`](Self::settle). Remote facilitators behind the HTTP
+    /// `FacilitatorClient` of `r402-http` use it too: the token is not
+    /// forwarded over the wire, so they fully re-verify on settle.
+    fn settle_verified(
+        &self,
+        request: proto::SettleRequest,
+        token: String,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        let _ = token;
+        self.settle(request)
+    }
+
     /// Returns the payment kinds supported by this facilitator.
     fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>>;
+
+    /// Runs the checks on `request` that need no chain access.
+    ///
+    /// Lets edge proxies and other pre-filters reject malformed, mismatched or
+    /// expired payments cheaply, without a round trip to an RPC node. Passing
+    /// does not mean the payment is valid: [`verify`](Self::verify) must
+    /// still be called.
+    ///
+    /// The default implementation only checks that the request names a
+    /// scheme and network and carries well-formed payment requirements.
+    /// Scheme facilitators override it with their local checks.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`PaymentVerificationError`] of the first failed check.
+    fn pre_validate(&self, request: &proto::VerifyRequest) -> Result<(), PaymentVerificationError> {
+        if request.scheme_slug().is_none() {
+            return Err(PaymentVerificationError::InvalidFormat(
+                "payment payload does not name a V2 scheme and network".into(),
+            ));
+        }
+        if request.payment_requirements().is_none() {
+            return Err(PaymentVerificationError::InvalidFormat(
+                "missing or malformed payment requirements".into(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl<T: Facilitator> Facilitator for Arc<T> {
@@ -99,7 +154,143 @@ fn settle(
         self.as_ref().settle(request)
     }
 
+    fn settle_verified(
+        &self,
+        request: proto::SettleRequest,
+        token: String,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        self.as_ref().settle_verified(request, token)
+    }
+
     fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
         self.as_ref().supported()
     }
+
+    fn pre_validate(&self, request: &proto::VerifyRequest) -> Result<(), PaymentVerificationError> {
+        self.as_ref().pre_validate(request)
+    }
+}
+
+/// Audit record of a successful settlement.
+///
+/// Scheme facilitators emit one record per settled payment via
+/// [`SettlementRecord::log`], using the same field names on every chain so a
+/// single log query covers all of them. Signatures and nonces are deliberately
+/// left out: they would let anyone reading the logs replay an authorization.
+#[derive(Debug, Clone, Copy)]
+pub struct SettlementRecord<'a> {
+    /// Address of the payer.
+    pub payer: &'a str,
+    /// Address of the recipient.
+    pub pay_to: &'a str,
+    /// Address of the token transferred.
+    pub asset: &'a str,
+    /// Amount transferred, in the token's smallest unit.
+    pub amount: &'a str,
+    /// CAIP-2 network identifier.
+    pub network: &'a str,
+    /// Settlement transaction hash or signature.
+    pub transaction: &'a str,
+    /// Facilitator signer that submitted the transaction, if known.
+    pub signer: Option<&'a str>,
+}
+
+impl SettlementRecord<'_> {
+    /// Emits the record as a single `info` event on the `x402::settlement` target.
+    #[cfg(feature = "telemetry")]
+    pub fn log(&self) {
+        tracing::info!(
+            target: "x402::settlement",
+            payer = self.payer,
+            pay_to = self.pay_to,
+            asset = self.asset,
+            amount = self.amount,
+            network = self.network,
+            transaction = self.transaction,
+            signer = self.signer,
+            "Payment settled"
+        );
+    }
+}
+
+#[cfg(all(test, feature = "telemetry"))]
+mod tests {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::{Mutex, PoisonError};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use super::*;
+
+    /// Subscriber capturing the fields of every event.
+    #[derive(Default)]
+    struct Capture(Mutex<Vec<HashMap<String, String>>>);
+
+    struct Fields<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_owned(), value.to_owned());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name().to_owned(), format!("{value:?}"));
+        }
+    }
+
+    impl Subscriber for &'static Capture {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = HashMap::new();
+            event.record(&mut Fields(&mut fields));
+            self.0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(fields);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn test_settlement_record_fields() {
+        let capture: &'static Capture = Box::leak(Box::default());
+        let record = SettlementRecord {
+            payer: "0xpayer",
+            pay_to: "0xmerchant",
+            asset: "0xusdc",
+            amount: "1000",
+            network: "eip155:8453",
+            transaction: "0xtx",
+            signer: Some("0xfacilitator"),
+        };
+        tracing::subscriber::with_default(capture, || record.log());
+
+        let events = capture.0.lock().unwrap();
+        let fields = &events[0];
+        assert_eq!(fields["payer"], "0xpayer");
+        assert_eq!(fields["pay_to"], "0xmerchant");
+        assert_eq!(fields["asset"], "0xusdc");
+        assert_eq!(fields["amount"], "1000");
+        assert_eq!(fields["network"], "eip155:8453");
+        assert_eq!(fields["transaction"], "0xtx");
+        assert_eq!(fields["signer"], "0xfacilitator");
+        assert!(!fields.contains_key("nonce") && !fields.contains_key("signature"));
+    }
 }
@@ -17,6 +17,7 @@
 //! lifecycle pattern as the official x402 Go SDK.
 
 use std::fmt::{self, Debug};
+use std::time::Instant;
 
 use crate::facilitator::{BoxFuture, Facilitator, FacilitatorError};
 use crate::proto;
@@ -59,12 +60,15 @@ pub enum FailureRecovery<T> {
 pub struct VerifyContext {
     /// The raw verify request (contains payload + requirements as JSON).
     pub request: proto::VerifyRequest,
+    /// When the operation started, before any hook ran.
+    pub started_at: Instant,
 }
 
 impl Debug for VerifyContext {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("VerifyContext")
             .field("request", &"<VerifyRequest>")
+            .field("started_at", &self.started_at)
             .finish()
     }
 }
@@ -76,12 +80,15 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 pub struct SettleContext {
     /// The raw settle request (same structure as verify request).
     pub request: proto::SettleRequest,
+    /// When the operation started, before any hook ran.
+    pub started_at: Instant,
 }
 
 impl Debug for SettleContext {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SettleContext")
             .field("request", &"<SettleRequest>")
+            .field("started_at", &self.started_at)
             .finish()
     }
 }
@@ -230,6 +237,7 @@ fn verify(
         Box::pin(async move {
             let ctx = VerifyContext {
                 request: request.clone(),
+                started_at: Instant::now(),
             };
             for hook in &self.hooks {
                 if let HookDecision::Abort { reason, message } = hook.before_verify(&ctx).await {
@@ -260,17 +268,53 @@ fn verify(
     fn settle(
         &self,
         request: proto::SettleRequest,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        self.settle_with_hooks(request, None)
+    }
+
+    fn settle_verified(
+        &self,
+        request: proto::SettleRequest,
+        token: String,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        self.settle_with_hooks(request, Some(token))
+    }
+
+    fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+        Box::pin(async move { self.inner.supported().await })
+    }
+
+    fn pre_validate(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<(), proto::PaymentVerificationError> {
+        self.inner.pre_validate(request)
+    }
+}
+
+impl<F: Facilitator> HookedFacilitator<F> {
+    /// Settles through the inner facilitator, presenting `token` if given,
+    /// with the settle hooks run around it.
+    fn settle_with_hooks(
+        &self,
+        request: proto::SettleRequest,
+        token: Option<String>,
     ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
         Box::pin(async move {
             let ctx = SettleContext {
                 request: request.clone(),
+                started_at: Instant::now(),
             };
             for hook in &self.hooks {
                 if let HookDecision::Abort { reason, message } = hook.before_settle(&ctx).await {
                     return Err(FacilitatorError::Aborted { reason, message });
                 }
             }
-            match self.inner.settle(request).await {
+            let settled = match token {
+                Some(token) => self.inner.settle_verified(request, token).await,
+                None => self.inner.settle(request).await,
+            };
+            match settled {
                 Ok(response) => {
                     for hook in &self.hooks {
                         hook.after_settle(&ctx, &response).await;
@@ -290,8 +334,4 @@ fn settle(
             }
         })
     }
-
-    fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
-        Box::pin(async move { self.inner.supported().await })
-    }
 }
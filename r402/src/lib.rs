@@ -16,21 +16,38 @@
 //! # Modules
 //!
 //! - [`amount`] - Human-readable currency amount parsing
+//! - [`attestation`] - Facilitator signatures over settlement receipts
 //! - [`chain`] - Blockchain identifiers and provider abstractions (CAIP-2 chain IDs)
 //! - [`facilitator`] - Core trait for payment verification and settlement
 //! - [`hooks`] - Lifecycle hooks for facilitator verify/settle operations
+//! - [`metrics`] - Pluggable verify/settle metrics reported through hooks
 //! - [`networks`] - Registry of well-known blockchain networks
+//! - [`preflight`] - Startup checks of a facilitator configuration without serving
 //! - [`proto`] - Wire format types, encoding utilities, and timestamps
+//! - [`receipts`] - Durable records of settlements for accounting
 //! - [`scheme`] - Payment scheme system for extensible payment methods
+//! - [`screening`] - Per-chain payer allowlists and blocklists
+//! - [`secret`] - Loading of signer keys and other secrets from files
+//! - [`verification`] - Tokens for settling verified payments without re-verification
 //!
 //! # Feature Flags
 //!
 //! - `telemetry` - Enables tracing instrumentation for debugging and monitoring
+//! - `prometheus` - Enables the Prometheus metrics exporter
+//! - `schemars` - Derives JSON Schemas for the wire types
+//! - `sqlite` - Enables the `SQLite` settlement receipt store
 
 pub mod amount;
+pub mod attestation;
 pub mod chain;
 pub mod facilitator;
 pub mod hooks;
+pub mod metrics;
 pub mod networks;
+pub mod preflight;
 pub mod proto;
+pub mod receipts;
 pub mod scheme;
+pub mod screening;
+pub mod secret;
+pub mod verification;
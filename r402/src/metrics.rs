@@ -0,0 +1,422 @@
+//! Pluggable metrics for facilitator verify and settle operations.
+//!
+//! The [`Metrics`] trait receives one observation per verify or settle call,
+//! labelled by network and [`Outcome`]. All methods default to no-ops, so the
+//! core stays dependency-free; the `prometheus` feature provides
+//! [`PrometheusMetrics`] as a ready-made exporter.
+//!
+//! Metrics plug into the hooks system through [`MetricsHooks`]:
+//!
+//! ```ignore
+//! let metrics = Arc::new(PrometheusMetrics::new(DEFAULT_LATENCY_BUCKETS.to_vec())?);
+//! let facilitator = HookedFacilitator::new(registry)
+//!     .with_hook(MetricsHooks::new(Arc::clone(&metrics)).with_networks(EVM_NETWORKS));
+//! // Serve `metrics.render()` on `GET /metrics`.
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::chain::ChainId;
+use crate::facilitator::{BoxFuture, FacilitatorError};
+use crate::hooks::{FacilitatorHooks, FailureRecovery, SettleContext, VerifyContext};
+use crate::networks::NetworkInfo;
+use crate::proto;
+use crate::proto::AsPaymentProblem;
+
+/// Default latency histogram buckets, in seconds.
+///
+/// Verification is usually a few RPC round-trips, while settlement waits for
+/// a transaction receipt, hence the long tail.
+pub const DEFAULT_LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+/// Result of a verify or settle operation, as reported to [`Metrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome<'a> {
+    /// The payment was valid (verify) or settled on-chain (settle).
+    Success,
+    /// The operation was rejected or failed with the given reason.
+    ///
+    /// When reported by [`MetricsHooks`], the reason is an
+    /// [`ErrorReason`](proto::ErrorReason) code, or `"other"` if the failure
+    /// reported none.
+    Failure(&'a str),
+}
+
+impl Outcome<'_> {
+    /// Returns `"success"` or `"failure"`, suitable as a metric label.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure(_) => "failure",
+        }
+    }
+
+    /// Returns the failure reason, or an empty string on success.
+    #[must_use]
+    pub const fn reason(&self) -> &str {
+        match self {
+            Self::Success => "",
+            Self::Failure(reason) => reason,
+        }
+    }
+}
+
+/// Sink for facilitator operation metrics.
+///
+/// All methods have default no-op implementations. Implementations must be
+/// cheap and non-blocking, as they run inline with every request.
+pub trait Metrics: Send + Sync {
+    /// Records a completed verify operation.
+    fn record_verify(&self, _network: &str, _outcome: Outcome<'_>, _duration: Duration) {}
+
+    /// Records a completed settle operation.
+    fn record_settle(&self, _network: &str, _outcome: Outcome<'_>, _duration: Duration) {}
+
+    /// Records a switch of the RPC endpoint used for `network`, after the
+    /// active endpoint kept failing.
+    fn record_rpc_failover(&self, _network: &str) {}
+}
+
+impl<M: Metrics + ?Sized> Metrics for Arc<M> {
+    fn record_verify(&self, network: &str, outcome: Outcome<'_>, duration: Duration) {
+        (**self).record_verify(network, outcome, duration);
+    }
+
+    fn record_settle(&self, network: &str, outcome: Outcome<'_>, duration: Duration) {
+        (**self).record_settle(network, outcome, duration);
+    }
+
+    fn record_rpc_failover(&self, network: &str) {
+        (**self).record_rpc_failover(network);
+    }
+}
+
+/// A [`Metrics`] implementation that discards all observations.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
+
+/// Lifecycle hooks that report every verify and settle call to a [`Metrics`] sink.
+///
+/// Register with [`HookedFacilitator::with_hook`](crate::hooks::HookedFacilitator::with_hook).
+/// Operations aborted by an earlier `before_*` hook are not recorded.
+///
+/// Labels are bounded so that clients cannot inflate the number of series:
+/// networks not registered with [`Self::with_networks`] are reported as
+/// `"unknown"`, and failure reasons are reduced to their
+/// [`ErrorReason`](proto::ErrorReason) code.
+#[derive(Debug, Clone)]
+pub struct MetricsHooks<M> {
+    metrics: M,
+    networks: Vec<ChainId>,
+}
+
+impl<M> MetricsHooks<M> {
+    /// Creates hooks reporting to the given metrics sink.
+    pub const fn new(metrics: M) -> Self {
+        Self {
+            metrics,
+            networks: Vec::new(),
+        }
+    }
+
+    /// Adds `networks` to those reported by name in the `network` label.
+    #[must_use]
+    pub fn with_networks(mut self, networks: &[NetworkInfo]) -> Self {
+        self.networks
+            .extend(networks.iter().map(NetworkInfo::chain_id));
+        self
+    }
+
+    /// Returns a reference to the underlying metrics sink.
+    pub const fn metrics(&self) -> &M {
+        &self.metrics
+    }
+
+    /// Returns `network` if it is a registered chain, or `"unknown"`.
+    fn network_label<'a>(&self, network: &'a str) -> &'a str {
+        let known = network
+            .parse::<ChainId>()
+            .is_ok_and(|chain_id| self.networks.contains(&chain_id));
+        if known { network } else { "unknown" }
+    }
+}
+
+/// Returns the [`ErrorReason`](proto::ErrorReason) code `reason` names, or
+/// `"other"` for free-form reasons.
+fn reason_label(reason: &str) -> &'static str {
+    serde_json::from_value::<proto::ErrorReason>(reason.into())
+        .map_or("other", |code| code.as_str())
+}
+
+impl<M: Metrics> FacilitatorHooks for MetricsHooks<M> {
+    fn after_verify<'a>(
+        &'a self,
+        ctx: &'a VerifyContext,
+        result: &'a proto::VerifyResponse,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let outcome = match result {
+                proto::VerifyResponse::Invalid { reason, .. } => {
+                    Outcome::Failure(reason_label(reason))
+                }
+                _ => Outcome::Success,
+            };
+            let network = self.network_label(ctx.request.network());
+            self.metrics
+                .record_verify(network, outcome, ctx.started_at.elapsed());
+        })
+    }
+
+    fn on_verify_failure<'a>(
+        &'a self,
+        ctx: &'a VerifyContext,
+        error: &'a FacilitatorError,
+    ) -> BoxFuture<'a, FailureRecovery<proto::VerifyResponse>> {
+        Box::pin(async move {
+            let reason = error.as_payment_problem().reason();
+            self.metrics.record_verify(
+                self.network_label(ctx.request.network()),
+                Outcome::Failure(reason.as_str()),
+                ctx.started_at.elapsed(),
+            );
+            FailureRecovery::Propagate
+        })
+    }
+
+    fn after_settle<'a>(
+        &'a self,
+        ctx: &'a SettleContext,
+        result: &'a proto::SettleResponse,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let outcome = match result {
+                proto::SettleResponse::Error { reason, code, .. } => Outcome::Failure(
+                    code.map_or_else(|| reason_label(reason), |code| code.as_str()),
+                ),
+                proto::SettleResponse::Success { .. } => Outcome::Success,
+            };
+            let network = self.network_label(ctx.request.network());
+            self.metrics
+                .record_settle(network, outcome, ctx.started_at.elapsed());
+        })
+    }
+
+    fn on_settle_failure<'a>(
+        &'a self,
+        ctx: &'a SettleContext,
+        error: &'a FacilitatorError,
+    ) -> BoxFuture<'a, FailureRecovery<proto::SettleResponse>> {
+        Box::pin(async move {
+            let reason = error.as_payment_problem().reason();
+            self.metrics.record_settle(
+                self.network_label(ctx.request.network()),
+                Outcome::Failure(reason.as_str()),
+                ctx.started_at.elapsed(),
+            );
+            FailureRecovery::Propagate
+        })
+    }
+}
+
+#[cfg(feature = "prometheus")]
+pub use prometheus_impl::PrometheusMetrics;
+
+#[cfg(feature = "prometheus")]
+mod prometheus_impl {
+    use std::time::Duration;
+
+    use prometheus::{
+        Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+    };
+
+    use super::{Metrics, Outcome};
+
+    /// Prometheus exporter for facilitator metrics.
+    ///
+    /// Exposes the following series:
+    ///
+    /// - `x402_verify_total{network, outcome, reason}` / `x402_settle_total{...}`
+    /// - `x402_verify_duration_seconds{network, outcome}` / `x402_settle_duration_seconds{...}`
+    /// - `x402_rpc_failovers_total{network}`
+    ///
+    /// Serve [`PrometheusMetrics::render`] on the server's `/metrics` route.
+    #[derive(Debug, Clone)]
+    pub struct PrometheusMetrics {
+        registry: Registry,
+        verify_total: IntCounterVec,
+        verify_duration: HistogramVec,
+        settle_total: IntCounterVec,
+        settle_duration: HistogramVec,
+        rpc_failovers_total: IntCounterVec,
+    }
+
+    impl PrometheusMetrics {
+        /// Creates an exporter with its own registry and the given latency buckets (seconds).
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if `buckets` is invalid (e.g. not strictly increasing).
+        pub fn new(buckets: Vec<f64>) -> Result<Self, prometheus::Error> {
+            Self::with_registry(Registry::new(), buckets)
+        }
+
+        /// Creates an exporter registering its series in an existing registry.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if `buckets` is invalid or the series are already registered.
+        pub fn with_registry(
+            registry: Registry,
+            buckets: Vec<f64>,
+        ) -> Result<Self, prometheus::Error> {
+            let verify_total = IntCounterVec::new(
+                Opts::new("x402_verify_total", "Number of x402 verify operations"),
+                &["network", "outcome", "reason"],
+            )?;
+            let settle_total = IntCounterVec::new(
+                Opts::new("x402_settle_total", "Number of x402 settle operations"),
+                &["network", "outcome", "reason"],
+            )?;
+            let verify_duration = HistogramVec::new(
+                HistogramOpts::new(
+                    "x402_verify_duration_seconds",
+                    "Latency of x402 verify operations",
+                )
+                .buckets(buckets.clone()),
+                &["network", "outcome"],
+            )?;
+            let settle_duration = HistogramVec::new(
+                HistogramOpts::new(
+                    "x402_settle_duration_seconds",
+                    "Latency of x402 settle operations",
+                )
+                .buckets(buckets),
+                &["network", "outcome"],
+            )?;
+            let rpc_failovers_total = IntCounterVec::new(
+                Opts::new(
+                    "x402_rpc_failovers_total",
+                    "Number of switches to another RPC endpoint",
+                ),
+                &["network"],
+            )?;
+            registry.register(Box::new(verify_total.clone()))?;
+            registry.register(Box::new(settle_total.clone()))?;
+            registry.register(Box::new(verify_duration.clone()))?;
+            registry.register(Box::new(settle_duration.clone()))?;
+            registry.register(Box::new(rpc_failovers_total.clone()))?;
+            Ok(Self {
+                registry,
+                verify_total,
+                verify_duration,
+                settle_total,
+                settle_duration,
+                rpc_failovers_total,
+            })
+        }
+
+        /// Returns the registry holding the exported series.
+        #[must_use]
+        pub const fn registry(&self) -> &Registry {
+            &self.registry
+        }
+
+        /// Renders all series in the Prometheus text exposition format.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if encoding fails.
+        pub fn render(&self) -> Result<String, prometheus::Error> {
+            let mut buffer = Vec::new();
+            TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+            String::from_utf8(buffer).map_err(|e| prometheus::Error::Msg(e.to_string()))
+        }
+    }
+
+    impl Metrics for PrometheusMetrics {
+        fn record_verify(&self, network: &str, outcome: Outcome<'_>, duration: Duration) {
+            self.verify_total
+                .with_label_values(&[network, outcome.as_str(), outcome.reason()])
+                .inc();
+            self.verify_duration
+                .with_label_values(&[network, outcome.as_str()])
+                .observe(duration.as_secs_f64());
+        }
+
+        fn record_settle(&self, network: &str, outcome: Outcome<'_>, duration: Duration) {
+            self.settle_total
+                .with_label_values(&[network, outcome.as_str(), outcome.reason()])
+                .inc();
+            self.settle_duration
+                .with_label_values(&[network, outcome.as_str()])
+                .observe(duration.as_secs_f64());
+        }
+
+        fn record_rpc_failover(&self, network: &str) {
+            self.rpc_failovers_total.with_label_values(&[network]).inc();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prometheus"))]
+mod tests {
+    use std::time::Instant;
+
+    use serde_json::json;
+
+    use super::*;
+
+    const BASE: &[NetworkInfo] = &[NetworkInfo {
+        name: "base",
+        namespace: "eip155",
+        reference: "8453",
+    }];
+
+    fn context(network: &str) -> VerifyContext {
+        VerifyContext {
+            request: proto::VerifyRequest::from(json!({
+                "x402Version": 2,
+                "paymentRequirements": { "network": network },
+            })),
+            started_at: Instant::now(),
+        }
+    }
+
+    fn invalid(reason: &str) -> proto::VerifyResponse {
+        proto::VerifyResponse::Invalid {
+            reason: reason.to_owned(),
+            message: None,
+            payer: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hook_labels_are_bounded() {
+        let metrics = Arc::new(PrometheusMetrics::new(DEFAULT_LATENCY_BUCKETS.to_vec()).unwrap());
+        let hooks = MetricsHooks::new(Arc::clone(&metrics)).with_networks(BASE);
+
+        let known = context("eip155:8453");
+        hooks
+            .after_verify(&known, &invalid("insufficient_funds"))
+            .await;
+        let made_up = context("eip155:424242");
+        hooks
+            .after_verify(&made_up, &invalid("balance 0 < 100"))
+            .await;
+
+        let rendered = metrics.render().unwrap();
+        assert!(rendered.contains(
+            r#"x402_verify_total{network="eip155:8453",outcome="failure",reason="insufficient_funds"} 1"#
+        ));
+        assert!(rendered.contains(
+            r#"x402_verify_total{network="unknown",outcome="failure",reason="other"} 1"#
+        ));
+        assert!(!rendered.contains("424242"));
+    }
+}
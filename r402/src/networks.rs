@@ -6,6 +6,19 @@
 //!
 //! - `r402-evm` provides `EVM_NETWORKS` for EIP-155 chains
 //! - `r402-svm` provides `SOLANA_NETWORKS` for Solana chains
+//!
+//! [`NetworkRegistry`] combines such tables into a single cross-chain lookup
+//! between V1 network names and CAIP-2 chain IDs:
+//!
+//! ```ignore
+//! let networks = NetworkRegistry::from_slices(&[EVM_NETWORKS, SOLANA_NETWORKS]);
+//! assert_eq!(
+//!     networks.chain_id_by_name("base-sepolia"),
+//!     Some(ChainId::new("eip155", "84532"))
+//! );
+//! ```
+
+use std::collections::HashMap;
 
 use crate::chain::ChainId;
 
@@ -27,3 +40,126 @@ pub fn chain_id(&self) -> ChainId {
         ChainId::new(self.namespace, self.reference)
     }
 }
+
+/// Cross-chain lookup between V1 network names and CAIP-2 chain IDs.
+///
+/// Networks are looked up in registration order: if two entries share a
+/// name, or two names map to the same chain, the first one registered wins.
+/// Register the tables of the chains you prefer first.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkRegistry {
+    networks: Vec<NetworkInfo>,
+    by_name: HashMap<&'static str, ChainId>,
+    by_chain_id: HashMap<ChainId, &'static str>,
+}
+
+impl NetworkRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry from network tables, in order of precedence.
+    #[must_use]
+    pub fn from_slices(tables: &[&[NetworkInfo]]) -> Self {
+        tables.iter().fold(Self::new(), |registry, networks| {
+            registry.with_networks(networks)
+        })
+    }
+
+    /// Registers `networks` after those already known.
+    #[must_use]
+    pub fn with_networks(mut self, networks: &[NetworkInfo]) -> Self {
+        for network in networks {
+            let chain_id = network.chain_id();
+            self.by_name
+                .entry(network.name)
+                .or_insert_with(|| chain_id.clone());
+            self.by_chain_id.entry(chain_id).or_insert(network.name);
+            self.networks.push(*network);
+        }
+        self
+    }
+
+    /// Returns the chain ID of the network called `name`.
+    #[must_use]
+    pub fn chain_id_by_name(&self, name: &str) -> Option<ChainId> {
+        self.by_name.get(name).cloned()
+    }
+
+    /// Returns the V1 name of the network identified by `chain_id`.
+    #[must_use]
+    pub fn name_by_chain_id(&self, chain_id: &ChainId) -> Option<&'static str> {
+        self.by_chain_id.get(chain_id).copied()
+    }
+
+    /// Returns every registered network, in registration order.
+    ///
+    /// Pass this to the V1 conversions taking a `&[NetworkInfo]`.
+    #[must_use]
+    pub fn networks(&self) -> &[NetworkInfo] {
+        &self.networks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVM: &[NetworkInfo] = &[
+        NetworkInfo {
+            name: "base-sepolia",
+            namespace: "eip155",
+            reference: "84532",
+        },
+        NetworkInfo {
+            name: "base",
+            namespace: "eip155",
+            reference: "8453",
+        },
+    ];
+
+    const SOLANA: &[NetworkInfo] = &[
+        NetworkInfo {
+            name: "solana",
+            namespace: "solana",
+            reference: "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp",
+        },
+        NetworkInfo {
+            name: "base",
+            namespace: "solana",
+            reference: "EtWTRABZaYq6iMfeYKouRu166VU2xqa1",
+        },
+    ];
+
+    #[test]
+    fn test_resolves_names_across_chains() {
+        let networks = NetworkRegistry::from_slices(&[EVM, SOLANA]);
+        assert_eq!(
+            networks.chain_id_by_name("base-sepolia"),
+            Some(ChainId::new("eip155", "84532"))
+        );
+        let solana = ChainId::new("solana", "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp");
+        assert_eq!(networks.chain_id_by_name("solana"), Some(solana.clone()));
+        assert_eq!(networks.name_by_chain_id(&solana), Some("solana"));
+        assert_eq!(networks.chain_id_by_name("unknown"), None);
+        assert_eq!(networks.networks().len(), 4);
+    }
+
+    #[test]
+    fn test_first_registered_name_wins() {
+        let evm_first = NetworkRegistry::from_slices(&[EVM, SOLANA]);
+        assert_eq!(
+            evm_first.chain_id_by_name("base"),
+            Some(ChainId::new("eip155", "8453"))
+        );
+        let solana_first = NetworkRegistry::from_slices(&[SOLANA, EVM]);
+        assert_eq!(
+            solana_first.chain_id_by_name("base").unwrap().namespace(),
+            "solana"
+        );
+        let shadowed = ChainId::new("solana", "EtWTRABZaYq6iMfeYKouRu166VU2xqa1");
+        assert_eq!(evm_first.name_by_chain_id(&shadowed), Some("base"));
+    }
+}
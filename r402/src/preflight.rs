@@ -0,0 +1,170 @@
+//! Startup checks of a facilitator configuration, without serving.
+//!
+//! Operators validate a configuration in CI or before a deploy by resolving
+//! every signer key, connecting to every RPC endpoint and confirming each
+//! endpoint serves the configured chain, then exiting instead of binding a
+//! port. Chain crates provide the per-chain checks (e.g.
+//! `Eip155ChainProvider::preflight` in `r402-evm`); this module provides the
+//! shared error type and the [`ValidationReport`] collecting their outcomes.
+//!
+//! The report prints as one line per chain, or as JSON via
+//! [`ValidationReport::to_json`] for machine consumption.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::ChainId;
+use crate::secret::{SecretError, resolve_secret};
+
+/// Reasons a configured chain cannot be served.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PreflightError {
+    /// A signer key could not be resolved or parsed.
+    #[error("unresolved signer key: {0}")]
+    Signer(String),
+    /// The RPC endpoint could not be reached.
+    #[error("RPC unreachable: {0}")]
+    Unreachable(String),
+    /// The RPC endpoint serves another chain than the configured one.
+    #[error("RPC serves chain {actual}, configured as {expected}")]
+    ChainIdMismatch {
+        /// The configured chain reference.
+        expected: String,
+        /// The chain reference reported by the RPC endpoint.
+        actual: String,
+    },
+}
+
+impl From<SecretError> for PreflightError {
+    fn from(error: SecretError) -> Self {
+        Self::Signer(error.to_string())
+    }
+}
+
+/// Resolves a configured signer key, see [`resolve_secret`].
+///
+/// # Errors
+///
+/// Returns [`PreflightError::Signer`] if the key references a secret file
+/// that cannot be read or is empty.
+pub fn resolve_signer_key(value: &str) -> Result<String, PreflightError> {
+    Ok(resolve_secret(value)?)
+}
+
+/// Outcome of the checks of one configured chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainReport {
+    /// The configured chain.
+    pub chain_id: ChainId,
+    /// Whether every check passed.
+    pub serviceable: bool,
+    /// Addresses of the resolved signers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signers: Vec<String>,
+    /// Tokens configured on the chain, serviceable if the chain is.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<String>,
+    /// The first failed check, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcomes of the startup checks of every configured chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// One entry per configured chain, in the order they were checked.
+    pub chains: Vec<ChainReport>,
+}
+
+impl ValidationReport {
+    /// Creates an empty report.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the outcome of checking `chain_id`: its signer addresses on
+    /// success, or the first failed check.
+    pub fn record(
+        &mut self,
+        chain_id: ChainId,
+        tokens: Vec<String>,
+        result: Result<Vec<String>, PreflightError>,
+    ) {
+        let (signers, error) = match result {
+            Ok(signers) => (signers, None),
+            Err(error) => (Vec::new(), Some(error.to_string())),
+        };
+        self.chains.push(ChainReport {
+            chain_id,
+            serviceable: error.is_none(),
+            signers,
+            tokens,
+            error,
+        });
+    }
+
+    /// Returns `true` if every configured chain is serviceable.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.chains.iter().all(|chain| chain.serviceable)
+    }
+
+    /// Serializes the report as pretty-printed JSON.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chain in &self.chains {
+            match &chain.error {
+                None => writeln!(
+                    f,
+                    "ok    {} signers=[{}] tokens=[{}]",
+                    chain.chain_id,
+                    chain.signers.join(", "),
+                    chain.tokens.join(", ")
+                )?,
+                Some(error) => writeln!(f, "FAIL  {}: {error}", chain.chain_id)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_flags_failed_chains() {
+        let mut report = ValidationReport::new();
+        report.record(
+            ChainId::new("eip155", "8453"),
+            vec!["USDC".into()],
+            Ok(vec!["0xsigner".into()]),
+        );
+        assert!(report.is_ok());
+
+        let missing = std::env::temp_dir().join("r402-preflight-missing-key");
+        let err = resolve_signer_key(&format!("file:{}", missing.display())).unwrap_err();
+        report.record(ChainId::new("eip155", "1"), vec![], Err(err));
+        assert!(!report.is_ok());
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["chains"][0]["serviceable"], true);
+        assert_eq!(json["chains"][0]["tokens"][0], "USDC");
+        assert_eq!(json["chains"][1]["serviceable"], false);
+        assert!(
+            json["chains"][1]["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("unresolved signer key")
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Canonical decimal amount strings.
+//!
+//! Token amounts travel on the wire as base-10 integer strings in the token's
+//! smallest unit. Several SDKs hash or compare these strings byte for byte, so
+//! every implementation must agree on a single encoding: ASCII digits only,
+//! without signs, separators, surrounding whitespace, or leading zeros.
+
+/// Reasons an amount string is not a canonical decimal integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum AmountFormatError {
+    /// The amount is an empty string.
+    #[error("Amount must not be empty")]
+    Empty,
+    /// The amount has a leading zero, e.g. `"01000000"`.
+    #[error("Amount must not have leading zeros")]
+    LeadingZero,
+    /// The amount contains a non-digit character, such as a sign,
+    /// an underscore, or whitespace.
+    #[error("Amount contains invalid character {0:?}")]
+    InvalidCharacter(char),
+}
+
+/// Checks that `amount` is a canonical decimal integer string.
+///
+/// Accepts `"0"` and digit strings without a leading zero. Rejects signs,
+/// underscores, whitespace, and any other non-digit character, which lenient
+/// integer parsers may silently accept.
+///
+/// # Errors
+///
+/// Returns [`AmountFormatError`] describing the first violation found.
+pub fn validate_amount(amount: &str) -> Result<&str, AmountFormatError> {
+    if let Some(c) = amount.chars().find(|c| !c.is_ascii_digit()) {
+        return Err(AmountFormatError::InvalidCharacter(c));
+    }
+    match amount.as_bytes() {
+        [] => Err(AmountFormatError::Empty),
+        [b'0', _, ..] => Err(AmountFormatError::LeadingZero),
+        _ => Ok(amount),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_amounts_are_accepted() {
+        assert_eq!(validate_amount("1000000"), Ok("1000000"));
+        assert_eq!(validate_amount("0"), Ok("0"));
+    }
+
+    #[test]
+    fn test_malformed_amounts_are_rejected() {
+        assert_eq!(validate_amount(""), Err(AmountFormatError::Empty));
+        assert_eq!(
+            validate_amount("01000000"),
+            Err(AmountFormatError::LeadingZero)
+        );
+        assert_eq!(validate_amount("00"), Err(AmountFormatError::LeadingZero));
+        assert_eq!(
+            validate_amount("1_000_000"),
+            Err(AmountFormatError::InvalidCharacter('_'))
+        );
+        assert_eq!(
+            validate_amount("+1000000"),
+            Err(AmountFormatError::InvalidCharacter('+'))
+        );
+        assert_eq!(
+            validate_amount("-1000000"),
+            Err(AmountFormatError::InvalidCharacter('-'))
+        );
+        assert_eq!(
+            validate_amount(" 1000000 "),
+            Err(AmountFormatError::InvalidCharacter(' '))
+        );
+    }
+}
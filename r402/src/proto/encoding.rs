@@ -3,7 +3,15 @@
 use std::fmt::{self, Display, Formatter};
 
 use base64::Engine;
+use base64::alphabet;
 use base64::engine::general_purpose::STANDARD as b64;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+
+/// Standard-alphabet engine that accepts input with or without padding.
+const STANDARD_INDIFFERENT: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
 
 /// A wrapper for base64-encoded byte data.
 ///
@@ -15,14 +23,54 @@
 impl Base64Bytes {
     /// Decodes the base64 string bytes to raw binary data.
     ///
+    /// Only padded base64 in the standard alphabet, as the spec requires, is
+    /// accepted. Use [`Self::decode_tolerant`] to also accept URL-safe or
+    /// unpadded input.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the data is not valid base64.
+    /// Returns an error if the data is not valid standard base64.
     pub fn decode(&self) -> Result<Vec<u8>, base64::DecodeError> {
         b64.decode(&self.0)
     }
 
-    /// Encodes raw binary data into base64 string bytes.
+    /// Decodes the base64 string bytes, tolerating non-standard encodings.
+    ///
+    /// Both the standard and the URL-safe alphabets are accepted (even mixed),
+    /// with or without padding. Some SDKs emit URL-safe base64 and some
+    /// intermediaries rewrite `+`/`/`, so this maximizes interop.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is not valid base64 in either alphabet.
+    pub fn decode_tolerant(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        let normalized: Vec<u8> = self
+            .0
+            .iter()
+            .map(|&byte| match byte {
+                b'-' => b'+',
+                b'_' => b'/',
+                byte => byte,
+            })
+            .collect();
+        STANDARD_INDIFFERENT.decode(normalized)
+    }
+
+    /// Decodes the base64 string bytes, using the strict [`Self::decode`]
+    /// when `strict` is set and [`Self::decode_tolerant`] otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is not valid base64.
+    pub fn decode_with(&self, strict: bool) -> Result<Vec<u8>, base64::DecodeError> {
+        if strict {
+            self.decode()
+        } else {
+            self.decode_tolerant()
+        }
+    }
+
+    /// Encodes raw binary data into standard base64 string bytes.
     pub fn encode<T: AsRef<[u8]>>(input: T) -> Self {
         let encoded = b64.encode(input.as_ref());
         Self(encoded.into_bytes())
@@ -46,3 +94,31 @@ fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", String::from_utf8_lossy(&self.0))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAW: [u8; 4] = [0xfb, 0xff, 0xbf, 0x01];
+
+    #[test]
+    fn test_encode_uses_standard_alphabet() {
+        assert_eq!(Base64Bytes::encode(RAW).to_string(), "+/+/AQ==");
+    }
+
+    #[test]
+    fn test_decode_tolerant_accepts_both_alphabets() {
+        let standard = Base64Bytes::from(b"+/+/AQ==".as_slice());
+        let url_safe = Base64Bytes::from(b"-_-_AQ".as_slice());
+        assert_eq!(standard.decode_tolerant().unwrap(), RAW);
+        assert_eq!(url_safe.decode_tolerant().unwrap(), RAW);
+    }
+
+    #[test]
+    fn test_decode_rejects_url_safe() {
+        let url_safe = Base64Bytes::from(b"-_-_AQ==".as_slice());
+        assert!(url_safe.decode().is_err());
+        assert!(url_safe.decode_with(true).is_err());
+        assert_eq!(url_safe.decode_with(false).unwrap(), RAW);
+    }
+}
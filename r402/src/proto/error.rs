@@ -57,12 +57,37 @@ pub enum PaymentVerificationError {
     /// The EIP-3009 authorization nonce has already been consumed on-chain.
     #[error("Authorization nonce already used")]
     NonceAlreadyUsed,
+    /// The payment payload lacks an extension required by the payment requirements.
+    #[error("Missing required extension: {0}")]
+    MissingExtension(String),
+    /// The authorized amount is below the facilitator's minimum settlement
+    /// amount (given in atomic token units).
+    #[error("Payment amount is below the minimum settlement amount of {0}")]
+    BelowMinimumAmount(String),
+    /// The payer is blocked, or not allowlisted, by the facilitator's policy.
+    #[error("Payer {0} is not allowed")]
+    PayerNotAllowed(String),
+    /// The authorization stays valid for longer than the facilitator accepts
+    /// (both in seconds).
+    #[error("Payment authorization is valid for {lifetime}s, more than the maximum of {max}s")]
+    AuthorizationTooLong {
+        /// Seconds the authorization remains valid for.
+        lifetime: u64,
+        /// Longest lifetime the facilitator accepts.
+        max: u64,
+    },
+    /// The verification token presented for settlement is forged, expired,
+    /// or was issued for a different request.
+    #[error("Invalid verification token: {0}")]
+    InvalidVerificationToken(String),
 }
 
 impl AsPaymentProblem for PaymentVerificationError {
     fn as_payment_problem(&self) -> PaymentProblem {
         let error_reason = match self {
-            Self::InvalidFormat(_) => ErrorReason::InvalidFormat,
+            Self::InvalidFormat(_)
+            | Self::MissingExtension(_)
+            | Self::InvalidVerificationToken(_) => ErrorReason::InvalidFormat,
             Self::InvalidPaymentAmount => ErrorReason::InvalidPaymentAmount,
             Self::InsufficientFunds => ErrorReason::InsufficientFunds,
             Self::Permit2AllowanceInsufficient => ErrorReason::Permit2AllowanceInsufficient,
@@ -77,6 +102,9 @@ fn as_payment_problem(&self) -> PaymentProblem {
             Self::UnsupportedScheme => ErrorReason::UnsupportedScheme,
             Self::AcceptedRequirementsMismatch => ErrorReason::AcceptedRequirementsMismatch,
             Self::NonceAlreadyUsed => ErrorReason::NonceAlreadyUsed,
+            Self::BelowMinimumAmount(_) => ErrorReason::BelowMinimumAmount,
+            Self::PayerNotAllowed(_) => ErrorReason::PayerNotAllowed,
+            Self::AuthorizationTooLong { .. } => ErrorReason::AuthorizationTooLong,
         };
         PaymentProblem::new(error_reason, self.to_string())
     }
@@ -93,6 +121,7 @@ fn from(value: serde_json::Error) -> Self {
 /// These codes are used in error responses to allow clients to
 /// programmatically handle different failure scenarios.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 #[non_exhaustive]
 pub enum ErrorReason {
@@ -126,6 +155,14 @@ pub enum ErrorReason {
     UnsupportedScheme,
     /// The authorization nonce has already been used.
     NonceAlreadyUsed,
+    /// The payment amount is below the facilitator's settlement minimum.
+    BelowMinimumAmount,
+    /// The payer is not allowed by the facilitator's policy.
+    PayerNotAllowed,
+    /// The settlement transaction was mined but failed on-chain.
+    TransactionFailed,
+    /// The authorization is valid for longer than the facilitator accepts.
+    AuthorizationTooLong,
     /// An unexpected error occurred.
     UnexpectedError,
 }
@@ -150,6 +187,10 @@ pub const fn as_str(&self) -> &'static str {
             Self::UnsupportedChain => "unsupported_chain",
             Self::UnsupportedScheme => "unsupported_scheme",
             Self::NonceAlreadyUsed => "nonce_already_used",
+            Self::BelowMinimumAmount => "below_minimum_amount",
+            Self::PayerNotAllowed => "payer_not_allowed",
+            Self::TransactionFailed => "transaction_failed",
+            Self::AuthorizationTooLong => "authorization_too_long",
             Self::UnexpectedError => "unexpected_error",
         }
     }
@@ -170,12 +211,20 @@ pub trait AsPaymentProblem {
 /// A structured payment error with reason code and details.
 ///
 /// This type is used to return detailed error information to clients
-/// when a payment fails verification or settlement.
-#[derive(Debug)]
+/// when a payment fails verification or settlement. It is the JSON body of
+/// every HTTP error response that is not a `402 Payment Required`:
+///
+/// ```json
+/// { "error": "unsupported_chain", "message": "Unsupported chain" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PaymentProblem {
     /// The machine-readable error reason.
+    #[serde(rename = "error")]
     reason: ErrorReason,
     /// Human-readable error details.
+    #[serde(rename = "message")]
     details: String,
 }
 
@@ -198,3 +247,24 @@ pub fn details(&self) -> &str {
         &self.details
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payment_problem_json_shape() {
+        let problem = PaymentVerificationError::UnsupportedChain.as_payment_problem();
+        assert_eq!(
+            serde_json::to_value(&problem).unwrap(),
+            serde_json::json!({ "error": "unsupported_chain", "message": "Unsupported chain" })
+        );
+
+        let problem = PaymentVerificationError::Permit2AllowanceInsufficient.as_payment_problem();
+        let json = serde_json::to_string(&problem).unwrap();
+        assert!(json.contains(r#""error":"permit2_allowance_insufficient""#));
+        let decoded: PaymentProblem = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.reason(), ErrorReason::Permit2AllowanceInsufficient);
+        assert_eq!(decoded, problem);
+    }
+}
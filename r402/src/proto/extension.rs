@@ -0,0 +1,103 @@
+//! Typed protocol extensions.
+//!
+//! x402 messages carry an `extensions` map keyed by extension id. A server
+//! advertises the extensions it understands (or requires) in the 402
+//! response, and a client echoes the ones it supports in its payment
+//! payload. The [`Extension`] trait binds a Rust type to its id so both sides
+//! can read and write entries without handling raw JSON.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::Extensions;
+
+/// A typed x402 protocol extension.
+///
+/// The default (de)serialization hooks go through `serde_json`; override them
+/// when the wire format differs from the type's serde representation.
+pub trait Extension: Serialize + DeserializeOwned {
+    /// Key under which the extension appears in `extensions` maps.
+    const ID: &'static str;
+
+    /// Serializes the extension into its wire value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the extension cannot be represented as JSON.
+    fn to_value(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+
+    /// Deserializes the extension from its wire value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` is not a valid encoding of the extension.
+    fn from_value(value: Value) -> Result<Self, serde_json::Error> {
+        serde_json::from_value(value)
+    }
+}
+
+/// Inserts `extension` into `extensions` under [`Extension::ID`].
+///
+/// # Errors
+///
+/// Returns an error if the extension cannot be serialized.
+pub fn insert_extension<E: Extension>(
+    extensions: &mut Extensions,
+    extension: &E,
+) -> Result<(), serde_json::Error> {
+    extensions.insert(E::ID.to_owned(), extension.to_value()?);
+    Ok(())
+}
+
+/// Reads the extension `E` from `extensions`.
+///
+/// Returns `None` if the extension is absent.
+///
+/// # Errors
+///
+/// Returns an error if the extension is present but malformed.
+pub fn get_extension<E: Extension>(
+    extensions: &Extensions,
+) -> Result<Option<E>, serde_json::Error> {
+    extensions
+        .get(E::ID)
+        .cloned()
+        .map(E::from_value)
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+    struct Kyc {
+        token: String,
+    }
+
+    impl Extension for Kyc {
+        const ID: &'static str = "kyc";
+    }
+
+    #[test]
+    fn test_extension_round_trip() {
+        let mut extensions = Extensions::new();
+        assert_eq!(get_extension::<Kyc>(&extensions).unwrap(), None);
+
+        let kyc = Kyc {
+            token: "abc".into(),
+        };
+        insert_extension(&mut extensions, &kyc).unwrap();
+        assert_eq!(extensions["kyc"], json!({ "token": "abc" }));
+        assert_eq!(get_extension::<Kyc>(&extensions).unwrap(), Some(kyc));
+
+        extensions.insert("kyc".into(), json!(42));
+        assert!(get_extension::<Kyc>(&extensions).is_err());
+    }
+}
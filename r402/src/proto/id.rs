@@ -0,0 +1,191 @@
+//! Deterministic payment identifiers.
+//!
+//! A payment id is a stable key for a single payment authorization, usable
+//! for journaling, idempotency checks, and webhooks before the settlement
+//! transaction hash is known. It is the hex-encoded SHA-256 digest of the
+//! fields that uniquely identify the authorization:
+//!
+//! - **EIP-155**: `(network, asset, payTo, nonce)`
+//! - **Solana**: `(network, signature)`, where the signatures are the
+//!   non-empty signatures embedded in the partially signed transaction
+
+use std::fmt::Write as _;
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::Base64Bytes;
+
+/// Length in bytes of an ed25519 signature in a Solana transaction.
+const SOLANA_SIGNATURE_LEN: usize = 64;
+
+/// Computes the stable payment id of a raw verify/settle request.
+///
+/// Returns `None` if the request does not carry the fields needed to
+/// identify the payment, or if its network namespace is not supported.
+#[must_use]
+pub fn payment_id(request: &Value) -> Option<String> {
+    let network = request
+        .get("paymentRequirements")?
+        .get("network")?
+        .as_str()?;
+    let (namespace, _) = network.split_once(':')?;
+    let parts = match namespace {
+        "eip155" => eip155_id_parts(request)?,
+        "solana" => solana_id_parts(request)?,
+        _ => return None,
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(network.as_bytes());
+    for part in &parts {
+        hasher.update([0]);
+        hasher.update(part);
+    }
+    let digest = hasher.finalize();
+
+    let mut id = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        let _ = write!(id, "{byte:02x}");
+    }
+    Some(id)
+}
+
+/// Extracts `(asset, payTo, nonce)` from an EIP-155 request.
+///
+/// Addresses and nonces are lowercased so that checksummed and plain hex
+/// encodings produce the same id.
+fn eip155_id_parts(request: &Value) -> Option<Vec<Vec<u8>>> {
+    let requirements = request.get("paymentRequirements")?;
+    let asset = requirements.get("asset")?.as_str()?;
+    let pay_to = requirements.get("payTo")?.as_str()?;
+
+    let payload = request.get("paymentPayload")?.get("payload")?;
+    let nonce = payload
+        .get("authorization")
+        .or_else(|| payload.get("permit2Authorization"))?
+        .get("nonce")?
+        .as_str()?;
+
+    Some(vec![
+        asset.to_ascii_lowercase().into_bytes(),
+        pay_to.to_ascii_lowercase().into_bytes(),
+        nonce.to_ascii_lowercase().into_bytes(),
+    ])
+}
+
+/// Extracts the non-empty signatures from a Solana request's transaction.
+///
+/// The fee payer slot is left empty by the client and filled in by the
+/// facilitator, so only the signatures already present identify the payment.
+fn solana_id_parts(request: &Value) -> Option<Vec<Vec<u8>>> {
+    let transaction = request
+        .get("paymentPayload")?
+        .get("payload")?
+        .get("transaction")?
+        .as_str()?;
+    let bytes = Base64Bytes::from(transaction.as_bytes()).decode().ok()?;
+
+    let (count, offset) = decode_short_vec_len(&bytes)?;
+    let end = offset.checked_add(count.checked_mul(SOLANA_SIGNATURE_LEN)?)?;
+    let signatures: Vec<Vec<u8>> = bytes
+        .get(offset..end)?
+        .chunks_exact(SOLANA_SIGNATURE_LEN)
+        .filter(|signature| signature.iter().any(|&b| b != 0))
+        .map(<[u8]>::to_vec)
+        .collect();
+
+    (!signatures.is_empty()).then_some(signatures)
+}
+
+/// Decodes a Solana compact-u16 length prefix, returning the length and the
+/// number of bytes it occupied.
+fn decode_short_vec_len(bytes: &[u8]) -> Option<(usize, usize)> {
+    let mut len = 0usize;
+    for (i, &byte) in bytes.iter().take(3).enumerate() {
+        len |= usize::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((len, i + 1));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn evm_request(nonce: &str) -> Value {
+        json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "payload": {
+                    "signature": "0x1234",
+                    "authorization": { "nonce": nonce }
+                }
+            },
+            "paymentRequirements": {
+                "network": "eip155:8453",
+                "asset": "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+                "payTo": "0x209693Bc6afc0C5328bA36FaF03C514EF312287C"
+            }
+        })
+    }
+
+    #[test]
+    fn test_evm_payment_id_is_stable() {
+        let nonce = "0x0101010101010101010101010101010101010101010101010101010101010101";
+        let id = payment_id(&evm_request(nonce)).unwrap();
+        assert_eq!(id.len(), 64);
+        assert_eq!(payment_id(&evm_request(nonce)), Some(id.clone()));
+
+        let mut checksum_insensitive = evm_request(nonce);
+        checksum_insensitive["paymentRequirements"]["asset"] =
+            json!("0x833589fcd6edb6e08f4c7c32d4f71b54bda02913");
+        assert_eq!(payment_id(&checksum_insensitive), Some(id));
+    }
+
+    #[test]
+    fn test_evm_payment_id_differs_by_nonce() {
+        let a = payment_id(&evm_request(
+            "0x0101010101010101010101010101010101010101010101010101010101010101",
+        ));
+        let b = payment_id(&evm_request(
+            "0x0202020202020202020202020202020202020202020202020202020202020202",
+        ));
+        assert!(a.is_some());
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_solana_payment_id_uses_signatures() {
+        let solana_request = |signature_byte: u8| {
+            let mut tx = vec![2u8];
+            tx.extend([0u8; SOLANA_SIGNATURE_LEN]);
+            tx.extend([signature_byte; SOLANA_SIGNATURE_LEN]);
+            tx.extend([9u8; 16]);
+            json!({
+                "paymentPayload": {
+                    "payload": { "transaction": Base64Bytes::encode(tx).to_string() }
+                },
+                "paymentRequirements": {
+                    "network": "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp"
+                }
+            })
+        };
+
+        let id = payment_id(&solana_request(7)).unwrap();
+        assert_eq!(payment_id(&solana_request(7)), Some(id.clone()));
+        assert_ne!(payment_id(&solana_request(8)), Some(id));
+        assert_eq!(payment_id(&solana_request(0)), None);
+    }
+
+    #[test]
+    fn test_payment_id_requires_known_namespace() {
+        let mut request = evm_request("0x01");
+        request["paymentRequirements"]["network"] = json!("cosmos:cosmoshub-4");
+        assert_eq!(payment_id(&request), None);
+    }
+}
@@ -11,6 +11,12 @@
 //! - [`SettleRequest`] / [`SettleResponse`] - Payment settlement messages
 //! - [`PaymentVerificationError`] - Errors that can occur during verification
 //! - [`PaymentProblem`] - Structured error response for payment failures
+//! - [`payment_id`] - Stable identifier of a payment authorization
+//! - [`validate_amount`] - Strict check for canonical decimal amount strings
+//! - [`Extension`] - Typed entry of an `extensions` map
+//! - [`ParseMode`] - Tolerant or strict handling of unknown request fields
+//! - [`split`] - Payments split across several recipients
+//! - `x402_json_schemas` - JSON Schemas of the wire types (`schemars` feature)
 //!
 //! # Wire Format
 //!
@@ -24,16 +30,29 @@
 use serde_with::{VecSkipError, serde_as};
 
 use crate::chain::ChainId;
+use crate::networks::NetworkRegistry;
 use crate::scheme::SchemeSlug;
 
+mod amount;
 mod encoding;
 mod error;
+mod extension;
+mod id;
+#[cfg(feature = "schemars")]
+mod schema;
+pub mod split;
 mod timestamp;
+pub mod v1;
 pub mod v2;
 mod version;
 
+pub use amount::{AmountFormatError, validate_amount};
 pub use encoding::Base64Bytes;
 pub use error::*;
+pub use extension::{Extension, get_extension, insert_extension};
+pub use id::payment_id;
+#[cfg(feature = "schemars")]
+pub use schema::x402_json_schemas;
 pub use timestamp::UnixTimestamp;
 pub use version::Version;
 
@@ -43,6 +62,7 @@
 ///
 /// Use [`v2::VerifyRequest`] type alias instead of constructing this directly.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TypedVerifyRequest<const V: u8, TPayload, TRequirements> {
     /// The protocol version marker.
@@ -80,6 +100,98 @@ pub fn from_settle(request: SettleRequest) -> Result<Self, PaymentVerificationEr
     }
 }
 
+impl<const V: u8, TPayload, TRequirements> TypedVerifyRequest<V, TPayload, TRequirements>
+where
+    Self: serde::de::DeserializeOwned + Serialize,
+{
+    /// Deserializes from a protocol-level [`VerifyRequest`], treating unknown
+    /// fields according to `mode`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PaymentVerificationError`] if deserialization fails, or if
+    /// `mode` is [`ParseMode::Strict`] and the request has unknown fields.
+    pub fn from_proto_with(
+        request: VerifyRequest,
+        mode: ParseMode,
+    ) -> Result<Self, PaymentVerificationError> {
+        mode.parse(request.into_json())
+    }
+
+    /// Deserializes from a protocol-level [`SettleRequest`], treating unknown
+    /// fields according to `mode`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PaymentVerificationError`] if deserialization fails, or if
+    /// `mode` is [`ParseMode::Strict`] and the request has unknown fields.
+    pub fn from_settle_with(
+        request: SettleRequest,
+        mode: ParseMode,
+    ) -> Result<Self, PaymentVerificationError> {
+        mode.parse(request.into_json())
+    }
+}
+
+/// How a facilitator treats fields of a request its protocol version does
+/// not know.
+///
+/// Known fields are validated in both modes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ParseMode {
+    /// Ignores unknown fields, accepting payloads from newer clients.
+    #[default]
+    Tolerant,
+    /// Rejects requests with unknown fields at the top level of the request,
+    /// its `paymentPayload` or its `paymentRequirements`.
+    ///
+    /// Unknown fields set to `null` are tolerated.
+    Strict,
+}
+
+impl ParseMode {
+    /// Deserializes `json`, then checks it for unknown fields if strict.
+    fn parse<T>(self, json: serde_json::Value) -> Result<T, PaymentVerificationError>
+    where
+        T: serde::de::DeserializeOwned + Serialize,
+    {
+        match self {
+            Self::Tolerant => Ok(serde_json::from_value(json)?),
+            Self::Strict => {
+                let parsed: T = serde_json::from_value(json.clone())?;
+                // Fields that do not survive a round trip are unknown to `T`.
+                let known = serde_json::to_value(&parsed)?;
+                unknown_field(&json, &known, 1).map_or(Ok(parsed), |field| {
+                    Err(PaymentVerificationError::InvalidFormat(format!(
+                        "unknown field `{field}`"
+                    )))
+                })
+            }
+        }
+    }
+}
+
+/// Returns the path of the first non-null field of `input` missing from
+/// `known`, looking `depth` levels into nested objects.
+fn unknown_field(
+    input: &serde_json::Value,
+    known: &serde_json::Value,
+    depth: usize,
+) -> Option<String> {
+    let (serde_json::Value::Object(input), serde_json::Value::Object(known)) = (input, known)
+    else {
+        return None;
+    };
+    input.iter().find_map(|(key, value)| match known.get(key) {
+        None if !value.is_null() => Some(key.clone()),
+        Some(known) if depth > 0 => {
+            unknown_field(value, known, depth - 1).map(|field| format!("{key}.{field}"))
+        }
+        _ => None,
+    })
+}
+
 impl<const V: u8, TPayload, TRequirements> TryInto<VerifyRequest>
     for TypedVerifyRequest<V, TPayload, TRequirements>
 where
@@ -140,6 +252,7 @@ fn from(value: U64String) -> Self {
 /// This type is returned in the [`SupportedResponse`] to indicate what
 /// payment schemes, networks, and protocol versions a facilitator can handle.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct SupportedPaymentKind {
     /// The x402 protocol version.
@@ -159,10 +272,12 @@ pub struct SupportedPaymentKind {
 /// including protocol versions, schemes, networks, and signer addresses.
 #[serde_as]
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct SupportedResponse {
     /// List of supported payment kinds.
     #[serde_as(as = "VecSkipError<_>")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<SupportedPaymentKind>"))]
     pub kinds: Vec<SupportedPaymentKind>,
     /// List of supported protocol extensions.
     #[serde(default)]
@@ -173,9 +288,24 @@ pub struct SupportedResponse {
     /// (e.g., `"eip155:*"`), matching the official x402 wire format.
     #[serde(default)]
     pub signers: HashMap<String, Vec<String>>,
+    /// Public key the facilitator signs settlement receipts with, if any.
+    ///
+    /// See [`crate::attestation`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation_key: Option<String>,
 }
 
 impl SupportedResponse {
+    /// Returns `true` if a supported kind settles `scheme` payments on
+    /// `chain_id`, in any protocol version.
+    #[must_use]
+    pub fn supports(&self, chain_id: &ChainId, scheme: &str) -> bool {
+        let network = chain_id.to_string();
+        self.kinds
+            .iter()
+            .any(|kind| kind.scheme == scheme && kind.network == network)
+    }
+
     /// Finds signer addresses that match the given chain ID.
     ///
     /// Checks both exact match (e.g., `"eip155:8453"`) and namespace wildcard
@@ -196,6 +326,42 @@ pub fn signers_for_chain(&self, chain_id: &ChainId) -> Vec<&str> {
     }
 }
 
+/// The x402 protocol version a request is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProtocolVersion {
+    /// Version 1, which identifies networks by name.
+    V1,
+    /// Version 2, which identifies networks by CAIP-2 chain ID.
+    V2,
+}
+
+impl ProtocolVersion {
+    /// Returns the version for the numeric `x402Version`, if it is known.
+    #[must_use]
+    pub const fn from_u8(version: u8) -> Option<Self> {
+        match version {
+            1 => Some(Self::V1),
+            2 => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    /// Returns the numeric `x402Version` of this version.
+    #[must_use]
+    pub const fn as_u8(self) -> u8 {
+        match self {
+            Self::V1 => 1,
+            Self::V2 => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}", self.as_u8())
+    }
+}
+
 /// Request to verify a payment before settlement.
 ///
 /// This wrapper contains the payment payload and requirements sent by a client
@@ -231,6 +397,15 @@ pub fn scheme_slug(&self) -> Option<SchemeSlug> {
         scheme_slug_from_json(&self.0)
     }
 
+    /// Extracts the scheme handler slug, resolving V1 network names with
+    /// `networks`.
+    ///
+    /// Delegates to the same logic as [`VerifyRequest::scheme_slug_with`].
+    #[must_use]
+    pub fn scheme_slug_with(&self, networks: &NetworkRegistry) -> Option<SchemeSlug> {
+        scheme_slug_with_networks(&self.0, networks)
+    }
+
     /// Returns the CAIP-2 network identifier from `paymentRequirements.network`.
     ///
     /// Returns an empty string if the field is absent or not a string.
@@ -242,6 +417,22 @@ pub fn network(&self) -> &str {
             .and_then(serde_json::Value::as_str)
             .unwrap_or_default()
     }
+
+    /// Returns the stable payment id of this request.
+    ///
+    /// See [`payment_id`] for how the id is derived.
+    #[must_use]
+    pub fn payment_id(&self) -> Option<String> {
+        payment_id(&self.0)
+    }
+
+    /// Returns the payment requirements the payment is settled against.
+    ///
+    /// Delegates to the same logic as [`VerifyRequest::payment_requirements`].
+    #[must_use]
+    pub fn payment_requirements(&self) -> Option<v2::PaymentRequirements> {
+        payment_requirements_from_json(&self.0)
+    }
 }
 
 impl From<serde_json::Value> for SettleRequest {
@@ -269,6 +460,23 @@ pub fn into_json(self) -> serde_json::Value {
         self.0
     }
 
+    /// Returns the top-level `x402Version` of the request.
+    ///
+    /// Returns `None` if the field is absent or not a small integer.
+    #[must_use]
+    pub fn protocol_version(&self) -> Option<u8> {
+        protocol_version_from_json(&self.0)
+    }
+
+    /// Returns the protocol version of the request, for branching or metrics
+    /// without parsing the payload.
+    ///
+    /// Returns `None` if the version is absent, invalid, or unknown.
+    #[must_use]
+    pub fn protocol(&self) -> Option<ProtocolVersion> {
+        self.protocol_version().and_then(ProtocolVersion::from_u8)
+    }
+
     /// Extracts the scheme handler slug from the request.
     ///
     /// This determines which scheme handler should process this payment
@@ -279,6 +487,54 @@ pub fn into_json(self) -> serde_json::Value {
     pub fn scheme_slug(&self) -> Option<SchemeSlug> {
         scheme_slug_from_json(&self.0)
     }
+
+    /// Extracts the scheme handler slug, resolving V1 network names with
+    /// `networks`.
+    ///
+    /// V2 requests are handled as by [`Self::scheme_slug`]. V1 requests name
+    /// their network (e.g. `"base-sepolia"`) and scheme in `paymentPayload`.
+    ///
+    /// Returns `None` if the request format is invalid or a V1 network name
+    /// is not in `networks`.
+    #[must_use]
+    pub fn scheme_slug_with(&self, networks: &NetworkRegistry) -> Option<SchemeSlug> {
+        scheme_slug_with_networks(&self.0, networks)
+    }
+
+    /// Returns the CAIP-2 network identifier from `paymentRequirements.network`.
+    ///
+    /// Returns an empty string if the field is absent or not a string.
+    #[must_use]
+    pub fn network(&self) -> &str {
+        self.0
+            .get("paymentRequirements")
+            .and_then(|r| r.get("network"))
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default()
+    }
+
+    /// Returns the stable payment id of this request.
+    ///
+    /// See [`payment_id`] for how the id is derived.
+    #[must_use]
+    pub fn payment_id(&self) -> Option<String> {
+        payment_id(&self.0)
+    }
+
+    /// Returns the payment requirements the payment is verified against,
+    /// i.e. the option the client selected among those offered.
+    ///
+    /// Returns `None` if `paymentRequirements` is absent or malformed.
+    #[must_use]
+    pub fn payment_requirements(&self) -> Option<v2::PaymentRequirements> {
+        payment_requirements_from_json(&self.0)
+    }
+}
+
+/// Deserializes `paymentRequirements` from a raw verify/settle JSON value.
+fn payment_requirements_from_json(json: &serde_json::Value) -> Option<v2::PaymentRequirements> {
+    json.get("paymentRequirements")
+        .and_then(|r| v2::PaymentRequirements::deserialize(r).ok())
 }
 
 /// Extracts a [`SchemeSlug`] from a raw verify/settle JSON value.
@@ -286,8 +542,7 @@ pub fn scheme_slug(&self) -> Option<SchemeSlug> {
 /// Navigates `x402Version`, `paymentPayload.accepted.network`, and
 /// `paymentPayload.accepted.scheme` without cloning the JSON tree.
 fn scheme_slug_from_json(json: &serde_json::Value) -> Option<SchemeSlug> {
-    let x402_version: u8 = json.get("x402Version")?.as_u64()?.try_into().ok()?;
-    if x402_version != v2::X402Version2::VALUE {
+    if protocol_version_from_json(json)? != v2::X402Version2::VALUE {
         return None;
     }
     let accepted = json.get("paymentPayload")?.get("accepted")?;
@@ -296,6 +551,26 @@ fn scheme_slug_from_json(json: &serde_json::Value) -> Option<SchemeSlug> {
     Some(SchemeSlug::new(chain_id, scheme.into()))
 }
 
+/// Extracts a [`SchemeSlug`] from a raw verify/settle JSON value of either
+/// protocol version, mapping V1 network names to chain IDs with `networks`.
+fn scheme_slug_with_networks(
+    json: &serde_json::Value,
+    networks: &NetworkRegistry,
+) -> Option<SchemeSlug> {
+    if protocol_version_from_json(json)? != ProtocolVersion::V1.as_u8() {
+        return scheme_slug_from_json(json);
+    }
+    let payload = json.get("paymentPayload")?;
+    let chain_id = networks.chain_id_by_name(payload.get("network")?.as_str()?)?;
+    let scheme = payload.get("scheme")?.as_str()?;
+    Some(SchemeSlug::new(chain_id, scheme.into()))
+}
+
+/// Reads the top-level `x402Version` of a raw verify/settle JSON value.
+fn protocol_version_from_json(json: &serde_json::Value) -> Option<u8> {
+    json.get("x402Version")?.as_u64()?.try_into().ok()
+}
+
 /// Result returned by a facilitator after verifying a payment payload
 /// against the provided payment requirements.
 ///
@@ -310,6 +585,17 @@ pub enum VerifyResponse {
     Valid {
         /// The address of the payer.
         payer: String,
+        /// Token that lets the facilitator settle this payment without
+        /// verifying it again, if it issues them.
+        ///
+        /// See [`crate::verification`].
+        verification_token: Option<String>,
+        /// Seconds the authorization remains valid for, i.e. until its
+        /// `validBefore`, if the scheme reports it.
+        ///
+        /// Lets a server skip handling a request whose payment would expire
+        /// before it can be settled.
+        remaining_validity: Option<u64>,
     },
     /// The payload was well-formed but failed verification.
     Invalid {
@@ -326,7 +612,63 @@ impl VerifyResponse {
     /// Constructs a successful verification response with the given payer address.
     #[must_use]
     pub const fn valid(payer: String) -> Self {
-        Self::Valid { payer }
+        Self::Valid {
+            payer,
+            verification_token: None,
+            remaining_validity: None,
+        }
+    }
+
+    /// Attaches a verification token to a successful response.
+    ///
+    /// Invalid responses are returned unchanged.
+    #[must_use]
+    pub fn with_verification_token(mut self, token: String) -> Self {
+        if let Self::Valid {
+            verification_token, ..
+        } = &mut self
+        {
+            *verification_token = Some(token);
+        }
+        self
+    }
+
+    /// Reports the seconds the authorization of a successful response
+    /// remains valid for.
+    ///
+    /// Invalid responses are returned unchanged.
+    #[must_use]
+    pub const fn with_remaining_validity(mut self, seconds: u64) -> Self {
+        if let Self::Valid {
+            remaining_validity, ..
+        } = &mut self
+        {
+            *remaining_validity = Some(seconds);
+        }
+        self
+    }
+
+    /// Returns the seconds the authorization of a successful response
+    /// remains valid for, if reported.
+    #[must_use]
+    pub const fn remaining_validity(&self) -> Option<u64> {
+        match self {
+            Self::Valid {
+                remaining_validity, ..
+            } => *remaining_validity,
+            Self::Invalid { .. } => None,
+        }
+    }
+
+    /// Returns the verification token of a successful response, if any.
+    #[must_use]
+    pub fn verification_token(&self) -> Option<&str> {
+        match self {
+            Self::Valid {
+                verification_token, ..
+            } => verification_token.as_deref(),
+            Self::Invalid { .. } => None,
+        }
     }
 
     /// Constructs a failed verification response.
@@ -377,6 +719,7 @@ pub fn from_facilitator_error(error: &crate::facilitator::FacilitatorError) -> S
 
 /// Wire format for [`VerifyResponse`], using a flat boolean discriminator.
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 struct VerifyResponseWire {
     is_valid: bool,
@@ -386,16 +729,26 @@ struct VerifyResponseWire {
     invalid_reason: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     invalid_message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    verification_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    remaining_validity: Option<u64>,
 }
 
 impl From<VerifyResponse> for VerifyResponseWire {
     fn from(resp: VerifyResponse) -> Self {
         match resp {
-            VerifyResponse::Valid { payer } => Self {
+            VerifyResponse::Valid {
+                payer,
+                verification_token,
+                remaining_validity,
+            } => Self {
                 is_valid: true,
                 payer: Some(payer),
                 invalid_reason: None,
                 invalid_message: None,
+                verification_token,
+                remaining_validity,
             },
             VerifyResponse::Invalid {
                 reason,
@@ -406,6 +759,8 @@ fn from(resp: VerifyResponse) -> Self {
                 payer,
                 invalid_reason: Some(reason),
                 invalid_message: message,
+                verification_token: None,
+                remaining_validity: None,
             },
         }
     }
@@ -417,7 +772,11 @@ impl TryFrom<VerifyResponseWire> for VerifyResponse {
     fn try_from(wire: VerifyResponseWire) -> Result<Self, Self::Error> {
         if wire.is_valid {
             let payer = wire.payer.ok_or("missing field: payer")?;
-            Ok(Self::Valid { payer })
+            Ok(Self::Valid {
+                payer,
+                verification_token: wire.verification_token,
+                remaining_validity: wire.remaining_validity,
+            })
         } else {
             let reason = wire.invalid_reason.ok_or("missing field: invalidReason")?;
             Ok(Self::Invalid {
@@ -452,6 +811,11 @@ pub enum SettleResponse {
     Error {
         /// Machine-readable reason for failure.
         reason: String,
+        /// Structured classification of the failure, if known.
+        ///
+        /// Lets clients decide whether to re-sign or give up without parsing
+        /// the free-form `reason`. Serialized as `errorCode`.
+        code: Option<ErrorReason>,
         /// Optional human-readable description of the failure.
         message: Option<String>,
         /// The payer address, if identifiable.
@@ -468,6 +832,28 @@ pub const fn is_success(&self) -> bool {
         matches!(self, Self::Success { .. })
     }
 
+    /// Returns the network the settlement occurred or was attempted on.
+    #[must_use]
+    pub fn network(&self) -> &str {
+        match self {
+            Self::Success { network, .. } | Self::Error { network, .. } => network,
+        }
+    }
+
+    /// Replaces the reported network.
+    ///
+    /// Used to report settlements under the network of the request, so the
+    /// response always names the chain the client asked to pay on.
+    #[must_use]
+    pub fn with_network(mut self, chain_id: &ChainId) -> Self {
+        match &mut self {
+            Self::Success { network, .. } | Self::Error { network, .. } => {
+                *network = chain_id.to_string();
+            }
+        }
+        self
+    }
+
     /// Converts a [`FacilitatorError`] into a settlement error response,
     /// preserving the structured reason code and message from the error.
     ///
@@ -481,6 +867,7 @@ pub fn from_facilitator_error(
         let problem = error.as_payment_problem();
         Self::Error {
             reason: problem.reason().to_string(),
+            code: Some(problem.reason()),
             message: Some(problem.details().to_owned()),
             payer: None,
             network,
@@ -490,6 +877,7 @@ pub fn from_facilitator_error(
 
 /// Wire format for [`SettleResponse`], using a flat boolean discriminator.
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 struct SettleResponseWire {
     success: bool,
@@ -497,6 +885,12 @@ struct SettleResponseWire {
     error_reason: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     error_message: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_error_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    error_code: Option<ErrorReason>,
     #[serde(skip_serializing_if = "Option::is_none")]
     payer: Option<String>,
     #[serde(default)]
@@ -518,6 +912,7 @@ fn from(resp: SettleResponse) -> Self {
                 success: true,
                 error_reason: None,
                 error_message: None,
+                error_code: None,
                 payer: Some(payer),
                 transaction,
                 network,
@@ -525,6 +920,7 @@ fn from(resp: SettleResponse) -> Self {
             },
             SettleResponse::Error {
                 reason,
+                code,
                 message,
                 payer,
                 network,
@@ -532,6 +928,7 @@ fn from(resp: SettleResponse) -> Self {
                 success: false,
                 error_reason: Some(reason),
                 error_message: message,
+                error_code: code,
                 payer,
                 transaction: String::new(),
                 network,
@@ -562,6 +959,7 @@ fn try_from(
             let reason = wire.error_reason.ok_or("missing field: errorReason")?;
             Ok(Self::Error {
                 reason,
+                code: wire.error_code,
                 message: wire.error_message,
                 payer: wire.payer,
                 network: wire.network,
@@ -570,8 +968,177 @@ fn try_from(
     }
 }
 
+/// Deserializes an optional `errorCode`, mapping unknown codes to `None`.
+///
+/// Facilitators may emit codes this version does not know about; those must
+/// not make the whole settlement response unreadable.
+fn deserialize_error_code<'de, D>(deserializer: D) -> Result<Option<ErrorReason>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.and_then(|v| serde_json::from_value(v).ok()))
+}
+
 /// A payment required response.
 ///
 /// This is returned with HTTP 402 status to indicate that payment is required.
 /// Currently aliases to the V2 wire format.
 pub type PaymentRequired = v2::PaymentRequired;
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::facilitator::FacilitatorError;
+
+    #[test]
+    fn test_remaining_validity_rides_on_valid_response() {
+        let response = VerifyResponse::valid("0xpayer".into()).with_remaining_validity(90);
+        let wire = serde_json::to_value(&response).unwrap();
+        assert_eq!(wire["remainingValidity"], 90);
+
+        let parsed: VerifyResponse = serde_json::from_value(wire).unwrap();
+        assert_eq!(parsed.remaining_validity(), Some(90));
+        let invalid = VerifyResponse::invalid(None, "expired".into()).with_remaining_validity(90);
+        assert_eq!(invalid.remaining_validity(), None);
+    }
+
+    #[test]
+    fn test_protocol_version() {
+        let v1 = VerifyRequest::from(json!({ "x402Version": 1, "paymentPayload": {} }));
+        assert_eq!(v1.protocol_version(), Some(1));
+        assert_eq!(v1.protocol(), Some(ProtocolVersion::V1));
+
+        let v2 = VerifyRequest::from(json!({ "x402Version": 2, "paymentPayload": {} }));
+        assert_eq!(v2.protocol_version(), Some(2));
+        assert_eq!(v2.protocol(), Some(ProtocolVersion::V2));
+        assert_eq!(ProtocolVersion::V2.to_string(), "v2");
+
+        let missing = VerifyRequest::from(json!({ "paymentPayload": {} }));
+        assert_eq!(missing.protocol_version(), None);
+        let invalid = VerifyRequest::from(json!({ "x402Version": "2" }));
+        assert_eq!(invalid.protocol(), None);
+        let unknown = VerifyRequest::from(json!({ "x402Version": 7 }));
+        assert_eq!(unknown.protocol_version(), Some(7));
+        assert_eq!(unknown.protocol(), None);
+    }
+
+    #[test]
+    fn test_scheme_slug_resolves_v1_network_names() {
+        use crate::networks::NetworkInfo;
+
+        let networks = NetworkRegistry::new().with_networks(&[NetworkInfo {
+            name: "base-sepolia",
+            namespace: "eip155",
+            reference: "84532",
+        }]);
+        let base_sepolia = SchemeSlug::new(ChainId::new("eip155", "84532"), "exact".into());
+
+        let v1 = VerifyRequest::from(json!({
+            "x402Version": 1,
+            "paymentPayload": { "scheme": "exact", "network": "base-sepolia" },
+        }));
+        assert_eq!(v1.scheme_slug(), None);
+        assert_eq!(v1.scheme_slug_with(&networks), Some(base_sepolia.clone()));
+
+        let v2 = VerifyRequest::from(json!({
+            "x402Version": 2,
+            "paymentPayload": { "accepted": { "scheme": "exact", "network": "eip155:84532" } },
+        }));
+        assert_eq!(v2.scheme_slug_with(&networks), Some(base_sepolia));
+
+        let unknown = VerifyRequest::from(json!({
+            "x402Version": 1,
+            "paymentPayload": { "scheme": "exact", "network": "atlantis" },
+        }));
+        assert_eq!(unknown.scheme_slug_with(&networks), None);
+    }
+
+    #[test]
+    fn test_unknown_fields_follow_parse_mode() {
+        type Request = v2::VerifyRequest<
+            v2::PaymentPayload<v2::PaymentRequirements, serde_json::Value>,
+            v2::PaymentRequirements,
+        >;
+        let requirements = json!({
+            "scheme": "exact",
+            "network": "eip155:8453",
+            "amount": "1000",
+            "payTo": "0x209693Bc6afc0C5328bA36FaF03C514EF312287C",
+            "maxTimeoutSeconds": 60,
+            "asset": "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+        });
+        let request = |extra: (&str, serde_json::Value)| {
+            let mut json = json!({
+                "x402Version": 2,
+                "paymentPayload": {
+                    "x402Version": 2,
+                    "accepted": requirements,
+                    "payload": { "signature": "0x" },
+                    "resource": null,
+                },
+                "paymentRequirements": requirements,
+            });
+            json[extra.0] = extra.1;
+            VerifyRequest::from(json)
+        };
+
+        let future = request(("futureField", json!(true)));
+        assert!(Request::from_proto_with(future.clone(), ParseMode::Tolerant).is_ok());
+        let err = Request::from_proto_with(future, ParseMode::Strict).unwrap_err();
+        assert!(
+            matches!(err, PaymentVerificationError::InvalidFormat(ref m) if m.contains("futureField"))
+        );
+
+        let mut nested = requirements.clone();
+        nested["futureField"] = json!("x");
+        let nested = request(("paymentRequirements", nested));
+        let err = Request::from_proto_with(nested, ParseMode::Strict).unwrap_err();
+        assert!(
+            matches!(err, PaymentVerificationError::InvalidFormat(ref m) if m.contains("paymentRequirements.futureField"))
+        );
+
+        // Known fields are still validated in tolerant mode.
+        let invalid = request(("paymentRequirements", json!({ "scheme": "exact" })));
+        assert!(Request::from_proto_with(invalid, ParseMode::Tolerant).is_err());
+        let null = request(("futureField", serde_json::Value::Null));
+        assert!(Request::from_proto_with(null, ParseMode::Strict).is_ok());
+    }
+
+    #[test]
+    fn test_settle_error_code_from_transaction_failure() {
+        let error = FacilitatorError::TransactionFailed("0xabc reverted".into());
+        let response = SettleResponse::from_facilitator_error(&error, "eip155:8453".into());
+
+        assert!(matches!(
+            &response,
+            SettleResponse::Error { reason, code: Some(ErrorReason::TransactionFailed), .. }
+                if reason == "transaction_failed"
+        ));
+
+        let wire = serde_json::to_value(&response).unwrap();
+        assert_eq!(wire["errorCode"], "transaction_failed");
+    }
+
+    #[test]
+    fn test_settle_error_code_is_tolerant() {
+        let wire = json!({
+            "success": false,
+            "errorReason": "something_new",
+            "errorCode": "something_new",
+            "network": "eip155:8453"
+        });
+        let response: SettleResponse = serde_json::from_value(wire).unwrap();
+        assert!(matches!(response, SettleResponse::Error { code: None, .. }));
+
+        let wire = json!({
+            "success": false,
+            "errorReason": "transaction_failed",
+            "network": "eip155:8453"
+        });
+        let response: SettleResponse = serde_json::from_value(wire).unwrap();
+        assert!(matches!(response, SettleResponse::Error { code: None, .. }));
+    }
+}
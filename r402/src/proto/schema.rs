@@ -0,0 +1,154 @@
+//! JSON Schemas of the x402 wire types.
+//!
+//! With the `schemars` feature, the protocol types implement
+//! [`schemars::JsonSchema`], so integrators in other languages can generate
+//! clients from [`x402_json_schemas`] instead of reverse-engineering the serde
+//! attributes. Types with a hand-written serde format ([`Version`],
+//! [`ChainId`], and the flattened verify/settle responses) describe their
+//! wire format here.
+
+use std::borrow::Cow;
+
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema, schema_for};
+
+use super::{SettleResponse, SettleResponseWire, SupportedResponse, VerifyResponse, Version};
+use super::{VerifyResponseWire, v2};
+use crate::chain::ChainId;
+
+/// Returns the JSON Schemas of the x402 v2 wire types, keyed by type name.
+///
+/// Covers `PaymentRequirements`, `PaymentPayload`, `VerifyRequest`,
+/// `VerifyResponse`, `SettleResponse`, and `SupportedResponse`. Each entry is
+/// a self-contained schema with its own `$defs`.
+#[must_use]
+pub fn x402_json_schemas() -> serde_json::Value {
+    type Payload = v2::PaymentPayload<v2::PaymentRequirements, serde_json::Value>;
+    serde_json::json!({
+        "PaymentRequirements": schema_for!(v2::PaymentRequirements),
+        "PaymentPayload": schema_for!(Payload),
+        "VerifyRequest": schema_for!(v2::VerifyRequest<Payload, v2::PaymentRequirements>),
+        "VerifyResponse": schema_for!(VerifyResponse),
+        "SettleResponse": schema_for!(SettleResponse),
+        "SupportedResponse": schema_for!(SupportedResponse),
+    })
+}
+
+impl<const N: u8> JsonSchema for Version<N> {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        format!("Version{N}").into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "integer",
+            "const": N,
+        })
+    }
+}
+
+impl JsonSchema for ChainId {
+    fn schema_name() -> Cow<'static, str> {
+        "ChainId".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "description": "CAIP-2 chain ID, e.g. \"eip155:8453\".",
+            "pattern": "^[-a-z0-9]{3,8}:[-_a-zA-Z0-9]{1,32}$",
+        })
+    }
+}
+
+impl JsonSchema for VerifyResponse {
+    fn schema_name() -> Cow<'static, str> {
+        "VerifyResponse".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        VerifyResponseWire::json_schema(generator)
+    }
+}
+
+impl JsonSchema for SettleResponse {
+    fn schema_name() -> Cow<'static, str> {
+        "SettleResponse".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        SettleResponseWire::json_schema(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    /// Checks `value` against the object structure of `schema`: required
+    /// properties are present, no undeclared property appears, and nested
+    /// objects conform to their (possibly referenced) schemas.
+    fn assert_conforms(root: &Value, schema: &Value, value: &Value) {
+        let schema = schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .map_or(schema, |path| {
+                &root["$defs"][path.trim_start_matches("#/$defs/")]
+            });
+        let (Some(properties), Some(object)) = (
+            schema.get("properties").and_then(Value::as_object),
+            value.as_object(),
+        ) else {
+            return;
+        };
+        for required in schema["required"].as_array().into_iter().flatten() {
+            let required = required.as_str().unwrap();
+            assert!(object.contains_key(required), "missing {required}");
+        }
+        for (key, field) in object {
+            assert!(properties.contains_key(key), "undeclared {key}");
+            assert_conforms(root, &properties[key.as_str()], field);
+        }
+    }
+
+    #[test]
+    fn test_schema_accepts_a_known_good_payload() {
+        let requirements = json!({
+            "scheme": "exact",
+            "network": "eip155:8453",
+            "amount": "10000",
+            "payTo": "0x209693Bc6afc0C5328bA36FaF03C514EF312287C",
+            "maxTimeoutSeconds": 60,
+            "asset": "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+            "extra": { "name": "USD Coin", "version": "2" },
+        });
+        let request = json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "x402Version": 2,
+                "accepted": requirements,
+                "payload": { "signature": "0xabcd" },
+                "resource": {
+                    "description": "Weather report",
+                    "mimeType": "application/json",
+                    "url": "https://example.com/weather",
+                },
+            },
+            "paymentRequirements": requirements,
+        });
+
+        let schemas = x402_json_schemas();
+        let schema = &schemas["VerifyRequest"];
+        assert_conforms(schema, schema, &request);
+        let version = &schema["properties"]["x402Version"];
+        assert_eq!(version["type"], "integer");
+        assert_eq!(version["const"], 2);
+        let required = schemas["VerifyResponse"]["required"].as_array().unwrap();
+        assert!(required.contains(&json!("isValid")));
+    }
+}
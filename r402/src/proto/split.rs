@@ -0,0 +1,164 @@
+//! Split payments: one resource paid by several transfers.
+//!
+//! The `exact` scheme pays a single recipient. A marketplace charging a
+//! platform fee on top of the seller's price instead advertises one
+//! requirement per recipient and marks the 402 response with the
+//! [`SPLIT_EXTENSION`], meaning every requirement in `accepts` must be paid
+//! rather than any one of them.
+//!
+//! The client answers with a [`SplitPaymentPayload`]: one signed V2 payment
+//! per requirement, in a single header.
+//!
+//! ```json
+//! { "x402Version": 2, "payments": [ { "accepted": ..., "payload": ... }, ... ] }
+//! ```
+//!
+//! The server pairs each payment with its requirement using
+//! [`SplitPaymentPayload::into_parts`], then verifies and settles each one as
+//! an ordinary payment.
+
+use serde::{Deserialize, Serialize};
+
+use super::PaymentVerificationError;
+use super::v2::{self, PaymentRequired, PaymentRequirements};
+
+/// Extension id marking a 402 response whose `accepts` must all be paid.
+///
+/// Its info is `{ "parts": <number of requirements> }`.
+pub const SPLIT_EXTENSION: &str = "split";
+
+/// Returns the info advertised under [`SPLIT_EXTENSION`] for `parts`
+/// requirements.
+#[must_use]
+pub fn split_info(parts: usize) -> serde_json::Value {
+    serde_json::json!({ "parts": parts })
+}
+
+/// Returns `true` if `payment_required` asks for a split payment.
+#[must_use]
+pub fn is_split(payment_required: &PaymentRequired) -> bool {
+    payment_required
+        .extensions
+        .as_ref()
+        .is_some_and(|extensions| extensions.contains_key(SPLIT_EXTENSION))
+}
+
+/// One signed payment per requirement of a split 402 response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitPaymentPayload<TPayload = serde_json::Value> {
+    /// Protocol version (always 2).
+    pub x402_version: v2::X402Version2,
+    /// The component payments, one per recipient.
+    pub payments: Vec<v2::PaymentPayload<PaymentRequirements, TPayload>>,
+}
+
+impl<TPayload> SplitPaymentPayload<TPayload> {
+    /// Pairs each of `parts` with the payment accepting it, returning the
+    /// payments in the order of `parts`.
+    ///
+    /// Only checks what each payment claims to accept; the signed transfers
+    /// still need verifying against their requirements.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PaymentVerificationError::InvalidPaymentAmount`] if a
+    /// payment to a recipient accepts another amount than required, and
+    /// [`PaymentVerificationError::InvalidFormat`] if a transfer is missing
+    /// or there are more payments than parts.
+    pub fn into_parts(
+        self,
+        parts: &[PaymentRequirements],
+    ) -> Result<Vec<v2::PaymentPayload<PaymentRequirements, TPayload>>, PaymentVerificationError>
+    {
+        if self.payments.len() != parts.len() {
+            return Err(PaymentVerificationError::InvalidFormat(format!(
+                "split payment has {} transfers, expected {}",
+                self.payments.len(),
+                parts.len()
+            )));
+        }
+        let mut payments: Vec<_> = self.payments.into_iter().map(Some).collect();
+        let mut matched = Vec::with_capacity(parts.len());
+        for part in parts {
+            let position = payments
+                .iter()
+                .position(|payment| payment.as_ref().is_some_and(|p| p.accepted == *part));
+            let Some(payment) = position.and_then(|i| payments[i].take()) else {
+                let short = payments.iter().flatten().any(|payment| {
+                    let accepted = &payment.accepted;
+                    accepted.pay_to == part.pay_to
+                        && accepted.asset == part.asset
+                        && accepted.network == part.network
+                });
+                return Err(if short {
+                    PaymentVerificationError::InvalidPaymentAmount
+                } else {
+                    PaymentVerificationError::InvalidFormat(format!(
+                        "split payment has no transfer to {}",
+                        part.pay_to
+                    ))
+                });
+            };
+            matched.push(payment);
+        }
+        Ok(matched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn part(pay_to: &str, amount: &str) -> PaymentRequirements {
+        serde_json::from_value(json!({
+            "scheme": "exact",
+            "network": "eip155:8453",
+            "amount": amount,
+            "payTo": pay_to,
+            "maxTimeoutSeconds": 60,
+            "asset": "0xusdc"
+        }))
+        .unwrap()
+    }
+
+    fn split(transfers: &[(&str, &str)]) -> SplitPaymentPayload {
+        serde_json::from_value(json!({
+            "x402Version": 2,
+            "payments": transfers
+                .iter()
+                .map(|(pay_to, amount)| json!({
+                    "x402Version": 2,
+                    "accepted": part(pay_to, amount),
+                    "payload": { "signature": "0xsig" }
+                }))
+                .collect::<Vec<_>>()
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_two_way_split_with_short_transfer() {
+        let parts = [part("0xplatform", "100"), part("0xseller", "900")];
+
+        let payments = split(&[("0xseller", "900"), ("0xplatform", "100")])
+            .into_parts(&parts)
+            .unwrap();
+        assert_eq!(payments[0].accepted.pay_to, "0xplatform");
+        assert_eq!(payments[1].accepted.pay_to, "0xseller");
+
+        let short = split(&[("0xplatform", "100"), ("0xseller", "800")]).into_parts(&parts);
+        assert!(matches!(
+            short,
+            Err(PaymentVerificationError::InvalidPaymentAmount)
+        ));
+
+        let missing = split(&[("0xplatform", "100"), ("0xplatform", "100")]).into_parts(&parts);
+        assert!(matches!(
+            missing,
+            Err(PaymentVerificationError::InvalidFormat(_))
+        ));
+    }
+}
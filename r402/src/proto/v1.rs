@@ -0,0 +1,320 @@
+//! Protocol version 1 compatibility types.
+//!
+//! V1 identified networks by human-readable names (e.g. `base-sepolia`)
+//! instead of CAIP-2 chain IDs. This module provides the V1 shape of the
+//! facilitator `/supported` response so that V1-speaking clients can consume
+//! the capabilities of a V2 facilitator.
+//!
+//! Conversions take a network registry (such as `EVM_NETWORKS` from
+//! `r402-evm` or `SOLANA_NETWORKS` from `r402-svm`) to map between names and
+//! chain IDs. They are lossy: kinds and requirements on networks missing from
+//! the registry are dropped, and V1 carries no signers, extensions, or
+//! attestation key.
+
+use serde::{Deserialize, Serialize};
+
+use super::v2::{self, PaymentRequired, PaymentRequirements, ResourceInfo};
+use super::{ProtocolVersion, SettleResponse, SupportedPaymentKind, SupportedResponse};
+use crate::networks::NetworkInfo;
+
+/// A payment method supported by a facilitator, in V1 form.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedPaymentKindV1 {
+    /// The x402 protocol version.
+    pub x402_version: u8,
+    /// The payment scheme identifier (e.g., "exact").
+    pub scheme: String,
+    /// The network name (e.g., "base-sepolia").
+    pub network: String,
+    /// Optional scheme-specific extra data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra: Option<serde_json::Value>,
+}
+
+/// Response from a V1 facilitator's `/supported` endpoint.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedResponseV1 {
+    /// List of supported payment kinds.
+    pub kinds: Vec<SupportedPaymentKindV1>,
+}
+
+impl SupportedResponseV1 {
+    /// Converts a unified [`SupportedResponse`] into its V1 form, with
+    /// `x402Version` 1.
+    ///
+    /// Kinds whose CAIP-2 network is not in `networks` are dropped.
+    #[must_use]
+    pub fn from_supported(response: &SupportedResponse, networks: &[NetworkInfo]) -> Self {
+        let kinds = response
+            .kinds
+            .iter()
+            .filter_map(|kind| {
+                let info = networks
+                    .iter()
+                    .find(|info| info.chain_id().to_string() == kind.network)?;
+                Some(SupportedPaymentKindV1 {
+                    x402_version: ProtocolVersion::V1.as_u8(),
+                    scheme: kind.scheme.clone(),
+                    network: info.name.to_owned(),
+                    extra: kind.extra.clone(),
+                })
+            })
+            .collect();
+        Self { kinds }
+    }
+
+    /// Converts this V1 response into a unified [`SupportedResponse`], with
+    /// `x402Version` 2.
+    ///
+    /// Kinds whose network name is not in `networks` are dropped.
+    #[must_use]
+    pub fn to_supported(&self, networks: &[NetworkInfo]) -> SupportedResponse {
+        let kinds = self
+            .kinds
+            .iter()
+            .filter_map(|kind| {
+                let info = networks.iter().find(|info| info.name == kind.network)?;
+                Some(SupportedPaymentKind {
+                    x402_version: ProtocolVersion::V2.as_u8(),
+                    scheme: kind.scheme.clone(),
+                    network: info.chain_id().to_string(),
+                    extra: kind.extra.clone(),
+                })
+            })
+            .collect();
+        SupportedResponse {
+            kinds,
+            ..SupportedResponse::default()
+        }
+    }
+}
+
+/// Payment requirements as sent by a V1 server.
+///
+/// Unlike V2, each V1 requirement carries its own resource description.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequirementsV1 {
+    /// The payment scheme identifier (e.g., "exact").
+    pub scheme: String,
+    /// The network name (e.g., "base-sepolia").
+    pub network: String,
+    /// The maximum payment amount in token units.
+    pub max_amount_required: String,
+    /// URL of the resource.
+    pub resource: String,
+    /// Human-readable description of the resource.
+    #[serde(default)]
+    pub description: String,
+    /// MIME type of the resource content.
+    #[serde(default)]
+    pub mime_type: String,
+    /// The recipient address for payment.
+    pub pay_to: String,
+    /// Maximum time in seconds for payment validity.
+    pub max_timeout_seconds: u64,
+    /// The token asset address.
+    pub asset: String,
+    /// Optional scheme-specific extra data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra: Option<serde_json::Value>,
+}
+
+/// HTTP 402 Payment Required response body for V1.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequiredV1 {
+    /// The x402 protocol version (always 1).
+    pub x402_version: u8,
+    /// Optional error message if the request was malformed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// List of acceptable payment methods.
+    #[serde(default)]
+    pub accepts: Vec<PaymentRequirementsV1>,
+}
+
+impl PaymentRequiredV1 {
+    /// Converts this V1 response into a V2 [`PaymentRequired`].
+    ///
+    /// Requirements whose network name is not in `networks` are dropped. The
+    /// resource is taken from the first requirement.
+    #[must_use]
+    pub fn to_payment_required(&self, networks: &[NetworkInfo]) -> PaymentRequired {
+        let first = self.accepts.first();
+        let resource = ResourceInfo {
+            description: first.map(|req| req.description.clone()).unwrap_or_default(),
+            mime_type: first.map(|req| req.mime_type.clone()).unwrap_or_default(),
+            url: first.map(|req| req.resource.clone()).unwrap_or_default(),
+        };
+        let accepts = self
+            .accepts
+            .iter()
+            .filter_map(|req| {
+                let info = networks.iter().find(|info| info.name == req.network)?;
+                Some(PaymentRequirements {
+                    scheme: req.scheme.clone(),
+                    network: info.chain_id(),
+                    amount: req.max_amount_required.clone(),
+                    pay_to: req.pay_to.clone(),
+                    max_timeout_seconds: req.max_timeout_seconds,
+                    asset: req.asset.clone(),
+                    extra: req.extra.clone(),
+                    required_extensions: None,
+                })
+            })
+            .collect();
+        PaymentRequired {
+            x402_version: v2::V2,
+            error: self.error.clone(),
+            resource,
+            accepts,
+            extensions: None,
+        }
+    }
+}
+
+/// A signed payment authorization from the buyer, in V1 form.
+///
+/// Unlike V2, the payload names the scheme and network it pays on instead of
+/// echoing the accepted requirements.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentPayloadV1 {
+    /// The x402 protocol version (always 1).
+    pub x402_version: u8,
+    /// The payment scheme identifier (e.g., "exact").
+    pub scheme: String,
+    /// The network name (e.g., "base-sepolia").
+    pub network: String,
+    /// The scheme-specific signed payload.
+    pub payload: serde_json::Value,
+}
+
+/// Settlement result in V1 form.
+///
+/// A V1 server returns it base64-encoded JSON in the `X-PAYMENT-RESPONSE`
+/// header, like V2 does in `PAYMENT-RESPONSE`. Unlike V2, the network is a
+/// name, and failures carry only an `errorReason`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SettleResponseV1 {
+    /// Whether the payment was settled.
+    pub success: bool,
+    /// Machine-readable reason for a failed settlement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_reason: Option<String>,
+    /// The settlement transaction hash, empty on failure.
+    #[serde(default)]
+    pub transaction: String,
+    /// The network name (e.g., "base-sepolia").
+    pub network: String,
+    /// The address that paid, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payer: Option<String>,
+}
+
+impl SettleResponseV1 {
+    /// Converts a unified [`SettleResponse`] into its V1 form.
+    ///
+    /// Returns `None` if the CAIP-2 network is not in `networks`.
+    #[must_use]
+    pub fn from_settle_response(
+        response: &SettleResponse,
+        networks: &[NetworkInfo],
+    ) -> Option<Self> {
+        let network = networks
+            .iter()
+            .find(|info| info.chain_id().to_string() == response.network())?
+            .name
+            .to_owned();
+        Some(match response {
+            SettleResponse::Success {
+                payer, transaction, ..
+            } => Self {
+                success: true,
+                error_reason: None,
+                transaction: transaction.clone(),
+                network,
+                payer: Some(payer.clone()),
+            },
+            SettleResponse::Error { reason, payer, .. } => Self {
+                success: false,
+                error_reason: Some(reason.clone()),
+                transaction: String::new(),
+                network,
+                payer: payer.clone(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const NETWORKS: &[NetworkInfo] = &[
+        NetworkInfo {
+            name: "base",
+            namespace: "eip155",
+            reference: "8453",
+        },
+        NetworkInfo {
+            name: "solana",
+            namespace: "solana",
+            reference: "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp",
+        },
+    ];
+
+    fn kind(network: &str, extra: Option<serde_json::Value>) -> SupportedPaymentKind {
+        SupportedPaymentKind {
+            x402_version: 2,
+            scheme: "exact".into(),
+            network: network.into(),
+            extra,
+        }
+    }
+
+    #[test]
+    fn test_mixed_chain_supported_round_trip() {
+        let fee_payer = json!({ "feePayer": "FeePayer1111111111111111111111111111111111" });
+        let supported = SupportedResponse {
+            kinds: vec![
+                kind("eip155:8453", None),
+                kind(
+                    "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp",
+                    Some(fee_payer.clone()),
+                ),
+                kind("eip155:999999", None),
+            ],
+            ..SupportedResponse::default()
+        };
+
+        let v1 = SupportedResponseV1::from_supported(&supported, NETWORKS);
+        let networks: Vec<&str> = v1.kinds.iter().map(|k| k.network.as_str()).collect();
+        assert_eq!(networks, ["base", "solana"]);
+        assert_eq!(v1.kinds[1].extra, Some(fee_payer));
+        assert_eq!(
+            serde_json::to_value(&v1.kinds[0]).unwrap(),
+            json!({ "x402Version": 1, "scheme": "exact", "network": "base" })
+        );
+
+        let back = v1.to_supported(NETWORKS);
+        assert!(back.kinds.iter().all(|k| k.x402_version == 2));
+        let networks: Vec<&str> = back.kinds.iter().map(|k| k.network.as_str()).collect();
+        assert_eq!(
+            networks,
+            ["eip155:8453", "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp"]
+        );
+    }
+}
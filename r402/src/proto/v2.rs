@@ -16,9 +16,11 @@
 //! - [`X402Version2`] - Version marker that serializes as `2`
 //! - [`PaymentPayload`] - Signed payment with accepted requirements
 //! - [`PaymentRequirements`] - Payment terms set by the seller
+//! - [`FieldDiff`] - A field that differs between two payment requirements
 //! - [`PaymentRequired`] - HTTP 402 response body
 //! - [`ResourceInfo`] - Metadata about the paid resource
 //! - [`PriceTag`] - Builder for creating payment requirements
+//! - [`PaymentRequirementsBuilder`] - Validating builder for payment requirements
 
 use std::fmt;
 use std::str::FromStr;
@@ -29,7 +31,7 @@
 
 use crate::chain::ChainId;
 use crate::proto;
-use crate::proto::SupportedResponse;
+use crate::proto::{PaymentVerificationError, SupportedResponse};
 
 /// Version marker for x402 protocol version 2.
 ///
@@ -42,6 +44,12 @@
 /// Convenience constant for constructing V2 protocol messages.
 pub const V2: X402Version2 = super::Version;
 
+/// Default `maxTimeoutSeconds` of price tags built by scheme servers.
+///
+/// Long enough for the client to sign and the facilitator to settle, short
+/// enough that a leaked authorization is not usable for long.
+pub const DEFAULT_MAX_TIMEOUT_SECONDS: u64 = 60;
+
 /// Response from a V2 payment verification request.
 ///
 /// V2 uses the same response format as the protocol-level type.
@@ -56,6 +64,7 @@
 ///
 /// This provides human-readable information about what the buyer is paying for.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceInfo {
     /// Human-readable description of the resource.
@@ -83,6 +92,7 @@ pub struct ResourceInfo {
 /// - `TAccepted` - The accepted requirements type
 /// - `TPayload` - The scheme-specific payload type
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct PaymentPayload<TAccepted, TPayload> {
     /// The payment requirements the buyer accepted.
@@ -110,6 +120,7 @@ pub struct PaymentPayload<TAccepted, TPayload> {
 /// - `TAddress` - The address type (default: `String`)
 /// - `TExtra` - Scheme-specific extra data type (default: `serde_json::Value`)
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct PaymentRequirements<
     TScheme = String,
@@ -132,9 +143,63 @@ pub struct PaymentRequirements<
     /// Scheme-specific extra data.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub extra: Option<TExtra>,
+    /// Keys that must be present in the payment payload's `extensions`.
+    ///
+    /// Lets a server require, for example, a KYC token with the payment.
+    /// The facilitator rejects payloads missing any of them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_extensions: Option<Vec<String>>,
+}
+
+impl<TScheme, TAmount, TAddress, TExtra> PaymentRequirements<TScheme, TAmount, TAddress, TExtra> {
+    /// Checks that `extensions` contains every required extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PaymentVerificationError::MissingExtension`] naming the first
+    /// required extension that is absent.
+    pub fn check_required_extensions(
+        &self,
+        extensions: Option<&proto::Extensions>,
+    ) -> Result<(), PaymentVerificationError> {
+        let missing = self
+            .required_extensions
+            .iter()
+            .flatten()
+            .find(|key| !extensions.is_some_and(|e| e.contains_key(key.as_str())));
+        missing.map_or(Ok(()), |key| {
+            Err(PaymentVerificationError::MissingExtension(key.clone()))
+        })
+    }
+
+    /// Picks the required extensions out of those `offered` in a 402 response.
+    ///
+    /// Clients use this to echo required extensions back in the payment
+    /// payload. Returns `None` if no extension is required.
+    #[must_use]
+    pub fn select_required_extensions(
+        &self,
+        offered: Option<&proto::Extensions>,
+    ) -> Option<proto::Extensions> {
+        let required = self.required_extensions.as_ref()?;
+        let offered = offered?;
+        Some(
+            required
+                .iter()
+                .filter_map(|key| Some((key.clone(), offered.get(key)?.clone())))
+                .collect(),
+        )
+    }
 }
 
 impl PaymentRequirements {
+    /// Returns a builder validating the requirements at
+    /// [`build`](PaymentRequirementsBuilder::build).
+    #[must_use]
+    pub fn builder() -> PaymentRequirementsBuilder {
+        PaymentRequirementsBuilder::default()
+    }
+
     /// Converts the payment requirements to a concrete type.
     #[must_use]
     pub fn as_concrete<
@@ -161,8 +226,156 @@ pub fn as_concrete<
             max_timeout_seconds: self.max_timeout_seconds,
             asset,
             extra,
+            required_extensions: self.required_extensions.clone(),
         })
     }
+
+    /// Reports the fields that differ between `self` and `other`.
+    ///
+    /// Useful for explaining why a client's `accepted` requirements match none
+    /// of the offered ones. Hex addresses are compared case-insensitively, so
+    /// checksummed and lowercase encodings are not reported as different.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> Vec<FieldDiff> {
+        let mut diffs = Vec::new();
+        let mut check = |field, differs: bool, left: String, right: String| {
+            if differs {
+                diffs.push(FieldDiff { field, left, right });
+            }
+        };
+        check(
+            "scheme",
+            self.scheme != other.scheme,
+            self.scheme.clone(),
+            other.scheme.clone(),
+        );
+        check(
+            "network",
+            self.network != other.network,
+            self.network.to_string(),
+            other.network.to_string(),
+        );
+        check(
+            "amount",
+            self.amount != other.amount,
+            self.amount.clone(),
+            other.amount.clone(),
+        );
+        check(
+            "asset",
+            !same_address(&self.asset, &other.asset),
+            self.asset.clone(),
+            other.asset.clone(),
+        );
+        check(
+            "payTo",
+            !same_address(&self.pay_to, &other.pay_to),
+            self.pay_to.clone(),
+            other.pay_to.clone(),
+        );
+        check(
+            "maxTimeoutSeconds",
+            self.max_timeout_seconds != other.max_timeout_seconds,
+            self.max_timeout_seconds.to_string(),
+            other.max_timeout_seconds.to_string(),
+        );
+        check(
+            "extra",
+            self.extra != other.extra,
+            extra_to_string(self.extra.as_ref()),
+            extra_to_string(other.extra.as_ref()),
+        );
+        check(
+            "requiredExtensions",
+            self.required_extensions != other.required_extensions,
+            format!("{:?}", self.required_extensions),
+            format!("{:?}", other.required_extensions),
+        );
+        diffs
+    }
+
+    /// Returns `true` if these requirements, as echoed back by a client in
+    /// `accepted`, are compatible with one of the `offered` requirements.
+    ///
+    /// Compared with an offered requirement:
+    ///
+    /// - `scheme`, `network` and `amount` must match exactly, and `asset` and
+    ///   `payTo` must match up to the case of hex addresses.
+    /// - `maxTimeoutSeconds` must not exceed the offered timeout.
+    /// - `extra` may be absent or partially echoed, but every key present must
+    ///   carry the offered value.
+    /// - `requiredExtensions` may be absent, but every key present must be
+    ///   among the offered ones.
+    #[must_use]
+    pub fn is_compatible_subset_of(&self, offered: &[Self]) -> bool {
+        offered.iter().any(|offer| self.is_compatible_with(offer))
+    }
+
+    fn is_compatible_with(&self, offer: &Self) -> bool {
+        self.scheme == offer.scheme
+            && self.network == offer.network
+            && self.amount == offer.amount
+            && same_address(&self.asset, &offer.asset)
+            && same_address(&self.pay_to, &offer.pay_to)
+            && self.max_timeout_seconds <= offer.max_timeout_seconds
+            && is_partial_extra(self.extra.as_ref(), offer.extra.as_ref())
+            && self.required_extensions.iter().flatten().all(|key| {
+                offer
+                    .required_extensions
+                    .as_ref()
+                    .is_some_and(|offered| offered.contains(key))
+            })
+    }
+}
+
+/// Returns `true` if every key of the `echoed` extra object carries the same
+/// value in `offered`. Non-object extras must be equal.
+fn is_partial_extra(
+    echoed: Option<&serde_json::Value>,
+    offered: Option<&serde_json::Value>,
+) -> bool {
+    match (echoed, offered) {
+        (None, _) => true,
+        (Some(serde_json::Value::Object(echoed)), Some(serde_json::Value::Object(offered))) => {
+            echoed
+                .iter()
+                .all(|(key, value)| offered.get(key) == Some(value))
+        }
+        (Some(serde_json::Value::Object(echoed)), None) => echoed.is_empty(),
+        (Some(echoed), offered) => offered == Some(echoed),
+    }
+}
+
+/// A single field that differs between two [`PaymentRequirements`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// Wire name of the differing field (e.g. `"payTo"`).
+    pub field: &'static str,
+    /// Value on the left-hand side of the comparison.
+    pub left: String,
+    /// Value on the right-hand side of the comparison.
+    pub right: String,
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} != {}", self.field, self.left, self.right)
+    }
+}
+
+/// Compares two addresses, ignoring case for `0x`-prefixed hex addresses.
+///
+/// Other encodings such as base58 are case-sensitive and compared exactly.
+fn same_address(a: &str, b: &str) -> bool {
+    if a.starts_with("0x") && b.starts_with("0x") {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+fn extra_to_string(extra: Option<&serde_json::Value>) -> String {
+    extra.map_or_else(|| "null".to_owned(), ToString::to_string)
 }
 
 /// HTTP 402 Payment Required response body for V2.
@@ -170,6 +383,7 @@ pub fn as_concrete<
 /// This is returned when a resource requires payment. It contains
 /// the list of acceptable payment methods and resource metadata.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct PaymentRequired {
     /// Protocol version (always 2).
@@ -232,6 +446,242 @@ pub const fn with_timeout(mut self, seconds: u64) -> Self {
         self.requirements.max_timeout_seconds = seconds;
         self
     }
+
+    /// Sets the maximum timeout, checking it against the facilitator's buffer.
+    ///
+    /// Facilitators reject authorizations that expire within
+    /// `min_valid_before_buffer` seconds (the EVM facilitator's
+    /// `clockSkewTolerance`). A timeout that does not exceed the buffer
+    /// produces payments that are already expired when verified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MaxTimeoutTooShort`] if `seconds` does not exceed
+    /// `min_valid_before_buffer`.
+    pub fn with_max_timeout_seconds(
+        self,
+        seconds: u64,
+        min_valid_before_buffer: u64,
+    ) -> Result<Self, MaxTimeoutTooShort> {
+        if seconds <= min_valid_before_buffer {
+            return Err(MaxTimeoutTooShort {
+                seconds,
+                min_valid_before_buffer,
+            });
+        }
+        Ok(self.with_timeout(seconds))
+    }
+
+    /// Requires the payment payload to include the extension `key`.
+    #[must_use]
+    pub fn with_required_extension(mut self, key: impl Into<String>) -> Self {
+        self.requirements
+            .required_extensions
+            .get_or_insert_with(Vec::new)
+            .push(key.into());
+        self
+    }
+}
+
+/// Error returned when a price tag's timeout is too short to be settled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "maxTimeoutSeconds of {seconds}s does not exceed the facilitator's {min_valid_before_buffer}s validity buffer"
+)]
+pub struct MaxTimeoutTooShort {
+    /// The rejected timeout in seconds.
+    pub seconds: u64,
+    /// The facilitator's minimum remaining validity in seconds.
+    pub min_valid_before_buffer: u64,
+}
+
+/// Accepted range of `maxTimeoutSeconds` in built requirements.
+const MAX_TIMEOUT_SECONDS_RANGE: std::ops::RangeInclusive<u64> = 1..=86_400;
+
+/// Builder for [`PaymentRequirements`] that validates them at
+/// [`build`](Self::build).
+///
+/// Catches misconfigured requirements when the server starts instead of when
+/// a client's payment fails.
+#[derive(Debug, Clone)]
+pub struct PaymentRequirementsBuilder {
+    scheme: Option<String>,
+    network: Option<String>,
+    amount: Option<String>,
+    pay_to: Option<String>,
+    asset: Option<String>,
+    max_timeout_seconds: u64,
+    extra: Option<serde_json::Value>,
+    required_extensions: Option<Vec<String>>,
+}
+
+impl Default for PaymentRequirementsBuilder {
+    fn default() -> Self {
+        Self {
+            scheme: None,
+            network: None,
+            amount: None,
+            pay_to: None,
+            asset: None,
+            max_timeout_seconds: DEFAULT_MAX_TIMEOUT_SECONDS,
+            extra: None,
+            required_extensions: None,
+        }
+    }
+}
+
+impl PaymentRequirementsBuilder {
+    /// Sets the payment scheme (e.g., "exact").
+    #[must_use]
+    pub fn scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.scheme = Some(scheme.into());
+        self
+    }
+
+    /// Sets the CAIP-2 network (e.g., "eip155:8453").
+    #[must_use]
+    pub fn network(mut self, network: impl Into<String>) -> Self {
+        self.network = Some(network.into());
+        self
+    }
+
+    /// Sets the amount in the token's smallest unit.
+    #[must_use]
+    pub fn amount(mut self, amount: impl Into<String>) -> Self {
+        self.amount = Some(amount.into());
+        self
+    }
+
+    /// Sets the recipient address.
+    #[must_use]
+    pub fn pay_to(mut self, pay_to: impl Into<String>) -> Self {
+        self.pay_to = Some(pay_to.into());
+        self
+    }
+
+    /// Sets the token asset address.
+    #[must_use]
+    pub fn asset(mut self, asset: impl Into<String>) -> Self {
+        self.asset = Some(asset.into());
+        self
+    }
+
+    /// Sets `maxTimeoutSeconds`, [`DEFAULT_MAX_TIMEOUT_SECONDS`] by default.
+    #[must_use]
+    pub const fn max_timeout_seconds(mut self, seconds: u64) -> Self {
+        self.max_timeout_seconds = seconds;
+        self
+    }
+
+    /// Sets the scheme-specific extra data.
+    ///
+    /// For `eip155` networks it must carry the token's EIP-712 domain `name`
+    /// and `version`.
+    #[must_use]
+    pub fn extra(mut self, extra: serde_json::Value) -> Self {
+        self.extra = Some(extra);
+        self
+    }
+
+    /// Requires the payment payload to include the extension `key`.
+    #[must_use]
+    pub fn required_extension(mut self, key: impl Into<String>) -> Self {
+        self.required_extensions
+            .get_or_insert_with(Vec::new)
+            .push(key.into());
+        self
+    }
+
+    /// Validates the fields and builds the requirements.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`RequirementsBuildError`] of the first missing or
+    /// invalid field.
+    pub fn build(self) -> Result<PaymentRequirements, RequirementsBuildError> {
+        let scheme = self
+            .scheme
+            .filter(|scheme| !scheme.is_empty())
+            .ok_or(RequirementsBuildError::MissingField("scheme"))?;
+        let network = self
+            .network
+            .ok_or(RequirementsBuildError::MissingField("network"))?;
+        let network = ChainId::from_str(&network)
+            .ok()
+            .filter(|id| !id.namespace().is_empty() && !id.reference().is_empty())
+            .ok_or(RequirementsBuildError::InvalidNetwork(network))?;
+        let amount = self
+            .amount
+            .ok_or(RequirementsBuildError::MissingField("amount"))?;
+        proto::validate_amount(&amount).map_err(RequirementsBuildError::InvalidAmount)?;
+        if amount == "0" {
+            return Err(RequirementsBuildError::ZeroAmount);
+        }
+        let pay_to = self
+            .pay_to
+            .ok_or(RequirementsBuildError::MissingField("payTo"))?;
+        if pay_to.trim().is_empty() {
+            return Err(RequirementsBuildError::EmptyPayTo);
+        }
+        let asset = self
+            .asset
+            .filter(|asset| !asset.trim().is_empty())
+            .ok_or(RequirementsBuildError::MissingField("asset"))?;
+        if !MAX_TIMEOUT_SECONDS_RANGE.contains(&self.max_timeout_seconds) {
+            return Err(RequirementsBuildError::TimeoutOutOfRange(
+                self.max_timeout_seconds,
+            ));
+        }
+        if network.namespace() == "eip155" {
+            let has = |field: &str| {
+                self.extra
+                    .as_ref()
+                    .and_then(|extra| extra.get(field))
+                    .and_then(serde_json::Value::as_str)
+                    .is_some_and(|value| !value.is_empty())
+            };
+            if !has("name") || !has("version") {
+                return Err(RequirementsBuildError::MissingEip712Domain);
+            }
+        }
+        Ok(PaymentRequirements {
+            scheme,
+            network,
+            amount,
+            pay_to,
+            max_timeout_seconds: self.max_timeout_seconds,
+            asset,
+            extra: self.extra,
+            required_extensions: self.required_extensions,
+        })
+    }
+}
+
+/// Error returned when [`PaymentRequirementsBuilder::build`] rejects a field.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum RequirementsBuildError {
+    /// A required field was not set.
+    #[error("Missing payment requirements field {0}")]
+    MissingField(&'static str),
+    /// The network is not a CAIP-2 chain ID.
+    #[error("Invalid CAIP-2 network {0:?}")]
+    InvalidNetwork(String),
+    /// The amount is not a canonical decimal integer.
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(proto::AmountFormatError),
+    /// The amount is zero.
+    #[error("Amount must be positive")]
+    ZeroAmount,
+    /// The recipient address is empty.
+    #[error("payTo must not be empty")]
+    EmptyPayTo,
+    /// `maxTimeoutSeconds` is zero or longer than a day.
+    #[error("maxTimeoutSeconds must be between 1 and 86400, got {0}")]
+    TimeoutOutOfRange(u64),
+    /// An `eip155` requirement lacks the EIP-712 domain in `extra`.
+    #[error("eip155 requirements need the EIP-712 domain name and version in extra")]
+    MissingEip712Domain,
 }
 
 /// Compares a [`PriceTag`] with [`PaymentRequirements`] on the five
@@ -250,3 +700,198 @@ fn eq(&self, b: &PaymentRequirements) -> bool {
             && a.pay_to == b.pay_to
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn requirements() -> PaymentRequirements {
+        PaymentRequirements {
+            scheme: "exact".into(),
+            network: ChainId::new("eip155", "8453"),
+            amount: "10000".into(),
+            pay_to: "0x209693Bc6afc0C5328bA36FaF03C514EF312287C".into(),
+            max_timeout_seconds: 60,
+            asset: "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913".into(),
+            extra: Some(json!({ "name": "USD Coin", "version": "2" })),
+            required_extensions: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_single_field() {
+        let offered = requirements();
+        let mut accepted = requirements();
+        accepted.amount = "20000".into();
+        accepted.pay_to = accepted.pay_to.to_ascii_lowercase();
+
+        assert_eq!(
+            offered.diff(&accepted),
+            vec![FieldDiff {
+                field: "amount",
+                left: "10000".into(),
+                right: "20000".into(),
+            }]
+        );
+        assert!(offered.diff(&requirements()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_extra_only_mismatch() {
+        let offered = requirements();
+        let mut accepted = requirements();
+        accepted.extra = Some(json!({ "name": "USDC", "version": "2" }));
+
+        let diffs = offered.diff(&accepted);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].field, "extra");
+        assert_eq!(diffs[0].right, r#"{"name":"USDC","version":"2"}"#);
+    }
+
+    #[test]
+    fn test_required_extension_present() {
+        let mut requirements = requirements();
+        requirements.required_extensions = Some(vec!["kyc".into()]);
+        let offered = proto::Extensions::from([
+            ("kyc".to_owned(), json!({ "token": "abc" })),
+            ("other".to_owned(), json!(true)),
+        ]);
+
+        let selected = requirements.select_required_extensions(Some(&offered));
+        assert_eq!(selected.as_ref().map(proto::Extensions::len), Some(1));
+        assert!(
+            requirements
+                .check_required_extensions(selected.as_ref())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_required_extension_absent() {
+        let mut gated = requirements();
+        gated.required_extensions = Some(vec!["kyc".into()]);
+        let unrelated = proto::Extensions::from([("other".to_owned(), json!(true))]);
+
+        for extensions in [None, Some(&unrelated)] {
+            assert!(matches!(
+                gated.check_required_extensions(extensions),
+                Err(PaymentVerificationError::MissingExtension(key)) if key == "kyc"
+            ));
+        }
+        assert!(requirements().check_required_extensions(None).is_ok());
+    }
+
+    fn builder() -> PaymentRequirementsBuilder {
+        PaymentRequirements::builder()
+            .scheme("exact")
+            .network("eip155:8453")
+            .amount("10000")
+            .pay_to("0x209693Bc6afc0C5328bA36FaF03C514EF312287C")
+            .asset("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913")
+            .extra(json!({ "name": "USD Coin", "version": "2" }))
+    }
+
+    #[test]
+    fn test_builder_builds_valid_requirements() {
+        assert_eq!(builder().build().unwrap(), requirements());
+
+        let solana = builder()
+            .network("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp")
+            .extra(json!({ "feePayer": "FeePayer1111111111111111111111111111111111" }))
+            .build();
+        assert!(solana.is_ok());
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_fields() {
+        let err = |builder: PaymentRequirementsBuilder| builder.build().unwrap_err();
+
+        assert_eq!(
+            err(PaymentRequirements::builder()),
+            RequirementsBuildError::MissingField("scheme")
+        );
+        assert_eq!(
+            err(builder().network("base")),
+            RequirementsBuildError::InvalidNetwork("base".into())
+        );
+        assert_eq!(
+            err(builder().amount("1_000")),
+            RequirementsBuildError::InvalidAmount(proto::AmountFormatError::InvalidCharacter('_'))
+        );
+        assert_eq!(
+            err(builder().amount("0")),
+            RequirementsBuildError::ZeroAmount
+        );
+        assert_eq!(
+            err(builder().pay_to("")),
+            RequirementsBuildError::EmptyPayTo
+        );
+        assert_eq!(
+            err(builder().asset(" ")),
+            RequirementsBuildError::MissingField("asset")
+        );
+        assert_eq!(
+            err(builder().max_timeout_seconds(0)),
+            RequirementsBuildError::TimeoutOutOfRange(0)
+        );
+        assert_eq!(
+            err(builder().max_timeout_seconds(86_401)),
+            RequirementsBuildError::TimeoutOutOfRange(86_401)
+        );
+        assert_eq!(
+            err(builder().extra(json!({ "name": "USD Coin" }))),
+            RequirementsBuildError::MissingEip712Domain
+        );
+    }
+
+    #[test]
+    fn test_compatible_subset_of_offers() {
+        let solana = PaymentRequirements {
+            network: ChainId::new("solana", "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp"),
+            ..requirements()
+        };
+        let offered = [solana, requirements()];
+
+        assert!(requirements().is_compatible_subset_of(&offered));
+        let partial = PaymentRequirements {
+            pay_to: "0x209693bc6afc0c5328ba36faf03c514ef312287c".into(),
+            max_timeout_seconds: 30,
+            extra: Some(json!({ "version": "2" })),
+            ..requirements()
+        };
+        assert!(partial.is_compatible_subset_of(&offered));
+        let without_extra = PaymentRequirements {
+            extra: None,
+            ..requirements()
+        };
+        assert!(without_extra.is_compatible_subset_of(&offered));
+    }
+
+    #[test]
+    fn test_incompatible_subset_of_offers() {
+        let offered = [requirements()];
+        let cheaper = PaymentRequirements {
+            amount: "9999".into(),
+            ..requirements()
+        };
+        assert!(!cheaper.is_compatible_subset_of(&offered));
+        let longer = PaymentRequirements {
+            max_timeout_seconds: 120,
+            ..requirements()
+        };
+        assert!(!longer.is_compatible_subset_of(&offered));
+        let altered_extra = PaymentRequirements {
+            extra: Some(json!({ "version": "1" })),
+            ..requirements()
+        };
+        assert!(!altered_extra.is_compatible_subset_of(&offered));
+        let unknown_extension = PaymentRequirements {
+            required_extensions: Some(vec!["kyc".into()]),
+            ..requirements()
+        };
+        assert!(!unknown_extension.is_compatible_subset_of(&offered));
+        assert!(!requirements().is_compatible_subset_of(&[]));
+    }
+}
@@ -0,0 +1,757 @@
+//! Durable records of facilitator settlements.
+//!
+//! Operators keep one [`SettlementReceipt`] per settle attempt for accounting,
+//! dashboards, and dispute resolution. Receipts are written to a
+//! [`ReceiptStore`]: [`InMemoryReceiptStore`] for tests and single-process
+//! setups, or, with the `sqlite` feature, `SqliteReceiptStore` to keep them
+//! across restarts. Without a store, nothing is recorded ([`NoopReceiptStore`]).
+//!
+//! Stores plug into the hooks system through [`ReceiptHooks`]:
+//!
+//! ```ignore
+//! let receipts = Arc::new(SqliteReceiptStore::open("receipts.db")?);
+//! let facilitator = HookedFacilitator::new(registry)
+//!     .with_hook(ReceiptHooks::new(Arc::clone(&receipts)));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::facilitator::{BoxFuture, FacilitatorError};
+use crate::hooks::{FacilitatorHooks, FailureRecovery, SettleContext, VerifyContext};
+use crate::proto;
+use crate::proto::{AsPaymentProblem, UnixTimestamp};
+
+/// Maximum number of verify durations [`ReceiptHooks`] holds for payments
+/// that were verified but not settled yet.
+const MAX_PENDING_VERIFICATIONS: usize = 10_000;
+
+/// Age after which [`ReceiptHooks`] forgets the verify duration of a payment
+/// that was never settled.
+const PENDING_VERIFICATION_TTL: Duration = Duration::from_mins(10);
+
+/// Outcome of a recorded settle attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiptStatus {
+    /// The payment was settled on-chain.
+    Settled,
+    /// The settlement failed.
+    Failed,
+}
+
+impl ReceiptStatus {
+    /// Returns `"settled"` or `"failed"`.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Settled => "settled",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Record of one settle attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementReceipt {
+    /// Stable id of the payment, see [`proto::payment_id`].
+    pub payment_id: Option<String>,
+    /// Whether the payment was settled.
+    pub status: ReceiptStatus,
+    /// Address of the payer, if known.
+    pub payer: Option<String>,
+    /// CAIP-2 network the settlement occurred or was attempted on.
+    pub network: String,
+    /// Address of the token transferred, if the requirements could be parsed.
+    pub asset: Option<String>,
+    /// Amount transferred, in the token's smallest unit, if the requirements
+    /// could be parsed.
+    pub amount: Option<String>,
+    /// Settlement transaction hash or signature, for settled payments.
+    pub transaction: Option<String>,
+    /// Failure reason, for failed settlements.
+    pub failure_reason: Option<String>,
+    /// When the settle attempt completed.
+    pub recorded_at: UnixTimestamp,
+    /// Duration of the verification preceding the settlement, if it went
+    /// through the same facilitator.
+    pub verify_duration: Option<Duration>,
+    /// Duration of the settle call.
+    pub settle_duration: Duration,
+}
+
+/// Filter of [`ReceiptStore::query`]. Empty fields match every receipt.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReceiptQuery {
+    /// Only receipts of this payer, compared exactly.
+    pub payer: Option<String>,
+    /// Only receipts on this CAIP-2 network.
+    pub network: Option<String>,
+    /// Only receipts recorded at or after this time.
+    pub since: Option<UnixTimestamp>,
+    /// Maximum number of receipts returned.
+    pub limit: Option<usize>,
+}
+
+impl ReceiptQuery {
+    /// Returns `true` if `receipt` passes the filter, ignoring the limit.
+    #[must_use]
+    pub fn matches(&self, receipt: &SettlementReceipt) -> bool {
+        self.payer
+            .as_ref()
+            .is_none_or(|payer| receipt.payer.as_ref() == Some(payer))
+            && self
+                .network
+                .as_ref()
+                .is_none_or(|network| &receipt.network == network)
+            && self.since.is_none_or(|since| receipt.recorded_at >= since)
+    }
+}
+
+/// Error of a [`ReceiptStore`] backend.
+#[derive(Debug, thiserror::Error)]
+#[error("Receipt store error: {0}")]
+pub struct ReceiptStoreError(Box<dyn std::error::Error + Send + Sync>);
+
+impl ReceiptStoreError {
+    /// Wraps a backend error.
+    #[must_use]
+    pub fn new(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self(error.into())
+    }
+}
+
+/// Persistence of settlement receipts.
+///
+/// This trait is dyn-compatible. Queries return receipts newest first.
+pub trait ReceiptStore: Send + Sync {
+    /// Stores a receipt.
+    fn record(&self, receipt: SettlementReceipt) -> BoxFuture<'_, Result<(), ReceiptStoreError>>;
+
+    /// Returns the receipts passing `query`, newest first.
+    fn query<'a>(
+        &'a self,
+        query: &'a ReceiptQuery,
+    ) -> BoxFuture<'a, Result<Vec<SettlementReceipt>, ReceiptStoreError>>;
+
+    /// Returns the receipt of the settlement sent in `transaction`, if any.
+    fn by_transaction<'a>(
+        &'a self,
+        transaction: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SettlementReceipt>, ReceiptStoreError>>;
+}
+
+impl<S: ReceiptStore + ?Sized> ReceiptStore for Arc<S> {
+    fn record(&self, receipt: SettlementReceipt) -> BoxFuture<'_, Result<(), ReceiptStoreError>> {
+        (**self).record(receipt)
+    }
+
+    fn query<'a>(
+        &'a self,
+        query: &'a ReceiptQuery,
+    ) -> BoxFuture<'a, Result<Vec<SettlementReceipt>, ReceiptStoreError>> {
+        (**self).query(query)
+    }
+
+    fn by_transaction<'a>(
+        &'a self,
+        transaction: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SettlementReceipt>, ReceiptStoreError>> {
+        (**self).by_transaction(transaction)
+    }
+}
+
+/// A [`ReceiptStore`] that discards all receipts.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopReceiptStore;
+
+impl ReceiptStore for NoopReceiptStore {
+    fn record(&self, _receipt: SettlementReceipt) -> BoxFuture<'_, Result<(), ReceiptStoreError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn query<'a>(
+        &'a self,
+        _query: &'a ReceiptQuery,
+    ) -> BoxFuture<'a, Result<Vec<SettlementReceipt>, ReceiptStoreError>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn by_transaction<'a>(
+        &'a self,
+        _transaction: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SettlementReceipt>, ReceiptStoreError>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+/// A [`ReceiptStore`] keeping receipts in memory, lost on restart.
+#[derive(Debug, Default)]
+pub struct InMemoryReceiptStore {
+    receipts: RwLock<Vec<SettlementReceipt>>,
+}
+
+impl InMemoryReceiptStore {
+    /// Creates an empty store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of stored receipts.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.receipts
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if no receipt was stored.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl ReceiptStore for InMemoryReceiptStore {
+    fn record(&self, receipt: SettlementReceipt) -> BoxFuture<'_, Result<(), ReceiptStoreError>> {
+        self.receipts
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(receipt);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn query<'a>(
+        &'a self,
+        query: &'a ReceiptQuery,
+    ) -> BoxFuture<'a, Result<Vec<SettlementReceipt>, ReceiptStoreError>> {
+        let found = self
+            .receipts
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .rev()
+            .filter(|receipt| query.matches(receipt))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        Box::pin(async { Ok(found) })
+    }
+
+    fn by_transaction<'a>(
+        &'a self,
+        transaction: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SettlementReceipt>, ReceiptStoreError>> {
+        let found = self
+            .receipts
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .rev()
+            .find(|receipt| receipt.transaction.as_deref() == Some(transaction))
+            .cloned();
+        Box::pin(async { Ok(found) })
+    }
+}
+
+/// Lifecycle hooks writing a [`SettlementReceipt`] for every settle call.
+///
+/// Register with [`HookedFacilitator::with_hook`](crate::hooks::HookedFacilitator::with_hook),
+/// ahead of any hook recovering from settle failures: failures recovered by
+/// an earlier hook are not recorded, nor are operations aborted by a
+/// `before_*` hook. Store errors are logged and never fail the settlement.
+///
+/// Verify durations are matched to the later settlement of the same payment
+/// by [`proto::payment_id`]. Durations of payments not settled within ten
+/// minutes are dropped.
+pub struct ReceiptHooks<S> {
+    store: S,
+    /// Verify duration and completion time of each pending payment.
+    verify_durations: Mutex<HashMap<String, (Duration, Instant)>>,
+}
+
+impl<S> ReceiptHooks<S> {
+    /// Creates hooks writing to the given store.
+    #[must_use]
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            verify_durations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns a reference to the underlying store.
+    #[must_use]
+    pub const fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Takes the verify duration of the payment settled in `ctx`, if known.
+    fn take_verify_duration(&self, ctx: &SettleContext) -> Option<Duration> {
+        let payment_id = ctx.request.payment_id()?;
+        self.verify_durations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&payment_id)
+            .map(|(duration, _)| duration)
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for ReceiptHooks<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReceiptHooks")
+            .field("store", &self.store)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: ReceiptStore> ReceiptHooks<S> {
+    /// Builds the receipt of the settle call in `ctx` and stores it.
+    async fn record(
+        &self,
+        ctx: &SettleContext,
+        status: ReceiptStatus,
+        payer: Option<String>,
+        transaction: Option<String>,
+        failure_reason: Option<String>,
+    ) {
+        let requirements = ctx.request.payment_requirements();
+        let receipt = SettlementReceipt {
+            payment_id: ctx.request.payment_id(),
+            status,
+            payer,
+            network: ctx.request.network().to_owned(),
+            asset: requirements.as_ref().map(|r| r.asset.clone()),
+            amount: requirements.map(|r| r.amount),
+            transaction,
+            failure_reason,
+            recorded_at: UnixTimestamp::now(),
+            verify_duration: self.take_verify_duration(ctx),
+            settle_duration: ctx.started_at.elapsed(),
+        };
+        let stored = self.store.record(receipt).await;
+        #[cfg(feature = "telemetry")]
+        if let Err(error) = stored {
+            tracing::warn!(%error, network = ctx.request.network(), "Failed to store settlement receipt");
+        }
+        #[cfg(not(feature = "telemetry"))]
+        let _ = stored;
+    }
+}
+
+impl<S: ReceiptStore> FacilitatorHooks for ReceiptHooks<S> {
+    fn after_verify<'a>(
+        &'a self,
+        ctx: &'a VerifyContext,
+        result: &'a proto::VerifyResponse,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            if !result.is_valid() {
+                return;
+            }
+            let Some(payment_id) = ctx.request.payment_id() else {
+                return;
+            };
+            let mut durations = self
+                .verify_durations
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if durations.len() >= MAX_PENDING_VERIFICATIONS {
+                durations
+                    .retain(|_, (_, verified_at)| verified_at.elapsed() < PENDING_VERIFICATION_TTL);
+            }
+            if durations.len() < MAX_PENDING_VERIFICATIONS {
+                durations.insert(payment_id, (ctx.started_at.elapsed(), Instant::now()));
+            }
+        })
+    }
+
+    fn after_settle<'a>(
+        &'a self,
+        ctx: &'a SettleContext,
+        result: &'a proto::SettleResponse,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            match result {
+                proto::SettleResponse::Success {
+                    payer, transaction, ..
+                } => {
+                    self.record(
+                        ctx,
+                        ReceiptStatus::Settled,
+                        Some(payer.clone()),
+                        Some(transaction.clone()),
+                        None,
+                    )
+                    .await;
+                }
+                proto::SettleResponse::Error { reason, payer, .. } => {
+                    let reason = Some(reason.clone());
+                    self.record(ctx, ReceiptStatus::Failed, payer.clone(), None, reason)
+                        .await;
+                }
+            }
+        })
+    }
+
+    fn on_settle_failure<'a>(
+        &'a self,
+        ctx: &'a SettleContext,
+        error: &'a FacilitatorError,
+    ) -> BoxFuture<'a, FailureRecovery<proto::SettleResponse>> {
+        Box::pin(async move {
+            let reason = error.as_payment_problem().reason().to_string();
+            self.record(ctx, ReceiptStatus::Failed, None, None, Some(reason))
+                .await;
+            FailureRecovery::Propagate
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use sqlite_impl::SqliteReceiptStore;
+
+#[cfg(feature = "sqlite")]
+mod sqlite_impl {
+    use std::path::Path;
+    use std::sync::{Arc, Mutex, PoisonError};
+    use std::time::Duration;
+
+    use rusqlite::{Connection, OptionalExtension, Row, params};
+
+    use super::{
+        BoxFuture, ReceiptQuery, ReceiptStatus, ReceiptStore, ReceiptStoreError, SettlementReceipt,
+    };
+    use crate::proto::UnixTimestamp;
+
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS settlement_receipts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            payment_id TEXT,
+            status TEXT NOT NULL,
+            payer TEXT,
+            network TEXT NOT NULL,
+            asset TEXT,
+            amount TEXT,
+            tx_hash TEXT,
+            failure_reason TEXT,
+            recorded_at INTEGER NOT NULL,
+            verify_duration_ms INTEGER,
+            settle_duration_ms INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS settlement_receipts_payer
+            ON settlement_receipts (payer, recorded_at);
+        CREATE INDEX IF NOT EXISTS settlement_receipts_tx_hash
+            ON settlement_receipts (tx_hash);
+    ";
+
+    const COLUMNS: &str = "payment_id, status, payer, network, asset, amount, tx_hash, \
+        failure_reason, recorded_at, verify_duration_ms, settle_duration_ms";
+
+    /// A [`ReceiptStore`] persisting receipts in a `SQLite` database.
+    ///
+    /// Statements run on Tokio's blocking thread pool, so that disk I/O never
+    /// stalls the async workers; the store must be used within a Tokio runtime.
+    #[derive(Debug)]
+    pub struct SqliteReceiptStore {
+        connection: Arc<Mutex<Connection>>,
+    }
+
+    impl SqliteReceiptStore {
+        /// Opens or creates the database at `path`, creating the receipts
+        /// table if needed.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the database cannot be opened or initialized.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self, ReceiptStoreError> {
+            Self::with_connection(Connection::open(path).map_err(ReceiptStoreError::new)?)
+        }
+
+        /// Creates a store backed by a transient in-memory database.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the database cannot be initialized.
+        pub fn open_in_memory() -> Result<Self, ReceiptStoreError> {
+            Self::with_connection(Connection::open_in_memory().map_err(ReceiptStoreError::new)?)
+        }
+
+        fn with_connection(connection: Connection) -> Result<Self, ReceiptStoreError> {
+            connection
+                .execute_batch(SCHEMA)
+                .map_err(ReceiptStoreError::new)?;
+            Ok(Self {
+                connection: Arc::new(Mutex::new(connection)),
+            })
+        }
+
+        /// Runs `statement` against the connection on the blocking thread pool.
+        fn run<T, F>(&self, statement: F) -> BoxFuture<'static, Result<T, ReceiptStoreError>>
+        where
+            T: Send + 'static,
+            F: FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+        {
+            let connection = Arc::clone(&self.connection);
+            Box::pin(async move {
+                tokio::task::spawn_blocking(move || {
+                    statement(&connection.lock().unwrap_or_else(PoisonError::into_inner))
+                })
+                .await
+                .map_err(ReceiptStoreError::new)?
+                .map_err(ReceiptStoreError::new)
+            })
+        }
+    }
+
+    impl ReceiptStore for SqliteReceiptStore {
+        fn record(
+            &self,
+            receipt: SettlementReceipt,
+        ) -> BoxFuture<'_, Result<(), ReceiptStoreError>> {
+            self.run(move |connection| insert(connection, &receipt))
+        }
+
+        fn query<'a>(
+            &'a self,
+            query: &'a ReceiptQuery,
+        ) -> BoxFuture<'a, Result<Vec<SettlementReceipt>, ReceiptStoreError>> {
+            let query = query.clone();
+            self.run(move |connection| select(connection, &query))
+        }
+
+        fn by_transaction<'a>(
+            &'a self,
+            transaction: &'a str,
+        ) -> BoxFuture<'a, Result<Option<SettlementReceipt>, ReceiptStoreError>> {
+            let transaction = transaction.to_owned();
+            self.run(move |connection| select_transaction(connection, &transaction))
+        }
+    }
+
+    fn insert(connection: &Connection, receipt: &SettlementReceipt) -> rusqlite::Result<()> {
+        connection.execute(
+            &format!(
+                "INSERT INTO settlement_receipts ({COLUMNS}) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"
+            ),
+            params![
+                receipt.payment_id,
+                receipt.status.as_str(),
+                receipt.payer,
+                receipt.network,
+                receipt.asset,
+                receipt.amount,
+                receipt.transaction,
+                receipt.failure_reason,
+                to_sql_integer(receipt.recorded_at.as_secs()),
+                receipt.verify_duration.map(duration_to_millis),
+                duration_to_millis(receipt.settle_duration),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn select(
+        connection: &Connection,
+        query: &ReceiptQuery,
+    ) -> rusqlite::Result<Vec<SettlementReceipt>> {
+        let mut statement = connection.prepare_cached(&format!(
+            "SELECT {COLUMNS} FROM settlement_receipts \
+             WHERE (?1 IS NULL OR payer = ?1) \
+               AND (?2 IS NULL OR network = ?2) \
+               AND (?3 IS NULL OR recorded_at >= ?3) \
+             ORDER BY recorded_at DESC, id DESC LIMIT ?4"
+        ))?;
+        // A negative limit means no limit in SQLite.
+        let limit = query
+            .limit
+            .map_or(-1, |limit| i64::try_from(limit).unwrap_or(i64::MAX));
+        let since = query.since.map(|since| to_sql_integer(since.as_secs()));
+        statement
+            .query_map(
+                params![query.payer, query.network, since, limit],
+                receipt_from_row,
+            )?
+            .collect()
+    }
+
+    fn select_transaction(
+        connection: &Connection,
+        transaction: &str,
+    ) -> rusqlite::Result<Option<SettlementReceipt>> {
+        connection
+            .query_row(
+                &format!(
+                    "SELECT {COLUMNS} FROM settlement_receipts \
+                     WHERE tx_hash = ?1 ORDER BY id DESC LIMIT 1"
+                ),
+                params![transaction],
+                receipt_from_row,
+            )
+            .optional()
+    }
+
+    fn receipt_from_row(row: &Row<'_>) -> rusqlite::Result<SettlementReceipt> {
+        let status = match row.get::<_, String>(1)?.as_str() {
+            "settled" => ReceiptStatus::Settled,
+            _ => ReceiptStatus::Failed,
+        };
+        let millis = |value: i64| Duration::from_millis(value.unsigned_abs());
+        Ok(SettlementReceipt {
+            payment_id: row.get(0)?,
+            status,
+            payer: row.get(2)?,
+            network: row.get(3)?,
+            asset: row.get(4)?,
+            amount: row.get(5)?,
+            transaction: row.get(6)?,
+            failure_reason: row.get(7)?,
+            recorded_at: UnixTimestamp::from_secs(row.get::<_, i64>(8)?.unsigned_abs()),
+            verify_duration: row.get::<_, Option<i64>>(9)?.map(millis),
+            settle_duration: millis(row.get(10)?),
+        })
+    }
+
+    fn to_sql_integer(value: u64) -> i64 {
+        i64::try_from(value).unwrap_or(i64::MAX)
+    }
+
+    fn duration_to_millis(duration: Duration) -> i64 {
+        i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::facilitator::Facilitator;
+    use crate::hooks::HookedFacilitator;
+
+    /// Settles every payment, unless its amount is zero.
+    struct Settler;
+
+    impl Facilitator for Settler {
+        fn verify(
+            &self,
+            _request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::VerifyResponse::valid("0xpayer".to_owned())) })
+        }
+
+        fn settle(
+            &self,
+            request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            Box::pin(async move {
+                let requirements = request.payment_requirements().unwrap();
+                if requirements.amount == "0" {
+                    return Err(FacilitatorError::TransactionFailed("reverted".to_owned()));
+                }
+                Ok(proto::SettleResponse::Success {
+                    payer: "0xpayer".to_owned(),
+                    transaction: "0xtx".to_owned(),
+                    network: requirements.network.to_string(),
+                    extensions: None,
+                })
+            })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::SupportedResponse::default()) })
+        }
+    }
+
+    fn request(amount: &str) -> proto::SettleRequest {
+        proto::SettleRequest::from(json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "payload": { "authorization": { "nonce": format!("0x{amount}") } },
+            },
+            "paymentRequirements": {
+                "scheme": "exact",
+                "network": "eip155:8453",
+                "amount": amount,
+                "payTo": "0x209693Bc6afc0C5328bA36FaF03C514EF312287C",
+                "maxTimeoutSeconds": 60,
+                "asset": "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+            },
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_settle_writes_one_receipt() {
+        let store = Arc::new(InMemoryReceiptStore::new());
+        let facilitator =
+            HookedFacilitator::new(Settler).with_hook(ReceiptHooks::new(Arc::clone(&store)));
+
+        let verify = proto::VerifyRequest::from(request("10000").into_json());
+        facilitator.verify(verify).await.unwrap();
+        facilitator.settle(request("10000")).await.unwrap();
+
+        assert_eq!(store.len(), 1);
+        let receipt = store.by_transaction("0xtx").await.unwrap().unwrap();
+        assert_eq!(receipt.status, ReceiptStatus::Settled);
+        assert_eq!(receipt.payer.as_deref(), Some("0xpayer"));
+        assert_eq!(receipt.network, "eip155:8453");
+        assert_eq!(receipt.amount.as_deref(), Some("10000"));
+        assert_eq!(
+            receipt.asset.as_deref(),
+            Some("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913")
+        );
+        assert!(receipt.verify_duration.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_failed_settle_writes_failure_receipt() {
+        let store = Arc::new(InMemoryReceiptStore::new());
+        let facilitator =
+            HookedFacilitator::new(Settler).with_hook(ReceiptHooks::new(Arc::clone(&store)));
+
+        assert!(facilitator.settle(request("0")).await.is_err());
+
+        let receipts = store.query(&ReceiptQuery::default()).await.unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].status, ReceiptStatus::Failed);
+        assert_eq!(
+            receipts[0].failure_reason.as_deref(),
+            Some("transaction_failed")
+        );
+        assert!(receipts[0].transaction.is_none());
+        assert!(receipts[0].verify_duration.is_none());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_sqlite_store_round_trips_receipts() {
+        let store = SqliteReceiptStore::open_in_memory().unwrap();
+        let receipt = SettlementReceipt {
+            payment_id: Some("id".to_owned()),
+            status: ReceiptStatus::Settled,
+            payer: Some("0xpayer".to_owned()),
+            network: "eip155:8453".to_owned(),
+            asset: Some("0xasset".to_owned()),
+            amount: Some("10000".to_owned()),
+            transaction: Some("0xtx".to_owned()),
+            failure_reason: None,
+            recorded_at: UnixTimestamp::from_secs(1_700_000_000),
+            verify_duration: Some(Duration::from_millis(12)),
+            settle_duration: Duration::from_millis(1_500),
+        };
+        store.record(receipt.clone()).await.unwrap();
+
+        let found = store.by_transaction("0xtx").await.unwrap();
+        assert_eq!(found, Some(receipt.clone()));
+        let query = ReceiptQuery {
+            payer: Some("0xpayer".to_owned()),
+            since: Some(UnixTimestamp::from_secs(1_700_000_001)),
+            ..ReceiptQuery::default()
+        };
+        assert!(store.query(&query).await.unwrap().is_empty());
+    }
+}
@@ -51,12 +51,22 @@ fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 }
 
 impl PaymentCandidate {
+    /// Returns `true` if this candidate asks for a zero amount.
+    #[must_use]
+    pub fn is_zero_amount(&self) -> bool {
+        self.amount.trim_start_matches('0').is_empty()
+    }
+
     /// Signs this payment candidate, producing a payment payload.
     ///
     /// # Errors
     ///
-    /// Returns [`ClientError`] if signing fails.
+    /// Returns [`ClientError::ZeroAmount`] if the candidate asks for a zero
+    /// amount, and [`ClientError`] if signing fails.
     pub async fn sign(&self) -> Result<String, ClientError> {
+        if self.is_zero_amount() {
+            return Err(ClientError::ZeroAmount);
+        }
         self.signer.sign_payment().await
     }
 }
@@ -91,6 +101,13 @@ pub enum ClientError {
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
 
+    /// The server asked for a zero amount.
+    ///
+    /// Signing it would only produce a pointless settlement; servers should
+    /// answer requests for free resources with `200`, not a zero-price `402`.
+    #[error("Payment requirements ask for a zero amount")]
+    ZeroAmount,
+
     /// A pre-condition for payment signing was not met.
     ///
     /// Returned when the client detects that an on-chain condition required
@@ -99,6 +116,36 @@ pub enum ClientError {
     /// having the payment silently fail at the facilitator.
     #[error("Payment pre-condition not met: {0}")]
     PreConditionFailed(String),
+
+    /// The facilitator reported a failed settlement.
+    #[error("Settlement failed: {0}")]
+    SettlementFailed(String),
+
+    /// A settlement did not reach the requested confirmation depth in time.
+    #[error(
+        "Transaction {transaction} has {confirmations} of {required} confirmations after timeout"
+    )]
+    ConfirmationTimeout {
+        /// The settlement transaction hash.
+        transaction: String,
+        /// The number of confirmations observed before giving up.
+        confirmations: u64,
+        /// The number of confirmations that was requested.
+        required: u64,
+    },
+
+    /// A settlement receipt failed facilitator attestation checks.
+    #[error("Invalid facilitator attestation: {0}")]
+    InvalidAttestation(String),
+
+    /// A client hook vetoed the payment.
+    #[error("Payment aborted: {reason}: {message}")]
+    Aborted {
+        /// Machine-readable reason for aborting (e.g., `"amount_too_high"`).
+        reason: String,
+        /// Human-readable message describing why the payment was aborted.
+        message: String,
+    },
 }
 
 /// Trait for selecting the best payment candidate from available options.
@@ -56,7 +56,7 @@ fn caip_family(&self) -> String {
     }
     /// Returns the full scheme identifier (e.g., "eip155-exact").
     fn id(&self) -> String {
-        format!("{}-{}", self.namespace(), self.scheme(),)
+        format!("{}-{}", self.namespace(), self.scheme())
     }
 }
 
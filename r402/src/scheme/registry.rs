@@ -14,6 +14,7 @@
 use crate::chain::{ChainId, ChainProvider};
 use crate::facilitator::{BoxFuture, Facilitator, FacilitatorError};
 use crate::proto;
+use crate::proto::PaymentVerificationError;
 
 /// Trait for building facilitator instances from chain providers.
 ///
@@ -145,6 +146,35 @@ pub fn by_slug(&self, slug: &SchemeSlug) -> Option<&dyn Facilitator> {
             .map(|h| &**h)
     }
 
+    /// Gets the handler of `slug`, like [`by_slug`](Self::by_slug), telling
+    /// which part of the slug is unknown when there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PaymentVerificationError::UnsupportedChain`] if no handler
+    /// serves the chain of `slug`, or
+    /// [`PaymentVerificationError::UnsupportedScheme`] if handlers serve the
+    /// chain but none runs the scheme. The former tells a client to try
+    /// another facilitator, the latter to pay with another scheme.
+    pub fn handler_for(
+        &self,
+        slug: &SchemeSlug,
+    ) -> Result<&dyn Facilitator, PaymentVerificationError> {
+        if let Some(handler) = self.by_slug(slug) {
+            return Ok(handler);
+        }
+        let namespace = slug.as_wildcard().chain_id;
+        let serves_chain = self
+            .0
+            .keys()
+            .any(|known| known.chain_id == slug.chain_id || known.chain_id == namespace);
+        Err(if serves_chain {
+            PaymentVerificationError::UnsupportedScheme
+        } else {
+            PaymentVerificationError::UnsupportedChain
+        })
+    }
+
     /// Registers a handler for an entire namespace (wildcard).
     ///
     /// The handler will match any chain within the blueprint's namespace
@@ -166,21 +196,46 @@ pub fn register_for_namespace<P: ChainProvider>(
         Ok(())
     }
 
+    /// Installs `handler` for `slug`, replacing any handler already registered.
+    ///
+    /// Unlike [`register`](Self::register), the handler is supplied already
+    /// built, which lets callers swap handlers at runtime (e.g. on a config
+    /// reload) or install test doubles. Returns the previous handler, if any.
+    pub fn replace(
+        &mut self,
+        slug: SchemeSlug,
+        handler: Box<dyn Facilitator>,
+    ) -> Option<Box<dyn Facilitator>> {
+        self.0.insert(slug, handler)
+    }
+
+    /// Removes the handler registered for exactly `slug`.
+    ///
+    /// No wildcard fallback is applied: unregistering `eip155:8453:exact`
+    /// leaves an `eip155:*:exact` handler in place. Returns the removed
+    /// handler, if any.
+    pub fn unregister(&mut self, slug: &SchemeSlug) -> Option<Box<dyn Facilitator>> {
+        self.0.remove(slug)
+    }
+
     /// Returns an iterator over all registered handlers.
     pub fn values(&self) -> impl Iterator<Item = &dyn Facilitator> {
         self.0.values().map(|v| &**v)
     }
 
-    /// Looks up a handler by slug, returning an `Aborted` error if not found.
+    /// Looks up the handler of a request's slug, failing with the
+    /// [`PaymentVerificationError`] of [`handler_for`](Self::handler_for) if
+    /// there is none.
     fn require_handler(
         &self,
-        slug: Option<SchemeSlug>,
-    ) -> Result<&dyn Facilitator, FacilitatorError> {
-        slug.and_then(|s| self.by_slug(&s))
-            .ok_or_else(|| FacilitatorError::Aborted {
-                reason: "no_facilitator_for_network".into(),
-                message: "no handler registered for this payment scheme".into(),
-            })
+        slug: Option<&SchemeSlug>,
+    ) -> Result<&dyn Facilitator, PaymentVerificationError> {
+        let slug = slug.ok_or_else(|| {
+            PaymentVerificationError::InvalidFormat(
+                "payment payload does not name a V2 scheme and network".into(),
+            )
+        })?;
+        self.handler_for(slug)
     }
 }
 
@@ -190,7 +245,7 @@ fn verify(
         request: proto::VerifyRequest,
     ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
         Box::pin(async move {
-            let handler = self.require_handler(request.scheme_slug())?;
+            let handler = self.require_handler(request.scheme_slug().as_ref())?;
             handler.verify(request).await
         })
     }
@@ -200,8 +255,23 @@ fn settle(
         request: proto::SettleRequest,
     ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
         Box::pin(async move {
-            let handler = self.require_handler(request.scheme_slug())?;
-            handler.settle(request).await
+            let slug = request.scheme_slug();
+            let handler = self.require_handler(slug.as_ref())?;
+            let response = handler.settle(request).await?;
+            Ok(with_request_network(response, slug))
+        })
+    }
+
+    fn settle_verified(
+        &self,
+        request: proto::SettleRequest,
+        token: String,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        Box::pin(async move {
+            let slug = request.scheme_slug();
+            let handler = self.require_handler(slug.as_ref())?;
+            let response = handler.settle_verified(request, token).await?;
+            Ok(with_request_network(response, slug))
         })
     }
 
@@ -225,7 +295,242 @@ fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, Facilitato
                 kinds,
                 extensions: Vec::new(),
                 signers,
+                attestation_key: None,
             })
         })
     }
+
+    fn pre_validate(&self, request: &proto::VerifyRequest) -> Result<(), PaymentVerificationError> {
+        let handler = self.require_handler(request.scheme_slug().as_ref())?;
+        handler.pre_validate(request)
+    }
+}
+
+/// Reports `response` under the CAIP-2 network of the settled request.
+///
+/// Handlers may report the network of their provider rather than the one the
+/// request named; the two only differ by a handler bug, but clients match the
+/// response against the network they paid on.
+fn with_request_network(
+    response: proto::SettleResponse,
+    slug: Option<SchemeSlug>,
+) -> proto::SettleResponse {
+    let Some(slug) = slug else {
+        return response;
+    };
+    if response.network() == slug.chain_id.to_string() {
+        return response;
+    }
+    #[cfg(feature = "telemetry")]
+    tracing::warn!(
+        reported = response.network(),
+        requested = %slug.chain_id,
+        "settlement reported a network other than the request's"
+    );
+    response.with_network(&slug.chain_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Handler that reports its name as the payer.
+    struct Named(&'static str);
+
+    impl Facilitator for Named {
+        fn verify(
+            &self,
+            _request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            Box::pin(async move { Ok(proto::VerifyResponse::valid(self.0.into())) })
+        }
+
+        fn settle(
+            &self,
+            _request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            Box::pin(async { Err(FacilitatorError::OnchainFailure("unused".into())) })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::SupportedResponse::default()) })
+        }
+    }
+
+    async fn payer(registry: &SchemeRegistry) -> Option<String> {
+        let request = proto::VerifyRequest::from(json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "accepted": { "network": "eip155:8453", "scheme": "exact" }
+            }
+        }));
+        match registry.verify(request).await {
+            Ok(proto::VerifyResponse::Valid { payer, .. }) => Some(payer),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_and_unregister_update_dispatch() {
+        let slug = SchemeSlug::new(ChainId::new("eip155", "8453"), "exact".into());
+        let mut registry = SchemeRegistry::new();
+        assert_eq!(payer(&registry).await, None);
+
+        assert!(
+            registry
+                .replace(slug.clone(), Box::new(Named("v1")))
+                .is_none()
+        );
+        assert_eq!(payer(&registry).await.as_deref(), Some("v1"));
+
+        assert!(
+            registry
+                .replace(slug.clone(), Box::new(Named("v2")))
+                .is_some()
+        );
+        assert_eq!(payer(&registry).await.as_deref(), Some("v2"));
+
+        registry.replace(slug.as_wildcard(), Box::new(Named("namespace")));
+        assert!(registry.unregister(&slug).is_some());
+        assert_eq!(payer(&registry).await.as_deref(), Some("namespace"));
+
+        assert!(registry.unregister(&slug).is_none());
+        registry.unregister(&slug.as_wildcard());
+        assert_eq!(payer(&registry).await, None);
+    }
+
+    fn verify_request(network: &str, scheme: &str) -> proto::VerifyRequest {
+        proto::VerifyRequest::from(json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "accepted": { "network": network, "scheme": scheme }
+            }
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_missing_handler_reports_unknown_chain_or_scheme() {
+        let mut registry = SchemeRegistry::new();
+        let base = ChainId::new("eip155", "8453");
+        registry.replace(
+            SchemeSlug::new(base, "exact".into()),
+            Box::new(Named("base")),
+        );
+
+        let unregistered_chain = registry.verify(verify_request("eip155:1", "exact"));
+        assert!(matches!(
+            unregistered_chain.await,
+            Err(FacilitatorError::PaymentVerification(
+                PaymentVerificationError::UnsupportedChain
+            ))
+        ));
+        let bad_scheme = registry.verify(verify_request("eip155:8453", "upto"));
+        assert!(matches!(
+            bad_scheme.await,
+            Err(FacilitatorError::PaymentVerification(
+                PaymentVerificationError::UnsupportedScheme
+            ))
+        ));
+
+        // A namespace-wide handler serves every chain of its namespace.
+        let solana = ChainId::new("solana", "*");
+        registry.replace(
+            SchemeSlug::new(solana, "exact".into()),
+            Box::new(Named("sol")),
+        );
+        let solana_scheme = registry.verify(verify_request(
+            "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp",
+            "upto",
+        ));
+        assert!(matches!(
+            solana_scheme.await,
+            Err(FacilitatorError::PaymentVerification(
+                PaymentVerificationError::UnsupportedScheme
+            ))
+        ));
+    }
+
+    /// Handler that settles on a fixed network regardless of the request.
+    struct SettlesOn(&'static str);
+
+    impl Facilitator for SettlesOn {
+        fn verify(
+            &self,
+            _request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            Box::pin(async { Err(FacilitatorError::OnchainFailure("unused".into())) })
+        }
+
+        fn settle(
+            &self,
+            _request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            Box::pin(async move {
+                Ok(proto::SettleResponse::Success {
+                    payer: "payer".into(),
+                    transaction: "0x01".into(),
+                    network: self.0.into(),
+                    extensions: None,
+                })
+            })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::SupportedResponse::default()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_settle_reports_the_request_network() {
+        let base = ChainId::new("eip155", "8453");
+        let solana = ChainId::new("solana", "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp");
+        let mut registry = SchemeRegistry::new();
+        registry.replace(
+            SchemeSlug::new(base.clone(), "exact".into()),
+            Box::new(SettlesOn("eip155:8453")),
+        );
+        // A namespace-wide handler reporting a different chain of its namespace.
+        registry.replace(
+            SchemeSlug::new(solana.clone(), "exact".into()).as_wildcard(),
+            Box::new(SettlesOn("solana:EtWTRABZaYq6iMfeYKouRu166VU2xqa1")),
+        );
+
+        for chain_id in [base, solana] {
+            let request = proto::SettleRequest::from(json!({
+                "x402Version": 2,
+                "paymentPayload": {
+                    "accepted": { "network": chain_id.to_string(), "scheme": "exact" }
+                }
+            }));
+            let response = registry.settle(request).await.unwrap();
+            assert_eq!(response.network(), chain_id.to_string());
+        }
+    }
+
+    #[test]
+    fn test_pre_validate_dispatches_by_slug() {
+        let mut registry = SchemeRegistry::new();
+        let request = proto::VerifyRequest::from(json!({
+            "x402Version": 2,
+            "paymentPayload": {
+                "accepted": { "network": "eip155:8453", "scheme": "exact" }
+            }
+        }));
+        assert!(matches!(
+            registry.pre_validate(&request),
+            Err(PaymentVerificationError::UnsupportedChain)
+        ));
+
+        let slug = SchemeSlug::new(ChainId::new("eip155", "8453"), "exact".into());
+        registry.replace(slug, Box::new(Named("v1")));
+        // The default check rejects the request for lacking requirements.
+        assert!(matches!(
+            registry.pre_validate(&request),
+            Err(PaymentVerificationError::InvalidFormat(_))
+        ));
+        let malformed = proto::VerifyRequest::from(json!({ "x402Version": 2 }));
+        assert!(registry.pre_validate(&malformed).is_err());
+    }
 }
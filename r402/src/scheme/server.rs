@@ -6,7 +6,7 @@
 //! interface.
 
 use crate::chain::ChainId;
-use crate::proto::v2;
+use crate::proto::{self, v2};
 
 /// A resolved token amount ready for use in payment requirements.
 #[derive(Debug, Clone)]
@@ -64,7 +64,8 @@ fn enhance_requirements(
     ///
     /// # Errors
     ///
-    /// Returns an error if the price cannot be parsed or the network is not supported.
+    /// Returns an error if the price cannot be parsed, the network is not supported,
+    /// or the resolved amount is not a canonical decimal integer.
     fn build_requirements(
         &self,
         price: &str,
@@ -73,6 +74,7 @@ fn build_requirements(
         max_timeout_seconds: u64,
     ) -> Result<v2::PaymentRequirements, Box<dyn std::error::Error>> {
         let asset_amount = self.parse_price(price, network)?;
+        proto::validate_amount(&asset_amount.amount)?;
         let base = v2::PaymentRequirements {
             scheme: self.scheme().to_owned(),
             network: network.clone(),
@@ -81,6 +83,7 @@ fn build_requirements(
             max_timeout_seconds,
             asset: asset_amount.asset,
             extra: None,
+            required_extensions: None,
         };
         Ok(self.enhance_requirements(base))
     }
@@ -0,0 +1,386 @@
+//! Facilitator-side payer screening.
+//!
+//! Operators under regulatory requirements can restrict which payers a
+//! facilitator accepts, per chain. This is a policy layer of the facilitator,
+//! independent of any on-chain compliance checks of the token itself.
+//!
+//! Wrap any [`Facilitator`] in a [`ScreeningFacilitator`] to reject payments
+//! whose payer is blocklisted, or missing from a non-empty allowlist, with
+//! [`PaymentVerificationError::PayerNotAllowed`]. The lists can be replaced at
+//! runtime through a shared [`PayerScreening`] handle, e.g. from the admin
+//! endpoint `ScreeningAdmin` of `r402-http`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::ChainId;
+use crate::facilitator::{BoxFuture, Facilitator, FacilitatorError};
+use crate::proto;
+use crate::proto::PaymentVerificationError;
+
+/// Payer allowlist and blocklist of one chain.
+///
+/// EVM (`eip155`) addresses are compared case-insensitively, addresses of any
+/// other namespace (e.g. Solana base58) exactly.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayerLists {
+    /// Payers accepted on the chain. If empty, every payer that is not
+    /// blocklisted is accepted.
+    ///
+    /// Default: empty.
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Payers rejected on the chain, even if they are also allowlisted.
+    ///
+    /// Default: empty.
+    #[serde(default)]
+    pub block: Vec<String>,
+}
+
+/// [`PayerLists`] normalized for lookups.
+#[derive(Debug, Default)]
+struct ChainScreening {
+    allow: HashSet<String>,
+    block: HashSet<String>,
+}
+
+impl ChainScreening {
+    fn new(chain: &ChainId, lists: PayerLists) -> Self {
+        let normalize = |payers: Vec<String>| {
+            payers
+                .into_iter()
+                .map(|payer| normalize_payer(chain, payer))
+                .collect()
+        };
+        Self {
+            allow: normalize(lists.allow),
+            block: normalize(lists.block),
+        }
+    }
+
+    fn allows(&self, payer: &str) -> bool {
+        !self.block.contains(payer) && (self.allow.is_empty() || self.allow.contains(payer))
+    }
+}
+
+/// Lowercases EVM addresses so that checksummed and plain forms compare equal.
+fn normalize_payer(chain: &ChainId, payer: String) -> String {
+    if chain.namespace() == "eip155" {
+        payer.to_ascii_lowercase()
+    } else {
+        payer
+    }
+}
+
+/// Shared, runtime-updatable payer lists of every screened chain.
+///
+/// Clones share the same lists, so a handle kept by an admin endpoint can
+/// update the lists a [`ScreeningFacilitator`] checks against. Chains without
+/// lists accept every payer.
+#[derive(Debug, Clone, Default)]
+pub struct PayerScreening {
+    chains: Arc<RwLock<HashMap<ChainId, ChainScreening>>>,
+}
+
+impl PayerScreening {
+    /// Creates a screening policy from per-chain lists, e.g. loaded from config.
+    #[must_use]
+    pub fn new(lists: impl IntoIterator<Item = (ChainId, PayerLists)>) -> Self {
+        let chains = lists
+            .into_iter()
+            .map(|(chain, lists)| {
+                let screening = ChainScreening::new(&chain, lists);
+                (chain, screening)
+            })
+            .collect();
+        Self {
+            chains: Arc::new(RwLock::new(chains)),
+        }
+    }
+
+    /// Replaces the lists of `chain`.
+    pub fn set_lists(&self, chain: ChainId, lists: PayerLists) {
+        let screening = ChainScreening::new(&chain, lists);
+        self.chains
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(chain, screening);
+    }
+
+    /// Removes the lists of `chain`, accepting every payer on it.
+    pub fn clear_lists(&self, chain: &ChainId) {
+        self.chains
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(chain);
+    }
+
+    /// Checks whether `payer` may pay on `chain`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PaymentVerificationError::PayerNotAllowed`] if the payer is
+    /// blocklisted, or the chain has an allowlist without the payer.
+    pub fn check(&self, chain: &ChainId, payer: &str) -> Result<(), PaymentVerificationError> {
+        let chains = self.chains.read().unwrap_or_else(PoisonError::into_inner);
+        let Some(screening) = chains.get(chain) else {
+            return Ok(());
+        };
+        if screening.allows(&normalize_payer(chain, payer.to_owned())) {
+            Ok(())
+        } else {
+            Err(PaymentVerificationError::PayerNotAllowed(payer.to_owned()))
+        }
+    }
+}
+
+/// A [`Facilitator`] wrapper that screens payers against [`PayerScreening`].
+///
+/// The payer is checked once the inner facilitator has identified it in a
+/// valid verify response. Settling re-verifies the payment through this
+/// wrapper first, so a payer calling settle directly is screened too. As the
+/// payer is only known after a verify, [`Facilitator::settle_verified`] does
+/// the same and its verification token goes unused.
+#[derive(Debug, Clone)]
+pub struct ScreeningFacilitator<F> {
+    inner: F,
+    screening: PayerScreening,
+}
+
+impl<F> ScreeningFacilitator<F> {
+    /// Wraps `inner`, rejecting payers not allowed by `screening`.
+    pub const fn new(inner: F, screening: PayerScreening) -> Self {
+        Self { inner, screening }
+    }
+
+    /// Returns a reference to the wrapped facilitator.
+    pub const fn inner(&self) -> &F {
+        &self.inner
+    }
+
+    /// Returns the screening policy, e.g. to update it at runtime.
+    pub const fn screening(&self) -> &PayerScreening {
+        &self.screening
+    }
+}
+
+impl<F> Facilitator for ScreeningFacilitator<F>
+where
+    F: Facilitator,
+{
+    fn verify(
+        &self,
+        request: proto::VerifyRequest,
+    ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+        Box::pin(async move {
+            let chain = request.network().parse::<ChainId>().ok();
+            let response = self.inner.verify(request).await?;
+            if let (proto::VerifyResponse::Valid { payer, .. }, Some(chain)) = (&response, chain) {
+                self.screening.check(&chain, payer)?;
+            }
+            Ok(response)
+        })
+    }
+
+    fn settle(
+        &self,
+        request: proto::SettleRequest,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        Box::pin(async move {
+            let verify = proto::VerifyRequest::from(request.clone().into_json());
+            if let proto::VerifyResponse::Invalid {
+                reason,
+                message,
+                payer,
+            } = self.verify(verify).await?
+            {
+                return Ok(proto::SettleResponse::Error {
+                    reason,
+                    code: None,
+                    message,
+                    payer,
+                    network: request.network().to_owned(),
+                });
+            }
+            self.inner.settle(request).await
+        })
+    }
+
+    fn settle_verified(
+        &self,
+        request: proto::SettleRequest,
+        _token: String,
+    ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+        self.settle(request)
+    }
+
+    fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+        self.inner.supported()
+    }
+
+    fn pre_validate(&self, request: &proto::VerifyRequest) -> Result<(), PaymentVerificationError> {
+        self.inner.pre_validate(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Accepts every payment, reporting the payer given in the payload.
+    struct PayerEcho;
+
+    impl Facilitator for PayerEcho {
+        fn verify(
+            &self,
+            request: proto::VerifyRequest,
+        ) -> BoxFuture<'_, Result<proto::VerifyResponse, FacilitatorError>> {
+            let payer = request.into_json()["paymentPayload"]["payer"]
+                .as_str()
+                .unwrap_or_default()
+                .to_owned();
+            Box::pin(async move { Ok(proto::VerifyResponse::valid(payer)) })
+        }
+
+        fn settle(
+            &self,
+            request: proto::SettleRequest,
+        ) -> BoxFuture<'_, Result<proto::SettleResponse, FacilitatorError>> {
+            let network = request.network().to_owned();
+            let payer = request.into_json()["paymentPayload"]["payer"]
+                .as_str()
+                .unwrap_or_default()
+                .to_owned();
+            Box::pin(async move {
+                Ok(proto::SettleResponse::Success {
+                    payer,
+                    transaction: "0xtx".into(),
+                    network,
+                    extensions: None,
+                })
+            })
+        }
+
+        fn supported(&self) -> BoxFuture<'_, Result<proto::SupportedResponse, FacilitatorError>> {
+            Box::pin(async { Ok(proto::SupportedResponse::default()) })
+        }
+    }
+
+    fn request(network: &str, payer: &str) -> proto::VerifyRequest {
+        proto::VerifyRequest::from(json!({
+            "x402Version": 2,
+            "paymentPayload": { "payer": payer },
+            "paymentRequirements": { "network": network },
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_allowlisted_payer_passes() {
+        let base = ChainId::new("eip155", "8453");
+        let allowed = "0x209693Bc6afc0C5328bA36FaF03C514EF312287C";
+        let screening = PayerScreening::new(HashMap::from([(
+            base,
+            PayerLists {
+                allow: vec![allowed.to_owned()],
+                block: Vec::new(),
+            },
+        )]));
+        let facilitator = ScreeningFacilitator::new(PayerEcho, screening);
+
+        let lowercase = allowed.to_ascii_lowercase();
+        let response = facilitator.verify(request("eip155:8453", &lowercase));
+        assert!(response.await.unwrap().is_valid());
+
+        let err = facilitator
+            .verify(request(
+                "eip155:8453",
+                "0x1111111111111111111111111111111111111111",
+            ))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            FacilitatorError::PaymentVerification(PaymentVerificationError::PayerNotAllowed(_))
+        ));
+
+        // Chains without lists are not screened.
+        let response = facilitator.verify(request("eip155:1", "0x1111"));
+        assert!(response.await.unwrap().is_valid());
+    }
+
+    #[tokio::test]
+    async fn test_blocklist_takes_precedence() {
+        let solana = ChainId::new("solana", "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp");
+        let payer = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+        let screening = PayerScreening::new(HashMap::from([(
+            solana.clone(),
+            PayerLists {
+                allow: vec![payer.to_owned()],
+                block: vec![payer.to_owned()],
+            },
+        )]));
+        let facilitator = ScreeningFacilitator::new(PayerEcho, screening.clone());
+
+        let network = solana.to_string();
+        let err = facilitator
+            .verify(request(&network, payer))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            FacilitatorError::PaymentVerification(PaymentVerificationError::PayerNotAllowed(ref p)) if p == payer
+        ));
+
+        // Base58 is case-sensitive, so a differently cased payer is not allowlisted.
+        let mut lists = PayerLists {
+            allow: vec![payer.to_owned()],
+            block: Vec::new(),
+        };
+        screening.set_lists(solana.clone(), lists.clone());
+        let response = facilitator.verify(request(&network, payer));
+        assert!(response.await.unwrap().is_valid());
+        let shouted = payer.to_ascii_uppercase();
+        assert!(
+            facilitator
+                .verify(request(&network, &shouted))
+                .await
+                .is_err()
+        );
+
+        lists.block.push(payer.to_owned());
+        screening.set_lists(solana, lists);
+        assert!(facilitator.verify(request(&network, payer)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_settle_screens_the_payer() {
+        let base = ChainId::new("eip155", "8453");
+        let blocked = "0x1111111111111111111111111111111111111111";
+        let screening = PayerScreening::new(HashMap::from([(
+            base,
+            PayerLists {
+                allow: Vec::new(),
+                block: vec![blocked.to_owned()],
+            },
+        )]));
+        let facilitator = ScreeningFacilitator::new(PayerEcho, screening);
+
+        let settle = proto::SettleRequest::from(request("eip155:8453", blocked));
+        let err = facilitator
+            .settle_verified(settle, "token".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            FacilitatorError::PaymentVerification(PaymentVerificationError::PayerNotAllowed(_))
+        ));
+
+        let settle = proto::SettleRequest::from(request("eip155:8453", "0x2222"));
+        assert!(facilitator.settle(settle).await.unwrap().is_success());
+    }
+}
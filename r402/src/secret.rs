@@ -0,0 +1,91 @@
+//! Loading of secrets such as facilitator signer keys.
+//!
+//! Secrets are usually given inline, but orchestrators like Kubernetes mount
+//! them as files. A value prefixed with [`FILE_PREFIX`] (e.g.
+//! `file:/var/run/secrets/signer`) is read from that file instead, so keys
+//! never have to appear in configuration text or the environment.
+
+use std::path::{Path, PathBuf};
+
+/// Prefix marking a secret value as a path to read the secret from.
+pub const FILE_PREFIX: &str = "file:";
+
+/// Errors that can occur when loading a secret.
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    /// The secret file could not be read.
+    #[error("failed to read secret file {path}: {source}")]
+    Io {
+        /// Path of the secret file.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+    /// The secret file contains only whitespace.
+    #[error("secret file {0} is empty")]
+    Empty(PathBuf),
+}
+
+/// Reads a secret from `path`, trimming surrounding whitespace.
+///
+/// # Errors
+///
+/// Returns [`SecretError`] if the file cannot be read or is empty.
+pub fn read_secret_file(path: impl AsRef<Path>) -> Result<String, SecretError> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path).map_err(|source| SecretError::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+    let secret = contents.trim();
+    if secret.is_empty() {
+        return Err(SecretError::Empty(path.to_owned()));
+    }
+    Ok(secret.to_owned())
+}
+
+/// Resolves a secret value, reading it from a file if it starts with
+/// [`FILE_PREFIX`] and returning it unchanged otherwise.
+///
+/// # Errors
+///
+/// Returns [`SecretError`] if the referenced file cannot be read or is empty.
+pub fn resolve_secret(value: &str) -> Result<String, SecretError> {
+    value
+        .strip_prefix(FILE_PREFIX)
+        .map_or_else(|| Ok(value.to_owned()), read_secret_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("r402-{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_secret_is_read_from_file() {
+        let path = temp_file("signer-key", "  0xabc123\n");
+        let value = format!("{FILE_PREFIX}{}", path.display());
+        assert_eq!(resolve_secret(&value).unwrap(), "0xabc123");
+        assert_eq!(resolve_secret("0xinline").unwrap(), "0xinline");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_empty_or_missing_secret_file_is_rejected() {
+        let path = temp_file("empty-key", " \n");
+        assert!(matches!(
+            read_secret_file(&path),
+            Err(SecretError::Empty(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            read_secret_file(&path),
+            Err(SecretError::Io { .. })
+        ));
+    }
+}
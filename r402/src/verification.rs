@@ -0,0 +1,203 @@
+//! Verification tokens for settling payments without re-verification.
+//!
+//! By default a facilitator re-runs every verify check when it settles, which
+//! costs a second round of RPC calls (balance, nonce, simulation) for every
+//! paid request. A facilitator holding [`VerificationTokens`] instead attaches
+//! a short-lived token to each valid [`proto::VerifyResponse`]. Presenting the
+//! token to [`Facilitator::settle_verified`](crate::facilitator::Facilitator::settle_verified)
+//! lets the facilitator skip the checks it already ran.
+//!
+//! A token is opaque to its holder: it is `<claims>.<mac>`, where the claims
+//! (payer, expiry, and a digest of the verified request) are base64url-encoded
+//! JSON and the MAC is an HMAC-SHA256 over them, keyed with a secret only the
+//! facilitator knows. A token is only accepted for the exact request it was
+//! issued for, and only until it expires.
+
+use std::fmt;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as b64;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::proto;
+use crate::proto::{PaymentVerificationError, UnixTimestamp};
+
+/// Default lifetime of a verification token.
+pub const DEFAULT_VERIFICATION_TOKEN_TTL: Duration = Duration::from_mins(1);
+
+/// The signed contents of a verification token.
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    /// Payer identified by the verification.
+    payer: String,
+    /// Time after which the token is rejected.
+    exp: UnixTimestamp,
+    /// Base64url SHA-256 digest of the verified request.
+    req: String,
+}
+
+/// Issues and checks verification tokens with a facilitator secret.
+///
+/// Every facilitator instance that may settle a payment must share the secret
+/// of the instance that verified it.
+#[derive(Clone)]
+pub struct VerificationTokens {
+    mac: Hmac<Sha256>,
+    ttl: Duration,
+}
+
+impl fmt::Debug for VerificationTokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerificationTokens")
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VerificationTokens {
+    /// Creates a token issuer keyed with `secret`, with the
+    /// [default lifetime](DEFAULT_VERIFICATION_TOKEN_TTL).
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)] // HMAC accepts keys of any length
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        let mac = Hmac::new_from_slice(secret.as_ref()).expect("HMAC accepts any key length");
+        Self {
+            mac,
+            ttl: DEFAULT_VERIFICATION_TOKEN_TTL,
+        }
+    }
+
+    /// Sets how long issued tokens are accepted.
+    #[must_use]
+    pub const fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Issues a token for `request`, verified as paid by `payer`.
+    #[must_use]
+    pub fn issue(&self, request: &proto::VerifyRequest, payer: &str) -> String {
+        self.issue_at(request, payer, UnixTimestamp::now())
+    }
+
+    fn issue_at(&self, request: &proto::VerifyRequest, payer: &str, now: UnixTimestamp) -> String {
+        let claims = Claims {
+            payer: payer.to_owned(),
+            exp: now + self.ttl.as_secs(),
+            req: request_digest(request),
+        };
+        let claims = b64.encode(serde_json::to_vec(&claims).unwrap_or_default());
+        let mac = b64.encode(self.mac(claims.as_bytes()));
+        format!("{claims}.{mac}")
+    }
+
+    /// Checks that `token` was issued by this facilitator for `request` and
+    /// has not expired, returning the verified payer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PaymentVerificationError::InvalidVerificationToken`] if the
+    /// token is malformed, forged, expired, or was issued for another request.
+    pub fn check(
+        &self,
+        token: &str,
+        request: &proto::SettleRequest,
+    ) -> Result<String, PaymentVerificationError> {
+        self.check_at(token, request, UnixTimestamp::now())
+    }
+
+    fn check_at(
+        &self,
+        token: &str,
+        request: &proto::SettleRequest,
+        now: UnixTimestamp,
+    ) -> Result<String, PaymentVerificationError> {
+        let invalid =
+            |reason: &str| PaymentVerificationError::InvalidVerificationToken(reason.into());
+        let (claims, mac) = token.split_once('.').ok_or_else(|| invalid("malformed"))?;
+        let mac = b64.decode(mac).map_err(|_| invalid("malformed"))?;
+        self.mac
+            .clone()
+            .chain_update(claims.as_bytes())
+            .verify_slice(&mac)
+            .map_err(|_| invalid("bad signature"))?;
+        let claims = b64.decode(claims).map_err(|_| invalid("malformed"))?;
+        let claims: Claims = serde_json::from_slice(&claims).map_err(|_| invalid("malformed"))?;
+        if claims.exp <= now {
+            return Err(invalid("expired"));
+        }
+        if claims.req != request_digest(request) {
+            return Err(invalid("issued for another request"));
+        }
+        Ok(claims.payer)
+    }
+
+    /// Computes the HMAC-SHA256 of `message`.
+    fn mac(&self, message: &[u8]) -> [u8; 32] {
+        self.mac
+            .clone()
+            .chain_update(message)
+            .finalize()
+            .into_bytes()
+            .into()
+    }
+}
+
+/// Digests the JSON of a verify or settle request.
+///
+/// The digest covers the JSON exactly as the request serializes it; it is
+/// not canonicalized. A token therefore only matches a settle request that
+/// serializes byte-for-byte like the verified one, as when both are built
+/// from the same payload. Re-encoding with another key order or whitespace
+/// yields a different digest and the token is rejected.
+fn request_digest(request: &impl Serialize) -> String {
+    let json = serde_json::to_vec(request).unwrap_or_default();
+    b64.encode(Sha256::digest(json))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn request(amount: &str) -> proto::VerifyRequest {
+        proto::VerifyRequest::from(json!({
+            "x402Version": 2,
+            "paymentPayload": { "payload": { "signature": "0xabcd" } },
+            "paymentRequirements": { "network": "eip155:8453", "amount": amount },
+        }))
+    }
+
+    #[test]
+    fn test_token_settles_only_its_request() {
+        let tokens = VerificationTokens::new("secret");
+        let now = UnixTimestamp::from_secs(1_000);
+        let token = tokens.issue_at(&request("100"), "0xpayer", now);
+
+        let settle = proto::SettleRequest::from(request("100"));
+        assert_eq!(tokens.check_at(&token, &settle, now).unwrap(), "0xpayer");
+
+        let tampered = proto::SettleRequest::from(request("1"));
+        assert!(tokens.check_at(&token, &tampered, now).is_err());
+    }
+
+    #[test]
+    fn test_expired_and_forged_tokens_are_rejected() {
+        let tokens = VerificationTokens::new("secret").with_ttl(Duration::from_secs(30));
+        let now = UnixTimestamp::from_secs(1_000);
+        let token = tokens.issue_at(&request("100"), "0xpayer", now);
+        let settle = proto::SettleRequest::from(request("100"));
+
+        let expired = tokens.check_at(&token, &settle, now + 30).unwrap_err();
+        assert!(expired.to_string().contains("expired"));
+
+        let forged =
+            VerificationTokens::new("other secret").issue_at(&request("100"), "0xpayer", now);
+        assert!(tokens.check_at(&forged, &settle, now).is_err());
+        assert!(tokens.check_at("not a token", &settle, now).is_err());
+    }
+}